@@ -0,0 +1,133 @@
+use drink::{
+	frame_support::sp_runtime::ModuleError,
+	minimal::{MinimalSandbox, RuntimeEvent},
+	pallet_contracts,
+	repl::ReplError,
+	scale::Encode,
+	session::error::SessionError,
+	DispatchError, Sandbox,
+};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use ink_sandbox::EventRecordOf;
+use ratatui::{
+	style::{Color, Modifier, Style},
+	text::Span,
+};
+
+use crate::app_state::AppState;
+
+type Runtime = <MinimalSandbox as Sandbox>::Runtime;
+
+impl AppState {
+	/// Shows the events emitted by `command` and its error (if any) in the events panel.
+	pub fn note_events(
+		&mut self,
+		command: &str,
+		events: &[EventRecordOf<Runtime>],
+		error: Option<String>,
+	) {
+		if events.is_empty() && error.is_none() {
+			return;
+		}
+
+		let events = events.iter().map(|record| self.format_event(record)).collect::<Vec<_>>();
+		let panel = &mut self.ui_state.events;
+		panel.push(
+			Span::styled(
+				format!("`{command}`"),
+				Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+			)
+			.into(),
+		);
+		for event in events {
+			panel
+				.push(Span::styled(format!("  {event}"), Style::default().fg(Color::White)).into());
+		}
+		if let Some(error) = error {
+			panel.push(
+				Span::styled(
+					format!("  Error: {error}"),
+					Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+				)
+				.into(),
+			);
+		}
+		panel.reset_scrolling();
+	}
+
+	/// Formats `error`, resolving the pallet of the dispatch errors from the runtime metadata.
+	pub fn format_session_error(&mut self, error: &SessionError) -> String {
+		match error {
+			SessionError::DeploymentFailed(error) |
+			SessionError::UploadFailed(error) |
+			SessionError::CallFailed(error) |
+			SessionError::UpgradeFailed(error) => self.format_dispatch_error(error),
+			error => error.to_string(),
+		}
+	}
+
+	/// Formats `error`, resolving the pallet of the dispatch errors from the runtime metadata.
+	pub fn format_repl_error(&mut self, error: &ReplError) -> String {
+		match error {
+			ReplError::Deployment(error) =>
+				format!("Failed to deploy contract\n{}", self.format_session_error(error)),
+			ReplError::Call(error) =>
+				format!("Failed to call contract\n{}", self.format_session_error(error)),
+			ReplError::AddTokens(error) =>
+				format!("Failed to add tokens: {}", self.format_dispatch_error(error)),
+			error => error.to_string(),
+		}
+	}
+
+	/// Formats `error` as `Pallet::Error` for module errors.
+	pub fn format_dispatch_error(&mut self, error: &DispatchError) -> String {
+		let DispatchError::Module(ModuleError { index, error: variant, message }) = error else {
+			return format!("{error:?}");
+		};
+		let metadata = self.repl.session().sandbox().execute_with(MinimalSandbox::get_metadata);
+		match (pallet_name(&metadata, *index), message) {
+			(Some(pallet), Some(message)) => format!("{pallet}::{message}"),
+			(Some(pallet), None) => format!("{pallet}::{variant:?}"),
+			(None, _) => format!("{error:?}"),
+		}
+	}
+
+	/// Formats `record`, decoding the events of the known contracts.
+	fn format_event(&self, record: &EventRecordOf<Runtime>) -> String {
+		let RuntimeEvent::Contracts(pallet_contracts::Event::ContractEmitted { contract, data }) =
+			&record.event
+		else {
+			return format!("{:?}", record.event);
+		};
+
+		let decoded = self
+			.repl
+			.contracts()
+			.get_all()
+			.iter()
+			.find(|c| c.address == *contract)
+			.zip(record.topics.first())
+			.and_then(|(c, topic)| {
+				// The transcoder decodes the events as encoded by the runtime.
+				c.transcoder.decode_contract_event(&topic.0, &mut &*data.encode()).ok()
+			});
+		match decoded {
+			Some(event) => format!("Contracts: {event} (emitted by {contract})"),
+			None => format!("Contracts: undecoded event 0x{} (emitted by {contract})", hex(data)),
+		}
+	}
+}
+
+fn pallet_name(metadata: &RuntimeMetadataPrefixed, index: u8) -> Option<String> {
+	match &metadata.1 {
+		RuntimeMetadata::V14(metadata) =>
+			metadata.pallets.iter().find(|p| p.index == index).map(|p| p.name.clone()),
+		RuntimeMetadata::V15(metadata) =>
+			metadata.pallets.iter().find(|p| p.index == index).map(|p| p.name.clone()),
+		_ => None,
+	}
+}
+
+fn hex(data: &[u8]) -> String {
+	data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -1,12 +1,17 @@
 use std::{env, path::PathBuf};
 
-pub use contracts::{Contract, ContractIndex, ContractRegistry};
-use drink::{minimal::MinimalSandbox, session::Session, AccountId32, Sandbox, Weight};
+use drink::{
+	minimal::MinimalSandbox,
+	repl::{Repl, ReplCommand},
+	sandbox_api::prelude::SystemAPI,
+	session::Session,
+	AccountId32, Sandbox, SandboxConfig, Weight,
+};
 pub use user_input::UserInput;
 
 use crate::app_state::output::Output;
 
-mod contracts;
+mod events;
 mod output;
 pub mod print;
 mod user_input;
@@ -42,6 +47,7 @@ pub struct UiState {
 
 	pub user_input: UserInput,
 	pub output: Output,
+	pub events: Output,
 
 	pub show_help: bool,
 }
@@ -56,6 +62,7 @@ impl UiState {
 			mode: Default::default(),
 			user_input: Default::default(),
 			output: Default::default(),
+			events: Default::default(),
 			show_help: false,
 		}
 	}
@@ -68,25 +75,45 @@ impl Default for UiState {
 }
 
 pub struct AppState {
-	pub session: Session<MinimalSandbox>,
+	pub repl: Repl<MinimalSandbox>,
 	pub chain_info: ChainInfo,
 	pub ui_state: UiState,
-	pub contracts: ContractRegistry,
+	/// The deployment or call previewed with a dry run, awaiting confirmation, with its input.
+	pub pending_command: Option<(String, ReplCommand)>,
 }
 
 impl AppState {
-	pub fn new(cwd_override: Option<PathBuf>) -> Self {
+	pub fn new(cwd_override: Option<PathBuf>, config: &SandboxConfig) -> Self {
+		let session = Session::with_sandbox(MinimalSandbox::with_config(config));
+		let ui_state = UiState::new(cwd_override);
 		AppState {
-			session: Session::default(),
-			chain_info: Default::default(),
-			ui_state: UiState::new(cwd_override),
-			contracts: Default::default(),
+			chain_info: ChainInfo { gas_limit: session.get_gas_limit(), ..Default::default() },
+			repl: Repl::new(session).with_cwd(ui_state.cwd.clone()),
+			ui_state,
+			pending_command: None,
+		}
+	}
+
+	/// Updates the chain info and the working directory after the REPL executed a command.
+	pub fn sync_with_repl(&mut self) {
+		let session = self.repl.session();
+		self.chain_info = ChainInfo {
+			block_height: session.sandbox().block_number(),
+			actor: session.get_actor(),
+			gas_limit: session.get_gas_limit(),
+		};
+		if self.repl.cwd() != self.ui_state.cwd {
+			let cwd = self.repl.cwd().to_path_buf();
+			match env::set_current_dir(&cwd) {
+				Ok(_) => self.ui_state.cwd = cwd,
+				Err(err) => self.print_error(&err.to_string()),
+			}
 		}
 	}
 }
 
 impl Default for AppState {
 	fn default() -> Self {
-		Self::new(None)
+		Self::new(None, &SandboxConfig::default())
 	}
 }
@@ -1,4 +1,4 @@
-use drink::{pallet_contracts::ContractResult, sandbox_api::contracts_api::decode_debug_buffer};
+use drink::{pallet_contracts::StorageDeposit, repl::DryRun, units::dot_style_format};
 use ratatui::{
 	style::{Color, Modifier, Style},
 	text::Span,
@@ -35,22 +35,30 @@ impl AppState {
 		);
 	}
 
+	/// Prints the estimates and the outcome of the dry run `dry_run`.
+	pub fn print_dry_run(&mut self, dry_run: &DryRun<u128>) {
+		let storage_deposit = match dry_run.storage_deposit {
+			StorageDeposit::Charge(amount) => format!("{} charged", dot_style_format(amount)),
+			StorageDeposit::Refund(amount) => format!("{} refunded", dot_style_format(amount)),
+		};
+		self.print(&format!(
+			"Dry run\nGas required: {:?}\nStorage deposit: {storage_deposit}",
+			dry_run.gas_required
+		));
+		match &dry_run.outcome {
+			Err(err) => {
+				let err = self.format_dispatch_error(err);
+				self.print_error(&format!("Expected to fail: {err}"));
+			},
+			Ok(true) => self.print_error("Expected to revert"),
+			Ok(false) => self.print("Expected to succeed"),
+		}
+		self.print("Confirm with `y`, anything else cancels");
+	}
+
 	fn print_sequence<'a, I: Iterator<Item = &'a str>>(&mut self, seq: I, style: Style) {
 		for line in seq {
 			self.ui_state.output.push(Span::styled(line.to_string(), style).into());
 		}
 	}
 }
-
-pub fn format_contract_action<R, E>(result: &ContractResult<R, u128, E>) -> String {
-	let mut output = format!(
-		"Gas consumed: {:?}\nGas required: {:?}\nDebug buffer:\n",
-		result.gas_consumed, result.gas_required
-	);
-
-	for line in &decode_debug_buffer(&result.debug_message) {
-		output.push_str(&format!("  {line}\n"));
-	}
-
-	output
-}
@@ -1,56 +1,24 @@
-use std::str::FromStr;
-
 use clap::Parser;
-use drink::AccountId32;
+use drink::repl::ReplCommand;
 
 #[derive(Parser)]
 pub enum CliCommand {
 	#[clap(alias = "c")]
 	Clear,
 	#[clap(alias = "cd")]
-	ChangeDir {
-		path: String,
-	},
-
-	#[clap(alias = "nb")]
-	NextBlock {
-		#[clap(default_value = "1")]
-		count: u32,
-	},
-	AddTokens {
-		// TODO: from_ss58_checked
-		#[clap(value_parser = AccountId32::from_str)]
-		recipient: AccountId32,
-		value: u128,
-	},
-	SetActor {
-		// TODO: from_ss58_checked
-		#[clap(value_parser = AccountId32::from_str)]
-		actor: AccountId32,
-	},
-	SetGasLimit {
-		ref_time: u64,
-		proof_size: u64,
-	},
+	ChangeDir { path: String },
 
 	#[clap(alias = "b")]
 	Build,
-	#[clap(alias = "d")]
-	Deploy {
-		#[clap(long, default_value = "new")]
-		constructor: String,
-		args: Vec<String>,
-		#[clap(long, default_values_t = Vec::<u8>::new(), value_delimiter = ',')]
-		salt: Vec<u8>,
-	},
-	Call {
-		message: String,
-		args: Vec<String>,
-	},
+
+	#[clap(flatten)]
+	Repl(ReplCommand),
 }
 
 #[cfg(test)]
 mod tests {
+	use drink::accounts::ALICE;
+
 	use super::*;
 
 	#[test]
@@ -58,4 +26,25 @@ mod tests {
 		use clap::CommandFactory;
 		CliCommand::command().debug_assert()
 	}
+
+	#[test]
+	fn actor_can_be_a_secret_uri() {
+		let command = CliCommand::try_parse_from(["", "set-actor", "//Alice"]).unwrap();
+		assert!(matches!(
+			command,
+			CliCommand::Repl(ReplCommand::SetActor { actor }) if actor == ALICE
+		));
+	}
+
+	#[test]
+	fn call_can_target_contract() {
+		let command =
+			CliCommand::try_parse_from(["", "call", "--contract", "token", "transfer", "1"])
+				.unwrap();
+		assert!(matches!(
+			command,
+			CliCommand::Repl(ReplCommand::Call { message, args, contract: Some(contract), yes: false })
+				if message == "transfer" && args == ["1"] && contract == "token"
+		));
+	}
 }
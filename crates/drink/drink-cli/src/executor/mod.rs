@@ -6,18 +6,34 @@ use std::env;
 use anyhow::Result;
 use clap::Parser;
 use drink::{
-	sandbox_api::prelude::{BalanceAPI, SystemAPI},
-	AccountId32, Weight,
+	minimal::MinimalSandbox,
+	repl::{split_arguments, ReplCommand},
+	sandbox_api::prelude::SystemAPI,
+	Sandbox,
 };
+use ink_sandbox::EventRecordOf;
 
 use crate::{app_state::AppState, cli::CliCommand};
 
 pub fn execute(app_state: &mut AppState) -> Result<()> {
-	let command = app_state.ui_state.user_input.current_input().to_string();
-	app_state.print_command(&command);
+	let input = app_state.ui_state.user_input.current_input().to_string();
+	app_state.print_command(&input);
 
-	let command = command.split_ascii_whitespace().map(|a| a.trim()).collect::<Vec<_>>();
-	let cli_command = match CliCommand::try_parse_from([vec![""], command].concat()) {
+	// A deployment or call previewed by the last command is executed once confirmed.
+	if let Some((pending_input, pending_command)) = app_state.pending_command.take() {
+		if matches!(input.trim(), "y" | "yes") {
+			run(app_state, &pending_input, CliCommand::Repl(pending_command));
+		} else {
+			app_state.print("Cancelled");
+		}
+		return Ok(());
+	}
+
+	let Ok(command) = split_arguments(&input) else {
+		app_state.print_error("Invalid command");
+		return Ok(());
+	};
+	let cli_command = match CliCommand::try_parse_from([vec![String::new()], command].concat()) {
 		Ok(cli_command) => cli_command,
 		Err(_) => {
 			app_state.print_error("Invalid command");
@@ -25,6 +41,34 @@ pub fn execute(app_state: &mut AppState) -> Result<()> {
 		},
 	};
 
+	match cli_command {
+		CliCommand::Repl(
+			command @ (ReplCommand::Deploy { yes: false, .. } |
+			ReplCommand::Call { yes: false, .. }),
+		) => preview(app_state, input, command),
+		cli_command => run(app_state, &input, cli_command),
+	}
+	Ok(())
+}
+
+/// Dry runs the deployment or call `command`, which is executed once confirmed.
+fn preview(app_state: &mut AppState, input: String, command: ReplCommand) {
+	match app_state.repl.dry_run(&command) {
+		Ok(Some(dry_run)) => {
+			app_state.print_dry_run(&dry_run);
+			app_state.pending_command = Some((input, command));
+		},
+		Ok(None) => unreachable!("Deployments and calls are dry run"),
+		Err(err) => {
+			let err = app_state.format_repl_error(&err);
+			app_state.print_error(&err);
+		},
+	}
+}
+
+fn run(app_state: &mut AppState, input: &str, cli_command: CliCommand) {
+	let checkpoint = events_checkpoint(app_state);
+	let mut error = None;
 	match cli_command {
 		CliCommand::Clear => app_state.ui_state.output.clear(),
 		CliCommand::ChangeDir { path } => {
@@ -33,43 +77,48 @@ pub fn execute(app_state: &mut AppState) -> Result<()> {
 				Ok(_) => {
 					app_state.ui_state.cwd =
 						env::current_dir().expect("Failed to get current directory");
+					app_state.repl.set_cwd(app_state.ui_state.cwd.clone());
 					app_state.print("Directory changed");
 				},
 				Err(err) => app_state.print_error(&err.to_string()),
 			}
 		},
 
-		CliCommand::NextBlock { count } => build_blocks(app_state, count),
-		CliCommand::AddTokens { recipient, value } => add_tokens(app_state, recipient, value)?,
-		CliCommand::SetActor { actor } => {
-			app_state.chain_info.actor = actor;
-			app_state.print("Actor was set");
-		},
-		CliCommand::SetGasLimit { ref_time, proof_size } => {
-			app_state.chain_info.gas_limit = Weight::from_parts(ref_time, proof_size);
-			app_state.print("Gas limit was set");
-		},
-
 		CliCommand::Build => contract::build(app_state),
-		CliCommand::Deploy { constructor, args, salt } =>
-			contract::deploy(app_state, constructor, args, salt),
-		CliCommand::Call { message, args } => contract::call(app_state, message, args),
+
+		CliCommand::Repl(command) => match app_state.repl.run(command) {
+			Ok(output) => app_state.print(&output),
+			Err(err) => {
+				let err = app_state.format_repl_error(&err);
+				app_state.print_error(&err);
+				error = Some(err);
+			},
+		},
 	}
+	app_state.sync_with_repl();
 
-	Ok(())
+	let events = events_since(app_state, checkpoint);
+	app_state.note_events(input, &events, error);
 }
 
-fn build_blocks(app_state: &mut AppState, count: u32) {
-	app_state.chain_info.block_height = app_state.session.sandbox().build_blocks(count);
-	app_state.print(&format!("{count} blocks built"));
+/// The block and the number of its events before a command.
+type EventsCheckpoint = (u32, usize);
+
+fn events_checkpoint(app_state: &mut AppState) -> EventsCheckpoint {
+	let sandbox = app_state.repl.session().sandbox();
+	(sandbox.block_number(), sandbox.events().len())
 }
 
-fn add_tokens(app_state: &mut AppState, recipient: AccountId32, value: u128) -> Result<()> {
-	app_state
-		.session
-		.sandbox()
-		.mint_into(&recipient, value)
-		.map_err(|err| anyhow::format_err!("Failed to add token: {err:?}"))?;
-	app_state.print(&format!("{value} tokens added to {recipient}",));
-	Ok(())
+/// Returns the events emitted since `checkpoint`. The events of the previous blocks are gone.
+fn events_since(
+	app_state: &mut AppState,
+	(block, count): EventsCheckpoint,
+) -> Vec<EventRecordOf<<MinimalSandbox as Sandbox>::Runtime>> {
+	let sandbox = app_state.repl.session().sandbox();
+	let events = sandbox.events();
+	if sandbox.block_number() == block {
+		events.get(count..).unwrap_or_default().to_vec()
+	} else {
+		events
+	}
 }
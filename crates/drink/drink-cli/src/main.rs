@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
+use drink::SandboxConfig;
 
 use crate::ui::run_ui;
 
@@ -16,9 +17,17 @@ struct Args {
 	/// Starts the CLI in the provided directory
 	#[arg(short, long, value_name = "DIRECTORY")]
 	path: Option<PathBuf>,
+
+	/// Configures the sandbox with the provided TOML file (see `ink_sandbox::SandboxConfig`)
+	#[arg(short, long, value_name = "FILE")]
+	config: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
 	let args = Args::parse();
-	run_ui(args.path)
+	let config = match args.config {
+		Some(path) => SandboxConfig::load(path)?,
+		None => SandboxConfig::default(),
+	};
+	run_ui(args.path, &config)
 }
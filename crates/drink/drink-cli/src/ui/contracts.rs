@@ -1,29 +1,28 @@
+use drink::repl::ContractIndex;
 use ratatui::{
 	style::{Color, Style},
 	text::{Line, Span},
 	widgets::{List, ListItem, Widget},
 };
 
-use crate::{
-	app_state::{AppState, ContractIndex},
-	ui::layout::section,
-};
+use crate::{app_state::AppState, ui::layout::section};
 
 pub(super) fn build(app_state: &mut AppState) -> impl Widget {
 	let items = app_state
-		.contracts
+		.repl
+		.contracts()
 		.get_all()
 		.iter()
 		.enumerate()
 		.map(|(idx, contract)| {
-			let style = match app_state.contracts.current_index() {
+			let style = match app_state.repl.contracts().current_index() {
 				ContractIndex::CurrentContract(cc) if cc == idx =>
 					Style::default().bg(Color::White).fg(Color::Black),
 				_ => Style::default(),
 			};
 
 			ListItem::new(Line::from(Span::styled(
-				format!("{} / {}", contract.name, &contract.address.to_string()[..8],),
+				format!("{} / {}", contract.alias, &contract.address.to_string()[..8],),
 				style,
 			)))
 		})
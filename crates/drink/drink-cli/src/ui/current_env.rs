@@ -6,11 +6,17 @@ use ratatui::{
 use crate::{app_state::AppState, ui::layout::section};
 
 pub(super) fn build(app_state: &mut AppState) -> impl Widget {
-	let current_contract_info = match app_state.contracts.current_contract() {
+	let current_contract_info = match app_state.repl.contracts().current_contract() {
 		Some(contract) => format!("name: {} | address: {}", contract.name, contract.address),
 		None => "No deployed contract".to_string(),
 	};
 
+	let actor = &app_state.chain_info.actor;
+	let actor_info = match app_state.repl.keyring().uri_of(actor) {
+		Some(uri) => format!("{actor} ({uri})"),
+		None => actor.to_string(),
+	};
+
 	Paragraph::new(format!(
 		r#"Current working directory: {}
 Block height: {}
@@ -19,8 +25,8 @@ Current actor: {}
 Current contract: {{ {} }}"#,
 		app_state.ui_state.cwd.to_str().unwrap(),
 		app_state.chain_info.block_height,
-		app_state.contracts.count(),
-		app_state.chain_info.actor,
+		app_state.repl.contracts().count(),
+		actor_info,
 		current_contract_info
 	))
 	.alignment(Alignment::Left)
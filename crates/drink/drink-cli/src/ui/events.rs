@@ -0,0 +1,9 @@
+use ratatui::widgets::{Paragraph, Widget};
+
+use crate::{app_state::AppState, ui::layout::section};
+
+pub(super) fn build(app_state: &AppState) -> impl Widget {
+	Paragraph::new(app_state.ui_state.events.content().to_vec())
+		.block(section("Events"))
+		.scroll((app_state.ui_state.events.offset(), 0))
+}
@@ -13,7 +13,9 @@ use crate::{
 pub(super) fn build(app_state: &AppState) -> impl Widget {
 	let instruction: Line = match app_state.ui_state.mode {
 		Mode::Managing => alternate_help([
-			"Use arrows to scroll through output. Press ",
+			"Use arrows to scroll through output and ",
+			"'PgUp'/'PgDn'",
+			" through events. Press ",
 			"'q'",
 			" to quit. Press ",
 			"'h'",
@@ -21,6 +23,13 @@ pub(super) fn build(app_state: &AppState) -> impl Widget {
 			"'i'",
 			" to enter editing mode.",
 		]),
+		Mode::Drinking if app_state.pending_command.is_some() => alternate_help([
+			"Press ",
+			"'y'",
+			" and ",
+			"'Enter'",
+			" to confirm the dry-run operation, anything else cancels it.",
+		]),
 		Mode::Drinking => alternate_help([
 			"Press ",
 			"'Esc'",
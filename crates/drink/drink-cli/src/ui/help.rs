@@ -12,17 +12,35 @@ pub(super) fn build(_app_state: &AppState) -> impl Widget {
 		command("clear / c", "clear output tab"),
 		command("build / b", "build contract from the sources in the current directory"),
 		command(
-			"deploy / d [--constructor <name>] [--salt <salt>]",
-			"deploy contract using <constructor> (`new` by default) and <salt> (empty by default)",
+			"deploy / d [--constructor <name>] [--salt <salt>] [--alias <alias>] [--yes]",
+			"deploy contract using <constructor> (`new` by default) and <salt> (empty by default) \
+			 as <alias> (contract name by default), after a dry run unless --yes",
 		),
-		command("call <message>", "call contract's message"),
+		command(
+			"call [--contract <alias>] [--yes] <message>",
+			"call message of contract <alias> (current contract by default), after a dry run \
+			 unless --yes",
+		),
+		command("use <alias>", "make contract <alias> the current one"),
 		command("next-block / nb [count]", "build next <count> blocks (by default a single block)"),
 		command("add-tokens <recipient> <value>", "add <value> tokens to <recipient>"),
 		command("set-actor <account>", "set <account> as the current actor (transaction sender)"),
+		command(
+			"new-account <uri> [--fund <units>]",
+			"derive account from <uri> (e.g. //Alice/stash) and fund it with <units> (1000 by \
+			 default)",
+		),
+		command("list-accounts / accounts", "list the accounts of the keyring with their balances"),
 		command(
 			"set-gas-limit <ref_time> <proof_size>",
 			"set gas limits to <ref_time> and <proof_size>",
 		),
+		command("snapshot save <name>", "save the current state as snapshot <name>"),
+		command("snapshot restore <name>", "restore the state of snapshot <name>"),
+		command(
+			"metadata [path]",
+			"write the SCALE-encoded runtime metadata to <path> (`metadata.scale` by default)",
+		),
 	])
 	.block(section("Help"))
 }
@@ -7,7 +7,7 @@ use ratatui::{
 
 use crate::{
 	app_state::AppState,
-	ui::{contracts, current_env, footer, help, output, user_input},
+	ui::{contracts, current_env, events, footer, help, output, user_input},
 };
 
 pub(super) fn section(title: &str) -> Block {
@@ -46,8 +46,14 @@ pub(super) fn layout<B: Backend>(f: &mut Frame<B>, app_state: &mut AppState) {
 	if app_state.ui_state.show_help {
 		f.render_widget(help::build(app_state), chunks[1]);
 	} else {
-		app_state.ui_state.output.note_display_height(chunks[1].height - 2);
-		f.render_widget(output::build(app_state), chunks[1]);
+		let subchunks = Layout::default()
+			.direction(Direction::Horizontal)
+			.constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+			.split(chunks[1]);
+		app_state.ui_state.output.note_display_height(subchunks[0].height - 2);
+		f.render_widget(output::build(app_state), subchunks[0]);
+		app_state.ui_state.events.note_display_height(subchunks[1].height - 2);
+		f.render_widget(events::build(app_state), subchunks[1]);
 	}
 
 	f.render_widget(user_input::build(app_state), chunks[2]);
@@ -1,5 +1,6 @@
 mod contracts;
 mod current_env;
+mod events;
 mod footer;
 mod help;
 mod layout;
@@ -15,6 +16,7 @@ use crossterm::{
 	execute,
 	terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use drink::SandboxConfig;
 use layout::layout;
 use ratatui::backend::CrosstermBackend;
 
@@ -28,9 +30,9 @@ use crate::{
 
 type Terminal = ratatui::Terminal<CrosstermBackend<Stdout>>;
 
-pub fn run_ui(cwd: Option<PathBuf>) -> Result<()> {
+pub fn run_ui(cwd: Option<PathBuf>, config: &SandboxConfig) -> Result<()> {
 	let mut terminal = setup_dedicated_terminal()?;
-	let app_result = run_ui_app(&mut terminal, cwd);
+	let app_result = run_ui_app(&mut terminal, cwd, config);
 	restore_original_terminal(terminal)?;
 	app_result
 }
@@ -49,8 +51,12 @@ fn restore_original_terminal(mut terminal: Terminal) -> Result<()> {
 	terminal.show_cursor().map_err(|e| anyhow!(e))
 }
 
-fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<()> {
-	let mut app_state = AppState::new(cwd_override);
+fn run_ui_app(
+	terminal: &mut Terminal,
+	cwd_override: Option<PathBuf>,
+	config: &SandboxConfig,
+) -> Result<()> {
+	let mut app_state = AppState::new(cwd_override, config);
 
 	loop {
 		terminal.draw(|f| layout(f, &mut app_state))?;
@@ -69,19 +75,22 @@ fn run_ui_app(terminal: &mut Terminal, cwd_override: Option<PathBuf>) -> Result<
 					app_state.ui_state.show_help = !app_state.ui_state.show_help,
 				(Managing, KeyCode::Down) => app_state.ui_state.output.scroll_down(),
 				(Managing, KeyCode::Up) => app_state.ui_state.output.scroll_up(),
+				(Managing, KeyCode::PageDown) => app_state.ui_state.events.scroll_down(),
+				(Managing, KeyCode::PageUp) => app_state.ui_state.events.scroll_up(),
 
 				(Drinking, KeyCode::Char(c)) => app_state.ui_state.user_input.push(c),
 				(Drinking, KeyCode::Backspace) => {
 					app_state.ui_state.user_input.pop();
 				},
 				(Drinking, KeyCode::Tab) => {
-					let prev_path = match app_state.contracts.current_contract() {
+					let prev_path = match app_state.repl.contracts().current_contract() {
 						Some(c) => c.base_path.clone(),
 						None => continue,
 					};
 
 					let new_path = &app_state
-						.contracts
+						.repl
+						.contracts_mut()
 						.next()
 						.expect("There is at least one contract - just checked")
 						.base_path;
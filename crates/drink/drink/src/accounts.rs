@@ -0,0 +1,197 @@
+//! Well-known dev accounts and helpers to set them up in tests.
+//!
+//! The accounts are the sr25519 keys derived from the usual dev seeds (`//Alice`, `//Bob`, ...),
+//! so they match the accounts of a development chain and can be used in the place of ad-hoc
+//! `AccountId32::new([1u8; 32])` constants.
+//!
+//! Accounts can also be parsed from SS58 addresses or `0x`-prefixed hex strings, e.g. with the
+//! [`account!`](crate::account) macro.
+
+use std::str::FromStr;
+
+#[cfg(feature = "session")]
+use frame_support::sp_runtime::DispatchError;
+#[cfg(feature = "session")]
+use ink_sandbox::{api::prelude::BalanceAPI, AccountIdFor, Sandbox};
+use ink_sandbox::{AccountId32, Ss58Codec};
+use sp_core::{crypto::Pair as _, sr25519};
+use thiserror::Error;
+
+use crate::hex::from_hex;
+#[cfg(feature = "session")]
+use crate::session::Session;
+
+/// The SS58 prefix of Pop Network.
+pub const POP_SS58_PREFIX: u16 = 0;
+
+/// The generic Substrate SS58 prefix.
+pub const SUBSTRATE_SS58_PREFIX: u16 = 42;
+
+/// The `//Alice` dev account (`5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY`).
+pub const ALICE: AccountId32 = AccountId32::new([
+	212, 53, 147, 199, 21, 253, 211, 28, 97, 20, 26, 189, 4, 169, 159, 214, 130, 44, 133, 88, 133,
+	76, 205, 227, 154, 86, 132, 231, 165, 109, 162, 125,
+]);
+
+/// The `//Bob` dev account (`5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty`).
+pub const BOB: AccountId32 = AccountId32::new([
+	142, 175, 4, 21, 22, 135, 115, 99, 38, 201, 254, 161, 126, 37, 252, 82, 135, 97, 54, 147, 201,
+	18, 144, 156, 178, 38, 170, 71, 148, 242, 106, 72,
+]);
+
+/// The `//Charlie` dev account (`5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y`).
+pub const CHARLIE: AccountId32 = AccountId32::new([
+	144, 181, 171, 32, 92, 105, 116, 201, 234, 132, 27, 230, 136, 134, 70, 51, 220, 156, 168, 163,
+	87, 132, 62, 234, 207, 35, 20, 100, 153, 101, 254, 34,
+]);
+
+/// The `//Dave` dev account (`5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy`).
+pub const DAVE: AccountId32 = AccountId32::new([
+	48, 103, 33, 33, 29, 84, 4, 189, 157, 168, 142, 2, 4, 54, 10, 26, 154, 184, 184, 124, 102, 193,
+	188, 47, 205, 211, 127, 60, 34, 34, 204, 32,
+]);
+
+/// The `//Eve` dev account (`5HGjWAeFDfFCWPsjFQdVV2Msvz2XtMktvgocEZcCj68kUMaw`).
+pub const EVE: AccountId32 = AccountId32::new([
+	230, 89, 167, 161, 98, 140, 221, 147, 254, 188, 4, 164, 224, 100, 110, 162, 14, 159, 95, 12,
+	224, 151, 217, 160, 82, 144, 212, 169, 224, 84, 223, 78,
+]);
+
+/// The `//Ferdie` dev account (`5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL`).
+pub const FERDIE: AccountId32 = AccountId32::new([
+	28, 189, 45, 67, 83, 10, 68, 112, 90, 208, 136, 175, 49, 62, 24, 248, 11, 83, 239, 22, 179, 97,
+	119, 205, 75, 119, 184, 70, 242, 165, 240, 124,
+]);
+
+/// All the well-known dev accounts.
+pub const DEV_ACCOUNTS: [AccountId32; 6] = [ALICE, BOB, CHARLIE, DAVE, EVE, FERDIE];
+
+/// Derives the sr25519 dev account from `seed`, e.g. `"//Alice"` or `"//Alice//stash"`.
+///
+/// # Panics
+///
+/// Panics if `seed` is not a valid secret URI.
+pub fn dev_account(seed: &str) -> AccountId32 {
+	derive_account(seed).unwrap_or_else(|_| panic!("Invalid dev account seed `{seed}`"))
+}
+
+/// Derives the sr25519 account from the secret URI `uri`, e.g. `"//Alice/stash"`.
+pub fn derive_account(uri: &str) -> Result<AccountId32, AccountParseError> {
+	sr25519::Pair::from_string(uri, None)
+		.map(|pair| pair.public().into())
+		.map_err(|err| AccountParseError::InvalidSecretUri(uri.to_string(), format!("{err:?}")))
+}
+
+/// Returns the SS58 representation of `account` with the generic Substrate prefix.
+pub fn to_ss58(account: &AccountId32) -> String {
+	account.to_ss58check()
+}
+
+/// Error returned when an account cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum AccountParseError {
+	/// The hex string is not a valid 32-byte account.
+	#[error("Invalid hex account `{0}`: {1}")]
+	InvalidHex(String, String),
+	/// The SS58 address is malformed or its checksum doesn't match.
+	#[error("Invalid SS58 address `{0}`: {1}")]
+	InvalidSs58(String, String),
+	/// The SS58 address is valid, but encoded for another network.
+	#[error("SS58 address `{0}` has an unexpected prefix {1}")]
+	UnexpectedPrefix(String, u16),
+	/// The secret URI cannot be derived into a key.
+	#[error("Invalid secret URI `{0}`: {1}")]
+	InvalidSecretUri(String, String),
+}
+
+/// Parses an account from a `0x`-prefixed hex string or from an SS58 address.
+///
+/// SS58 addresses must be encoded with Pop's prefix ([`POP_SS58_PREFIX`]) or the generic
+/// Substrate one ([`SUBSTRATE_SS58_PREFIX`]), and their checksum is verified.
+pub fn parse_account(account: &str) -> Result<AccountId32, AccountParseError> {
+	if account.starts_with("0x") {
+		let bytes = from_hex(account)
+			.map_err(|err| AccountParseError::InvalidHex(account.to_string(), err))?;
+		return <[u8; 32]>::try_from(bytes).map(AccountId32::new).map_err(|bytes| {
+			AccountParseError::InvalidHex(
+				account.to_string(),
+				format!("expected 32 bytes, got {}", bytes.len()),
+			)
+		});
+	}
+
+	let (parsed, format) = AccountId32::from_ss58check_with_version(account)
+		.map_err(|err| AccountParseError::InvalidSs58(account.to_string(), format!("{err:?}")))?;
+	match u16::from(format) {
+		POP_SS58_PREFIX | SUBSTRATE_SS58_PREFIX => Ok(parsed),
+		prefix => Err(AccountParseError::UnexpectedPrefix(account.to_string(), prefix)),
+	}
+}
+
+/// An account parsed from a string with [`parse_account`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Account(pub AccountId32);
+
+impl TryFrom<&str> for Account {
+	type Error = AccountParseError;
+
+	fn try_from(account: &str) -> Result<Self, Self::Error> {
+		parse_account(account).map(Self)
+	}
+}
+
+impl FromStr for Account {
+	type Err = AccountParseError;
+
+	fn from_str(account: &str) -> Result<Self, Self::Err> {
+		Self::try_from(account)
+	}
+}
+
+impl From<Account> for AccountId32 {
+	fn from(account: Account) -> Self {
+		account.0
+	}
+}
+
+/// Parses an account from an SS58 address or a `0x`-prefixed hex string (see [`parse_account`]).
+///
+/// # Panics
+///
+/// Panics if the account is invalid.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let alice = account!("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+/// assert_eq!(alice, ALICE);
+/// ```
+#[macro_export]
+macro_rules! account {
+	($account:expr) => {
+		$crate::accounts::parse_account($account).unwrap_or_else(|err| panic!("{err}"))
+	};
+}
+
+/// Mints `amount` to each of the `accounts` in the session's sandbox.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// fund_accounts(&mut session, &[ALICE, BOB], 1_000 * UNIT)?;
+/// ```
+#[cfg(feature = "session")]
+pub fn fund_accounts<T>(
+	session: &mut Session<T>,
+	accounts: &[AccountIdFor<T::Runtime>],
+	amount: <T::Runtime as crate::pallet_balances::Config>::Balance,
+) -> Result<(), DispatchError>
+where
+	T: Sandbox,
+	T::Runtime: crate::pallet_balances::Config + crate::pallet_contracts::Config,
+{
+	for account in accounts {
+		session.sandbox().mint_into(account, amount)?;
+	}
+	Ok(())
+}
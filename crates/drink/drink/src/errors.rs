@@ -11,6 +11,24 @@ pub enum Error {
 	/// Bundle loading and parsing has failed
 	#[error("Loading the contract bundle has failed: {0}")]
 	BundleLoadFailed(String),
+	/// The contract bundle is inconsistent, or can't be deployed to the sandbox.
+	#[error("Verifying the contract bundle has failed: {0}")]
+	BundleVerificationFailed(String),
+	/// Exporting or loading a session recording has failed.
+	#[error("Handling the session recording has failed: {0}")]
+	RecordingFailed(String),
+	/// Loading or saving the gas baselines has failed.
+	#[error("Handling the gas baselines has failed: {0}")]
+	GasBaselinesFailed(String),
+	/// Loading or saving the gas profile of a contract has failed.
+	#[error("Handling the gas profile has failed: {0}")]
+	GasProfileFailed(String),
+	/// Exporting or importing the state of a contract has failed.
+	#[error("Handling the contract state has failed: {0}")]
+	ContractStateFailed(String),
+	/// Loading a call scenario has failed.
+	#[error("Loading the scenario has failed: {0}")]
+	ScenarioLoadFailed(String),
 }
 
 /// Every contract message wraps its return value in `Result<T, LangResult>`. This is the error
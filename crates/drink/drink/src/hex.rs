@@ -0,0 +1,26 @@
+//! Minimal helpers for rendering and parsing `0x`-prefixed hex strings.
+
+use std::fmt::Write;
+
+/// Renders `bytes` as a `0x`-prefixed, lowercase hex string.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().fold("0x".to_string(), |mut hex, byte| {
+		let _ = write!(hex, "{byte:02x}");
+		hex
+	})
+}
+
+/// Parses a hex string (with or without the `0x` prefix) into bytes.
+pub(crate) fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+	let hex = hex.strip_prefix("0x").unwrap_or(hex);
+	if !hex.is_ascii() || hex.len() % 2 != 0 {
+		return Err(format!("Invalid length of the hex string: {hex}"));
+	}
+	(0..hex.len())
+		.step_by(2)
+		.map(|i| {
+			u8::from_str_radix(&hex[i..i + 2], 16)
+				.map_err(|err| format!("Invalid hex `{hex}`: {err}"))
+		})
+		.collect()
+}
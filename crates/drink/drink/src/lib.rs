@@ -1,21 +1,35 @@
 //! The drink crate provides a sandboxed runtime for testing smart contracts without a need for
 //! a running node.
 
+pub mod accounts;
 pub mod errors;
+mod hex;
 pub mod pallet_contracts_debugging;
+#[cfg(feature = "repl")]
+pub mod repl;
+#[cfg(feature = "session")]
+pub mod report;
 /// Necessary exports in ink_e2e_sandbox
 #[cfg(feature = "session")]
 pub mod session;
+pub mod signatures;
+#[cfg(feature = "subxt")]
+pub mod subxt_client;
+pub mod units;
 
 #[cfg(feature = "macros")]
-pub use drink_test_macro::{contract_bundle_provider, test};
+pub use drink_test_macro::{contract_bundle_provider, contract_client, contract_events, test};
 pub use errors::Error;
 pub use frame_support;
 pub use ink_sandbox::{
 	self, api as sandbox_api, create_sandbox, impl_sandbox, pallet_assets, pallet_balances,
-	pallet_contracts, pallet_nfts, pallet_timestamp, sp_externalities, AccountId32, DispatchError,
-	Sandbox, Ss58Codec, Weight,
+	pallet_collective, pallet_contracts, pallet_identity, pallet_nfts, pallet_timestamp,
+	sp_externalities, AccountId32, DispatchError, Sandbox, SandboxConfig, SharedSandbox, Ss58Codec,
+	Weight,
 };
+/// Used by the code generated by the `contract_client` macro.
+#[doc(hidden)]
+pub use scale;
 #[cfg(feature = "session")]
 pub use session::mock::{mock_message, ContractMock, MessageMock, MockedCallResult, Selector};
 
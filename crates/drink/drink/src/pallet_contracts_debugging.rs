@@ -29,3 +29,6 @@ pub use runtime::{InterceptingExt, InterceptingExtT, NoopExt, TracingExt, Tracin
 /// Main configuration parameter for the contracts pallet debugging. Provides all the necessary
 /// trait implementations.
 pub enum DrinkDebug {}
+
+// Calls are observed through the runtime extensions instead.
+impl ink_sandbox::debug_hooks::DebugHooks for DrinkDebug {}
@@ -0,0 +1,427 @@
+//! A headless interpreter of the interactive command set of `drink-cli`, so other tools
+//! (notebooks, web backends, `pop-cli`, ...) can drive a session with the same textual commands:
+//!
+//! ```rust, ignore
+//! let mut repl = Repl::new(Session::<MinimalSandbox>::default()).with_cwd("examples/flipper");
+//! repl.execute("deploy true")?;
+//! repl.execute("call flip")?;
+//! println!("{}", repl.execute("list-accounts")?);
+//! ```
+//!
+//! Commands are parsed like the command line of `drink-cli` (see [`ReplCommand`]), and their
+//! arguments are split like by a shell: quoted arguments may contain whitespace. Contracts are
+//! deployed from the `target/ink` directory of the working directory of the REPL, and targeted by
+//! alias afterwards.
+
+use std::{
+	collections::HashMap,
+	env, fs,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use clap::Parser;
+use contract_transcode::ContractMessageTranscoder;
+pub use contracts::{Contract, ContractIndex, ContractRegistry};
+use frame_support::weights::Weight;
+use ink_sandbox::{api::prelude::*, snapshot::Snapshot as SandboxSnapshot, BalanceFor, Sandbox};
+pub use keyring::Keyring;
+use thiserror::Error;
+
+pub use crate::repl::command::{ReplCommand, SnapshotAction};
+use crate::{
+	accounts::AccountParseError,
+	pallet_balances,
+	pallet_contracts::{Config, ContractResult, StorageDeposit},
+	sandbox_api::contracts_api::decode_debug_buffer,
+	session::{error::SessionError, ContractBundle, Session},
+	units::{dot_style_format, units},
+	AccountId32, DispatchError,
+};
+
+mod command;
+mod contracts;
+mod keyring;
+
+/// Error of a REPL command.
+#[derive(Clone, Debug, Error)]
+pub enum ReplError {
+	/// The line is not a valid command.
+	#[error("Invalid command\n{0}")]
+	InvalidCommand(String),
+	/// The contract built in the working directory cannot be loaded.
+	#[error("Failed to load the contract: {0}")]
+	Bundle(String),
+	/// The alias is already taken by another contract.
+	#[error("Alias `{0}` is already taken")]
+	AliasTaken(String),
+	/// There is no contract with the alias.
+	#[error("No contract with alias `{0}`")]
+	UnknownAlias(String),
+	/// No contract was deployed yet.
+	#[error("No deployed contract")]
+	NoContract,
+	/// There is no snapshot with the name.
+	#[error("No snapshot `{0}`")]
+	UnknownSnapshot(String),
+	/// The account or secret URI is invalid.
+	#[error("{0}")]
+	Account(#[from] AccountParseError),
+	/// Minting the tokens failed.
+	#[error("Failed to add tokens: {0:?}")]
+	AddTokens(DispatchError),
+	/// The working directory cannot be changed, or the metadata cannot be written.
+	#[error("{0}")]
+	Io(String),
+	/// The deployment failed.
+	#[error("Failed to deploy contract\n{0}")]
+	Deployment(SessionError),
+	/// The call failed.
+	#[error("Failed to call contract\n{0}")]
+	Call(SessionError),
+}
+
+/// The outcome of the dry run of a deployment or call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DryRun<Balance> {
+	/// The gas required by the deployment or call.
+	pub gas_required: Weight,
+	/// The storage deposit charged or refunded by the deployment or call.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// Whether the contract reverted, or the error of the contracts pallet.
+	pub outcome: Result<bool, DispatchError>,
+}
+
+/// A saved state of the sandbox, together with the contracts deployed at that time.
+#[derive(Clone)]
+pub struct Snapshot {
+	/// The state of the sandbox.
+	pub sandbox: SandboxSnapshot,
+	/// The deployed contracts.
+	pub contracts: ContractRegistry,
+}
+
+/// An interpreter of textual commands over a session. See [`crate::repl`].
+pub struct Repl<T: Sandbox>
+where
+	T::Runtime: Config,
+{
+	session: Session<T>,
+	cwd: PathBuf,
+	contracts: ContractRegistry,
+	keyring: Keyring,
+	snapshots: HashMap<String, Snapshot>,
+}
+
+impl<T: Sandbox> Repl<T>
+where
+	T::Runtime: Config
+		+ pallet_balances::Config<Balance = u128>
+		+ frame_system::Config<AccountId = AccountId32>,
+{
+	/// Creates a REPL over `session`, in the current directory.
+	pub fn new(session: Session<T>) -> Self {
+		Self {
+			session,
+			cwd: env::current_dir().unwrap_or_default(),
+			contracts: Default::default(),
+			keyring: Default::default(),
+			snapshots: Default::default(),
+		}
+	}
+
+	/// Sets the working directory and returns updated `self`.
+	pub fn with_cwd<P: Into<PathBuf>>(mut self, cwd: P) -> Self {
+		self.set_cwd(cwd);
+		self
+	}
+
+	/// Sets the working directory, which contracts are deployed from.
+	pub fn set_cwd<P: Into<PathBuf>>(&mut self, cwd: P) {
+		self.cwd = cwd.into();
+	}
+
+	/// Returns the working directory.
+	pub fn cwd(&self) -> &Path {
+		&self.cwd
+	}
+
+	/// Returns the session.
+	pub fn session(&mut self) -> &mut Session<T> {
+		&mut self.session
+	}
+
+	/// Returns the deployed contracts.
+	pub fn contracts(&self) -> &ContractRegistry {
+		&self.contracts
+	}
+
+	/// Returns the deployed contracts, e.g. to switch the current one or to restore them.
+	pub fn contracts_mut(&mut self) -> &mut ContractRegistry {
+		&mut self.contracts
+	}
+
+	/// Returns the accounts known by their secret URI.
+	pub fn keyring(&self) -> &Keyring {
+		&self.keyring
+	}
+
+	/// Returns the saved snapshots, by name.
+	pub fn snapshots(&self) -> &HashMap<String, Snapshot> {
+		&self.snapshots
+	}
+
+	/// Parses `line` into a command and executes it. Returns the output of the command.
+	pub fn execute(&mut self, line: &str) -> Result<String, ReplError> {
+		let args = [String::new()].into_iter().chain(split_arguments(line)?);
+		let command = ReplCommand::try_parse_from(args)
+			.map_err(|e| ReplError::InvalidCommand(e.to_string()))?;
+		self.run(command)
+	}
+
+	/// Executes `command`. Returns the output of the command.
+	pub fn run(&mut self, command: ReplCommand) -> Result<String, ReplError> {
+		match command {
+			ReplCommand::NextBlock { count } => {
+				self.session.sandbox().build_blocks(count);
+				Ok(format!("{count} blocks built"))
+			},
+			ReplCommand::AddTokens { recipient, value } => self.add_tokens(recipient, value),
+			ReplCommand::SetActor { actor } => {
+				self.session.set_actor(actor);
+				Ok("Actor was set".to_string())
+			},
+			ReplCommand::NewAccount { uri, fund } => {
+				let account = self.keyring.add(&uri)?;
+				let mut output = format!("Account {uri} added: {account}");
+				if fund > 0 {
+					output = format!("{output}\n{}", self.add_tokens(account, units(fund))?);
+				}
+				Ok(output)
+			},
+			ReplCommand::ListAccounts => Ok(self
+				.keyring
+				.get_all()
+				.iter()
+				.map(|(uri, account)| {
+					let balance = self.session.sandbox().free_balance(account);
+					format!("{uri}: {account} ({})", dot_style_format(balance))
+				})
+				.collect::<Vec<_>>()
+				.join("\n")),
+			ReplCommand::SetGasLimit { ref_time, proof_size } => {
+				self.session.set_gas_limit(Weight::from_parts(ref_time, proof_size));
+				Ok("Gas limit was set".to_string())
+			},
+
+			ReplCommand::Deploy { constructor, args, salt, alias, .. } =>
+				self.deploy(constructor, args, salt, alias),
+			ReplCommand::Call { message, args, contract, .. } =>
+				self.call(message, args, contract.as_deref()),
+			ReplCommand::Use { alias } => {
+				let contract = self
+					.contracts
+					.select(&alias)
+					.ok_or_else(|| ReplError::UnknownAlias(alias.clone()))?;
+				self.cwd = contract.base_path.clone();
+				Ok(format!("Using contract `{alias}`"))
+			},
+			ReplCommand::Snapshot { action: SnapshotAction::Save { name } } => {
+				let snapshot = Snapshot {
+					sandbox: self.session.sandbox().take_snapshot(),
+					contracts: self.contracts.clone(),
+				};
+				let output = format!("Snapshot `{name}` saved");
+				self.snapshots.insert(name, snapshot);
+				Ok(output)
+			},
+			ReplCommand::Snapshot { action: SnapshotAction::Restore { name } } => {
+				let snapshot = self
+					.snapshots
+					.get(&name)
+					.ok_or_else(|| ReplError::UnknownSnapshot(name.clone()))?;
+				self.session.sandbox().restore_snapshot(&snapshot.sandbox);
+				self.contracts = snapshot.contracts.clone();
+				Ok(format!("Snapshot `{name}` restored"))
+			},
+
+			ReplCommand::Metadata { path } => {
+				let path = self.cwd.join(path);
+				self.session
+					.sandbox()
+					.export_metadata(&path)
+					.map_err(|err| ReplError::Io(format!("Failed to write metadata: {err}")))?;
+				Ok(format!("Runtime metadata written to {}", path.display()))
+			},
+		}
+	}
+
+	/// Performs a dry run of `command`, if it is a deployment or a call.
+	pub fn dry_run(
+		&mut self,
+		command: &ReplCommand,
+	) -> Result<Option<DryRun<BalanceFor<T::Runtime>>>, ReplError> {
+		match command {
+			ReplCommand::Deploy { constructor, args, salt, .. } => {
+				let bundle = self.load_bundle()?;
+				let result = self
+					.session
+					.dry_run_deployment(bundle, constructor, args, salt.clone(), None)
+					.map_err(ReplError::Deployment)?;
+				let outcome = result.result.as_ref().map(|value| value.result.did_revert());
+				Ok(Some(dry_run(&result, outcome)))
+			},
+			ReplCommand::Call { message, args, contract, .. } => {
+				let address = self.target(contract.as_deref())?.address.clone();
+				let result = self
+					.session
+					.dry_run_call(address, message, args, None)
+					.map_err(ReplError::Call)?;
+				let outcome = result.result.as_ref().map(|value| value.did_revert());
+				Ok(Some(dry_run(&result, outcome)))
+			},
+			_ => Ok(None),
+		}
+	}
+
+	fn add_tokens(&mut self, recipient: AccountId32, value: u128) -> Result<String, ReplError> {
+		self.session
+			.sandbox()
+			.mint_into(&recipient, value)
+			.map_err(ReplError::AddTokens)?;
+		Ok(format!("{value} tokens added to {recipient}"))
+	}
+
+	/// Loads the contract built in the working directory.
+	fn load_bundle(&self) -> Result<ContractBundle, ReplError> {
+		let (name, wasm_path) = find_wasm_blob(&self.cwd)
+			.ok_or_else(|| ReplError::Bundle("Failed to find contract file".to_string()))?;
+		let wasm = fs::read(wasm_path)
+			.map_err(|err| ReplError::Bundle(format!("Failed to read contract bytes\n{err}")))?;
+		let metadata_path = self.cwd.join(format!("target/ink/{name}.json"));
+		let transcoder = ContractMessageTranscoder::load(metadata_path).map_err(|_| {
+			ReplError::Bundle("Failed to create transcoder from metadata file.".to_string())
+		})?;
+		Ok(ContractBundle { name, wasm, transcoder: Arc::new(transcoder), ink_version: None })
+	}
+
+	fn deploy(
+		&mut self,
+		constructor: String,
+		args: Vec<String>,
+		salt: Vec<u8>,
+		alias: Option<String>,
+	) -> Result<String, ReplError> {
+		let ContractBundle { name, wasm, transcoder, .. } = self.load_bundle()?;
+		let alias = match alias {
+			Some(alias) if self.contracts.find(&alias).is_some() =>
+				return Err(ReplError::AliasTaken(alias)),
+			Some(alias) => alias,
+			None => self.contracts.unique_alias(&name),
+		};
+
+		let address = self
+			.session
+			.deploy(wasm, &constructor, &args, salt, None, &transcoder)
+			.map_err(ReplError::Deployment)?;
+		self.contracts.add(Contract {
+			name,
+			alias: alias.clone(),
+			address,
+			base_path: self.cwd.clone(),
+			transcoder,
+		});
+
+		let mut output = format!("Contract deployed successfully as `{alias}`");
+		if let Some(info) = self.session.record().deploy_results().last() {
+			output = format!("{output}\n{}", format_contract_action(info));
+		}
+		Ok(output)
+	}
+
+	/// Returns the contract with the alias `contract`, or the current one.
+	fn target(&self, contract: Option<&str>) -> Result<&Contract, ReplError> {
+		match contract {
+			Some(alias) => self
+				.contracts
+				.find(alias)
+				.ok_or_else(|| ReplError::UnknownAlias(alias.to_string())),
+			None => self.contracts.current_contract().ok_or(ReplError::NoContract),
+		}
+	}
+
+	fn call(
+		&mut self,
+		message: String,
+		args: Vec<String>,
+		contract: Option<&str>,
+	) -> Result<String, ReplError> {
+		let address = self.target(contract)?.address.clone();
+		let result = self
+			.session
+			.call_with_address::<_, ()>(address, &message, &args, None)
+			.map_err(ReplError::Call)?;
+
+		let mut output = format!("Result: {result:?}");
+		if let Some(info) = self.session.record().call_results().last() {
+			output = format!("{output}\n{}", format_contract_action(info));
+		}
+		Ok(output)
+	}
+}
+
+/// Splits `line` into arguments like a shell does: single or double quotes group whitespace
+/// separated words into one argument, and backslashes escape the next character.
+pub fn split_arguments(line: &str) -> Result<Vec<String>, ReplError> {
+	shlex::split(line).ok_or_else(|| {
+		ReplError::InvalidCommand(format!("Unbalanced quotes or escape in `{line}`"))
+	})
+}
+
+fn dry_run<V, Balance: Clone, E>(
+	result: &ContractResult<V, Balance, E>,
+	outcome: Result<bool, &DispatchError>,
+) -> DryRun<Balance> {
+	DryRun {
+		gas_required: result.gas_required,
+		storage_deposit: result.storage_deposit.clone(),
+		outcome: outcome.map_err(|err| *err),
+	}
+}
+
+/// Formats the gas and the debug buffer of a deployment or call.
+pub fn format_contract_action<R, Balance, E>(result: &ContractResult<R, Balance, E>) -> String {
+	let mut output = format!(
+		"Gas consumed: {:?}\nGas required: {:?}\nDebug buffer:\n",
+		result.gas_consumed, result.gas_required
+	);
+
+	for line in &decode_debug_buffer(&result.debug_message) {
+		output.push_str(&format!("  {line}\n"));
+	}
+
+	output
+}
+
+fn find_wasm_blob(cwd: &Path) -> Option<(String, PathBuf)> {
+	let Ok(entries) = fs::read_dir(cwd.join("target/ink")) else {
+		return None;
+	};
+	let Some(file) = entries
+		.into_iter()
+		.filter_map(|e| e.ok())
+		.find(|e| e.path().extension().unwrap_or_default() == "wasm")
+	else {
+		return None;
+	};
+
+	let raw_name = file
+		.file_name()
+		.into_string()
+		.expect("Invalid file name")
+		.strip_suffix(".wasm")
+		.expect("We have just checked file extension")
+		.to_string();
+
+	Some((raw_name, file.path()))
+}
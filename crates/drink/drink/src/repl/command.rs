@@ -0,0 +1,98 @@
+//! The commands of a REPL, parsed like command-line arguments.
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+	accounts::{derive_account, parse_account, AccountParseError},
+	AccountId32,
+};
+
+/// A command of a REPL. See [`crate::repl`].
+#[derive(Clone, Debug, Parser)]
+pub enum ReplCommand {
+	/// Builds the next `count` blocks.
+	#[clap(alias = "nb")]
+	NextBlock {
+		#[clap(default_value = "1")]
+		count: u32,
+	},
+	/// Mints `value` tokens into `recipient`.
+	AddTokens {
+		#[clap(value_parser = parse_account_or_uri)]
+		recipient: AccountId32,
+		value: u128,
+	},
+	/// Sets the actor of the following deployments and calls.
+	SetActor {
+		#[clap(value_parser = parse_account_or_uri)]
+		actor: AccountId32,
+	},
+	/// Derives an account from a secret URI, adds it to the keyring and funds it with `fund`
+	/// units.
+	NewAccount {
+		uri: String,
+		#[clap(long, default_value = "1000")]
+		fund: u128,
+	},
+	/// Lists the accounts of the keyring with their balances.
+	#[clap(alias = "accounts")]
+	ListAccounts,
+	/// Sets the gas limit of the following deployments and calls.
+	SetGasLimit { ref_time: u64, proof_size: u64 },
+
+	/// Deploys the contract built in the working directory.
+	#[clap(alias = "d")]
+	Deploy {
+		#[clap(long, default_value = "new")]
+		constructor: String,
+		args: Vec<String>,
+		#[clap(long, default_values_t = Vec::<u8>::new(), value_delimiter = ',')]
+		salt: Vec<u8>,
+		#[clap(long)]
+		alias: Option<String>,
+		/// Skips the dry-run preview of interactive front-ends.
+		#[clap(long, short)]
+		yes: bool,
+	},
+	/// Calls a message of the contract with the alias `contract`, or of the current one.
+	Call {
+		message: String,
+		args: Vec<String>,
+		#[clap(long)]
+		contract: Option<String>,
+		/// Skips the dry-run preview of interactive front-ends.
+		#[clap(long, short)]
+		yes: bool,
+	},
+	/// Makes the contract with `alias` the current one, and its directory the working one.
+	Use { alias: String },
+	/// Saves or restores the state of the sandbox, together with the deployed contracts.
+	Snapshot {
+		#[clap(subcommand)]
+		action: SnapshotAction,
+	},
+
+	/// Writes the SCALE-encoded runtime metadata to `path`.
+	Metadata {
+		#[clap(default_value = "metadata.scale")]
+		path: String,
+	},
+}
+
+/// An action of [`ReplCommand::Snapshot`].
+#[derive(Clone, Debug, Subcommand)]
+pub enum SnapshotAction {
+	/// Saves the current state as `name`, replacing any snapshot with the same name.
+	Save { name: String },
+	/// Restores the state saved as `name`.
+	Restore { name: String },
+}
+
+/// Parses an account from a secret URI (e.g. `//Alice/stash`), an SS58 address or a hex string.
+fn parse_account_or_uri(account: &str) -> Result<AccountId32, AccountParseError> {
+	if account.starts_with("//") {
+		derive_account(account)
+	} else {
+		parse_account(account)
+	}
+}
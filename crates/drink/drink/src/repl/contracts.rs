@@ -0,0 +1,104 @@
+//! The contracts deployed from a REPL, targeted by alias.
+
+use std::{path::PathBuf, sync::Arc};
+
+use contract_transcode::ContractMessageTranscoder;
+use ContractIndex::{CurrentContract, NoContracts};
+
+use crate::AccountId32;
+
+/// A contract deployed from a REPL.
+#[derive(Clone)]
+pub struct Contract {
+	/// The name of the contract.
+	pub name: String,
+	/// The unique name under which the contract is targeted by commands.
+	pub alias: String,
+	/// The address of the contract.
+	pub address: AccountId32,
+	/// The directory the contract was deployed from.
+	pub base_path: PathBuf,
+	/// The transcoder of the messages of the contract.
+	pub transcoder: Arc<ContractMessageTranscoder>,
+}
+
+/// The position of the current contract in a [`ContractRegistry`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum ContractIndex {
+	/// No contract was deployed yet.
+	#[default]
+	NoContracts,
+	/// The index of the current contract.
+	CurrentContract(usize),
+}
+
+/// The contracts deployed from a REPL, one of them being the current one.
+#[derive(Clone, Default)]
+pub struct ContractRegistry {
+	contracts: Vec<Contract>,
+	index: ContractIndex,
+}
+
+impl ContractRegistry {
+	/// Adds `contract` and makes it the current one.
+	pub fn add(&mut self, contract: Contract) {
+		self.contracts.push(contract);
+		self.index = CurrentContract(self.contracts.len() - 1);
+	}
+
+	/// Returns the position of the current contract.
+	pub fn current_index(&self) -> ContractIndex {
+		self.index
+	}
+
+	/// Returns the current contract, if any.
+	pub fn current_contract(&self) -> Option<&Contract> {
+		match self.index {
+			NoContracts => None,
+			CurrentContract(idx) => Some(&self.contracts[idx]),
+		}
+	}
+
+	/// Returns all the contracts, in the order of deployment.
+	pub fn get_all(&self) -> &[Contract] {
+		&self.contracts
+	}
+
+	/// Returns the contract with `alias`, if any.
+	pub fn find(&self, alias: &str) -> Option<&Contract> {
+		self.contracts.iter().find(|c| c.alias == alias)
+	}
+
+	/// Makes the contract with `alias` the current one.
+	pub fn select(&mut self, alias: &str) -> Option<&Contract> {
+		let idx = self.contracts.iter().position(|c| c.alias == alias)?;
+		self.index = CurrentContract(idx);
+		self.current_contract()
+	}
+
+	/// Returns `name` if no contract has it as alias, `name_<n>` otherwise.
+	pub fn unique_alias(&self, name: &str) -> String {
+		(1..)
+			.map(|n| match n {
+				1 => name.to_string(),
+				n => format!("{name}_{n}"),
+			})
+			.find(|alias| self.find(alias).is_none())
+			.expect("There are finitely many contracts")
+	}
+
+	/// Makes the next contract (in the order of deployment) the current one, and returns it.
+	pub fn next(&mut self) -> Option<&Contract> {
+		let CurrentContract(old_index) = self.index else {
+			return None;
+		};
+
+		self.index = CurrentContract((old_index + 1) % self.contracts.len());
+		self.current_contract()
+	}
+
+	/// Returns the number of contracts.
+	pub fn count(&self) -> usize {
+		self.contracts.len()
+	}
+}
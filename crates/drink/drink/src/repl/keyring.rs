@@ -0,0 +1,41 @@
+//! The accounts known to a REPL by their secret URI.
+
+use crate::{
+	accounts::{derive_account, AccountParseError, DEV_ACCOUNTS},
+	AccountId32,
+};
+
+/// The accounts derived from secret URIs during the session (and the dev accounts), so they can
+/// be referred to by URI instead of SS58 address.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Keyring {
+	accounts: Vec<(String, AccountId32)>,
+}
+
+impl Default for Keyring {
+	fn default() -> Self {
+		let uris = ["//Alice", "//Bob", "//Charlie", "//Dave", "//Eve", "//Ferdie"];
+		Self { accounts: uris.into_iter().map(str::to_string).zip(DEV_ACCOUNTS).collect() }
+	}
+}
+
+impl Keyring {
+	/// Derives the account of `uri` and adds it to the keyring, unless it is already there.
+	pub fn add(&mut self, uri: &str) -> Result<AccountId32, AccountParseError> {
+		let account = derive_account(uri)?;
+		if self.uri_of(&account).is_none() {
+			self.accounts.push((uri.to_string(), account.clone()));
+		}
+		Ok(account)
+	}
+
+	/// Returns the secret URIs and the accounts of the keyring.
+	pub fn get_all(&self) -> &[(String, AccountId32)] {
+		&self.accounts
+	}
+
+	/// Returns the secret URI of `account`, if it is in the keyring.
+	pub fn uri_of(&self, account: &AccountId32) -> Option<&str> {
+		self.accounts.iter().find(|(_, a)| a == account).map(|(uri, _)| uri.as_str())
+	}
+}
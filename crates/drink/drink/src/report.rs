@@ -0,0 +1,68 @@
+//! Reports on the footprint of contracts, e.g. to gate contract bloat in CI.
+//!
+//! ```rust, ignore
+//! let bundle = BundleProvider::local()?;
+//! let stats = report::code_stats(session.sandbox(), &bundle)?;
+//! println!("{} bytes, upload deposit: {}", stats.wasm_size, stats.upload_deposit);
+//! report::assert_max_code_size(&bundle, 32 * 1024);
+//! ```
+
+use ink_sandbox::{api::prelude::*, BalanceFor, Sandbox};
+
+use crate::{
+	pallet_contracts::{Config, Determinism},
+	session::{error::SessionError, ContractBundle},
+};
+
+/// The footprint of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeStats<Balance> {
+	/// Size of the wasm blob, in bytes.
+	pub wasm_size: usize,
+	/// Number of messages exported by the contract.
+	pub messages: usize,
+	/// Number of constructors exported by the contract.
+	pub constructors: usize,
+	/// The deposit held for uploading the code on the sandbox.
+	pub upload_deposit: Balance,
+}
+
+/// Returns the footprint of the contract of `bundle`.
+///
+/// The upload deposit is estimated by a dry run of the upload on `sandbox`, so it reflects the
+/// deposit parameters of the sandbox. Fails if the code cannot be uploaded.
+pub fn code_stats<S: Sandbox>(
+	sandbox: &mut S,
+	bundle: &ContractBundle,
+) -> Result<CodeStats<BalanceFor<S::Runtime>>, SessionError>
+where
+	S::Runtime: Config,
+{
+	let interface = bundle.interface();
+	let upload = sandbox.dry_run(|sandbox| {
+		sandbox.upload_contract(
+			bundle.wasm.clone(),
+			S::default_actor(),
+			None,
+			Determinism::Enforced,
+		)
+	});
+
+	Ok(CodeStats {
+		wasm_size: bundle.wasm.len(),
+		messages: interface.messages.len(),
+		constructors: interface.constructors.len(),
+		upload_deposit: upload.map_err(SessionError::UploadFailed)?.deposit,
+	})
+}
+
+/// Asserts that the wasm blob of `bundle` is at most `max_size` bytes large.
+#[track_caller]
+pub fn assert_max_code_size(bundle: &ContractBundle, max_size: usize) {
+	let size = bundle.wasm.len();
+	assert!(
+		size <= max_size,
+		"Contract code is {size} bytes large, exceeding the maximum of {max_size} bytes by {}",
+		size - max_size
+	);
+}
@@ -4,34 +4,74 @@ use std::{
 	fmt::Debug,
 	mem,
 	sync::{Arc, Mutex},
+	time::Duration,
 };
 
 pub use contract_transcode;
 use contract_transcode::ContractMessageTranscoder;
 use error::SessionError;
-use frame_support::{traits::fungible::Inspect, weights::Weight};
+use frame_support::{
+	sp_runtime::{DispatchError, TransactionOutcome},
+	storage::with_transaction,
+	traits::{fungible::Inspect, Get},
+	weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+pub use ink_sandbox::host_fns::CallMetrics;
 use ink_sandbox::{
-	api::prelude::*, AccountIdFor, ContractExecResultFor, ContractInstantiateResultFor, Sandbox,
+	api::prelude::*, determinism::DeterminismAudit, seed, AccountIdFor, ContractExecResultFor,
+	ContractInstantiateResultFor, EventRecordOf, Sandbox,
 };
-pub use record::{EventBatch, Record};
+pub use record::{event_topic, Action, Checkpoint, ContractEvent, EventBatch, Record};
 use scale::Decode;
 
 use crate::{
 	minimal::MinimalSandboxRuntime,
-	pallet_contracts::{Config, Determinism},
+	pallet_contracts::{CollectEvents, Config, DebugInfo, Determinism},
 	pallet_contracts_debugging::{InterceptingExt, TracingExt},
-	session::mock::MockRegistry,
+	session::{
+		breakpoints::Breakpoints,
+		endowment::EndowmentPolicy,
+		interceptors::{CallOutcome, CallParameters, Interceptor},
+		mock::{MockRegistry, Selector},
+		test_report::TestReporter,
+		timeout::TimedRunner,
+		unused_value::{Balances, UnusedValue},
+		value_conservation::ValueLedger,
+	},
 };
 
+pub mod access_matrix;
+pub mod asset_supply;
+pub mod balance_changes;
+pub mod breakpoints;
+pub mod differential;
+pub mod endowment;
 pub mod mock;
 use mock::MockingExtension;
 pub mod bundle;
+mod deposits;
 pub mod error;
+pub mod event_coverage;
+pub mod fuzz;
+pub mod gas_baselines;
+pub mod gas_profile;
+pub mod interceptors;
+pub mod interface;
 pub mod mocking_api;
 mod record;
+pub mod relay;
+pub mod replay;
+pub mod snapshot;
+pub mod test_report;
+pub mod timeout;
 mod transcoding;
+pub mod unused_value;
+pub mod upgrade;
+pub mod value_conservation;
 
 pub use bundle::ContractBundle;
+pub use interface::{ArgumentInfo, ContractInterface, MessageInfo};
 
 use self::mocking_api::MockingApi;
 use crate::{
@@ -54,6 +94,9 @@ pub const NO_SALT: Vec<u8> = vec![];
 ///
 /// Compatible with any runtime with `u128` as the balance type.
 pub const NO_ENDOWMENT: Option<BalanceOf<MinimalSandboxRuntime>> = None;
+/// Safety margin (in percent) added on top of the weight consumed by a dry run by
+/// `Session::estimate_gas`, like wallets and `pop-cli` do when submitting contract calls.
+pub const GAS_ESTIMATE_MARGIN_PERCENT: u64 = 10;
 
 /// Wrapper around `Sandbox` that provides a convenient API for interacting with multiple contracts.
 ///
@@ -157,12 +200,25 @@ where
 	pub sandbox: T,
 
 	actor: AccountIdFor<T::Runtime>,
-	gas_limit: Weight,
+	gas_limit: Option<Weight>,
+	default_gas_limit: Weight,
 	determinism: Determinism,
+	determinism_audit: bool,
+	endowment_policy: EndowmentPolicy<BalanceOf<T::Runtime>>,
 
 	transcoders: TranscoderRegistry<AccountIdFor<T::Runtime>>,
 	record: Record<T::Runtime>,
 	mocks: Arc<Mutex<MockRegistry<AccountIdFor<T::Runtime>>>>,
+	breakpoints: Arc<Mutex<Breakpoints>>,
+	unused_value_reports: Option<Vec<UnusedValue<T::Runtime>>>,
+	value_ledger: Option<ValueLedger<T::Runtime>>,
+	interceptors: Vec<Box<dyn Interceptor<T::Runtime>>>,
+	timed_runner: Option<TimedRunner<T>>,
+	timed_out: Option<Duration>,
+	test_report: Option<TestReporter<T::Runtime>>,
+
+	seed: u64,
+	salt_nonce: u64,
 }
 
 impl<T: Sandbox> Default for Session<T>
@@ -171,29 +227,48 @@ where
 	T: Default,
 {
 	fn default() -> Self {
+		Self::with_sandbox(T::default())
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	const LOG_TARGET: &'static str = "drink::session";
+
+	/// Creates a new session on `sandbox`, e.g. created from a configuration (see
+	/// `ink_sandbox::SandboxConfig`).
+	pub fn with_sandbox(mut sandbox: T) -> Self {
 		let mocks = Arc::new(Mutex::new(MockRegistry::new()));
-		let mut sandbox = T::default();
+		let breakpoints = Arc::new(Mutex::new(Breakpoints::default()));
 		sandbox.register_extension(InterceptingExt(Box::new(MockingExtension {
 			mock_registry: Arc::clone(&mocks),
+			breakpoints: Arc::clone(&breakpoints),
 		})));
 
 		Self {
+			default_gas_limit: sandbox.configured_gas_limit(),
 			sandbox,
 			mocks,
+			breakpoints,
 			actor: T::default_actor(),
-			gas_limit: T::default_gas_limit(),
+			gas_limit: None,
 			determinism: Determinism::Enforced,
+			determinism_audit: false,
+			endowment_policy: EndowmentPolicy::None,
 			transcoders: TranscoderRegistry::new(),
 			record: Default::default(),
+			unused_value_reports: None,
+			value_ledger: None,
+			interceptors: Vec::new(),
+			timed_runner: None,
+			timed_out: None,
+			test_report: None,
+			seed: seed::suite_seed(),
+			salt_nonce: 0,
 		}
 	}
-}
-
-impl<T: Sandbox> Session<T>
-where
-	T::Runtime: Config,
-{
-	const LOG_TARGET: &'static str = "drink::session";
 
 	/// Sets a new actor and returns updated `self`.
 	pub fn with_actor(self, actor: AccountIdFor<T::Runtime>) -> Self {
@@ -210,19 +285,63 @@ where
 		mem::replace(&mut self.actor, actor)
 	}
 
+	/// Sets the seed of the salts generated by `next_salt` and returns updated `self`.
+	///
+	/// By default, the seed of the test suite is used (see `ink_sandbox::seed`), so runs are
+	/// reproducible with the `DRINK_SEED` environment variable.
+	pub fn with_seed(self, seed: u64) -> Self {
+		Self { seed, salt_nonce: 0, ..self }
+	}
+
+	/// Returns the seed of the salts generated by `next_salt`.
+	pub fn get_seed(&self) -> u64 {
+		self.seed
+	}
+
+	/// Returns a new salt, derived from the seed of the session.
+	///
+	/// An alternative to `NO_SALT` for deploying the same code multiple times, which is
+	/// reproducible for a given seed.
+	pub fn next_salt(&mut self) -> Vec<u8> {
+		self.salt_nonce += 1;
+		seed::derive(self.seed, (b"salt", self.salt_nonce)).to_vec()
+	}
+
 	/// Sets a new gas limit and returns updated `self`.
 	pub fn with_gas_limit(self, gas_limit: Weight) -> Self {
-		Self { gas_limit, ..self }
+		Self { gas_limit: Some(gas_limit), ..self }
 	}
 
 	/// Sets a new gas limit and returns the old one.
 	pub fn set_gas_limit(&mut self, gas_limit: Weight) -> Weight {
-		mem::replace(&mut self.gas_limit, gas_limit)
+		let old = self.get_gas_limit();
+		self.gas_limit = Some(gas_limit);
+		old
 	}
 
-	/// Returns currently set gas limit.
+	/// Returns currently set gas limit: the default gas limit, unless another one was set.
 	pub fn get_gas_limit(&self) -> Weight {
-		self.gas_limit
+		self.gas_limit.unwrap_or(self.default_gas_limit)
+	}
+
+	/// Sets a new default gas limit and returns updated `self`.
+	pub fn with_default_gas_limit(mut self, default_gas_limit: Weight) -> Self {
+		self.set_default_gas_limit(default_gas_limit);
+		self
+	}
+
+	/// Sets a new default gas limit and returns the old one.
+	///
+	/// The default gas limit is used by the deployments and calls (and the mocks) of the session
+	/// until a gas limit is set with `set_gas_limit`. It starts as the one configured for the
+	/// sandbox (see `ink_sandbox::SandboxParameters::default_gas_limit`).
+	pub fn set_default_gas_limit(&mut self, default_gas_limit: Weight) -> Weight {
+		mem::replace(&mut self.default_gas_limit, default_gas_limit)
+	}
+
+	/// Returns the default gas limit.
+	pub fn get_default_gas_limit(&self) -> Weight {
+		self.default_gas_limit
 	}
 
 	/// Sets a new determinism policy and returns updated `self`.
@@ -235,6 +354,34 @@ where
 		mem::replace(&mut self.determinism, determinism)
 	}
 
+	/// Enables the determinism audit mode and returns updated `self`, e.g. for testing contracts
+	/// meant to be used as dependencies (through `delegate_call`).
+	///
+	/// Code is uploaded and called with `Determinism::Enforced`, and uploading or deploying code
+	/// using non-deterministic instructions fails with `SessionError::NonDeterministicCode`
+	/// instead of an opaque `CodeRejected` (see `ink_sandbox::determinism`).
+	pub fn with_determinism_audit(self) -> Self {
+		Self { determinism: Determinism::Enforced, determinism_audit: true, ..self }
+	}
+
+	/// Returns `SessionError::NonDeterministicCode` if `error` is due to `code` using
+	/// non-deterministic instructions and the determinism audit mode is enabled.
+	fn audit_rejection(
+		&mut self,
+		code: Option<Vec<u8>>,
+		error: DispatchError,
+	) -> Option<SessionError> {
+		let code_rejected: DispatchError =
+			crate::pallet_contracts::Error::<T::Runtime>::CodeRejected.into();
+		if error != code_rejected {
+			return None;
+		}
+		match self.sandbox.audit_determinism(code?, self.actor.clone()) {
+			DeterminismAudit::NonDeterministic => Some(SessionError::NonDeterministicCode),
+			_ => None,
+		}
+	}
+
 	/// Register a transcoder for a particular contract and returns updated `self`.
 	pub fn with_transcoder(
 		mut self,
@@ -251,9 +398,18 @@ where
 		contract_address: AccountIdFor<T::Runtime>,
 		transcoder: &Arc<ContractMessageTranscoder>,
 	) {
+		self.report_transcoder(&contract_address, transcoder);
 		self.transcoders.register(contract_address, transcoder);
 	}
 
+	/// Returns the transcoder registered for a particular contract, if any.
+	pub fn get_transcoder(
+		&self,
+		contract_address: &AccountIdFor<T::Runtime>,
+	) -> Option<Arc<ContractMessageTranscoder>> {
+		self.transcoders.get(contract_address)
+	}
+
 	/// The underlying `Sandbox` instance.
 	pub fn sandbox(&mut self) -> &mut T {
 		&mut self.sandbox
@@ -284,11 +440,40 @@ where
 			.map(|_| self)
 	}
 
+	/// Returns the current position in the events of the sandbox: the current block and the
+	/// number of events emitted in it so far.
+	fn events_cursor(&mut self) -> (BlockNumberFor<T::Runtime>, usize) {
+		(self.sandbox.block_number(), self.sandbox.events().len())
+	}
+
+	/// Returns the events emitted since `cursor` (see [`Self::events_cursor`]). If a block was
+	/// built in the meantime, the events were reset along with it, so the events emitted since the
+	/// reset are returned.
+	fn events_since_cursor(
+		&mut self,
+		(block, start): (BlockNumberFor<T::Runtime>, usize),
+	) -> Vec<EventRecordOf<T::Runtime>> {
+		let events = self.sandbox.events();
+		if self.sandbox.block_number() != block {
+			return events;
+		}
+		// After a timeout, the sandbox is a fresh one.
+		events.get(start..).unwrap_or_default().to_vec()
+	}
+
 	fn record_events<V>(&mut self, recording: impl FnOnce(&mut Self) -> V) -> V {
-		let start = self.sandbox.events().len();
+		let cursor = self.events_cursor();
+		// Discard the runtime calls dispatched outside of the recorded interactions (e.g. during
+		// dry runs).
+		self.sandbox.take_runtime_calls();
+		self.sandbox.take_call_metrics();
 		let result = recording(self);
-		let events = self.sandbox.events()[start..].to_vec();
+		let events = self.events_since_cursor(cursor);
 		self.record.push_event_batches(events);
+		let calls = self.sandbox.take_runtime_calls();
+		self.record.push_runtime_calls(calls);
+		let metrics = self.sandbox.take_call_metrics();
+		self.record.push_call_metrics(metrics);
 		result
 	}
 
@@ -307,32 +492,71 @@ where
 			.encode(constructor, args)
 			.map_err(|err| SessionError::Encoding(err.to_string()))?;
 
-		let result = self.record_events(|session| {
-			session.sandbox.deploy_contract(
-				contract_bytes,
-				endowment.unwrap_or_default(),
-				data,
-				salt,
-				session.actor.clone(),
-				session.gas_limit,
-				None,
-			)
+		let address =
+			self.deploy_encoded(contract_bytes, data, salt, endowment.unwrap_or_default())?;
+		self.set_transcoder(address.clone(), transcoder);
+		Ok(address)
+	}
+
+	fn deploy_encoded(
+		&mut self,
+		contract_bytes: Vec<u8>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		endowment: BalanceOf<T::Runtime>,
+	) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+		let mut params =
+			CallParameters::new(None, self.actor.clone(), data, endowment, self.get_gas_limit());
+		self.intercept_before(&mut params);
+		self.record.push_action(Action::Deploy {
+			actor: params.actor.clone(),
+			code: contract_bytes.clone(),
+			data: params.data.clone(),
+			salt: salt.clone(),
+			endowment: params.endowment,
+			gas_limit: params.gas_limit,
 		});
 
+		let audited_code = self.determinism_audit.then(|| contract_bytes.clone());
+		let (actor, input, endowment, gas_limit) =
+			(params.actor.clone(), params.data.clone(), params.endowment, params.gas_limit);
+		let result = self.record_events(|session| {
+			session.execute_contract(move |sandbox| {
+				sandbox.deploy_contract(
+					contract_bytes,
+					endowment,
+					input,
+					salt,
+					actor,
+					gas_limit,
+					None,
+				)
+			})
+		})?;
+		self.check_value_conservation("deploying a contract");
+		let outcome = CallOutcome {
+			gas_consumed: result.gas_consumed,
+			gas_required: result.gas_required,
+			result: result.result.as_ref().map(|result| result.result.clone()).map_err(|err| *err),
+		};
+		self.intercept_after(&params, &outcome);
+
 		let ret = match &result.result {
 			Ok(exec_result) if exec_result.result.did_revert() =>
 				Err(SessionError::DeploymentReverted),
 			Ok(exec_result) => {
 				let address = exec_result.account_id.clone();
 				self.record.push_deploy_return(address.clone());
-				self.transcoders.register(address.clone(), transcoder);
 
 				Ok(address)
 			},
-			Err(err) => Err(SessionError::DeploymentFailed(*err)),
+			Err(err) => Err(self
+				.audit_rejection(audited_code, *err)
+				.unwrap_or(SessionError::DeploymentFailed(*err))),
 		};
 
 		self.record.push_deploy_result(result);
+		self.report_interaction();
 		ret
 	}
 
@@ -349,6 +573,7 @@ where
 		salt: Vec<u8>,
 		endowment: Option<BalanceOf<T::Runtime>>,
 	) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+		self.verify_api_version(&contract_file)?;
 		self.deploy(
 			contract_file.wasm,
 			constructor,
@@ -359,6 +584,41 @@ where
 		)
 	}
 
+	/// Similar to `deploy_bundle`, but with a salt generated by `next_salt`.
+	///
+	/// Deploying the same bundle repeatedly with identical arguments doesn't fail with
+	/// `DuplicateContract`, as every deployment gets a new salt.
+	pub fn deploy_with_auto_salt<S: AsRef<str> + Debug>(
+		&mut self,
+		contract_file: ContractBundle,
+		constructor: &str,
+		args: &[S],
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+		let salt = self.next_salt();
+		self.deploy_bundle(contract_file, constructor, args, salt, endowment)
+	}
+
+	/// Deploys a contract with the constructor of the given selector and already encoded
+	/// arguments. In case of success, returns the address of the deployed contract.
+	///
+	/// No transcoder is registered for the contract, see `set_transcoder`.
+	pub fn deploy_by_selector(
+		&mut self,
+		contract_bytes: Vec<u8>,
+		selector: Selector,
+		input: Vec<u8>,
+		salt: Vec<u8>,
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> Result<AccountIdFor<T::Runtime>, SessionError> {
+		log::debug!(
+			target: Self::LOG_TARGET,
+			"deploy_by_selector: selector={selector:?}, input={input:?}, endowment={endowment:?}",
+		);
+		let data = [selector.as_slice(), &input].concat();
+		self.deploy_encoded(contract_bytes, data, salt, endowment.unwrap_or_default())
+	}
+
 	/// Performs a dry run of the deployment of a contract.
 	pub fn dry_run_deployment<S: AsRef<str> + Debug>(
 		&mut self,
@@ -373,6 +633,7 @@ where
 			.encode(constructor, args)
 			.map_err(|err| SessionError::Encoding(err.to_string()))?;
 
+		let gas_limit = self.get_gas_limit();
 		Ok(self.sandbox.dry_run(|sandbox| {
 			sandbox.deploy_contract(
 				contract_file.wasm,
@@ -380,7 +641,7 @@ where
 				data,
 				salt,
 				self.actor.clone(),
-				self.gas_limit,
+				gas_limit,
 				None,
 			)
 		}))
@@ -415,24 +676,36 @@ where
 			"upload: bytes={:?}",
 			contract_bytes
 		);
+		self.record.push_action(Action::Upload {
+			actor: self.actor.clone(),
+			code: contract_bytes.clone(),
+			determinism: self.determinism,
+		});
+
+		let audited_code = self.determinism_audit.then(|| contract_bytes.clone());
 		let result = self.sandbox.upload_contract(
 			contract_bytes,
 			self.actor.clone(),
 			None,
 			self.determinism,
 		);
+		self.check_value_conservation("uploading code");
 
-		result
-			.map(|upload_result| upload_result.code_hash)
-			.map_err(SessionError::UploadFailed)
+		result.map(|upload_result| upload_result.code_hash).map_err(|err| {
+			self.audit_rejection(audited_code, err)
+				.unwrap_or(SessionError::UploadFailed(err))
+		})
 	}
 
 	/// Similar to `upload_and` but takes the contract bundle as the first argument.
 	///
 	/// You can obtain it using `ContractBundle::load("some/path/your.contract")` or
 	/// `local_contract_file!()`
-	pub fn upload_bundle_and(self, contract_file: ContractBundle) -> Result<Self, SessionError> {
-		self.upload_and(contract_file.wasm)
+	pub fn upload_bundle_and(
+		mut self,
+		contract_file: ContractBundle,
+	) -> Result<Self, SessionError> {
+		self.upload_bundle(contract_file).map(|_| self)
 	}
 
 	/// Similar to `upload` but takes the contract bundle as the first argument.
@@ -443,6 +716,7 @@ where
 		&mut self,
 		contract_file: ContractBundle,
 	) -> Result<HashFor<T::Runtime>, SessionError> {
+		self.verify_api_version(&contract_file)?;
 		self.upload(contract_file.wasm)
 	}
 
@@ -481,6 +755,9 @@ where
 	}
 
 	/// Calls the last deployed contract. In case of a successful call, returns the encoded result.
+	///
+	/// Without `endowment`, payable messages are endowed according to the default endowment policy
+	/// of the session (see `set_default_endowment`).
 	pub fn call<S: AsRef<str> + Debug, V: Decode>(
 		&mut self,
 		message: &str,
@@ -530,6 +807,27 @@ where
 		self.call_internal(Some(address), message, args, endowment)
 	}
 
+	/// Calls the message with the given selector of a contract, with already encoded arguments.
+	///
+	/// Unlike the other call methods, it doesn't need a transcoder for the contract, so it can
+	/// invoke messages whose names clash across traits or selectors that the contract doesn't
+	/// declare at all (e.g. to assert the behavior of its fallback). In case of a successful call,
+	/// returns the decoded result.
+	pub fn call_by_selector<V: Decode>(
+		&mut self,
+		address: AccountIdFor<T::Runtime>,
+		selector: Selector,
+		input: Vec<u8>,
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> Result<MessageResult<V>, SessionError> {
+		log::debug!(
+			target: Self::LOG_TARGET,
+			"call_by_selector: address={address}, selector={selector:?}, input={input:?}, endowment={endowment:?}",
+		);
+		let data = [selector.as_slice(), &input].concat();
+		self.call_encoded(address, data, endowment.unwrap_or_default())
+	}
+
 	/// Performs a dry run of a contract call.
 	pub fn dry_run_call<S: AsRef<str> + Debug>(
 		&mut self,
@@ -538,27 +836,110 @@ where
 		args: &[S],
 		endowment: Option<BalanceOf<T::Runtime>>,
 	) -> Result<ContractExecResultFor<T::Runtime>, SessionError> {
-		let data = self
-			.transcoders
-			.get(&address)
-			.as_ref()
-			.ok_or(SessionError::NoTranscoder)?
+		let transcoder = self.transcoders.get(&address).ok_or(SessionError::NoTranscoder)?;
+		let endowment = self.endowment_of(&transcoder, message, endowment)?;
+		let data = transcoder
 			.encode(message, args)
 			.map_err(|err| SessionError::Encoding(err.to_string()))?;
 
+		let gas_limit = self.get_gas_limit();
 		Ok(self.sandbox.dry_run(|sandbox| {
 			sandbox.call_contract(
 				address,
-				endowment.unwrap_or_default(),
+				endowment,
 				data,
 				self.actor.clone(),
-				self.gas_limit,
+				gas_limit,
 				None,
 				self.determinism,
 			)
 		}))
 	}
 
+	/// Estimates the gas limit of a call of the last deployed contract, the way wallets do.
+	///
+	/// Performs a dry run of the call and returns the required weight increased by
+	/// `GAS_ESTIMATE_MARGIN_PERCENT`, so tests can assert that production gas estimates fit within
+	/// the per-call limits. Fails if the dry run fails or reverts.
+	pub fn estimate_gas<S: AsRef<str> + Debug>(
+		&mut self,
+		message: &str,
+		args: &[S],
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> Result<Weight, SessionError> {
+		let address = self.record.deploy_returns().last().ok_or(SessionError::NoContract)?.clone();
+		let result = self.dry_run_call(address, message, args, endowment)?;
+		match result.result {
+			Ok(exec_result) if exec_result.did_revert() =>
+				Err(SessionError::CallReverted(exec_result.data)),
+			Ok(_) => {
+				let margin = |value: u64| {
+					value.saturating_add(value.saturating_mul(GAS_ESTIMATE_MARGIN_PERCENT) / 100)
+				};
+				Ok(Weight::from_parts(
+					margin(result.gas_required.ref_time()),
+					margin(result.gas_required.proof_size()),
+				))
+			},
+			Err(err) => Err(SessionError::CallFailed(err)),
+		}
+	}
+
+	/// Queries the last deployed contract, e.g. calls a getter. In case of a successful call,
+	/// returns the decoded result.
+	///
+	/// A fast path for read-only messages, e.g. for assertion loops over many accounts: the call
+	/// runs with the maximum weight of a block as its gas limit, without debug buffer and events,
+	/// and its storage changes are rolled back instead of snapshotting the whole state like
+	/// `dry_run_call` does. The call isn't recorded in the record of the session.
+	pub fn query<V: Decode>(
+		&mut self,
+		message: &str,
+		args: &[impl AsRef<str> + Debug],
+	) -> Result<MessageResult<V>, SessionError> {
+		let address = self.record.deploy_returns().last().ok_or(SessionError::NoContract)?.clone();
+		let data = self
+			.transcoders
+			.get(&address)
+			.as_ref()
+			.ok_or(SessionError::NoTranscoder)?
+			.encode(message, args)
+			.map_err(|err| SessionError::Encoding(err.to_string()))?;
+
+		let gas_limit = <T::Runtime as frame_system::Config>::BlockWeights::get().max_block;
+		let origin = self.actor.clone();
+		let determinism = self.determinism;
+		let result = self.sandbox.execute_with(|| {
+			with_transaction(|| {
+				TransactionOutcome::Rollback(Ok::<_, DispatchError>(
+					crate::pallet_contracts::Pallet::<T::Runtime>::bare_call(
+						origin,
+						address,
+						Default::default(),
+						gas_limit,
+						None,
+						data,
+						DebugInfo::Skip,
+						CollectEvents::Skip,
+						determinism,
+					)
+					.result,
+				))
+			})
+		});
+
+		match result.and_then(|result| result) {
+			Ok(exec_result) if exec_result.did_revert() =>
+				Err(SessionError::CallReverted(exec_result.data)),
+			Ok(exec_result) => MessageResult::decode(&mut &exec_result.data[..]).map_err(|err| {
+				SessionError::Decoding(format!(
+					"Failed to decode the result of calling a contract: {err:?}"
+				))
+			}),
+			Err(err) => Err(SessionError::CallFailed(err)),
+		}
+	}
+
 	fn call_internal<S: AsRef<str> + Debug, V: Decode>(
 		&mut self,
 		address: Option<AccountIdFor<T::Runtime>>,
@@ -571,30 +952,76 @@ where
 			None => self.record.deploy_returns().last().ok_or(SessionError::NoContract)?.clone(),
 		};
 
-		let data = self
-			.transcoders
-			.get(&address)
-			.as_ref()
-			.ok_or(SessionError::NoTranscoder)?
+		let transcoder = self.transcoders.get(&address).ok_or(SessionError::NoTranscoder)?;
+		let endowment = self.endowment_of(&transcoder, message, endowment)?;
+		let data = transcoder
 			.encode(message, args)
 			.map_err(|err| SessionError::Encoding(err.to_string()))?;
 
-		let result = self.record_events(|session| {
-			session.sandbox.call_contract(
-				address,
-				endowment.unwrap_or_default(),
-				data,
-				session.actor.clone(),
-				session.gas_limit,
-				None,
-				session.determinism,
-			)
+		self.call_encoded(address, data, endowment)
+	}
+
+	fn call_encoded<V: Decode>(
+		&mut self,
+		address: AccountIdFor<T::Runtime>,
+		data: Vec<u8>,
+		endowment: BalanceOf<T::Runtime>,
+	) -> Result<MessageResult<V>, SessionError> {
+		let mut params = CallParameters::new(
+			Some(address.clone()),
+			self.actor.clone(),
+			data,
+			endowment,
+			self.get_gas_limit(),
+		);
+		self.intercept_before(&mut params);
+		self.record.push_action(Action::Call {
+			actor: params.actor.clone(),
+			address: address.clone(),
+			data: params.data.clone(),
+			endowment: params.endowment,
+			gas_limit: params.gas_limit,
+			determinism: self.determinism,
 		});
 
+		// Only calls with an endowment are checked, so the balances are taken for them only.
+		let balances_before = (self.unused_value_reports.is_some() &&
+			params.endowment > Default::default())
+		.then(|| self.sandbox.execute_with(Balances::<T::Runtime>::take));
+
+		let (callee, actor, input, endowment, gas_limit, determinism) = (
+			address.clone(),
+			params.actor.clone(),
+			params.data.clone(),
+			params.endowment,
+			params.gas_limit,
+			self.determinism,
+		);
+		let result = self.record_events(|session| {
+			session.execute_contract(move |sandbox| {
+				sandbox.call_contract(callee, endowment, input, actor, gas_limit, None, determinism)
+			})
+		})?;
+		self.check_value_conservation("calling a contract");
+		let outcome = CallOutcome {
+			gas_consumed: result.gas_consumed,
+			gas_required: result.gas_required,
+			result: result.result.clone(),
+		};
+		self.intercept_after(&params, &outcome);
+
 		let ret = match &result.result {
 			Ok(exec_result) if exec_result.did_revert() =>
 				Err(SessionError::CallReverted(exec_result.data.clone())),
 			Ok(exec_result) => {
+				if let Some(before) = balances_before {
+					let report = self.sandbox.execute_with(|| {
+						before.unused_value(&address, &params.actor, &params.data, params.endowment)
+					});
+					if let Some(reports) = &mut self.unused_value_reports {
+						reports.extend(report);
+					}
+				}
 				self.record.push_call_return(exec_result.data.clone());
 				self.record.last_call_return_decoded::<V>()
 			},
@@ -602,6 +1029,7 @@ where
 		};
 
 		self.record.push_call_result(result);
+		self.report_interaction();
 		ret
 	}
 
@@ -0,0 +1,188 @@
+//! Authorization audits of whole contracts, checking which actors can call which messages.
+//!
+//! Every message is called by every actor (each call in a dry run, so the calls don't affect each
+//! other), and whether the call went through is compared with the expected permissions. See
+//! `access_matrix!`.
+
+use std::fmt::{self, Display};
+
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, Session},
+};
+
+/// A message whose permissions are audited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageAccess {
+	/// The name of the message.
+	pub message: String,
+	/// The arguments of the message.
+	pub args: Vec<String>,
+	/// Whether each actor is expected to be allowed to call the message, in the order of the
+	/// actors of the matrix.
+	pub expected: Vec<bool>,
+}
+
+/// The permissions of the actors for a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessRow {
+	/// The name of the message.
+	pub message: String,
+	/// Whether each actor is expected to be allowed to call the message.
+	pub expected: Vec<bool>,
+	/// Whether the call of each actor went through (neither failed nor reverted).
+	pub actual: Vec<bool>,
+}
+
+/// The outcome of an authorization audit: for every message and every actor, whether the actor
+/// could call the message, against the expected permissions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessMatrix {
+	/// The names of the actors.
+	pub actors: Vec<String>,
+	/// The permissions, by message.
+	pub rows: Vec<AccessRow>,
+}
+
+impl AccessMatrix {
+	/// Returns the (message, actor) pairs whose permission differs from the expected one.
+	pub fn mismatches(&self) -> Vec<(&str, &str)> {
+		self.rows
+			.iter()
+			.flat_map(|row| {
+				self.actors
+					.iter()
+					.zip(row.expected.iter().zip(&row.actual))
+					.filter(|(_, (expected, actual))| expected != actual)
+					.map(|(actor, _)| (row.message.as_str(), actor.as_str()))
+			})
+			.collect()
+	}
+
+	/// Asserts that all the permissions are the expected ones, printing the matrix otherwise.
+	#[track_caller]
+	pub fn assert(&self) {
+		let mismatches = self.mismatches();
+		assert!(
+			mismatches.is_empty(),
+			"{} permission(s) differ from the expected ones:\n{self}",
+			mismatches.len()
+		);
+	}
+}
+
+impl Display for AccessMatrix {
+	/// Renders the matrix as a table, `✓` marking the allowed calls and `✗` the denied ones.
+	/// Unexpected permissions are marked with `!`.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let message_width = self.rows.iter().map(|row| row.message.len()).max().unwrap_or_default();
+		write!(f, "{:message_width$}", "")?;
+		for actor in &self.actors {
+			write!(f, " | {actor:^3}")?;
+		}
+		writeln!(f)?;
+
+		for row in &self.rows {
+			write!(f, "{:message_width$}", row.message)?;
+			for (actor, (expected, actual)) in
+				self.actors.iter().zip(row.expected.iter().zip(&row.actual))
+			{
+				let cell = match (actual, expected == actual) {
+					(true, true) => "✓",
+					(false, true) => "✗",
+					(true, false) => "✓!",
+					(false, false) => "✗!",
+				};
+				write!(f, " | {cell:^width$}", width = actor.chars().count().max(3))?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Calls every message with every actor on the last deployed contract, and returns the
+	/// resulting access matrix. Each call is dry-run, so the state of the session is unchanged.
+	///
+	/// # Arguments
+	///
+	/// * `actors` - The actors, with their names.
+	/// * `messages` - The messages, with the expected permissions of the actors.
+	pub fn access_matrix(
+		&mut self,
+		actors: &[(String, AccountIdFor<T::Runtime>)],
+		messages: &[MessageAccess],
+	) -> Result<AccessMatrix, SessionError> {
+		let address = self.record.deploy_returns().last().ok_or(SessionError::NoContract)?.clone();
+		let original_actor = self.get_actor();
+
+		let mut rows = Vec::with_capacity(messages.len());
+		for access in messages {
+			let mut actual = Vec::with_capacity(actors.len());
+			for (_, actor) in actors {
+				self.set_actor(actor.clone());
+				let result =
+					self.dry_run_call(address.clone(), &access.message, &access.args, None);
+				actual.push(
+					matches!(result, Ok(result) if result.result.is_ok_and(|r| !r.did_revert())),
+				);
+			}
+			rows.push(AccessRow {
+				message: access.message.clone(),
+				expected: access.expected.clone(),
+				actual,
+			});
+		}
+		self.set_actor(original_actor);
+
+		Ok(AccessMatrix { actors: actors.iter().map(|(name, _)| name.clone()).collect(), rows })
+	}
+}
+
+/// Audits the permissions of the last deployed contract of the session: calls every message with
+/// every actor (in dry runs) and asserts that exactly the expected calls go through. Evaluates to
+/// the [`AccessMatrix`](crate::session::access_matrix::AccessMatrix).
+///
+/// A call goes through if it neither fails nor reverts, e.g. a message returning an `Err` is
+/// denied.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// access_matrix!(session,
+///     actors: [ALICE, BOB],
+///     messages: [
+///         "mint"(BOB.to_string(), "10") => [true, false],
+///         "transfer"(ALICE.to_string(), "0") => [true, true],
+///     ]
+/// );
+/// ```
+#[macro_export]
+macro_rules! access_matrix {
+	(
+		$session:expr,
+		actors: [ $( $actor:expr ),* $(,)? ],
+		messages: [ $( $message:literal ( $( $arg:expr ),* $(,)? ) => [ $( $expected:expr ),* $(,)? ] ),* $(,)? ] $(,)?
+	) => {{
+		let matrix = $session
+			.access_matrix(
+				&[ $( (stringify!($actor).to_string(), ($actor).clone()) ),* ],
+				&[ $(
+					$crate::session::access_matrix::MessageAccess {
+						message: $message.to_string(),
+						args: vec![ $( ($arg).to_string() ),* ],
+						expected: vec![ $( $expected ),* ],
+					}
+				),* ],
+			)
+			.expect("Failed to build the access matrix");
+		matrix.assert();
+		matrix
+	}};
+}
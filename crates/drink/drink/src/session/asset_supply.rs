@@ -0,0 +1,170 @@
+//! Token accounting of trust-backed assets, for detecting supply leaks of contracts wrapping them
+//! (e.g. as PSP22 tokens) without listing the holders of the asset by hand.
+//!
+//! The holders of an asset are tracked from the events of the assets pallet recorded by the
+//! session (issuances, transfers, burns, ...). The total supply of the asset must be equal to the
+//! sum of their balances, which is asserted with [`crate::assert_asset_supply_invariant`] after
+//! each call:
+//!
+//! ```rust, ignore
+//! session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+//! assert_asset_supply_invariant!(session, TOKEN);
+//! ```
+//!
+//! Holders funded outside the session (e.g. directly through the sandbox) don't appear in the
+//! recorded events, and must be listed explicitly.
+
+use std::collections::BTreeSet;
+
+use frame_support::sp_runtime::traits::Saturating;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{api::prelude::AssetsAPI, AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_assets::{self, Instance1},
+	pallet_contracts::Config,
+	session::{EventBatch, Record, Session},
+};
+
+type AssetIdOf<R> = <R as pallet_assets::Config<Instance1>>::AssetId;
+type AssetBalanceOf<R> = <R as pallet_assets::Config<Instance1>>::Balance;
+
+/// The total supply of an asset and the sum of the balances of its tracked holders.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetSupply<Balance> {
+	/// The total supply of the asset.
+	pub total_supply: Balance,
+	/// The sum of the balances of the tracked holders of the asset.
+	pub holders_balance: Balance,
+	/// The number of tracked holders of the asset.
+	pub holders: usize,
+}
+
+impl<Balance: PartialEq> AssetSupply<Balance> {
+	/// Returns whether the whole supply is held by the tracked holders.
+	pub fn is_accounted_for(&self) -> bool {
+		self.total_supply == self.holders_balance
+	}
+}
+
+impl<R> EventBatch<R>
+where
+	R: pallet_assets::Config<Instance1>,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_assets::Event<R, Instance1>>,
+{
+	/// Returns the accounts whose balance in `asset` may have changed during the contract
+	/// interaction, according to the events of the assets pallet.
+	pub fn asset_holders(&self, asset: &AssetIdOf<R>) -> BTreeSet<AccountIdFor<R>> {
+		use pallet_assets::Event;
+
+		self.all_events()
+			.iter()
+			.filter_map(|record| record.event.clone().try_into().ok())
+			.flat_map(|event: Event<R, Instance1>| match event {
+				Event::<R, Instance1>::Issued { asset_id, owner, .. } |
+				Event::<R, Instance1>::Burned { asset_id, owner, .. }
+					if asset_id == *asset =>
+					vec![owner],
+				Event::<R, Instance1>::Transferred { asset_id, from, to, .. }
+					if asset_id == *asset =>
+					vec![from, to],
+				Event::<R, Instance1>::TransferredApproved {
+					asset_id, owner, destination, ..
+				} if asset_id == *asset => vec![owner, destination],
+				Event::<R, Instance1>::Deposited { asset_id, who, .. } |
+				Event::<R, Instance1>::Withdrawn { asset_id, who, .. } |
+				Event::<R, Instance1>::Touched { asset_id, who, .. }
+					if asset_id == *asset =>
+					vec![who],
+				_ => vec![],
+			})
+			.collect()
+	}
+}
+
+impl<R> Record<R>
+where
+	R: Config + pallet_assets::Config<Instance1>,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_assets::Event<R, Instance1>>,
+{
+	/// Returns the accounts whose balance in `asset` may have changed during the session,
+	/// according to the events of the assets pallet.
+	pub fn asset_holders(&self, asset: &AssetIdOf<R>) -> BTreeSet<AccountIdFor<R>> {
+		self.event_batches()
+			.iter()
+			.flat_map(|batch| batch.asset_holders(asset))
+			.collect()
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config + pallet_assets::Config<Instance1>,
+	<T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_assets::Event<T::Runtime, Instance1>>,
+{
+	/// Returns the total supply of `asset` and the sum of the balances of its holders tracked
+	/// during the session, along with `extra_holders` (e.g. funded outside the session).
+	pub fn asset_supply(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime>,
+		extra_holders: &[AccountIdFor<T::Runtime>],
+	) -> AssetSupply<AssetBalanceOf<T::Runtime>> {
+		let mut holders = self.record.asset_holders(asset);
+		holders.extend(extra_holders.iter().cloned());
+
+		let total_supply = AssetsAPI::<T, Instance1>::total_supply(&mut self.sandbox, asset);
+		let holders_balance = holders.iter().fold(Default::default(), |sum, holder| {
+			AssetsAPI::<T, Instance1>::balance_of(&mut self.sandbox, asset, holder)
+				.saturating_add(sum)
+		});
+		AssetSupply { total_supply, holders_balance, holders: holders.len() }
+	}
+}
+
+/// Panics unless the total supply of `asset` is held by its tracked holders. See
+/// [`crate::assert_asset_supply_invariant`].
+#[doc(hidden)]
+#[track_caller]
+pub fn check_asset_supply<T: Sandbox>(
+	session: &mut Session<T>,
+	asset: &AssetIdOf<T::Runtime>,
+	extra_holders: &[AccountIdFor<T::Runtime>],
+) where
+	T::Runtime: Config + pallet_assets::Config<Instance1>,
+	<T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_assets::Event<T::Runtime, Instance1>>,
+{
+	let supply = session.asset_supply(asset, extra_holders);
+	assert!(
+		supply.is_accounted_for(),
+		"Supply of asset {asset:?} leaked: the total supply is {:?}, but its {} tracked holders \
+		 hold {:?}",
+		supply.total_supply,
+		supply.holders,
+		supply.holders_balance,
+	);
+}
+
+/// Asserts that the total supply of a trust-backed asset is equal to the sum of the balances of its
+/// holders, tracked from the events recorded by the session. The holders funded outside the
+/// session must be listed explicitly.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+/// assert_asset_supply_invariant!(session, TOKEN);
+/// assert_asset_supply_invariant!(session, TOKEN, [ALICE]);
+/// ```
+#[macro_export]
+macro_rules! assert_asset_supply_invariant {
+	($session:expr, $asset:expr $(,)?) => {
+		$crate::session::asset_supply::check_asset_supply(&mut $session, &$asset, &[])
+	};
+	($session:expr, $asset:expr, [$($holder:expr),* $(,)?] $(,)?) => {
+		$crate::session::asset_supply::check_asset_supply(
+			&mut $session,
+			&$asset,
+			&[$($holder.clone()),*],
+		)
+	};
+}
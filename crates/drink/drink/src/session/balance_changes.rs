@@ -0,0 +1,104 @@
+//! Assertions on the balance changes caused by a block of code, replacing the boilerplate of
+//! querying every balance before and after a call. See [`crate::assert_balance_changes`].
+
+use frame_support::sp_runtime::SaturatedConversion;
+use ink_sandbox::{
+	api::prelude::{AssetsAPI, BalanceAPI},
+	AccountIdFor, Sandbox,
+};
+
+use crate::{
+	pallet_assets::{self, Instance1},
+	pallet_balances,
+	pallet_contracts::Config,
+	session::Session,
+};
+
+/// Returns the free native balance of `account`, as a signed amount to compute changes with.
+#[doc(hidden)]
+pub fn native_balance<T: Sandbox>(
+	session: &mut Session<T>,
+	account: &AccountIdFor<T::Runtime>,
+) -> i128
+where
+	T::Runtime: Config + pallet_balances::Config,
+{
+	let balance = BalanceAPI::free_balance(session.sandbox(), account);
+	balance.saturated_into::<u128>().saturated_into()
+}
+
+/// Returns the balance of `account` in the trust-backed asset `asset`, as a signed amount to
+/// compute changes with.
+#[doc(hidden)]
+pub fn asset_balance<T: Sandbox>(
+	session: &mut Session<T>,
+	account: &AccountIdFor<T::Runtime>,
+	asset: &<T::Runtime as pallet_assets::Config<Instance1>>::AssetId,
+) -> i128
+where
+	T::Runtime: Config + pallet_assets::Config<Instance1>,
+{
+	let balance = AssetsAPI::<T, Instance1>::balance_of(session.sandbox(), asset, account);
+	balance.saturated_into::<u128>().saturated_into()
+}
+
+/// Panics unless the balances went from `before` to `after` by the `expected` changes, listing all
+/// the unexpected ones. See [`crate::assert_balance_changes`].
+#[doc(hidden)]
+#[track_caller]
+pub fn check_balance_changes(expected: &[(&str, i128)], before: &[i128], after: &[i128]) {
+	let unexpected: Vec<String> = expected
+		.iter()
+		.zip(before.iter().zip(after))
+		.filter_map(|(&(balance, expected), (before, after))| {
+			let actual = after - before;
+			(actual != expected)
+				.then(|| format!("{balance}: expected a change of {expected:+}, got {actual:+}"))
+		})
+		.collect();
+	assert!(unexpected.is_empty(), "Unexpected balance changes:\n{}", unexpected.join("\n"));
+}
+
+/// Asserts the exact changes of balances caused by a block of code, and returns the value of the
+/// block.
+///
+/// The changes of the native balances are listed as `ACCOUNT: change`, and the changes of the
+/// balances in a trust-backed asset as `ACCOUNT[ASSET]: change`. Changes are signed amounts, an
+/// explicit `+` being allowed for increases. The balances which aren't listed aren't checked.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let result = assert_balance_changes!(session, {
+///     ALICE: -100,
+///     BOB: +100,
+///     BOB[USDC]: -10,
+///     ALICE[USDC]: +10,
+/// }, {
+///     session.call::<_, ()>("swap", &[BOB.to_string(), "100".to_string()], Some(100))
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_balance_changes {
+	(@balance $session:expr, $account:path) => {
+		$crate::session::balance_changes::native_balance(&mut $session, &$account)
+	};
+	(@balance $session:expr, $account:path, $asset:expr) => {
+		$crate::session::balance_changes::asset_balance(&mut $session, &$account, &$asset)
+	};
+	(
+		$session:expr,
+		{ $($account:path $([$asset:expr])? : $(+)? $change:expr),+ $(,)? },
+		$body:block $(,)?
+	) => {{
+		let before = [$($crate::assert_balance_changes!(@balance $session, $account $(, $asset)?)),+];
+		let result = $body;
+		let after = [$($crate::assert_balance_changes!(@balance $session, $account $(, $asset)?)),+];
+		$crate::session::balance_changes::check_balance_changes(
+			&[$((stringify!($account $([$asset])?), $change)),+],
+			&before,
+			&after,
+		);
+		result
+	}};
+}
@@ -0,0 +1,135 @@
+//! Breakpoints on contract messages, for inspecting the state of the sandbox in the middle of a
+//! call instead of instrumenting the contract with debug prints.
+//!
+//! A breakpoint is hit right before the message is executed, including when it is called by
+//! another contract. Execution is paused while the callback runs, so it sees the state as left by
+//! the calls so far (e.g. by the callers of a nested call):
+//!
+//! ```rust, ignore
+//! session.set_breakpoint_on_message("transfer", |hit| {
+//!     println!("`{}` called on {} with {:?}", hit.message, hit.contract, hit.input);
+//!     println!("Balance of Bob: {:?}", hit.balance(&BOB));
+//!     println!("Total supply: {:?}", hit.contract_storage(&[0]));
+//! })?;
+//! session.call("transfer", &[BOB.to_string(), "400".to_string()], None)?;
+//! ```
+//!
+//! Like mocks, breakpoints are only hit in sandboxes whose runtime uses
+//! [`DrinkDebug`](crate::pallet_contracts_debugging::DrinkDebug) as the `Debug` type of the
+//! contracts pallet, e.g. [`MinimalSandbox`](crate::minimal::MinimalSandbox).
+
+use frame_support::traits::fungible::Inspect;
+use frame_system::pallet_prelude::BlockNumberFor;
+use ink_sandbox::{AccountIdFor, BalanceFor, Sandbox};
+use scale::Decode;
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, mock::Selector, Session},
+};
+
+type Callback = Box<dyn FnMut(&[u8], &[u8]) + Send>;
+
+/// A breakpoint, calling `callback` with the encoded address of the contract and the input of the
+/// call when one of `selectors` is called.
+struct Breakpoint {
+	selectors: Vec<Selector>,
+	callback: Callback,
+}
+
+/// The breakpoints of a session, shared with the runtime extension detecting the calls.
+#[derive(Default)]
+pub(crate) struct Breakpoints(Vec<Breakpoint>);
+
+impl Breakpoints {
+	/// Calls the callbacks of the breakpoints on the selector of `input`.
+	pub fn hit(&mut self, contract_address: &[u8], input: &[u8]) {
+		for breakpoint in &mut self.0 {
+			if breakpoint.selectors.iter().any(|selector| input.starts_with(selector)) {
+				(breakpoint.callback)(contract_address, input);
+			}
+		}
+	}
+}
+
+/// A breakpoint being hit: the message about to be executed, and accessors to the state of the
+/// sandbox at that point.
+pub struct BreakpointHit<R: Config> {
+	/// The address of the called contract.
+	pub contract: AccountIdFor<R>,
+	/// The name of the called message.
+	pub message: String,
+	/// The encoded arguments of the message (the input of the call, without the selector).
+	pub input: Vec<u8>,
+}
+
+impl<R: Config> BreakpointHit<R> {
+	/// Returns the balance of `account`.
+	pub fn balance(&self, account: &AccountIdFor<R>) -> BalanceFor<R> {
+		<R::Currency as Inspect<AccountIdFor<R>>>::balance(account)
+	}
+
+	/// Returns the value stored under `key` by the called contract.
+	pub fn contract_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
+		self.contract_storage_of(&self.contract, key)
+	}
+
+	/// Returns the value stored under `key` by the contract at `address`.
+	pub fn contract_storage_of(&self, address: &AccountIdFor<R>, key: &[u8]) -> Option<Vec<u8>> {
+		crate::pallet_contracts::Pallet::<R>::get_storage(address.clone(), key.to_vec())
+			.ok()
+			.flatten()
+	}
+
+	/// Returns the number of the current block.
+	pub fn block_number(&self) -> BlockNumberFor<R> {
+		frame_system::Pallet::<R>::block_number()
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Sets a breakpoint on the message `message` of the contracts deployed so far (with a
+	/// transcoder), calling `callback` whenever the message is about to be executed.
+	///
+	/// Fails if none of these contracts has a message `message`.
+	pub fn set_breakpoint_on_message(
+		&mut self,
+		message: &str,
+		mut callback: impl FnMut(&BreakpointHit<T::Runtime>) + Send + 'static,
+	) -> Result<(), SessionError> {
+		let selectors = self.transcoders.selectors_of(message);
+		if selectors.is_empty() {
+			return Err(SessionError::Encoding(format!(
+				"No message `{message}` in the contracts of the session"
+			)));
+		}
+
+		let message = message.to_string();
+		let callback = move |contract_address: &[u8], input: &[u8]| {
+			callback(&BreakpointHit {
+				contract: Decode::decode(&mut &contract_address[..])
+					.expect("Contract address should be decodable"),
+				message: message.clone(),
+				input: input[4..].to_vec(),
+			})
+		};
+		self.breakpoints
+			.lock()
+			.expect("Should be able to acquire breakpoints")
+			.0
+			.push(Breakpoint { selectors, callback: Box::new(callback) });
+		Ok(())
+	}
+
+	/// Removes all the breakpoints.
+	pub fn clear_breakpoints(&mut self) {
+		self.breakpoints
+			.lock()
+			.expect("Should be able to acquire breakpoints")
+			.0
+			.clear();
+	}
+}
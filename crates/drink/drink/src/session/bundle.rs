@@ -1,12 +1,29 @@
 //! This module provides simple utilities for loading and parsing `.contract` files in context of
 //! `drink` tests.
+//!
+//! Bundles are verified when loaded, so that a stale or foreign bundle is reported with the actual
+//! problem rather than rejected with an opaque `CodeRejected` when deployed.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{ops::RangeInclusive, path::PathBuf, sync::Arc};
 
-use contract_metadata::ContractMetadata;
+use contract_metadata::{Compiler, ContractMetadata, Language};
 use contract_transcode::ContractMessageTranscoder;
+use ink_sandbox::{
+	api_version::{contracts_api_version, required_api_version, InkVersion},
+	Sandbox,
+};
+use sp_core::hashing::blake2_256;
 
-use crate::{DrinkResult, Error};
+use crate::{
+	hex::to_hex,
+	pallet_contracts::Config,
+	session::{error::SessionError, Session},
+	DrinkResult, Error,
+};
+
+/// The major versions of ink! whose contracts can be deployed to the contracts pallet of the
+/// sandbox. ink! 6 targets `pallet-revive` instead.
+const SUPPORTED_INK_VERSIONS: RangeInclusive<u64> = 4..=5;
 
 /// A struct representing the result of parsing a `.contract` bundle file.
 ///
@@ -17,15 +34,23 @@ use crate::{DrinkResult, Error};
 /// - `upload_bundle_and`
 #[derive(Clone)]
 pub struct ContractBundle {
+	/// Name of the contract
+	pub name: String,
 	/// WASM blob of the contract
 	pub wasm: Vec<u8>,
 	/// Transcoder derived from the ABI/metadata
 	pub transcoder: Arc<ContractMessageTranscoder>,
+	/// Version of ink! the contract was built with, if known. Checked against the API version of
+	/// the contracts pallet when the contract is deployed or uploaded.
+	pub ink_version: Option<InkVersion>,
 }
 
 impl ContractBundle {
 	/// Load and parse the information in a `.contract` bundle under `path`, producing a
 	/// `ContractBundle` struct.
+	///
+	/// Fails with `Error::BundleVerificationFailed` if the bundle isn't consistent (the code hash
+	/// in the metadata doesn't match the WASM blob) or wasn't built by a compatible toolchain.
 	pub fn load<P>(path: P) -> DrinkResult<Self>
 	where
 		P: AsRef<std::path::Path>,
@@ -33,6 +58,12 @@ impl ContractBundle {
 		let metadata: ContractMetadata = ContractMetadata::load(&path).map_err(|e| {
 			Error::BundleLoadFailed(format!("Failed to load the contract file:\n{e:?}"))
 		})?;
+		verify_toolchain(&metadata)?;
+		let name = metadata.contract.name.clone();
+		let ink_version = matches!(metadata.source.language.language, Language::Ink).then(|| {
+			let version = &metadata.source.language.version;
+			InkVersion::new(version.major, version.minor, version.patch)
+		});
 
 		let ink_metadata = serde_json::from_value(serde_json::Value::Object(metadata.abi))
 			.map_err(|e| {
@@ -50,8 +81,9 @@ impl ContractBundle {
 				"Failed to get the WASM blob from the contract file".to_string(),
 			))?
 			.0;
+		verify_code_hash(metadata.source.hash.0, &wasm)?;
 
-		Ok(Self { wasm, transcoder })
+		Ok(Self { name, wasm, transcoder, ink_version })
 	}
 
 	/// Load the `.contract` bundle (`contract_file_name`) located in the `project_dir`` working
@@ -67,6 +99,82 @@ impl ContractBundle {
 	}
 }
 
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Returns the API version of the contracts pallet of the sandbox, see
+	/// `ink_sandbox::api_version`.
+	pub fn contracts_api_version(&mut self) -> u16 {
+		self.sandbox().execute_with(contracts_api_version::<T::Runtime>)
+	}
+
+	/// Checks that the contracts pallet of the sandbox provides the host functions required by the
+	/// release of ink! `bundle` was built with, if known.
+	///
+	/// Called by `deploy_bundle` and `upload_bundle`, so incompatible contracts fail with
+	/// `SessionError::IncompatibleApiVersion` instead of an opaque `CodeRejected`.
+	pub fn verify_api_version(&mut self, bundle: &ContractBundle) -> Result<(), SessionError> {
+		let Some(ink_version) = bundle.ink_version else { return Ok(()) };
+		let required = required_api_version(&ink_version);
+		let available = self.contracts_api_version();
+		if required > available {
+			return Err(SessionError::IncompatibleApiVersion { ink_version, required, available });
+		}
+		Ok(())
+	}
+}
+
+/// Checks that the contract was written and compiled with a toolchain targeting the contracts
+/// pallet of the sandbox.
+fn verify_toolchain(metadata: &ContractMetadata) -> DrinkResult<()> {
+	let language = &metadata.source.language;
+	let compiler = &metadata.source.compiler;
+	let compiled_by_its_compiler = match language.language {
+		Language::Ink => matches!(compiler.compiler, Compiler::RustC),
+		Language::Solidity => matches!(compiler.compiler, Compiler::Solang),
+		_ =>
+			return Err(Error::BundleVerificationFailed(format!(
+				"Contracts written in {} are not supported",
+				language.language
+			))),
+	};
+	if !compiled_by_its_compiler {
+		return Err(Error::BundleVerificationFailed(format!(
+			"A contract written in {} can't be compiled by {}",
+			language.language, compiler.compiler
+		)));
+	}
+
+	if matches!(language.language, Language::Ink) &&
+		!SUPPORTED_INK_VERSIONS.contains(&language.version.major)
+	{
+		return Err(Error::BundleVerificationFailed(format!(
+			"The contract was built with ink! {}, but the sandbox supports ink! {} to {} \
+			 (pallet-contracts)",
+			language.version,
+			SUPPORTED_INK_VERSIONS.start(),
+			SUPPORTED_INK_VERSIONS.end()
+		)));
+	}
+	Ok(())
+}
+
+/// Checks that the code hash recorded in the metadata (`expected`) is the hash of the WASM blob,
+/// i.e. that the metadata describes this very code.
+fn verify_code_hash(expected: [u8; 32], wasm: &[u8]) -> DrinkResult<()> {
+	let actual = blake2_256(wasm);
+	if expected != actual {
+		return Err(Error::BundleVerificationFailed(format!(
+			"The code hash in the metadata ({}) doesn't match the WASM blob ({}); the bundle may \
+			 be stale or assembled from different builds",
+			to_hex(&expected),
+			to_hex(&actual)
+		)));
+	}
+	Ok(())
+}
+
 /// A convenience macro that allows you to load a bundle found in the target directory
 /// of the current project.
 #[macro_export]
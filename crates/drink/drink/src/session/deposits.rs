@@ -0,0 +1,113 @@
+//! Storage deposit accounting.
+
+use frame_support::traits::fungible::InspectHold;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::{self, Config, HoldReason, StorageDeposit},
+	session::{BalanceOf, EventBatch, Record, Session},
+};
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Returns the storage deposit currently held for the contract under `address`.
+	pub fn contract_storage_deposit(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> BalanceOf<T::Runtime> {
+		self.sandbox.execute_with(|| {
+			<T::Runtime as Config>::Currency::balance_on_hold(
+				&HoldReason::StorageDepositReserve.into(),
+				address,
+			)
+		})
+	}
+}
+
+impl<R> EventBatch<R>
+where
+	R: Config,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+	/// Returns the net storage deposit charged during the contract interaction, across all the
+	/// depositors and contracts.
+	pub fn storage_deposit(&self) -> StorageDeposit<BalanceOf<R>> {
+		net_storage_deposit(self, |_| true)
+	}
+
+	/// Returns the net storage deposit charged from `depositor` during the contract interaction.
+	pub fn storage_deposit_of(&self, depositor: &AccountIdFor<R>) -> StorageDeposit<BalanceOf<R>> {
+		net_storage_deposit(self, |account| account == depositor)
+	}
+}
+
+impl<R> Record<R>
+where
+	R: Config,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+	/// Returns the net storage deposit charged from `depositor` during the whole session.
+	pub fn storage_deposit_of(&self, depositor: &AccountIdFor<R>) -> StorageDeposit<BalanceOf<R>> {
+		self.event_batches()
+			.iter()
+			.map(|batch| batch.storage_deposit_of(depositor))
+			.fold(StorageDeposit::default(), |total, deposit| total.saturating_add(&deposit))
+	}
+}
+
+/// Sums the deposits held from and released to the depositors matching `filter`.
+fn net_storage_deposit<R>(
+	batch: &EventBatch<R>,
+	filter: impl Fn(&AccountIdFor<R>) -> bool,
+) -> StorageDeposit<BalanceOf<R>>
+where
+	R: Config,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+	batch
+		.all_events()
+		.iter()
+		.filter_map(|record| match record.event.clone().try_into() {
+			Ok(pallet_contracts::Event::<R>::StorageDepositTransferredAndHeld {
+				from,
+				amount,
+				..
+			}) if filter(&from) => Some(StorageDeposit::Charge(amount)),
+			Ok(pallet_contracts::Event::<R>::StorageDepositTransferredAndReleased {
+				to,
+				amount,
+				..
+			}) if filter(&to) => Some(StorageDeposit::Refund(amount)),
+			_ => None,
+		})
+		.fold(StorageDeposit::default(), |total, deposit| total.saturating_add(&deposit))
+}
+
+/// Asserts that the last contract interaction of the session charged the given net storage
+/// deposit, optionally from a single depositor only.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("insert", &["42"], NO_ENDOWMENT)??;
+/// assert_deposit_charged!(session, 75);
+/// assert_deposit_charged!(session, ALICE, 75);
+/// ```
+#[macro_export]
+macro_rules! assert_deposit_charged {
+	($session:expr, $amount:expr $(,)?) => {
+		assert_eq!(
+			$session.record().last_event_batch().storage_deposit(),
+			$crate::pallet_contracts::StorageDeposit::Charge($amount),
+		)
+	};
+	($session:expr, $depositor:expr, $amount:expr $(,)?) => {
+		assert_eq!(
+			$session.record().last_event_batch().storage_deposit_of(&$depositor),
+			$crate::pallet_contracts::StorageDeposit::Charge($amount),
+		)
+	};
+}
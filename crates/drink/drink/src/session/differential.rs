@@ -0,0 +1,238 @@
+//! Differential testing of two versions of a contract, e.g. to verify an upgrade or a refactor.
+//!
+//! [`DiffHarness`] deploys both versions in separate sessions, applies the same sequence of
+//! actions to both and compares the observable behavior of every action: its result, the
+//! contract events it emitted and the changes it made to the storage of the contract. Gas
+//! consumption is not compared.
+//!
+//! ```rust, ignore
+//! let mut harness = DiffHarness::<MinimalSandbox>::new(bundle_v1, bundle_v2);
+//! harness
+//!     .deploy("new", &["1000"], NO_SALT, None)
+//!     .call("PSP22::transfer", &[BOB.to_string(), "10".to_string(), "[]".to_string()], None)
+//!     .call("PSP22::balance_of", &[BOB.to_string()], None);
+//! harness.assert_no_divergence();
+//! ```
+
+use std::{
+	collections::BTreeMap,
+	fmt::{self, Debug, Display},
+};
+
+use frame_system::Config as SysConfig;
+use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+
+use crate::{
+	hex::to_hex,
+	pallet_contracts::{self, Config},
+	session::{error::SessionError, BalanceOf, ContractBundle, Session},
+};
+
+/// Changes made to the storage of a contract, by key: the new value, or `None` if removed.
+type StorageDiff = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// The aspect of an action in which the versions of a contract diverged.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aspect {
+	/// The result of the action (the returned data, or the error).
+	Result,
+	/// The contract events emitted by the action.
+	Events,
+	/// The changes made to the storage of the contract by the action.
+	Storage,
+}
+
+/// A behavioral divergence between the versions of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+	/// The action, e.g. `call PSP22::transfer(..)`.
+	pub action: String,
+	/// The aspect in which the versions diverged.
+	pub aspect: Aspect,
+	/// The behavior of the first version.
+	pub v1: String,
+	/// The behavior of the second version.
+	pub v2: String,
+}
+
+impl Display for Divergence {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "`{}`: {:?} diverged", self.action, self.aspect)?;
+		writeln!(f, "  v1: {}", self.v1)?;
+		write!(f, "  v2: {}", self.v2)
+	}
+}
+
+/// What was observed of an action performed on one version.
+struct Observation {
+	result: Result<String, String>,
+	events: Vec<Vec<u8>>,
+	storage: StorageDiff,
+}
+
+/// Harness applying the same actions to two versions of a contract and comparing their behavior.
+pub struct DiffHarness<T: Sandbox>
+where
+	T::Runtime: Config,
+{
+	sessions: [Session<T>; 2],
+	bundles: [ContractBundle; 2],
+	divergences: Vec<Divergence>,
+}
+
+impl<T: Sandbox + Default> DiffHarness<T>
+where
+	T::Runtime: Config,
+	<T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+	/// Creates a harness comparing the contract of `bundle_v1` with the contract of `bundle_v2`,
+	/// each in a fresh session.
+	pub fn new(bundle_v1: ContractBundle, bundle_v2: ContractBundle) -> Self {
+		Self {
+			sessions: [Session::default(), Session::default()],
+			bundles: [bundle_v1, bundle_v2],
+			divergences: Vec::new(),
+		}
+	}
+
+	/// Returns the sessions of the first and the second version, e.g. to configure them or to
+	/// inspect their state.
+	pub fn sessions(&mut self) -> (&mut Session<T>, &mut Session<T>) {
+		let [v1, v2] = &mut self.sessions;
+		(v1, v2)
+	}
+
+	/// Sets the actor of both sessions.
+	pub fn set_actor(&mut self, actor: AccountIdFor<T::Runtime>) -> &mut Self {
+		for session in &mut self.sessions {
+			session.set_actor(actor.clone());
+		}
+		self
+	}
+
+	/// Deploys both versions with the same constructor call, and compares the outcomes.
+	pub fn deploy<S: AsRef<str> + Debug>(
+		&mut self,
+		constructor: &str,
+		args: &[S],
+		salt: Vec<u8>,
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> &mut Self {
+		let action = format!("deploy {constructor}({args:?})");
+		let observations = [0, 1].map(|version| {
+			let bundle = self.bundles[version].clone();
+			observe(&mut self.sessions[version], None, |session| {
+				session
+					.deploy_bundle(bundle, constructor, args, salt.clone(), endowment)
+					.map(|_| Vec::new())
+			})
+		});
+		self.compare(action, observations);
+		self
+	}
+
+	/// Calls the last deployed contract of both versions with the same message, and compares the
+	/// outcomes.
+	pub fn call<S: AsRef<str> + Debug>(
+		&mut self,
+		message: &str,
+		args: &[S],
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> &mut Self {
+		let action = format!("call {message}({args:?})");
+		let observations = self.sessions.each_mut().map(|session| {
+			let address = session.record().deploy_returns().last().cloned();
+			observe(session, address, |session| {
+				session
+					.call::<_, ()>(message, args, endowment)
+					.map(|_| session.record().last_call_return().to_vec())
+			})
+		});
+		self.compare(action, observations);
+		self
+	}
+
+	/// Returns the divergences found so far.
+	pub fn divergences(&self) -> &[Divergence] {
+		&self.divergences
+	}
+
+	/// Asserts that the versions behaved the same so far, listing the divergences otherwise.
+	#[track_caller]
+	pub fn assert_no_divergence(&self) {
+		if !self.divergences.is_empty() {
+			let divergences =
+				self.divergences.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+			panic!(
+				"The versions of the contract diverged {} time(s):\n{divergences}",
+				self.divergences.len()
+			);
+		}
+	}
+
+	fn compare(&mut self, action: String, [v1, v2]: [Observation; 2]) {
+		let mut diverge = |aspect, v1: String, v2: String| {
+			if v1 != v2 {
+				self.divergences.push(Divergence { action: action.clone(), aspect, v1, v2 });
+			}
+		};
+		diverge(Aspect::Result, format!("{:?}", v1.result), format!("{:?}", v2.result));
+		diverge(Aspect::Events, render_events(&v1.events), render_events(&v2.events));
+		diverge(Aspect::Storage, render_storage(&v1.storage), render_storage(&v2.storage));
+	}
+}
+
+/// Performs `action` on `session` and observes its effects on the storage of the last deployed
+/// contract. `address` is the contract the action is performed on, if it exists already.
+fn observe<T: Sandbox>(
+	session: &mut Session<T>,
+	address: Option<AccountIdFor<T::Runtime>>,
+	action: impl FnOnce(&mut Session<T>) -> Result<Vec<u8>, SessionError>,
+) -> Observation
+where
+	T::Runtime: Config,
+	<T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+	let checkpoint = session.record().checkpoint();
+	let before: BTreeMap<_, _> = match &address {
+		Some(address) => session.sandbox().contract_storage_iter(address).into_iter().collect(),
+		None => BTreeMap::new(),
+	};
+
+	let result = action(session).map(|data| to_hex(&data)).map_err(|e| e.to_string());
+
+	let after: BTreeMap<_, _> =
+		match address.or_else(|| session.record().deploy_returns().last().cloned()) {
+			Some(address) if result.is_ok() =>
+				session.sandbox().contract_storage_iter(&address).into_iter().collect(),
+			_ => before.clone(),
+		};
+	let mut storage: StorageDiff = after
+		.iter()
+		.filter(|(key, value)| before.get(*key) != Some(value))
+		.map(|(key, value)| (key.clone(), Some(value.clone())))
+		.collect();
+	storage.extend(
+		before
+			.keys()
+			.filter(|key| !after.contains_key(*key))
+			.map(|key| (key.clone(), None)),
+	);
+
+	Observation { result, events: session.record().contract_events_since(checkpoint), storage }
+}
+
+fn render_events(events: &[Vec<u8>]) -> String {
+	format!("[{}]", events.iter().map(|event| to_hex(event)).collect::<Vec<_>>().join(", "))
+}
+
+fn render_storage(storage: &StorageDiff) -> String {
+	let changes = storage
+		.iter()
+		.map(|(key, value)| match value {
+			Some(value) => format!("{} = {}", to_hex(key), to_hex(value)),
+			None => format!("{} removed", to_hex(key)),
+		})
+		.collect::<Vec<_>>();
+	format!("{{{}}}", changes.join(", "))
+}
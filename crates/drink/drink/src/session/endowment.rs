@@ -0,0 +1,85 @@
+//! Default endowments of the calls of a session, so suites testing payable-heavy contracts don't
+//! repeat the same endowment on every call.
+//!
+//! The policy applies to the calls of payable messages given no endowment (`None`); an explicit
+//! endowment always takes precedence:
+//!
+//! ```rust, ignore
+//! session.set_default_endowment(EndowmentPolicy::PerMessage(BTreeMap::from([
+//!     ("deposit".to_string(), 100),
+//!     ("bid".to_string(), 1_000),
+//! ])));
+//! session.call::<_, ()>("deposit", NO_ARGS, None)??; // Transfers 100.
+//! session.call::<_, ()>("bid", NO_ARGS, Some(5_000))??; // Transfers 5000.
+//! ```
+
+use std::{collections::BTreeMap, mem};
+
+use contract_transcode::ContractMessageTranscoder;
+use ink_sandbox::Sandbox;
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, BalanceOf, ContractInterface, Session},
+};
+
+/// The endowment of the calls of payable messages given no endowment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EndowmentPolicy<Balance> {
+	/// The calls transfer nothing.
+	#[default]
+	None,
+	/// The calls transfer the value.
+	Fixed(Balance),
+	/// The calls transfer the value of their message, by label. Calling a payable message missing
+	/// from the map fails with `SessionError::NoEndowment`.
+	PerMessage(BTreeMap<String, Balance>),
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Sets the default endowment policy and returns updated `self`.
+	pub fn with_default_endowment(self, policy: EndowmentPolicy<BalanceOf<T::Runtime>>) -> Self {
+		Self { endowment_policy: policy, ..self }
+	}
+
+	/// Sets the default endowment policy of the calls of payable messages given no endowment, and
+	/// returns the old one. See [`crate::session::endowment`].
+	pub fn set_default_endowment(
+		&mut self,
+		policy: EndowmentPolicy<BalanceOf<T::Runtime>>,
+	) -> EndowmentPolicy<BalanceOf<T::Runtime>> {
+		mem::replace(&mut self.endowment_policy, policy)
+	}
+
+	/// Returns the endowment of a call of `message`: `endowment` if any, the one of the policy if
+	/// the message is payable, nothing otherwise.
+	pub(super) fn endowment_of(
+		&self,
+		transcoder: &ContractMessageTranscoder,
+		message: &str,
+		endowment: Option<BalanceOf<T::Runtime>>,
+	) -> Result<BalanceOf<T::Runtime>, SessionError> {
+		if let Some(endowment) = endowment {
+			return Ok(endowment);
+		}
+		if matches!(self.endowment_policy, EndowmentPolicy::None) {
+			return Ok(Default::default());
+		}
+
+		let interface = ContractInterface::from_transcoder(transcoder);
+		if !interface.message(message).is_some_and(|message| message.payable) {
+			return Ok(Default::default());
+		}
+		match &self.endowment_policy {
+			EndowmentPolicy::None => Ok(Default::default()),
+			EndowmentPolicy::Fixed(value) => Ok(*value),
+			EndowmentPolicy::PerMessage(values) => values
+				.get(message)
+				.copied()
+				.ok_or_else(|| SessionError::NoEndowment(message.to_string())),
+		}
+	}
+}
@@ -1,6 +1,9 @@
 //! Module exposing errors and result types for the session API.
 
+use std::time::Duration;
+
 use frame_support::sp_runtime::DispatchError;
+use ink_sandbox::api_version::InkVersion;
 use scale::Decode;
 use thiserror::Error;
 
@@ -24,21 +27,48 @@ pub enum SessionError {
 	/// Deployment failed (aborted by the pallet).
 	#[error("Contract deployment failed before execution: {0:?}")]
 	DeploymentFailed(DispatchError),
+	/// The contract was built with a release of ink! requiring host functions the contracts pallet
+	/// of the sandbox doesn't provide.
+	#[error(
+		"The contract was built with ink! {ink_version}, which requires the API version \
+		 {required} of pallet-contracts, but the sandbox provides the version {available}"
+	)]
+	IncompatibleApiVersion {
+		/// The version of ink! the contract was built with.
+		ink_version: InkVersion,
+		/// The API version required by the contract.
+		required: u16,
+		/// The API version of the contracts pallet of the sandbox.
+		available: u16,
+	},
 	/// Code upload failed (aborted by the pallet).
 	#[error("Code upload failed: {0:?}")]
 	UploadFailed(DispatchError),
+	/// The code uses non-deterministic instructions, detected in the determinism audit mode.
+	#[error("The code uses non-deterministic (e.g. floating point) instructions")]
+	NonDeterministicCode,
 	/// Call has been reverted by the contract.
 	#[error("Contract call has been reverted. Encoded error: {0:?}")]
 	CallReverted(Vec<u8>),
 	/// Contract call failed (aborted by the pallet).
 	#[error("Contract call failed before execution: {0:?}")]
 	CallFailed(DispatchError),
+	/// Replacing the code of a contract failed.
+	#[error("Contract upgrade failed: {0:?}")]
+	UpgradeFailed(DispatchError),
+	/// The deployment or call didn't finish within the timeout of the session.
+	#[error("Contract execution timed out after {0:?}")]
+	ExecutionTimeout(Duration),
 	/// There is no deployed contract to call.
 	#[error("No deployed contract")]
 	NoContract,
 	/// There is no registered transcoder to encode/decode messages for the called contract.
 	#[error("Missing transcoder")]
 	NoTranscoder,
+	/// A payable message was called without an endowment, and the default endowment policy of
+	/// the session has none for it.
+	#[error("No endowment for the payable message `{0}`")]
+	NoEndowment(String),
 }
 
 impl SessionError {
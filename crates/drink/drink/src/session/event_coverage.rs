@@ -0,0 +1,158 @@
+//! Coverage of the events of a contract by the test suite: the events declared in the metadata of
+//! the contract are compared with the events asserted at least once, so that untested events (or
+//! events drifting from the assertions) become visible.
+//!
+//! The events are collected by an [`EventCoverage`] handle that sessions register into with
+//! `Session::with_event_coverage`. The event assertions (`assert_last_event!`,
+//! `assert_event_topic!`) of a registered session note the signature topic of the asserted event
+//! in the handle:
+//!
+//! ```rust, ignore
+//! let coverage = EventCoverage::new();
+//! let mut session = session.with_event_coverage(&coverage);
+//! session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+//! assert_last_event!(session, events::Transfer { from: Some(ALICE), to: Some(BOB), value: 10 });
+//! assert!(coverage.report(&bundle).is_complete());
+//! ```
+//!
+//! For a report of the whole suite, the sessions of all the tests register into
+//! [`EventCoverage::suite`], and [`EventCoverage::print_reports`] is called once they all ran, e.g.
+//! at the end of the `main` function of a test target with `harness = false`.
+//!
+//! Anonymous events have no signature topic, and aren't covered.
+
+use std::{
+	collections::BTreeSet,
+	fmt,
+	sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use frame_system::Config as SysConfig;
+use ink_sandbox::Sandbox;
+
+use crate::{
+	pallet_contracts::{self, Config},
+	session::{ContractBundle, EventBatch, Session},
+};
+
+/// Collector of the signature topics of the events asserted by the sessions registered into it.
+///
+/// Clones share the collected events.
+#[derive(Clone, Debug, Default)]
+pub struct EventCoverage {
+	asserted: Arc<Mutex<BTreeSet<Vec<u8>>>>,
+}
+
+impl EventCoverage {
+	/// Creates an empty collector.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the collector shared by the whole test binary.
+	pub fn suite() -> &'static Self {
+		static SUITE: OnceLock<EventCoverage> = OnceLock::new();
+		SUITE.get_or_init(Self::new)
+	}
+
+	fn note(&self, signature_topic: &[u8]) {
+		self.asserted
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(signature_topic.to_vec());
+	}
+
+	/// Returns the coverage of the (non-anonymous) events declared in the metadata of `bundle` by
+	/// the assertions collected so far.
+	pub fn report(&self, bundle: &ContractBundle) -> EventCoverageReport {
+		let asserted_events = self.asserted.lock().unwrap_or_else(PoisonError::into_inner);
+		let mut report =
+			EventCoverageReport { contract: bundle.name.clone(), ..Default::default() };
+		for event in bundle.transcoder.metadata().spec().events() {
+			let Some(signature_topic) = event.signature_topic() else { continue };
+			let label = event.label().to_string();
+			if asserted_events.contains(signature_topic.as_bytes()) {
+				report.asserted.push(label);
+			} else {
+				report.untested.push(label);
+			}
+		}
+		report
+	}
+
+	/// Prints the coverage of the events of the contracts of `bundles`, and returns it.
+	///
+	/// Meant to be called once all the sessions registered into the collector are done, e.g. at
+	/// the end of a test suite.
+	pub fn print_reports(&self, bundles: &[ContractBundle]) -> Vec<EventCoverageReport> {
+		bundles
+			.iter()
+			.map(|bundle| {
+				let report = self.report(bundle);
+				println!("{report}");
+				report
+			})
+			.collect()
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Registers the session into `coverage`, which collects the events asserted by the session,
+	/// and returns updated `self`.
+	pub fn with_event_coverage(mut self, coverage: &EventCoverage) -> Self {
+		self.record.set_event_coverage(coverage.clone());
+		self
+	}
+}
+
+impl<R> EventBatch<R>
+where
+	R: pallet_contracts::Config,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+	/// Notes the last contract event emitted during the contract interaction as asserted in the
+	/// event coverage of the session, if any. Called by the event assertions.
+	pub fn note_last_contract_event_asserted(&self) {
+		let Some(coverage) = self.event_coverage() else { return };
+		let Some(event) = self.contract_events_with_topics().pop() else { return };
+		if let Some(topic) = event.topics.first() {
+			coverage.note(topic.as_ref());
+		}
+	}
+}
+
+/// The events of a contract asserted and not asserted by the sessions registered into an
+/// [`EventCoverage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventCoverageReport {
+	/// The name of the contract.
+	pub contract: String,
+	/// The labels of the events asserted at least once.
+	pub asserted: Vec<String>,
+	/// The labels of the events never asserted.
+	pub untested: Vec<String>,
+}
+
+impl EventCoverageReport {
+	/// Returns whether all the events were asserted at least once.
+	pub fn is_complete(&self) -> bool {
+		self.untested.is_empty()
+	}
+}
+
+impl fmt::Display for EventCoverageReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let total = self.asserted.len() + self.untested.len();
+		writeln!(f, "Event coverage of `{}`: {}/{total}", self.contract, self.asserted.len())?;
+		for label in &self.asserted {
+			writeln!(f, "  [x] {label}")?;
+		}
+		for label in &self.untested {
+			writeln!(f, "  [ ] {label}")?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,327 @@
+//! Fuzzing of the constructors of a contract with arguments generated from its metadata.
+//!
+//! Every constructor is called with random (SCALE-encoded) arguments of the types declared in the
+//! metadata, biased towards edge cases like zero and maximum values, and with random value
+//! transfers if it is payable. A constructor must either deploy the contract or revert with the
+//! error it declares, but never trap (e.g. panic on an overflow or an `unwrap`):
+//!
+//! ```rust, ignore
+//! let report = session.fuzz_constructors(&BundleProvider::local()?, 100);
+//! report.assert_no_violation();
+//! ```
+//!
+//! The inputs are derived from the seed of the session, so a failure can be reproduced with the
+//! `DRINK_SEED` environment variable.
+
+use std::fmt::{self, Debug, Display};
+
+use frame_support::{sp_runtime::DispatchError, traits::fungible::Inspect};
+use ink_sandbox::{api::prelude::*, seed, Sandbox};
+use scale::{Compact, Encode};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+
+use crate::{
+	hex::to_hex,
+	pallet_contracts::{self, Config},
+	session::{BalanceOf, ContractBundle, Session},
+};
+
+/// The maximum nesting depth of generated values. Deeper (recursive) types are generated as small
+/// as possible.
+const MAX_DEPTH: usize = 8;
+
+/// The outcome of a constructor call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstructorOutcome {
+	/// The contract was deployed.
+	Deployed,
+	/// The constructor reverted with the error it declares.
+	Reverted,
+	/// The constructor reverted, though it doesn't declare an error.
+	UndeclaredRevert,
+	/// The constructor trapped.
+	Trapped,
+	/// The deployment failed for another reason, e.g. an insufficient storage deposit limit.
+	Failed(DispatchError),
+}
+
+impl ConstructorOutcome {
+	/// Returns whether the outcome reveals a bug of the constructor.
+	pub fn is_violation(&self) -> bool {
+		matches!(self, Self::UndeclaredRevert | Self::Trapped)
+	}
+}
+
+/// A fuzzed constructor call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzCase<Balance> {
+	/// The name of the constructor.
+	pub constructor: String,
+	/// The encoded arguments.
+	pub input: Vec<u8>,
+	/// The transferred value.
+	pub value: Balance,
+	/// The outcome of the call.
+	pub outcome: ConstructorOutcome,
+}
+
+impl<Balance: Debug> Display for FuzzCase<Balance> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}(0x{}) with value {:?}: {:?}",
+			self.constructor,
+			to_hex(&self.input),
+			self.value,
+			self.outcome
+		)
+	}
+}
+
+/// The fuzzed constructor calls of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzReport<Balance> {
+	/// The calls, in the order of execution.
+	pub cases: Vec<FuzzCase<Balance>>,
+}
+
+impl<Balance: Debug> FuzzReport<Balance> {
+	/// Returns the calls revealing a bug of a constructor.
+	pub fn violations(&self) -> Vec<&FuzzCase<Balance>> {
+		self.cases.iter().filter(|case| case.outcome.is_violation()).collect()
+	}
+
+	/// Asserts that no constructor call trapped or reverted without declaring an error, listing
+	/// the offending calls otherwise.
+	#[track_caller]
+	pub fn assert_no_violation(&self) {
+		let violations = self.violations();
+		if !violations.is_empty() {
+			let cases = violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+			panic!(
+				"{} of {} constructor call(s) trapped or reverted without declaring an \
+				 error:\n{cases}",
+				violations.len(),
+				self.cases.len()
+			);
+		}
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Calls every constructor of the contract of `bundle` `iterations` times, with random
+	/// arguments and value transfers, and returns the outcomes. Every call is dry-run, so the
+	/// state of the session is unchanged.
+	///
+	/// # Arguments
+	///
+	/// * `bundle` - The contract to fuzz.
+	/// * `iterations` - The number of calls of each constructor.
+	pub fn fuzz_constructors(
+		&mut self,
+		bundle: &ContractBundle,
+		iterations: u32,
+	) -> FuzzReport<BalanceOf<T::Runtime>> {
+		let metadata = bundle.transcoder.metadata();
+		let registry = metadata.registry();
+		let actor = self.get_actor();
+		let balance = self
+			.sandbox
+			.execute_with(|| <<T::Runtime as Config>::Currency as Inspect<_>>::balance(&actor));
+
+		let gas_limit = self.get_gas_limit();
+		let mut cases = Vec::new();
+		for constructor in metadata.spec().constructors() {
+			let fallible = is_fallible(registry, constructor.return_type().ret_type().ty().id);
+			for iteration in 0..iterations {
+				let mut entropy = Entropy {
+					seed: self.get_seed(),
+					subject: (constructor.label(), iteration),
+					n: 0,
+				};
+
+				let mut input = Vec::new();
+				for arg in constructor.args() {
+					generate(registry, arg.ty().ty().id, &mut entropy, 0, &mut input);
+				}
+				let value = match (constructor.payable(), entropy.below(3)) {
+					(false, _) | (true, 0) => Default::default(),
+					(true, 1) => balance / 2u32.into(),
+					(true, _) => balance / (entropy.below(1_000_000) as u32 + 2).into(),
+				};
+
+				let data = [&constructor.selector().to_bytes()[..], &input].concat();
+				let salt = entropy.next().to_vec();
+				let result = self.sandbox.dry_run(|sandbox| {
+					sandbox.deploy_contract(
+						bundle.wasm.clone(),
+						value,
+						data,
+						salt,
+						actor.clone(),
+						gas_limit,
+						None,
+					)
+				});
+				let outcome = match result.result {
+					Ok(result) if !result.result.did_revert() => ConstructorOutcome::Deployed,
+					Ok(_) if fallible => ConstructorOutcome::Reverted,
+					Ok(_) => ConstructorOutcome::UndeclaredRevert,
+					Err(error)
+						if error ==
+							pallet_contracts::Error::<T::Runtime>::ContractTrapped.into() =>
+						ConstructorOutcome::Trapped,
+					Err(error) => ConstructorOutcome::Failed(error),
+				};
+
+				cases.push(FuzzCase {
+					constructor: constructor.label().to_string(),
+					input,
+					value,
+					outcome,
+				});
+			}
+		}
+		FuzzReport { cases }
+	}
+}
+
+/// Source of pseudo-random numbers for a subject, derived from a seed.
+struct Entropy<S> {
+	seed: u64,
+	subject: S,
+	n: u64,
+}
+
+impl<S: Encode> Entropy<S> {
+	fn next(&mut self) -> [u8; 32] {
+		self.n += 1;
+		seed::derive(self.seed, (&self.subject, self.n))
+	}
+
+	fn u128(&mut self) -> u128 {
+		u128::from_le_bytes(self.next()[..16].try_into().expect("16 bytes"))
+	}
+
+	fn below(&mut self, n: u64) -> u64 {
+		(self.u128() % n as u128) as u64
+	}
+}
+
+/// Returns whether the constructor returning the type `ret_type` (`ConstructorResult<_>`)
+/// declares an error, i.e. returns a `Result`.
+fn is_fallible(registry: &PortableRegistry, ret_type: u32) -> bool {
+	let ok_type = registry
+		.resolve(ret_type)
+		.and_then(|ty| ty.type_params.iter().find(|param| param.name == "T"))
+		.and_then(|param| param.ty.as_ref())
+		.and_then(|ty| registry.resolve(ty.id));
+	ok_type.is_some_and(|ty| ty.path.segments.last().is_some_and(|name| name == "Result"))
+}
+
+/// Appends a random encoded value of the type `type_id` to `output`.
+fn generate<S: Encode>(
+	registry: &PortableRegistry,
+	type_id: u32,
+	entropy: &mut Entropy<S>,
+	depth: usize,
+	output: &mut Vec<u8>,
+) {
+	let Some(ty) = registry.resolve(type_id) else { return };
+	let max_len = if depth < MAX_DEPTH { 8 } else { 0 };
+	match &ty.type_def {
+		TypeDef::Composite(composite) =>
+			for field in &composite.fields {
+				generate(registry, field.ty.id, entropy, depth + 1, output);
+			},
+		TypeDef::Variant(variant) => {
+			let Some(variant) = (if depth < MAX_DEPTH {
+				let index = entropy.below(variant.variants.len().max(1) as u64) as usize;
+				variant.variants.get(index)
+			} else {
+				variant.variants.iter().min_by_key(|variant| variant.fields.len())
+			}) else {
+				return;
+			};
+			output.push(variant.index);
+			for field in &variant.fields {
+				generate(registry, field.ty.id, entropy, depth + 1, output);
+			}
+		},
+		TypeDef::Sequence(sequence) => {
+			let len = entropy.below(max_len + 1);
+			Compact(len as u32).encode_to(output);
+			for _ in 0..len {
+				generate(registry, sequence.type_param.id, entropy, depth + 1, output);
+			}
+		},
+		TypeDef::Array(array) =>
+			for _ in 0..array.len {
+				generate(registry, array.type_param.id, entropy, depth + 1, output);
+			},
+		TypeDef::Tuple(tuple) =>
+			for field in &tuple.fields {
+				generate(registry, field.id, entropy, depth + 1, output);
+			},
+		TypeDef::Primitive(primitive) => generate_primitive(primitive, entropy, max_len, output),
+		TypeDef::Compact(compact) => {
+			let max = match registry.resolve(compact.type_param.id).map(|ty| &ty.type_def) {
+				Some(TypeDef::Primitive(primitive)) => primitive_max(primitive),
+				_ => u32::MAX as u128,
+			};
+			// The compact encoding of a value doesn't depend on its type.
+			Compact(edge_case(entropy, max)).encode_to(output)
+		},
+		TypeDef::BitSequence(_) => Compact(0u32).encode_to(output),
+	}
+}
+
+/// Returns `0`, `1`, `max` or a random value up to `max`, with equal probability.
+fn edge_case<S: Encode>(entropy: &mut Entropy<S>, max: u128) -> u128 {
+	match entropy.below(4) {
+		0 => 0,
+		1 => 1,
+		2 => max,
+		_ => entropy.u128() & max,
+	}
+}
+
+fn generate_primitive<S: Encode>(
+	primitive: &TypeDefPrimitive,
+	entropy: &mut Entropy<S>,
+	max_len: u64,
+	output: &mut Vec<u8>,
+) {
+	match primitive {
+		TypeDefPrimitive::Bool => (entropy.below(2) == 1).encode_to(output),
+		TypeDefPrimitive::Char => (entropy.below(0x80) as u32).encode_to(output),
+		TypeDefPrimitive::Str => {
+			let len = entropy.below(max_len * 2 + 1);
+			let text: String =
+				(0..len).map(|_| char::from(b' ' + entropy.below(95) as u8)).collect();
+			text.encode_to(output)
+		},
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			output.extend_from_slice(&entropy.next()),
+		_ => {
+			let max = primitive_max(primitive);
+			let len = (u128::BITS - max.leading_zeros()) as usize / 8;
+			output.extend_from_slice(&edge_case(entropy, max).to_le_bytes()[..len])
+		},
+	}
+}
+
+/// Returns the maximum value of the (fixed-size, at most 128-bit) integer `primitive`, as
+/// unsigned.
+fn primitive_max(primitive: &TypeDefPrimitive) -> u128 {
+	match primitive {
+		TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => u8::MAX as u128,
+		TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => u16::MAX as u128,
+		TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => u32::MAX as u128,
+		TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => u64::MAX as u128,
+		_ => u128::MAX,
+	}
+}
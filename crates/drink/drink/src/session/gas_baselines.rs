@@ -0,0 +1,204 @@
+//! Persistent per-message gas baselines, used to detect gas regressions.
+//!
+//! The gas consumed by contract calls is compared with the baselines committed to a JSON file
+//! (see `assert_no_gas_regression!`). Missing baselines are recorded on the first run. To accept
+//! a new gas consumption, re-run the tests with `DRINK_UPDATE_GAS_BASELINES=1` and commit the
+//! updated file.
+
+use std::{
+	collections::BTreeMap,
+	path::Path,
+	sync::{Mutex, PoisonError},
+};
+
+use frame_support::weights::Weight;
+use ink_sandbox::Sandbox;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{interface::ContractInterface, mock::Selector, record::Action, Session},
+	DrinkResult, Error,
+};
+
+/// Name of the baselines file used by `assert_no_gas_regression!` by default, relative to the
+/// crate root.
+pub const BASELINES_FILE: &str = "gas-baselines.json";
+
+/// Environment variable which, when set, makes the assertions overwrite the baselines with the
+/// current gas consumption instead of comparing with them.
+pub const UPDATE_BASELINES_ENV_VAR: &str = "DRINK_UPDATE_GAS_BASELINES";
+
+/// Serializes the read-modify-write cycles of the tests running in parallel.
+static BASELINES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Gas consumed by a contract call.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasUsage {
+	/// The consumed `ref_time`.
+	pub ref_time: u64,
+	/// The consumed `proof_size`.
+	pub proof_size: u64,
+}
+
+impl From<Weight> for GasUsage {
+	fn from(weight: Weight) -> Self {
+		Self { ref_time: weight.ref_time(), proof_size: weight.proof_size() }
+	}
+}
+
+/// The gas baselines of a test suite, by key (e.g. `test_name::PSP22::transfer`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasBaselines(BTreeMap<String, GasUsage>);
+
+impl GasBaselines {
+	/// Loads the baselines from the JSON file under `path`. A missing file has no baselines.
+	pub fn load<P: AsRef<Path>>(path: P) -> DrinkResult<Self> {
+		let json = match std::fs::read_to_string(path) {
+			Ok(json) => json,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+			Err(e) =>
+				return Err(Error::GasBaselinesFailed(format!("Failed to read the file:\n{e:?}"))),
+		};
+		serde_json::from_str(&json)
+			.map_err(|e| Error::GasBaselinesFailed(format!("Failed to deserialize:\n{e:?}")))
+	}
+
+	/// Saves the baselines to a JSON file under `path`.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> DrinkResult<()> {
+		let json = serde_json::to_string_pretty(self)
+			.map_err(|e| Error::GasBaselinesFailed(format!("Failed to serialize:\n{e:?}")))?;
+		std::fs::write(path, json + "\n")
+			.map_err(|e| Error::GasBaselinesFailed(format!("Failed to write the file:\n{e:?}")))
+	}
+
+	/// Returns the baseline under `key`, if any.
+	pub fn get(&self, key: &str) -> Option<GasUsage> {
+		self.0.get(key).copied()
+	}
+
+	/// Sets the baseline under `key`, returning the previous one.
+	pub fn set(&mut self, key: String, usage: GasUsage) -> Option<GasUsage> {
+		self.0.insert(key, usage)
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Returns the gas consumed by the last contract call. Panics if there were no contract calls.
+	pub fn last_call_gas(&self) -> GasUsage {
+		self.record.last_call_result().gas_consumed.into()
+	}
+
+	/// Returns the label of the message invoked by the last contract call, if the session has a
+	/// transcoder for the called contract.
+	pub fn last_call_label(&self) -> Option<String> {
+		let (address, data) =
+			self.record.actions().iter().rev().find_map(|action| match action {
+				Action::Call { address, data, .. } => Some((address, data)),
+				_ => None,
+			})?;
+		let selector: Selector = data.get(..4)?.try_into().ok()?;
+		let interface = ContractInterface::from_transcoder(&self.transcoders.get(address)?);
+		Some(interface.message_by_selector(selector)?.label.clone())
+	}
+}
+
+/// Compares the gas consumed by the last contract call of `session` with the baseline under
+/// `key` in the file under `path`. See `assert_no_gas_regression!`.
+///
+/// Panics if the consumed `ref_time` or `proof_size` exceed the baseline by more than
+/// `threshold_pct` percent. Records the baseline if it is missing or if
+/// `DRINK_UPDATE_GAS_BASELINES` is set.
+#[track_caller]
+pub fn check_gas_regression<T: Sandbox, P: AsRef<Path>>(
+	session: &Session<T>,
+	path: P,
+	key: Option<&str>,
+	threshold_pct: u64,
+) where
+	T::Runtime: Config,
+{
+	let consumed = session.last_call_gas();
+	let key = match key {
+		Some(key) => key.to_string(),
+		None => default_key(session),
+	};
+
+	let _guard = BASELINES_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+	let mut baselines = GasBaselines::load(&path).expect("Failed to load the gas baselines");
+	let baseline = match baselines.get(&key) {
+		Some(baseline) if std::env::var_os(UPDATE_BASELINES_ENV_VAR).is_none() => baseline,
+		_ => {
+			baselines.set(key, consumed);
+			baselines.save(&path).expect("Failed to save the gas baselines");
+			return;
+		},
+	};
+
+	let limit = |value: u64| value.saturating_add(value.saturating_mul(threshold_pct) / 100);
+	assert!(
+		consumed.ref_time <= limit(baseline.ref_time) &&
+			consumed.proof_size <= limit(baseline.proof_size),
+		"Gas regression of `{key}` beyond {threshold_pct}%: consumed {consumed:?}, baseline \
+		 {baseline:?}. Re-run with `{UPDATE_BASELINES_ENV_VAR}=1` to accept it."
+	);
+}
+
+/// The name of the current test (the test harness names the test threads after them) and the
+/// label of the called message.
+fn default_key<T: Sandbox>(session: &Session<T>) -> String
+where
+	T::Runtime: Config,
+{
+	let test = std::thread::current().name().unwrap_or("main").to_string();
+	let message = session.last_call_label().unwrap_or_else(|| "<unknown>".to_string());
+	format!("{test}::{message}")
+}
+
+/// Asserts that the gas consumed by the last contract call of the session didn't grow by more
+/// than `threshold_pct` percent compared to the committed baseline.
+///
+/// Baselines are stored in `gas-baselines.json` at the crate root (or the given file), under the
+/// name of the test and the label of the called message (or the given key). Missing baselines are
+/// recorded. Run the tests with `DRINK_UPDATE_GAS_BASELINES=1` to accept new gas consumptions.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+/// assert_no_gas_regression!(session, 5);
+/// assert_no_gas_regression!(session, 5, key = "transfer_with_data");
+/// assert_no_gas_regression!(session, 5, key = "transfer", path = "baselines/psp22.json");
+/// ```
+#[macro_export]
+macro_rules! assert_no_gas_regression {
+	($session:expr, $threshold_pct:expr $(,)?) => {
+		$crate::session::gas_baselines::check_gas_regression(
+			&$session,
+			::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+				.join($crate::session::gas_baselines::BASELINES_FILE),
+			None,
+			$threshold_pct,
+		)
+	};
+	($session:expr, $threshold_pct:expr, key = $key:expr $(,)?) => {
+		$crate::session::gas_baselines::check_gas_regression(
+			&$session,
+			::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+				.join($crate::session::gas_baselines::BASELINES_FILE),
+			Some($key),
+			$threshold_pct,
+		)
+	};
+	($session:expr, $threshold_pct:expr, key = $key:expr, path = $path:expr $(,)?) => {
+		$crate::session::gas_baselines::check_gas_regression(
+			&$session,
+			::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+			Some($key),
+			$threshold_pct,
+		)
+	};
+}
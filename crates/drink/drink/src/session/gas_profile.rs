@@ -0,0 +1,197 @@
+//! Gas profiles of contracts: the reference gas consumption of their constructors and messages,
+//! committed next to the tests in a `<contract>.gas.toml` file, so that gas changes show up in the
+//! reviewed artifacts of the contract.
+//!
+//! The profile is measured from the deployments and calls of the session, and compared with the
+//! committed one with [`verify_gas_profile`] at the end of a test exercising the typical calls:
+//!
+//! ```rust, ignore
+//! let bundle = BundleProvider::local()?;
+//! session.deploy_bundle(bundle.clone(), "new", &["1000"], NO_SALT, NO_ENDOWMENT)?;
+//! session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+//! verify_gas_profile(&session, &bundle);
+//! ```
+//!
+//! A missing profile is emitted on the first run. To accept new gas consumptions, re-run the tests
+//! with `DRINK_UPDATE_GAS_PROFILES=1` and commit the updated file.
+
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	sync::{Mutex, PoisonError},
+};
+
+use ink_sandbox::Sandbox;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{
+		gas_baselines::GasUsage, interface::MessageInfo, mock::Selector, record::Action,
+		ContractBundle, Session,
+	},
+	DrinkResult, Error,
+};
+
+/// Environment variable which, when set, makes `verify_gas_profile` overwrite the profiles with
+/// the current gas consumption instead of comparing with them.
+pub const UPDATE_PROFILES_ENV_VAR: &str = "DRINK_UPDATE_GAS_PROFILES";
+
+/// Serializes the read-modify-write cycles of the tests running in parallel.
+static PROFILES_LOCK: Mutex<()> = Mutex::new(());
+
+/// The reference gas consumption of the constructors and messages of a contract, by label.
+///
+/// When a constructor or message is called several times, the largest consumption is kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfile {
+	/// The gas consumed by the deployments, by constructor.
+	#[serde(default)]
+	pub constructors: BTreeMap<String, GasUsage>,
+	/// The gas consumed by the calls, by message.
+	#[serde(default)]
+	pub messages: BTreeMap<String, GasUsage>,
+}
+
+impl GasProfile {
+	/// Returns the path of the profile of the contract of `bundle`: `<contract>.gas.toml` at the
+	/// root of the crate being tested (or the current directory if unknown).
+	pub fn path_of(bundle: &ContractBundle) -> PathBuf {
+		std::env::var_os("CARGO_MANIFEST_DIR")
+			.map(PathBuf::from)
+			.unwrap_or_default()
+			.join(format!("{}.gas.toml", bundle.name))
+	}
+
+	/// Loads the profile from the TOML file under `path`, if it exists.
+	pub fn load<P: AsRef<Path>>(path: P) -> DrinkResult<Option<Self>> {
+		let toml = match std::fs::read_to_string(path) {
+			Ok(toml) => toml,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+			Err(e) =>
+				return Err(Error::GasProfileFailed(format!("Failed to read the file:\n{e:?}"))),
+		};
+		toml::from_str(&toml)
+			.map(Some)
+			.map_err(|e| Error::GasProfileFailed(format!("Failed to deserialize:\n{e:?}")))
+	}
+
+	/// Saves the profile to a TOML file under `path`.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> DrinkResult<()> {
+		let toml = toml::to_string_pretty(self)
+			.map_err(|e| Error::GasProfileFailed(format!("Failed to serialize:\n{e:?}")))?;
+		std::fs::write(path, toml)
+			.map_err(|e| Error::GasProfileFailed(format!("Failed to write the file:\n{e:?}")))
+	}
+
+	/// Records `usage` for `label` in `entries`, keeping the largest consumption.
+	fn record(entries: &mut BTreeMap<String, GasUsage>, label: &str, usage: GasUsage) {
+		let entry = entries.entry(label.to_string()).or_insert(usage);
+		entry.ref_time = entry.ref_time.max(usage.ref_time);
+		entry.proof_size = entry.proof_size.max(usage.proof_size);
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Returns the gas profile of the contract of `bundle`, measured from the deployments of its
+	/// code and the calls to the contracts deployed from it during the session.
+	pub fn gas_profile(&self, bundle: &ContractBundle) -> GasProfile {
+		let interface = bundle.interface();
+		let label_of = |entries: &[MessageInfo], data: &[u8]| {
+			let selector: Selector = data.get(..4)?.try_into().ok()?;
+			let entry = entries.iter().find(|entry| entry.selector == selector)?;
+			Some(entry.label.clone())
+		};
+
+		let mut profile = GasProfile::default();
+		let mut addresses = Vec::new();
+		let mut deploy_results = self.record.deploy_results().iter();
+		let mut call_results = self.record.call_results().iter();
+		for action in self.record.actions() {
+			match action {
+				Action::Deploy { code, data, .. } => {
+					let Some(result) = deploy_results.next() else { break };
+					if *code != bundle.wasm {
+						continue;
+					}
+					match &result.result {
+						Ok(exec_result) if !exec_result.result.did_revert() =>
+							addresses.push(exec_result.account_id.clone()),
+						_ => {},
+					}
+					if let Some(label) = label_of(&interface.constructors, data) {
+						GasProfile::record(
+							&mut profile.constructors,
+							&label,
+							result.gas_consumed.into(),
+						);
+					}
+				},
+				Action::Call { address, data, .. } => {
+					let Some(result) = call_results.next() else { break };
+					if !addresses.contains(address) {
+						continue;
+					}
+					if let Some(label) = label_of(&interface.messages, data) {
+						GasProfile::record(
+							&mut profile.messages,
+							&label,
+							result.gas_consumed.into(),
+						);
+					}
+				},
+				Action::Upload { .. } => {},
+			}
+		}
+		profile
+	}
+}
+
+/// Compares the gas profile of the contract of `bundle` measured during `session` with the
+/// committed one (see [`GasProfile::path_of`]).
+///
+/// Panics if a constructor or message consumed a different amount of gas than its reference, or
+/// has no reference. Emits the profile if it is missing or if `DRINK_UPDATE_GAS_PROFILES` is set.
+#[track_caller]
+pub fn verify_gas_profile<T: Sandbox>(session: &Session<T>, bundle: &ContractBundle)
+where
+	T::Runtime: Config,
+{
+	let measured = session.gas_profile(bundle);
+	let path = GasProfile::path_of(bundle);
+
+	let _guard = PROFILES_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+	let reference = match GasProfile::load(&path).expect("Failed to load the gas profile") {
+		Some(reference) if std::env::var_os(UPDATE_PROFILES_ENV_VAR).is_none() => reference,
+		_ => {
+			measured.save(&path).expect("Failed to save the gas profile");
+			return;
+		},
+	};
+
+	let differences: Vec<String> = [
+		("constructor", &measured.constructors, &reference.constructors),
+		("message", &measured.messages, &reference.messages),
+	]
+	.into_iter()
+	.flat_map(|(kind, measured, reference)| {
+		measured.iter().filter_map(move |(label, usage)| match reference.get(label) {
+			Some(expected) if expected == usage => None,
+			Some(expected) =>
+				Some(format!("{kind} `{label}`: consumed {usage:?}, reference {expected:?}")),
+			None => Some(format!("{kind} `{label}`: consumed {usage:?}, no reference")),
+		})
+	})
+	.collect();
+	assert!(
+		differences.is_empty(),
+		"The gas profile of `{}` ({}) changed:\n{}\nRe-run with `{UPDATE_PROFILES_ENV_VAR}=1` to \
+		 accept it.",
+		bundle.name,
+		path.display(),
+		differences.join("\n"),
+	);
+}
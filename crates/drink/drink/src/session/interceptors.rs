@@ -0,0 +1,123 @@
+//! Interceptors of the deployments and calls of a session, for cross-cutting test policies (e.g.
+//! always adding a gas margin, timing the calls or calling as another actor) without wrapping
+//! every call site.
+//!
+//! Interceptors can modify the parameters of every interaction before it is executed, and observe
+//! its outcome afterwards:
+//!
+//! ```rust, ignore
+//! struct GasMargin;
+//!
+//! impl<R: Config> Interceptor<R> for GasMargin {
+//!     fn before(&mut self, params: &mut CallParameters<R>) {
+//!         params.gas_limit = params.gas_limit.saturating_mul(2);
+//!     }
+//! }
+//!
+//! session.add_interceptor(GasMargin);
+//! ```
+//!
+//! Interceptors form a chain: they are run in the order they were added before the interaction,
+//! and in the reverse order after it.
+
+use frame_support::{sp_runtime::DispatchError, weights::Weight};
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::{Config, ExecReturnValue},
+	session::{BalanceOf, Session},
+};
+
+/// The parameters of a deployment or call, as about to be executed.
+pub struct CallParameters<R: Config> {
+	address: Option<AccountIdFor<R>>,
+	/// The account deploying or calling the contract.
+	pub actor: AccountIdFor<R>,
+	/// The input of the constructor or message (selector and encoded arguments).
+	pub data: Vec<u8>,
+	/// The value transferred to the contract.
+	pub endowment: BalanceOf<R>,
+	/// The gas limit of the interaction.
+	pub gas_limit: Weight,
+}
+
+impl<R: Config> CallParameters<R> {
+	pub(crate) fn new(
+		address: Option<AccountIdFor<R>>,
+		actor: AccountIdFor<R>,
+		data: Vec<u8>,
+		endowment: BalanceOf<R>,
+		gas_limit: Weight,
+	) -> Self {
+		Self { address, actor, data, endowment, gas_limit }
+	}
+
+	/// Returns the address of the called contract, or `None` for a deployment.
+	pub fn address(&self) -> Option<&AccountIdFor<R>> {
+		self.address.as_ref()
+	}
+
+	/// Returns whether the interaction is a deployment.
+	pub fn is_deployment(&self) -> bool {
+		self.address.is_none()
+	}
+}
+
+/// The outcome of a deployment or call.
+#[derive(Clone, Debug)]
+pub struct CallOutcome {
+	/// The gas consumed by the interaction.
+	pub gas_consumed: Weight,
+	/// The gas required by the interaction, which may exceed the consumed gas.
+	pub gas_required: Weight,
+	/// The value returned by the contract, or the error of the contracts pallet.
+	pub result: Result<ExecReturnValue, DispatchError>,
+}
+
+/// An interceptor of the deployments and calls of a session. Both hooks do nothing by default.
+pub trait Interceptor<R: Config>: Send {
+	/// Called before a deployment or call is executed (and recorded), with its parameters.
+	fn before(&mut self, _params: &mut CallParameters<R>) {}
+
+	/// Called after a deployment or call was executed, with its parameters and outcome.
+	fn after(&mut self, _params: &CallParameters<R>, _outcome: &CallOutcome) {}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Adds `interceptor` at the end of the chain of interceptors.
+	pub fn add_interceptor(&mut self, interceptor: impl Interceptor<T::Runtime> + 'static) {
+		self.interceptors.push(Box::new(interceptor));
+	}
+
+	/// Adds `interceptor` at the end of the chain of interceptors and returns updated `self`.
+	pub fn with_interceptor(mut self, interceptor: impl Interceptor<T::Runtime> + 'static) -> Self {
+		self.add_interceptor(interceptor);
+		self
+	}
+
+	/// Removes all the interceptors.
+	pub fn clear_interceptors(&mut self) {
+		self.interceptors.clear();
+	}
+
+	/// Runs the `before` hooks of the interceptors on `params`.
+	pub(crate) fn intercept_before(&mut self, params: &mut CallParameters<T::Runtime>) {
+		for interceptor in &mut self.interceptors {
+			interceptor.before(params);
+		}
+	}
+
+	/// Runs the `after` hooks of the interceptors, in the reverse order.
+	pub(crate) fn intercept_after(
+		&mut self,
+		params: &CallParameters<T::Runtime>,
+		outcome: &CallOutcome,
+	) {
+		for interceptor in self.interceptors.iter_mut().rev() {
+			interceptor.after(params, outcome);
+		}
+	}
+}
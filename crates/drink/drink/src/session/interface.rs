@@ -0,0 +1,155 @@
+//! Introspection of the messages and constructors of a contract, based on its metadata.
+
+use contract_transcode::ContractMessageTranscoder;
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{mock::Selector, ContractBundle, Session},
+};
+
+/// An argument of a contract message or constructor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArgumentInfo {
+	/// Name of the argument.
+	pub label: String,
+	/// Display name of the argument type, e.g. `Option<AccountId>`.
+	pub type_name: String,
+	/// Id of the argument type in the type registry of the metadata.
+	pub type_id: u32,
+}
+
+/// A message or a constructor of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageInfo {
+	/// Name of the message, e.g. `PSP22::transfer`.
+	pub label: String,
+	/// Selector of the message.
+	pub selector: Selector,
+	/// Arguments of the message, in order.
+	pub args: Vec<ArgumentInfo>,
+	/// Display name of the return type, if the metadata specifies one.
+	pub return_type: Option<String>,
+	/// Whether the message mutates the contract storage. Always `true` for constructors.
+	pub mutates: bool,
+	/// Whether the message accepts a transferred value.
+	pub payable: bool,
+	/// Whether the message is marked as the default one.
+	pub default: bool,
+}
+
+/// The constructors and messages of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractInterface {
+	/// Constructors of the contract.
+	pub constructors: Vec<MessageInfo>,
+	/// Messages of the contract.
+	pub messages: Vec<MessageInfo>,
+}
+
+impl ContractInterface {
+	/// Parses the interface from the metadata of the transcoder.
+	pub fn from_transcoder(transcoder: &ContractMessageTranscoder) -> Self {
+		let spec = transcoder.metadata().spec();
+		let constructors = spec
+			.constructors()
+			.iter()
+			.map(|constructor| MessageInfo {
+				label: constructor.label().to_string(),
+				selector: selector(&constructor.selector().to_bytes()[..]),
+				args: arguments(constructor.args().iter().map(|arg| {
+					(arg.label(), arg.ty().ty().id, arg.ty().display_name().segments())
+				})),
+				return_type: type_name(
+					constructor.return_type().ret_type().display_name().segments(),
+				),
+				mutates: true,
+				payable: constructor.payable(),
+				default: constructor.default(),
+			})
+			.collect();
+		let messages = spec
+			.messages()
+			.iter()
+			.map(|message| MessageInfo {
+				label: message.label().to_string(),
+				selector: selector(&message.selector().to_bytes()[..]),
+				args: arguments(message.args().iter().map(|arg| {
+					(arg.label(), arg.ty().ty().id, arg.ty().display_name().segments())
+				})),
+				return_type: type_name(message.return_type().ret_type().display_name().segments()),
+				mutates: message.mutates(),
+				payable: message.payable(),
+				default: message.default(),
+			})
+			.collect();
+		Self { constructors, messages }
+	}
+
+	/// Returns the message with the given label, if any.
+	pub fn message(&self, label: &str) -> Option<&MessageInfo> {
+		self.messages.iter().find(|message| message.label == label)
+	}
+
+	/// Returns the message with the given selector, if any.
+	pub fn message_by_selector(&self, selector: Selector) -> Option<&MessageInfo> {
+		self.messages.iter().find(|message| message.selector == selector)
+	}
+
+	/// Returns the constructor with the given selector, if any.
+	pub fn constructor_by_selector(&self, selector: Selector) -> Option<&MessageInfo> {
+		self.constructors.iter().find(|constructor| constructor.selector == selector)
+	}
+}
+
+fn selector(bytes: &[u8]) -> Selector {
+	let mut selector = Selector::default();
+	selector.copy_from_slice(bytes);
+	selector
+}
+
+fn arguments<'a>(args: impl Iterator<Item = (&'a String, u32, &'a [String])>) -> Vec<ArgumentInfo> {
+	args.map(|(label, type_id, type_segments)| ArgumentInfo {
+		label: label.clone(),
+		type_name: type_name(type_segments).unwrap_or_default(),
+		type_id,
+	})
+	.collect()
+}
+
+fn type_name(segments: &[String]) -> Option<String> {
+	(!segments.is_empty()).then(|| segments.join("::"))
+}
+
+impl ContractBundle {
+	/// Returns the constructors and messages of the contract.
+	pub fn interface(&self) -> ContractInterface {
+		ContractInterface::from_transcoder(&self.transcoder)
+	}
+
+	/// Returns the messages of the contract.
+	pub fn messages(&self) -> Vec<MessageInfo> {
+		self.interface().messages
+	}
+
+	/// Returns the constructors of the contract.
+	pub fn constructors(&self) -> Vec<MessageInfo> {
+		self.interface().constructors
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Returns the constructors and messages of the contract under `address`, if a transcoder is
+	/// registered for it (e.g. because it was deployed in this session).
+	pub fn contract_metadata(
+		&self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> Option<ContractInterface> {
+		self.transcoders
+			.get(address)
+			.map(|transcoder| ContractInterface::from_transcoder(&transcoder))
+	}
+}
@@ -6,7 +6,10 @@ use crate::{
 	errors::MessageResult,
 	pallet_contracts::{chain_extension::ReturnFlags, debug::ExecResult, ExecReturnValue},
 	pallet_contracts_debugging::InterceptingExtT,
-	session::mock::{MockRegistry, Selector},
+	session::{
+		breakpoints::Breakpoints,
+		mock::{MockRegistry, Selector},
+	},
 };
 
 /// Runtime extension enabling contract call interception.
@@ -16,15 +19,24 @@ pub(crate) struct MockingExtension<AccountId: Ord> {
 	/// Potentially the runtime is executed in parallel and thus we need to wrap the registry in
 	/// `Arc<Mutex>` instead of `Rc<RefCell>`.
 	pub mock_registry: Arc<Mutex<MockRegistry<AccountId>>>,
+	/// Breakpoints, shared with the session.
+	pub breakpoints: Arc<Mutex<Breakpoints>>,
 }
 
 impl<AccountId: Ord + Decode> InterceptingExtT for MockingExtension<AccountId> {
 	fn intercept_call(
 		&self,
 		contract_address: Vec<u8>,
-		_is_call: bool,
+		is_call: bool,
 		input_data: Vec<u8>,
 	) -> Vec<u8> {
+		if is_call {
+			self.breakpoints
+				.lock()
+				.expect("Should be able to acquire breakpoints")
+				.hit(&contract_address, &input_data);
+		}
+
 		let contract_address = Decode::decode(&mut &contract_address[..])
 			.expect("Contract address should be decodable");
 
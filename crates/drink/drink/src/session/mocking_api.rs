@@ -29,6 +29,7 @@ where
 		let mock_bytes = wat::parse_str(DUMMY_CONTRACT).expect("Dummy contract should be valid");
 		let salt = self.mocks.lock().expect("Should be able to acquire lock on registry").salt();
 
+		let gas_limit = self.get_default_gas_limit();
 		let mock_address = self
 			.sandbox()
 			.deploy_contract(
@@ -37,7 +38,7 @@ where
 				vec![],
 				salt,
 				T::default_actor(),
-				T::default_gas_limit(),
+				gas_limit,
 				None,
 			)
 			.result
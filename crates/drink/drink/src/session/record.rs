@@ -1,13 +1,20 @@
-use std::sync::Arc;
+use std::{fmt::Debug, sync::Arc};
 
 use contract_transcode::{ContractMessageTranscoder, Value};
+use frame_support::weights::Weight;
 use frame_system::Config as SysConfig;
-use ink_sandbox::{pallet_contracts, AccountIdFor, EventRecordOf};
+use ink_sandbox::{host_fns::CallMetrics, pallet_contracts, AccountIdFor, EventRecordOf};
 use scale::{Decode, Encode};
+use sp_core::hashing::blake2_256;
 
 use crate::{
 	errors::MessageResult,
-	session::{error::SessionError, BalanceOf},
+	hex::to_hex,
+	pallet_contracts::Determinism,
+	session::{
+		error::SessionError, event_coverage::EventCoverage, replay::RecordedSession, BalanceOf,
+	},
+	DrinkResult,
 };
 
 type ContractInstantiateResult<R> =
@@ -36,6 +43,43 @@ pub struct Record<Config: pallet_contracts::Config> {
 
 	/// The events emitted by the contracts.
 	event_batches: Vec<EventBatch<Config>>,
+
+	/// The runtime calls dispatched by the contracts via `call_runtime`.
+	runtime_calls: Vec<<Config as SysConfig>::RuntimeCall>,
+
+	/// The metrics of the host functions called during the contract interactions.
+	call_metrics: Vec<CallMetrics>,
+
+	/// The inputs of all the contract interactions, in the order of execution.
+	actions: Vec<Action<Config>>,
+
+	/// The collector of the asserted events, if the session is registered into one.
+	event_coverage: Option<EventCoverage>,
+}
+
+/// A contract interaction performed during a session, together with all the inputs needed to
+/// replay it.
+pub enum Action<Config: pallet_contracts::Config> {
+	/// Contract deployment (code upload together with instantiation).
+	Deploy {
+		actor: AccountIdFor<Config>,
+		code: Vec<u8>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		endowment: BalanceOf<Config>,
+		gas_limit: Weight,
+	},
+	/// Code upload.
+	Upload { actor: AccountIdFor<Config>, code: Vec<u8>, determinism: Determinism },
+	/// Contract call.
+	Call {
+		actor: AccountIdFor<Config>,
+		address: AccountIdFor<Config>,
+		data: Vec<u8>,
+		endowment: BalanceOf<Config>,
+		gas_limit: Weight,
+		determinism: Determinism,
+	},
 }
 
 // API for `Session` to record results and events related to contract interaction.
@@ -57,7 +101,24 @@ impl<Config: pallet_contracts::Config> Record<Config> {
 	}
 
 	pub(super) fn push_event_batches(&mut self, events: Vec<EventRecordOf<Config>>) {
-		self.event_batches.push(EventBatch { events });
+		self.event_batches
+			.push(EventBatch { events, event_coverage: self.event_coverage.clone() });
+	}
+
+	pub(super) fn set_event_coverage(&mut self, coverage: EventCoverage) {
+		self.event_coverage = Some(coverage);
+	}
+
+	pub(super) fn push_action(&mut self, action: Action<Config>) {
+		self.actions.push(action);
+	}
+
+	pub(super) fn push_runtime_calls(&mut self, calls: Vec<<Config as SysConfig>::RuntimeCall>) {
+		self.runtime_calls.extend(calls);
+	}
+
+	pub(super) fn push_call_metrics(&mut self, metrics: CallMetrics) {
+		self.call_metrics.push(metrics);
 	}
 }
 
@@ -118,10 +179,16 @@ impl<Config: pallet_contracts::Config> Record<Config> {
 		})
 	}
 
-	/// Returns all the event batches that were recorded for contract interactions during the
-	/// session.
-	pub fn event_batches(&self) -> &[EventBatch<Config>] {
-		&self.event_batches
+	/// Returns the metrics of the host functions called during each contract interaction
+	/// (instantiation or call) of the session, in the order of execution.
+	pub fn call_metrics(&self) -> &[CallMetrics] {
+		&self.call_metrics
+	}
+
+	/// Returns the metrics of the host functions called during the last contract interaction of
+	/// the session, including the nested calls. Panics if there were no contract interactions.
+	pub fn last_call_metrics(&self) -> &CallMetrics {
+		self.call_metrics.last().expect("No call metrics")
 	}
 
 	/// Returns the last event batch that was recorded for contract interactions during the session.
@@ -129,11 +196,85 @@ impl<Config: pallet_contracts::Config> Record<Config> {
 	pub fn last_event_batch(&self) -> &EventBatch<Config> {
 		self.event_batches.last().expect("No event batches")
 	}
+
+	/// Returns the event batches of all the contract interactions of the session, in the order of
+	/// execution. Unlike `last_event_batch` and `event_batches_since`, this covers the whole
+	/// session.
+	pub fn event_batches(&self) -> &[EventBatch<Config>] {
+		&self.event_batches
+	}
+
+	/// Returns a checkpoint marking the current end of the recorded events.
+	///
+	/// Events recorded after taking the checkpoint can be retrieved with `events_since`, e.g. to
+	/// assert the cumulative events of a multi-step scenario.
+	pub fn checkpoint(&self) -> Checkpoint {
+		Checkpoint(self.event_batches.len())
+	}
+
+	/// Returns the event batches that were recorded after `checkpoint` was taken.
+	pub fn event_batches_since(&self, checkpoint: Checkpoint) -> &[EventBatch<Config>] {
+		self.event_batches.get(checkpoint.0..).unwrap_or_default()
+	}
+
+	/// Returns all the events that were recorded after `checkpoint` was taken, flattened across
+	/// the contract interactions, in the order of emission.
+	pub fn events_since(&self, checkpoint: Checkpoint) -> Vec<&EventRecordOf<Config>> {
+		self.event_batches_since(checkpoint)
+			.iter()
+			.flat_map(|batch| batch.all_events())
+			.collect()
+	}
+
+	/// Returns the runtime calls dispatched by the contracts via `call_runtime`, in the order of
+	/// dispatch.
+	pub fn runtime_calls(&self) -> &[<Config as SysConfig>::RuntimeCall] {
+		&self.runtime_calls
+	}
+
+	/// Returns all the contract interactions (deployments, uploads and calls) performed during the
+	/// session, in the order of execution.
+	pub fn actions(&self) -> &[Action<Config>] {
+		&self.actions
+	}
+
+	/// Exports the contract interactions performed during the session to a JSON file under `path`.
+	///
+	/// The file can be loaded with `Replay::load` and re-executed against a fresh sandbox, which
+	/// makes it suitable for attaching reproducible scenarios to bug reports.
+	pub fn export_json<P: AsRef<std::path::Path>>(&self, path: P) -> DrinkResult<()> {
+		RecordedSession::from_actions(&self.actions).save(path)
+	}
 }
 
+/// An event emitted by a contract, together with its topics.
+#[derive(
+	frame_support::CloneNoBound,
+	frame_support::DebugNoBound,
+	frame_support::PartialEqNoBound,
+	frame_support::EqNoBound,
+)]
+pub struct ContractEvent<R: SysConfig> {
+	/// Address of the contract that emitted the event.
+	pub contract: AccountIdFor<R>,
+	/// The (encoded) event data.
+	pub data: Vec<u8>,
+	/// The topics of the event, i.e. its signature topic (unless the event is anonymous) followed
+	/// by the topics of its indexed fields.
+	pub topics: Vec<R::Hash>,
+}
+
+/// A position in the history of the events recorded during a session.
+///
+/// See `Record::checkpoint`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(usize);
+
 /// A batch of runtime events that were emitted during a single contract interaction.
 pub struct EventBatch<R: SysConfig> {
 	events: Vec<EventRecordOf<R>>,
+	/// The collector of the asserted events of the session, if any.
+	event_coverage: Option<EventCoverage>,
 }
 
 impl<R: SysConfig> EventBatch<R> {
@@ -141,6 +282,10 @@ impl<R: SysConfig> EventBatch<R> {
 	pub fn all_events(&self) -> &[EventRecordOf<R>] {
 		&self.events
 	}
+
+	pub(super) fn event_coverage(&self) -> Option<&EventCoverage> {
+		self.event_coverage.as_ref()
+	}
 }
 
 impl<R> EventBatch<R>
@@ -172,6 +317,21 @@ where
 			.collect::<Vec<Vec<u8>>>()
 	}
 
+	/// The same as `contract_events`, but also returns the emitting contract and the topics of
+	/// every event.
+	///
+	/// **WARNING**: This method will return all the events that were emitted by ANY contract.
+	pub fn contract_events_with_topics(&self) -> Vec<ContractEvent<R>> {
+		self.events
+			.iter()
+			.filter_map(|event_record| match event_record.event.clone().try_into() {
+				Ok(pallet_contracts::Event::<R>::ContractEmitted { contract, data }) =>
+					Some(ContractEvent { contract, data, topics: event_record.topics.clone() }),
+				_ => None,
+			})
+			.collect()
+	}
+
 	/// The same as `contract_events`, but decodes the events using the given transcoder.
 	///
 	/// **WARNING**: This method will try to decode all the events that were emitted by ANY
@@ -210,4 +370,161 @@ where
 			})
 			.collect()
 	}
+
+	/// Asserts that the last contract event emitted during the contract interaction has the topic
+	/// of the indexed field `value` (see [`event_topic`]).
+	///
+	/// See the `assert_event_topic!` macro.
+	#[track_caller]
+	pub fn assert_last_contract_event_topic<V: Encode + Debug>(&self, value: V) {
+		let topic = event_topic(&value);
+		let Some(event) = self.contract_events_with_topics().pop() else {
+			panic!("No contract event emitted, expected an event with the topic of {value:?}");
+		};
+		assert!(
+			event.topics.iter().any(|t| t.as_ref() == topic),
+			"Last contract event has no topic {} (of {value:?}), its topics are: [{}]",
+			to_hex(&topic),
+			event.topics.iter().map(|t| to_hex(t.as_ref())).collect::<Vec<_>>().join(", "),
+		);
+		self.note_last_contract_event_asserted();
+	}
+
+	/// Asserts that the last contract event emitted during the contract interaction is `expected`,
+	/// decoding the event as the type of `expected`.
+	///
+	/// See the `assert_last_event!` macro.
+	#[track_caller]
+	pub fn assert_last_contract_event<E: Decode + PartialEq + Debug>(&self, expected: E) {
+		let last_event = self.contract_events().pop();
+		match last_event.as_deref().map(|mut data| E::decode(&mut data)) {
+			Some(Ok(event)) => {
+				assert_eq!(event, expected, "Unexpected last contract event");
+				self.note_last_contract_event_asserted();
+			},
+			Some(Err(_)) => panic!(
+				"Last contract event {} cannot be decoded as {expected:?}",
+				to_hex(last_event.as_deref().unwrap_or_default())
+			),
+			None => panic!("No contract event emitted, expected {expected:?}"),
+		}
+	}
+}
+
+impl<R> Record<R>
+where
+	R: pallet_contracts::Config,
+	<R as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<R>>,
+{
+	/// Returns all the contract events that were recorded after `checkpoint` was taken, flattened
+	/// across the contract interactions, in the order of emission.
+	///
+	/// **WARNING**: The same caveats as for `EventBatch::contract_events` apply.
+	pub fn contract_events_since(&self, checkpoint: Checkpoint) -> Vec<Vec<u8>> {
+		self.event_batches_since(checkpoint)
+			.iter()
+			.flat_map(|batch| batch.contract_events())
+			.collect()
+	}
+}
+
+/// Computes the topic under which ink! contracts index the event field `value`.
+///
+/// Values whose encoding fits into 32 bytes are used directly (padded with zeros), longer values
+/// are hashed with `blake2_256`.
+pub fn event_topic<V: Encode + ?Sized>(value: &V) -> [u8; 32] {
+	let encoded = value.encode();
+	if encoded.len() > 32 {
+		return blake2_256(&encoded);
+	}
+	let mut topic = [0u8; 32];
+	topic[..encoded.len()].copy_from_slice(&encoded);
+	topic
+}
+
+/// Asserts that the last contract event emitted during the last contract interaction of the
+/// session has the topic of the given indexed field value.
+///
+/// Useful to validate the topic encoding of events that are queried by indexers.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+/// assert_event_topic!(session, Some(ink::primitives::AccountId::from(BOB)));
+/// ```
+#[macro_export]
+macro_rules! assert_event_topic {
+	($session:expr, $value:expr $(,)?) => {
+		$session.record().last_event_batch().assert_last_contract_event_topic($value)
+	};
+}
+
+/// Asserts that the last contract event emitted during the last contract interaction of the
+/// session is the given event.
+///
+/// The recorded event is decoded as the type of the expected event, e.g. a type generated with
+/// `#[drink::contract_events]`.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+/// assert_last_event!(session, events::Transfer { from: Some(ALICE), to: Some(BOB), value: 10 });
+/// ```
+#[macro_export]
+macro_rules! assert_last_event {
+	($session:expr, $event:expr $(,)?) => {
+		$session.record().last_event_batch().assert_last_contract_event($event)
+	};
+}
+
+/// Asserts that a contract dispatched a runtime call matching the given pattern via
+/// `call_runtime` during the session.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("transfer_through_runtime", &[BOB.to_string(), "10"], None)??;
+/// assert_runtime_call_made!(
+///     session,
+///     RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death { value: 10, .. })
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_runtime_call_made {
+	($session:expr, $call:pat $(if $guard:expr)? $(,)?) => {{
+		let calls = $session.record().runtime_calls();
+		assert!(
+			calls.iter().any(|call| matches!(call, $call $(if $guard)?)),
+			"No runtime call matching `{}` was made. Runtime calls: {:?}",
+			stringify!($call),
+			calls
+		);
+	}};
+}
+
+/// Asserts that the last contract interaction of the session (including the nested calls)
+/// consumed less than the given proof size, in bytes.
+///
+/// Parachain blocks are bounded by their PoV size: a contract fitting the `ref_time` budget may
+/// still not fit a block in production.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+/// assert_pov_below!(session, 64 * 1024);
+/// ```
+#[macro_export]
+macro_rules! assert_pov_below {
+	($session:expr, $bytes:expr $(,)?) => {{
+		let consumed = $session.record().last_call_metrics().proof_size_consumed;
+		let limit: u64 = $bytes;
+		assert!(
+			consumed < limit,
+			"The last contract interaction consumed a proof size of {consumed} bytes, not below \
+			 {limit} bytes"
+		);
+	}};
 }
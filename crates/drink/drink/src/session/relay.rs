@@ -0,0 +1,263 @@
+//! Simulation of relayers submitting signed meta-transactions to a forwarder contract, for
+//! stress-testing its replay protection (nonces, deadlines, ...).
+//!
+//! Every meta-transaction is relayed a configurable number of times, by different relayers, in a
+//! configurable order and spread across blocks. A sound forwarder executes every meta-transaction
+//! at most once, however often and in whatever order it is relayed:
+//!
+//! ```rust, ignore
+//! let report = RelaySimulation::new(forwarder, "execute")
+//!     .relayers([BOB, CHARLIE])
+//!     .meta_tx([signature_arg(&request_0.encode()), signature_arg(&signature_0)])
+//!     .meta_tx([signature_arg(&request_1.encode()), signature_arg(&signature_1)])
+//!     .duplicates(2)
+//!     .ordering(RelayOrdering::Shuffled)
+//!     .per_block(2)
+//!     .run(&mut session);
+//! report.assert_no_replay();
+//! report.assert_all_executed();
+//! ```
+
+use std::fmt::{self, Display};
+
+use frame_support::sp_runtime::SaturatedConversion;
+use ink_sandbox::{api::prelude::*, seed, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, Session},
+};
+
+/// The order in which the meta-transactions (including their duplicates) are relayed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RelayOrdering {
+	/// In the order they were added, all the copies of a meta-transaction in a row.
+	#[default]
+	InOrder,
+	/// In the reverse order, e.g. to relay higher nonces first.
+	Reversed,
+	/// Every meta-transaction once in order, then every duplicate round in order, as if the
+	/// relayers raced each other.
+	Interleaved,
+	/// In a random order, derived from the seed of the session.
+	Shuffled,
+}
+
+/// The outcome of a relayed meta-transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RelayOutcome {
+	/// The forwarder executed the meta-transaction.
+	Executed,
+	/// The forwarder rejected the meta-transaction (the call failed or reverted).
+	Rejected(String),
+}
+
+/// A meta-transaction relayed to the forwarder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Submission<AccountId> {
+	/// The index of the meta-transaction, in the order they were added.
+	pub meta_tx: usize,
+	/// The relayer submitting it.
+	pub relayer: AccountId,
+	/// The number of the block it was submitted in.
+	pub block: u64,
+	/// The outcome of the submission.
+	pub outcome: RelayOutcome,
+}
+
+impl<AccountId: Display> Display for Submission<AccountId> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"meta-tx #{} relayed by {} in block {}: {:?}",
+			self.meta_tx, self.relayer, self.block, self.outcome
+		)
+	}
+}
+
+/// The submissions of a relay simulation, in the order they were made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayReport<AccountId> {
+	/// The number of distinct meta-transactions.
+	pub meta_txs: usize,
+	/// The submissions.
+	pub submissions: Vec<Submission<AccountId>>,
+}
+
+impl<AccountId: Display> RelayReport<AccountId> {
+	/// Returns how many times the meta-transaction `meta_tx` was executed.
+	pub fn executions(&self, meta_tx: usize) -> usize {
+		self.submissions
+			.iter()
+			.filter(|submission| {
+				submission.meta_tx == meta_tx && submission.outcome == RelayOutcome::Executed
+			})
+			.count()
+	}
+
+	/// Asserts that no meta-transaction was executed more than once, listing the replays otherwise.
+	#[track_caller]
+	pub fn assert_no_replay(&self) {
+		let replayed = (0..self.meta_txs).filter(|&tx| self.executions(tx) > 1).collect::<Vec<_>>();
+		if !replayed.is_empty() {
+			panic!(
+				"{} meta-transaction(s) were replayed:\n{}",
+				replayed.len(),
+				self.render(|submission| {
+					replayed.contains(&submission.meta_tx) &&
+						submission.outcome == RelayOutcome::Executed
+				})
+			);
+		}
+	}
+
+	/// Asserts that every meta-transaction was executed (at least once), listing the submissions of
+	/// the others otherwise.
+	#[track_caller]
+	pub fn assert_all_executed(&self) {
+		let missing = (0..self.meta_txs).filter(|&tx| self.executions(tx) == 0).collect::<Vec<_>>();
+		if !missing.is_empty() {
+			panic!(
+				"{} meta-transaction(s) were never executed:\n{}",
+				missing.len(),
+				self.render(|submission| missing.contains(&submission.meta_tx))
+			);
+		}
+	}
+
+	fn render(&self, filter: impl Fn(&Submission<AccountId>) -> bool) -> String {
+		self.submissions
+			.iter()
+			.filter(|submission| filter(submission))
+			.map(ToString::to_string)
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+}
+
+/// Simulation of relayers submitting meta-transactions to a forwarder contract.
+#[derive(Clone, Debug)]
+pub struct RelaySimulation<AccountId> {
+	forwarder: AccountId,
+	message: String,
+	relayers: Vec<AccountId>,
+	meta_txs: Vec<Vec<String>>,
+	duplicates: usize,
+	ordering: RelayOrdering,
+	per_block: usize,
+}
+
+impl<AccountId: Clone + Display> RelaySimulation<AccountId> {
+	/// Creates a simulation relaying meta-transactions to the `message` of the `forwarder`
+	/// contract. By default, the actor of the session relays every meta-transaction once, in
+	/// order, one per block.
+	pub fn new(forwarder: AccountId, message: &str) -> Self {
+		Self {
+			forwarder,
+			message: message.to_string(),
+			relayers: Vec::new(),
+			meta_txs: Vec::new(),
+			duplicates: 0,
+			ordering: RelayOrdering::default(),
+			per_block: 1,
+		}
+	}
+
+	/// Sets the relayers, taking turns in submitting the meta-transactions.
+	pub fn relayers(mut self, relayers: impl IntoIterator<Item = AccountId>) -> Self {
+		self.relayers = relayers.into_iter().collect();
+		self
+	}
+
+	/// Adds a meta-transaction, given by the arguments of the message of the forwarder (typically
+	/// the request and its signature).
+	pub fn meta_tx<S: AsRef<str>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+		self.meta_txs
+			.push(args.into_iter().map(|arg| arg.as_ref().to_string()).collect());
+		self
+	}
+
+	/// Sets how many times every meta-transaction is relayed again, after the first time.
+	pub fn duplicates(mut self, duplicates: usize) -> Self {
+		self.duplicates = duplicates;
+		self
+	}
+
+	/// Sets the order in which the meta-transactions are relayed.
+	pub fn ordering(mut self, ordering: RelayOrdering) -> Self {
+		self.ordering = ordering;
+		self
+	}
+
+	/// Sets how many meta-transactions are submitted in every block (at least one).
+	pub fn per_block(mut self, per_block: usize) -> Self {
+		self.per_block = per_block.max(1);
+		self
+	}
+
+	/// Returns the indexes of the meta-transactions in the order they are relayed.
+	fn schedule(&self, seed: u64) -> Vec<usize> {
+		let copies = self.duplicates + 1;
+		let mut schedule = match self.ordering {
+			RelayOrdering::InOrder | RelayOrdering::Shuffled =>
+				(0..self.meta_txs.len()).flat_map(|tx| vec![tx; copies]).collect::<Vec<_>>(),
+			RelayOrdering::Reversed =>
+				(0..self.meta_txs.len()).rev().flat_map(|tx| vec![tx; copies]).collect(),
+			RelayOrdering::Interleaved =>
+				(0..copies).flat_map(|_| 0..self.meta_txs.len()).collect(),
+		};
+		if self.ordering == RelayOrdering::Shuffled {
+			for i in (1..schedule.len()).rev() {
+				let random = seed::derive(seed, (b"relay", i as u64));
+				let j = (u64::from_le_bytes(random[..8].try_into().expect("8 bytes")) %
+					(i as u64 + 1)) as usize;
+				schedule.swap(i, j);
+			}
+		}
+		schedule
+	}
+
+	/// Runs the simulation on `session` and returns the outcomes of the submissions. A new block
+	/// is built after every `per_block` submissions. The actor of the session is restored
+	/// afterwards.
+	pub fn run<T>(&self, session: &mut Session<T>) -> RelayReport<AccountId>
+	where
+		T: Sandbox,
+		T::Runtime: Config + frame_system::Config<AccountId = AccountId>,
+	{
+		let relayers = if self.relayers.is_empty() {
+			vec![session.get_actor()]
+		} else {
+			self.relayers.clone()
+		};
+		let original_actor = session.get_actor();
+
+		let mut submissions = Vec::new();
+		for (i, meta_tx) in self.schedule(session.get_seed()).into_iter().enumerate() {
+			if i > 0 && i % self.per_block == 0 {
+				session.sandbox().build_block();
+			}
+			let relayer = relayers[i % relayers.len()].clone();
+			session.set_actor(relayer.clone());
+
+			let result = session.call_with_address::<_, ()>(
+				self.forwarder.clone(),
+				&self.message,
+				&self.meta_txs[meta_tx],
+				None,
+			);
+			let outcome = match result {
+				Ok(Ok(())) => RelayOutcome::Executed,
+				Ok(Err(error)) => RelayOutcome::Rejected(format!("{error:?}")),
+				Err(SessionError::CallReverted(data)) =>
+					RelayOutcome::Rejected(format!("reverted with {data:?}")),
+				Err(error) => RelayOutcome::Rejected(error.to_string()),
+			};
+			let block = session.sandbox().block_number().saturated_into();
+			submissions.push(Submission { meta_tx, relayer, block, outcome });
+		}
+		session.set_actor(original_actor);
+
+		RelayReport { meta_txs: self.meta_txs.len(), submissions }
+	}
+}
@@ -0,0 +1,270 @@
+//! Exporting session recordings and replaying them against a fresh sandbox.
+
+use std::path::Path;
+
+use frame_support::weights::Weight;
+use ink_sandbox::{AccountIdFor, Sandbox};
+use scale::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	hex::{from_hex, to_hex},
+	pallet_contracts::{Config, Determinism},
+	session::{error::SessionError, record::Action, BalanceOf, Session},
+	DrinkResult, Error,
+};
+
+/// Version of the recording file format.
+const FORMAT_VERSION: u32 = 1;
+
+/// Serializable form of a session recording.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedSession {
+	version: u32,
+	actions: Vec<RecordedAction>,
+}
+
+/// Serializable form of `Action`. Runtime-specific types are stored as hex-encoded SCALE.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum RecordedAction {
+	Deploy {
+		actor: String,
+		code: String,
+		data: String,
+		salt: String,
+		endowment: String,
+		gas_limit: RecordedWeight,
+	},
+	Upload {
+		actor: String,
+		code: String,
+		determinism: RecordedDeterminism,
+	},
+	Call {
+		actor: String,
+		address: String,
+		data: String,
+		endowment: String,
+		gas_limit: RecordedWeight,
+		determinism: RecordedDeterminism,
+	},
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedWeight {
+	ref_time: u64,
+	proof_size: u64,
+}
+
+impl From<Weight> for RecordedWeight {
+	fn from(weight: Weight) -> Self {
+		Self { ref_time: weight.ref_time(), proof_size: weight.proof_size() }
+	}
+}
+
+impl From<RecordedWeight> for Weight {
+	fn from(weight: RecordedWeight) -> Self {
+		Weight::from_parts(weight.ref_time, weight.proof_size)
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedDeterminism {
+	Enforced,
+	Relaxed,
+}
+
+impl From<Determinism> for RecordedDeterminism {
+	fn from(determinism: Determinism) -> Self {
+		match determinism {
+			Determinism::Enforced => Self::Enforced,
+			Determinism::Relaxed => Self::Relaxed,
+		}
+	}
+}
+
+impl From<RecordedDeterminism> for Determinism {
+	fn from(determinism: RecordedDeterminism) -> Self {
+		match determinism {
+			RecordedDeterminism::Enforced => Self::Enforced,
+			RecordedDeterminism::Relaxed => Self::Relaxed,
+		}
+	}
+}
+
+impl RecordedSession {
+	pub(crate) fn from_actions<R: Config>(actions: &[Action<R>]) -> Self {
+		let actions = actions
+			.iter()
+			.map(|action| match action {
+				Action::Deploy { actor, code, data, salt, endowment, gas_limit } =>
+					RecordedAction::Deploy {
+						actor: to_hex(&actor.encode()),
+						code: to_hex(code),
+						data: to_hex(data),
+						salt: to_hex(salt),
+						endowment: to_hex(&endowment.encode()),
+						gas_limit: (*gas_limit).into(),
+					},
+				Action::Upload { actor, code, determinism } => RecordedAction::Upload {
+					actor: to_hex(&actor.encode()),
+					code: to_hex(code),
+					determinism: (*determinism).into(),
+				},
+				Action::Call { actor, address, data, endowment, gas_limit, determinism } =>
+					RecordedAction::Call {
+						actor: to_hex(&actor.encode()),
+						address: to_hex(&address.encode()),
+						data: to_hex(data),
+						endowment: to_hex(&endowment.encode()),
+						gas_limit: (*gas_limit).into(),
+						determinism: (*determinism).into(),
+					},
+			})
+			.collect();
+
+		Self { version: FORMAT_VERSION, actions }
+	}
+
+	pub(crate) fn save<P: AsRef<Path>>(&self, path: P) -> DrinkResult<()> {
+		let json = serde_json::to_string_pretty(self)
+			.map_err(|e| Error::RecordingFailed(format!("Failed to serialize:\n{e:?}")))?;
+		std::fs::write(path, json)
+			.map_err(|e| Error::RecordingFailed(format!("Failed to write the file:\n{e:?}")))
+	}
+}
+
+/// A session recording loaded from a file, ready to be re-executed.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// # use drink::{minimal::MinimalSandbox, session::replay::Replay};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let session = Replay::load("bug-report.json")?.run::<MinimalSandbox>()?;
+/// println!("{:?}", session.record().last_call_return());
+/// # Ok(()) }
+/// ```
+pub struct Replay {
+	recording: RecordedSession,
+}
+
+impl Replay {
+	/// Loads a recording previously exported with `Record::export_json`.
+	pub fn load<P: AsRef<Path>>(path: P) -> DrinkResult<Self> {
+		let json = std::fs::read_to_string(path)
+			.map_err(|e| Error::RecordingFailed(format!("Failed to read the file:\n{e:?}")))?;
+		let recording: RecordedSession = serde_json::from_str(&json)
+			.map_err(|e| Error::RecordingFailed(format!("Failed to parse the file:\n{e:?}")))?;
+
+		if recording.version != FORMAT_VERSION {
+			return Err(Error::RecordingFailed(format!(
+				"Unsupported recording version {} (expected {FORMAT_VERSION})",
+				recording.version
+			)));
+		}
+
+		Ok(Self { recording })
+	}
+
+	/// Returns the number of recorded actions.
+	pub fn len(&self) -> usize {
+		self.recording.actions.len()
+	}
+
+	/// Returns `true` if there are no recorded actions.
+	pub fn is_empty(&self) -> bool {
+		self.recording.actions.is_empty()
+	}
+
+	/// Re-executes the recording against a fresh sandbox and returns the resulting session.
+	pub fn run<T: Sandbox + Default>(&self) -> Result<Session<T>, SessionError>
+	where
+		T::Runtime: Config,
+	{
+		let mut session = Session::<T>::default();
+		self.run_on(&mut session)?;
+		Ok(session)
+	}
+
+	/// Re-executes the recording on the given session.
+	///
+	/// The actor and gas limit of the session are overridden with the recorded ones. Deployed
+	/// contracts won't have any transcoders registered, since recordings contain only encoded
+	/// inputs.
+	///
+	/// The recorded actions that were reverted or failed (e.g. a call reverted on purpose by a
+	/// test) are reverted or fail again, and the replay goes on. Execution stops at the first
+	/// action that can't be replayed, e.g. because its inputs can't be decoded or it timed out.
+	pub fn run_on<T: Sandbox>(&self, session: &mut Session<T>) -> Result<(), SessionError>
+	where
+		T::Runtime: Config,
+	{
+		for action in &self.recording.actions {
+			match action {
+				RecordedAction::Deploy { actor, code, data, salt, endowment, gas_limit } => {
+					session.set_actor(decode(actor)?);
+					session.set_gas_limit((*gas_limit).into());
+					let result = session.deploy_encoded(
+						bytes(code)?,
+						bytes(data)?,
+						bytes(salt)?,
+						decode::<BalanceOf<T::Runtime>>(endowment)?,
+					);
+					replayed(result)?;
+				},
+				RecordedAction::Upload { actor, code, determinism } => {
+					session.set_actor(decode(actor)?);
+					session.set_determinism((*determinism).into());
+					replayed(session.upload(bytes(code)?))?;
+				},
+				RecordedAction::Call {
+					actor,
+					address,
+					data,
+					endowment,
+					gas_limit,
+					determinism,
+				} => {
+					session.set_actor(decode(actor)?);
+					session.set_gas_limit((*gas_limit).into());
+					session.set_determinism((*determinism).into());
+					// We ignore the message result, so we can pass `()` as its type, which will
+					// never fail at decoding.
+					let result = session.call_encoded::<()>(
+						decode::<AccountIdFor<T::Runtime>>(address)?,
+						bytes(data)?,
+						decode::<BalanceOf<T::Runtime>>(endowment)?,
+					);
+					replayed(result)?;
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Returns the error of a replayed action, unless it is the outcome of the action itself (a revert
+/// or a failure reported by the contracts pallet), which was recorded as well.
+fn replayed<V>(result: Result<V, SessionError>) -> Result<(), SessionError> {
+	match result {
+		Ok(_) |
+		Err(SessionError::DeploymentReverted) |
+		Err(SessionError::DeploymentFailed(_)) |
+		Err(SessionError::UploadFailed(_)) |
+		Err(SessionError::CallReverted(_)) |
+		Err(SessionError::CallFailed(_)) => Ok(()),
+		Err(error) => Err(error),
+	}
+}
+
+fn bytes(hex: &str) -> Result<Vec<u8>, SessionError> {
+	from_hex(hex).map_err(SessionError::Decoding)
+}
+
+fn decode<V: Decode>(hex: &str) -> Result<V, SessionError> {
+	V::decode(&mut &bytes(hex)?[..])
+		.map_err(|err| SessionError::Decoding(format!("Failed to decode `{hex}`: {err:?}")))
+}
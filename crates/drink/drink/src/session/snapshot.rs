@@ -0,0 +1,219 @@
+//! Golden (snapshot) testing of contract calls.
+//!
+//! A snapshot is a human-readable rendering of the call result, gas usage, emitted events and the
+//! storage diff caused by an action performed on a session. The first time a snapshot is taken, it
+//! is written to a file. Subsequent runs compare against the stored file and panic on any
+//! difference.
+//!
+//! To accept new results, rerun the tests with the `DRINK_UPDATE_SNAPSHOTS` environment variable
+//! set, or pass [`SnapshotMode::Update`] explicitly.
+
+use std::{
+	collections::BTreeMap,
+	fmt::Write,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use frame_support::storage::storage_prefix;
+use ink_sandbox::{api::prelude::*, Sandbox};
+
+use crate::{hex::to_hex, pallet_contracts::Config, session::Session};
+
+/// Environment variable that, when set, makes snapshot assertions overwrite the stored snapshots
+/// instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "DRINK_UPDATE_SNAPSHOTS";
+
+/// Name of the directory (relative to the crate root) where snapshots are stored.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+type Storage = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Whether snapshot assertions compare against the stored snapshots or overwrite them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotMode {
+	/// Compare against the stored snapshot, writing it only if it doesn't exist yet.
+	Compare,
+	/// Overwrite the stored snapshot.
+	Update,
+}
+
+impl SnapshotMode {
+	/// Returns [`SnapshotMode::Update`] if the `DRINK_UPDATE_SNAPSHOTS` environment variable is
+	/// set, [`SnapshotMode::Compare`] otherwise.
+	pub fn from_env() -> Self {
+		if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+			Self::Update
+		} else {
+			Self::Compare
+		}
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Performs `action` and renders a snapshot of its effects.
+	///
+	/// The snapshot covers the last contract call performed by `action` (if any), the events
+	/// emitted during `action` (since the last block it built, if any) and the diff of the
+	/// (top-level) storage. Events are excluded from the storage diff, as they are already listed
+	/// separately.
+	pub fn snapshot_call<V>(&mut self, action: impl FnOnce(&mut Self) -> V) -> (V, String) {
+		let events_cursor = self.events_cursor();
+		let calls_before = self.record.call_results().len();
+		let storage_before = self.dump_storage();
+
+		let value = action(self);
+
+		let storage_after = self.dump_storage();
+		let events = self.events_since_cursor(events_cursor);
+		let call_result = self.record.call_results().get(calls_before..).and_then(<[_]>::last);
+
+		let mut snapshot = String::new();
+		match call_result {
+			Some(call_result) => {
+				let _ = writeln!(snapshot, "result: {:?}", call_result.result);
+				let _ = writeln!(snapshot, "gas_consumed: {:?}", call_result.gas_consumed);
+				let _ = writeln!(snapshot, "gas_required: {:?}", call_result.gas_required);
+				let _ = writeln!(snapshot, "storage_deposit: {:?}", call_result.storage_deposit);
+			},
+			None => {
+				let _ = writeln!(snapshot, "result: no contract call");
+			},
+		}
+		let _ = writeln!(snapshot, "events:");
+		for event in events {
+			let _ = writeln!(snapshot, "  - {:?}", event.event);
+		}
+		let _ = writeln!(snapshot, "storage_diff:");
+		snapshot.push_str(&render_storage_diff(&storage_before, &storage_after));
+
+		(value, snapshot)
+	}
+
+	/// Collects the whole top-level storage, except for the system events.
+	fn dump_storage(&mut self) -> Storage {
+		let excluded =
+			[storage_prefix(b"System", b"Events"), storage_prefix(b"System", b"EventCount")];
+
+		self.sandbox
+			.storage_pairs(&[], None, None)
+			.into_iter()
+			.filter(|(key, _)| !excluded.iter().any(|prefix| key.starts_with(prefix)))
+			.collect()
+	}
+}
+
+fn render_storage_diff(before: &Storage, after: &Storage) -> String {
+	let mut diff = String::new();
+	for (key, value) in after {
+		match before.get(key) {
+			None => {
+				let _ = writeln!(diff, "  + {}: {}", to_hex(key), to_hex(value));
+			},
+			Some(old) if old != value => {
+				let _ = writeln!(diff, "  ~ {}: {} -> {}", to_hex(key), to_hex(old), to_hex(value));
+			},
+			_ => {},
+		}
+	}
+	for key in before.keys().filter(|key| !after.contains_key(*key)) {
+		let _ = writeln!(diff, "  - {}", to_hex(key));
+	}
+	diff
+}
+
+/// Returns the path of the snapshot `name` for the crate located at `crate_root`.
+pub fn snapshot_path(crate_root: &str, name: &str) -> PathBuf {
+	Path::new(crate_root).join(SNAPSHOTS_DIR).join(format!("{name}.snap"))
+}
+
+/// Compares `actual` against the snapshot stored under `path`.
+///
+/// If the snapshot doesn't exist yet, or `mode` is [`SnapshotMode::Update`], the snapshot is
+/// (over)written instead. Panics with a line diff in case of a mismatch.
+#[track_caller]
+pub fn assert_snapshot(path: &Path, actual: &str, mode: SnapshotMode) {
+	match fs::read_to_string(path) {
+		Ok(expected) if mode == SnapshotMode::Compare =>
+			if expected != actual {
+				panic!(
+					"Snapshot mismatch for {}\n{}\nRerun with `{UPDATE_SNAPSHOTS_ENV}=1` to \
+					 accept the new snapshot.",
+					path.display(),
+					render_line_diff(&expected, actual)
+				);
+			},
+		_ => {
+			if let Some(dir) = path.parent() {
+				fs::create_dir_all(dir).expect("Failed to create the snapshots directory");
+			}
+			fs::write(path, actual).expect("Failed to write the snapshot");
+		},
+	}
+}
+
+fn render_line_diff(expected: &str, actual: &str) -> String {
+	let (expected, actual) =
+		(expected.lines().collect::<Vec<_>>(), actual.lines().collect::<Vec<_>>());
+	let mut diff = String::new();
+	for index in 0..expected.len().max(actual.len()) {
+		match (expected.get(index), actual.get(index)) {
+			(Some(e), Some(a)) if e == a => {
+				let _ = writeln!(diff, "  {e}");
+			},
+			(e, a) => {
+				if let Some(e) = e {
+					let _ = writeln!(diff, "- {e}");
+				}
+				if let Some(a) = a {
+					let _ = writeln!(diff, "+ {a}");
+				}
+			},
+		}
+	}
+	diff
+}
+
+/// Asserts that the effects of a contract call match the stored snapshot.
+///
+/// The snapshot is stored in the `snapshots/<name>.snap` file of the current crate. Evaluates to
+/// the value returned by the action. The snapshot mode is read from the environment (see
+/// [`SnapshotMode::from_env`]), unless given with `mode = ...`.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let result = assert_call_snapshot!(session, "flip", |session| {
+///     session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)
+/// });
+/// // Overwrites the stored snapshot.
+/// assert_call_snapshot!(
+///     session,
+///     "flip",
+///     |session| session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT),
+///     mode = SnapshotMode::Update,
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_call_snapshot {
+	($session:expr, $name:expr, $action:expr $(,)?) => {
+		$crate::assert_call_snapshot!(
+			$session,
+			$name,
+			$action,
+			mode = $crate::session::snapshot::SnapshotMode::from_env()
+		)
+	};
+	($session:expr, $name:expr, $action:expr, mode = $mode:expr $(,)?) => {{
+		let (value, snapshot) = $session.snapshot_call($action);
+		$crate::session::snapshot::assert_snapshot(
+			&$crate::session::snapshot::snapshot_path(env!("CARGO_MANIFEST_DIR"), $name),
+			&snapshot,
+			$mode,
+		);
+		value
+	}};
+}
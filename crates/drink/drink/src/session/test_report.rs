@@ -0,0 +1,309 @@
+//! Machine-readable reports of the contract interactions of the tests, e.g. for `pop test
+//! contract` to summarize every test or for CI dashboards to ingest the results.
+//!
+//! When the `DRINK_TEST_REPORT` environment variable is set to the path of a file, every session
+//! created by `#[drink::test]` (or enabled with `Session::with_test_report`) appends a
+//! [`TestReport`] to the file, as a line of JSON, when it is dropped at the end of the test:
+//!
+//! ```sh
+//! DRINK_TEST_REPORT=$PWD/drink-report.jsonl cargo test
+//! ```
+//!
+//! A session can also report to a given file, regardless of the environment, with
+//! `Session::with_test_report_to`.
+//!
+//! A test is reported as failed when it panics. The name of a test is the one of its thread, as
+//! named by the test harness.
+
+use std::{
+	fs::OpenOptions,
+	io::{self, Write},
+	path::PathBuf,
+	sync::{Arc, Mutex, PoisonError},
+};
+
+use contract_transcode::ContractMessageTranscoder;
+use frame_support::weights::Weight;
+use frame_system::Config as SysConfig;
+use ink_sandbox::{AccountIdFor, Sandbox};
+use scale::Encode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	hex::to_hex,
+	pallet_contracts::{self, Config, ExecReturnValue},
+	session::{
+		gas_baselines::GasUsage, interface::ContractInterface, mock::Selector, record::Action,
+		transcoding::TranscoderRegistry, ContractEvent, EventBatch, Record, Session,
+	},
+	DispatchError,
+};
+
+/// Environment variable holding the path of the file the test reports are appended to.
+pub const TEST_REPORT_ENV_VAR: &str = "DRINK_TEST_REPORT";
+
+/// Serializes the writes of the tests running in parallel.
+static REPORT_LOCK: Mutex<()> = Mutex::new(());
+
+/// The contract interactions of a test and its result.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReport {
+	/// The name of the test.
+	pub test: String,
+	/// Whether the test passed, i.e. didn't panic.
+	pub passed: bool,
+	/// The gas consumed by all the deployments and calls.
+	pub gas_consumed: GasUsage,
+	/// The deployments, in the order of execution.
+	pub deploys: Vec<InteractionReport>,
+	/// The calls, in the order of execution.
+	pub calls: Vec<InteractionReport>,
+}
+
+/// A deployment or a call of a test.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InteractionReport {
+	/// The address of the called or deployed contract, unless the deployment failed.
+	pub contract: Option<String>,
+	/// The label of the message or constructor, if the session has a transcoder for the contract.
+	pub message: Option<String>,
+	/// The consumed gas.
+	pub gas_consumed: GasUsage,
+	/// The required gas.
+	pub gas_required: GasUsage,
+	/// The outcome of the interaction.
+	pub outcome: Outcome,
+	/// The events emitted by the contracts, decoded if the session has a transcoder for the
+	/// emitting contract, hex-encoded otherwise.
+	pub events: Vec<String>,
+}
+
+/// The outcome of a deployment or a call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+	/// The contract returned successfully.
+	Success,
+	/// The contract reverted.
+	Reverted,
+	/// The contracts pallet failed, with the given error.
+	Failed(String),
+}
+
+impl Outcome {
+	fn of(result: Result<&ExecReturnValue, &DispatchError>) -> Self {
+		match result {
+			Ok(value) if value.did_revert() => Self::Reverted,
+			Ok(_) => Self::Success,
+			Err(err) => Self::Failed(format!("{err:?}")),
+		}
+	}
+}
+
+/// Collects the contract interactions of a session and writes its report when dropped.
+pub(crate) struct TestReporter<R: Config> {
+	path: PathBuf,
+	/// Extracts the contract events, which requires bounds the session doesn't have.
+	contract_events: fn(&EventBatch<R>) -> Vec<ContractEvent<R>>,
+	transcoders: TranscoderRegistry<AccountIdFor<R>>,
+	deploys: Vec<Interaction<R>>,
+	calls: Vec<Interaction<R>>,
+}
+
+/// A contract interaction, resolved against the transcoders when the report is written.
+struct Interaction<R: Config> {
+	contract: Option<AccountIdFor<R>>,
+	selector: Option<Selector>,
+	gas_consumed: Weight,
+	gas_required: Weight,
+	outcome: Outcome,
+	events: Vec<ContractEvent<R>>,
+}
+
+impl<R: Config> TestReporter<R> {
+	/// Notes the last contract interaction of `record`.
+	fn note(&mut self, record: &Record<R>) {
+		let events = record.event_batches().last().map(self.contract_events).unwrap_or_default();
+		match record.actions().last() {
+			Some(Action::Deploy { data, .. }) => {
+				let Some(result) = record.deploy_results().last() else { return };
+				let deployed = result.result.as_ref();
+				self.deploys.push(Interaction {
+					contract: deployed
+						.ok()
+						.filter(|value| !value.result.did_revert())
+						.map(|value| value.account_id.clone()),
+					selector: selector(data),
+					gas_consumed: result.gas_consumed,
+					gas_required: result.gas_required,
+					outcome: Outcome::of(deployed.map(|value| &value.result)),
+					events,
+				});
+			},
+			Some(Action::Call { address, data, .. }) => {
+				let Some(result) = record.call_results().last() else { return };
+				self.calls.push(Interaction {
+					contract: Some(address.clone()),
+					selector: selector(data),
+					gas_consumed: result.gas_consumed,
+					gas_required: result.gas_required,
+					outcome: Outcome::of(result.result.as_ref()),
+					events,
+				});
+			},
+			Some(Action::Upload { .. }) | None => {},
+		}
+	}
+
+	fn report(&self) -> TestReport {
+		let deploys = self
+			.deploys
+			.iter()
+			.map(|deploy| {
+				self.interaction_report(deploy, |interface, selector| {
+					interface.constructor_by_selector(selector).map(|c| c.label.clone())
+				})
+			})
+			.collect::<Vec<_>>();
+		let calls = self
+			.calls
+			.iter()
+			.map(|call| {
+				self.interaction_report(call, |interface, selector| {
+					interface.message_by_selector(selector).map(|m| m.label.clone())
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let (ref_time, proof_size) = deploys.iter().chain(&calls).fold(
+			(0u64, 0u64),
+			|(ref_time, proof_size), interaction| {
+				(
+					ref_time.saturating_add(interaction.gas_consumed.ref_time),
+					proof_size.saturating_add(interaction.gas_consumed.proof_size),
+				)
+			},
+		);
+
+		TestReport {
+			test: std::thread::current().name().unwrap_or("main").to_string(),
+			passed: !std::thread::panicking(),
+			gas_consumed: GasUsage { ref_time, proof_size },
+			deploys,
+			calls,
+		}
+	}
+
+	fn interaction_report(
+		&self,
+		interaction: &Interaction<R>,
+		label: impl Fn(&ContractInterface, Selector) -> Option<String>,
+	) -> InteractionReport {
+		let transcoder = interaction.contract.as_ref().and_then(|c| self.transcoders.get(c));
+		let message = transcoder
+			.as_ref()
+			.zip(interaction.selector)
+			.and_then(|(t, selector)| label(&ContractInterface::from_transcoder(t), selector));
+
+		InteractionReport {
+			contract: interaction.contract.as_ref().map(ToString::to_string),
+			message,
+			gas_consumed: interaction.gas_consumed.into(),
+			gas_required: interaction.gas_required.into(),
+			outcome: interaction.outcome.clone(),
+			events: interaction.events.iter().map(|event| self.format_event(event)).collect(),
+		}
+	}
+
+	fn format_event(&self, event: &ContractEvent<R>) -> String {
+		let decoded = self.transcoders.get(&event.contract).zip(event.topics.first()).and_then(
+			|(transcoder, topic)| {
+				let topic: [u8; 32] = topic.as_ref().try_into().ok()?;
+				// The transcoder decodes the events as encoded by the runtime.
+				transcoder.decode_contract_event(&topic, &mut &*event.data.encode()).ok()
+			},
+		);
+		match decoded {
+			Some(event) => event.to_string(),
+			None => to_hex(&event.data),
+		}
+	}
+
+	fn save(&self, report: &TestReport) -> io::Result<()> {
+		let line = serde_json::to_string(report)?;
+		let _guard = REPORT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+		let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+		writeln!(file, "{line}")
+	}
+}
+
+impl<R: Config> Drop for TestReporter<R> {
+	fn drop(&mut self) {
+		let report = self.report();
+		if let Err(err) = self.save(&report) {
+			log::warn!(
+				target: "drink::test_report",
+				"Failed to write the report of `{}` to {}: {err}",
+				report.test,
+				self.path.display()
+			);
+		}
+	}
+}
+
+fn selector(data: &[u8]) -> Option<Selector> {
+	data.get(..4)?.try_into().ok()
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+	<T::Runtime as SysConfig>::RuntimeEvent: TryInto<pallet_contracts::Event<T::Runtime>>,
+{
+	/// Enables the test report if `DRINK_TEST_REPORT` is set, and returns updated `self`. See
+	/// [`crate::session::test_report`].
+	///
+	/// Called by `#[drink::test]`.
+	pub fn with_test_report(self) -> Self {
+		match std::env::var_os(TEST_REPORT_ENV_VAR) {
+			Some(path) => self.with_test_report_to(path),
+			None => self,
+		}
+	}
+
+	/// Enables the test report, appending it to the file under `path`, and returns updated
+	/// `self`. See [`crate::session::test_report`].
+	pub fn with_test_report_to(self, path: impl Into<PathBuf>) -> Self {
+		let reporter = TestReporter {
+			path: path.into(),
+			contract_events: EventBatch::contract_events_with_topics,
+			transcoders: self.transcoders.clone(),
+			deploys: Vec::new(),
+			calls: Vec::new(),
+		};
+		Self { test_report: Some(reporter), ..self }
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Notes the last contract interaction in the test report, if enabled.
+	pub(super) fn report_interaction(&mut self) {
+		if let Some(reporter) = &mut self.test_report {
+			reporter.note(&self.record);
+		}
+	}
+
+	/// Registers `transcoder` for the contract in the test report, if enabled.
+	pub(super) fn report_transcoder(
+		&mut self,
+		contract: &AccountIdFor<T::Runtime>,
+		transcoder: &Arc<ContractMessageTranscoder>,
+	) {
+		if let Some(reporter) = &mut self.test_report {
+			reporter.transcoders.register(contract.clone(), transcoder);
+		}
+	}
+}
@@ -0,0 +1,127 @@
+//! Wall-clock timeouts of deployments and calls, so that a contract looping (almost) forever fails
+//! the test with `SessionError::ExecutionTimeout` instead of hanging the whole suite.
+//!
+//! Once a timeout is set, contracts are executed in a worker thread, which the sandbox is moved to
+//! for the time of the execution:
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default()
+//!     .with_execution_timeout(Duration::from_secs(5));
+//! assert!(matches!(
+//!     session.call::<_, ()>("loop_forever", NO_ARGS, None),
+//!     Err(SessionError::ExecutionTimeout(_))
+//! ));
+//! ```
+//!
+//! A running wasm execution can't be interrupted right away: after a timeout, it is aborted at
+//! the next host function it calls (see `ink_sandbox::failures::AbortSignal`), and runs out of
+//! gas at the latest otherwise. It keeps the sandbox until then, so the state of the session is
+//! lost: the session is left with a fresh (default) sandbox, and all its later deployments and
+//! calls fail with `SessionError::ExecutionTimeout` as well.
+
+use std::{
+	any::Any,
+	sync::mpsc::{self, RecvTimeoutError},
+	time::Duration,
+};
+
+use ink_sandbox::{failures::AbortSignal, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, Session},
+};
+
+/// An execution of a contract, erased to be run in a worker thread.
+type Job<T> = Box<dyn FnOnce(&mut T) -> Box<dyn Any + Send> + Send>;
+
+/// Runs jobs on a sandbox, failing with `SessionError::ExecutionTimeout` if they take too long.
+pub(crate) type TimedRunner<T> =
+	Box<dyn FnMut(&mut T, Job<T>) -> Result<Box<dyn Any + Send>, SessionError> + Send>;
+
+/// Returns a runner of jobs in a worker thread, with the timeout `timeout`.
+///
+/// The sandbox is swapped with a spare one while a job runs, so that a new sandbox is only created
+/// after a timeout. The job is aborted after a timeout, so the worker doesn't outlive it for long.
+fn timed_runner<T: Sandbox + Default + Send + 'static>(timeout: Duration) -> TimedRunner<T> {
+	let mut spare = None;
+	Box::new(move |sandbox: &mut T, job: Job<T>| {
+		let mut worker_sandbox =
+			std::mem::replace(sandbox, spare.take().unwrap_or_else(T::default));
+		let abort = AbortSignal::default();
+		worker_sandbox.register_extension(abort.clone());
+		let (sender, receiver) = mpsc::channel();
+		let worker = std::thread::spawn(move || {
+			let output = job(&mut worker_sandbox);
+			// The session may have given up on the job already.
+			let _ = sender.send((worker_sandbox, output));
+		});
+
+		match receiver.recv_timeout(timeout) {
+			Ok((worker_sandbox, output)) => {
+				spare = Some(std::mem::replace(sandbox, worker_sandbox));
+				Ok(output)
+			},
+			Err(RecvTimeoutError::Timeout) => {
+				abort.raise();
+				Err(SessionError::ExecutionTimeout(timeout))
+			},
+			Err(RecvTimeoutError::Disconnected) => match worker.join() {
+				Err(panic) => std::panic::resume_unwind(panic),
+				Ok(()) => unreachable!("The worker sends the output before exiting"),
+			},
+		}
+	})
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Sets a wall-clock timeout on deployments and calls and returns updated `self`. See
+	/// [`crate::session::timeout`].
+	pub fn with_execution_timeout(mut self, timeout: Duration) -> Self
+	where
+		T: Default + Send + 'static,
+	{
+		self.set_execution_timeout(Some(timeout));
+		self
+	}
+
+	/// Sets (or removes, with `None`) the wall-clock timeout on deployments and calls.
+	pub fn set_execution_timeout(&mut self, timeout: Option<Duration>)
+	where
+		T: Default + Send + 'static,
+	{
+		self.timed_runner = timeout.map(timed_runner::<T>);
+	}
+
+	/// Returns the timeout of the execution which timed out, if any. The session can't deploy or
+	/// call contracts anymore in that case.
+	pub fn timed_out(&self) -> Option<Duration> {
+		self.timed_out
+	}
+
+	/// Runs `execution` on the sandbox, within the timeout if one is set.
+	///
+	/// Fails with `SessionError::ExecutionTimeout` once an execution timed out, as the state of
+	/// the session was lost along with it.
+	pub(crate) fn execute_contract<V: Send + 'static>(
+		&mut self,
+		execution: impl FnOnce(&mut T) -> V + Send + 'static,
+	) -> Result<V, SessionError> {
+		if let Some(timeout) = self.timed_out {
+			return Err(SessionError::ExecutionTimeout(timeout));
+		}
+		let Some(runner) = &mut self.timed_runner else {
+			return Ok(execution(&mut self.sandbox));
+		};
+		let job: Job<T> =
+			Box::new(move |sandbox| Box::new(execution(sandbox)) as Box<dyn Any + Send>);
+		let output = runner(&mut self.sandbox, job);
+		if let Err(SessionError::ExecutionTimeout(timeout)) = output {
+			self.timed_out = Some(timeout);
+		}
+		output.map(|output| *output.downcast::<V>().expect("The job returns `V`"))
+	}
+}
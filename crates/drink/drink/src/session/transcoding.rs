@@ -2,6 +2,9 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use contract_transcode::ContractMessageTranscoder;
 
+use crate::session::mock::Selector;
+
+#[derive(Clone)]
 pub struct TranscoderRegistry<Contract: Ord> {
 	transcoders: BTreeMap<Contract, Arc<ContractMessageTranscoder>>,
 }
@@ -18,4 +21,18 @@ impl<Contract: Ord> TranscoderRegistry<Contract> {
 	pub fn get(&self, contract: &Contract) -> Option<Arc<ContractMessageTranscoder>> {
 		self.transcoders.get(contract).map(Arc::clone)
 	}
+
+	/// Returns the selectors of the messages named `message` of the registered contracts.
+	pub fn selectors_of(&self, message: &str) -> Vec<Selector> {
+		let mut selectors = self
+			.transcoders
+			.values()
+			.flat_map(|transcoder| transcoder.metadata().spec().messages().to_vec())
+			.filter(|spec| spec.label() == message)
+			.map(|spec| spec.selector().to_bytes())
+			.collect::<Vec<_>>();
+		selectors.sort();
+		selectors.dedup();
+		selectors
+	}
 }
@@ -0,0 +1,112 @@
+//! Detection of the value endowed to payable messages but not used by the contract, e.g. sent
+//! back to the caller instead of being kept or forwarded, without asserting the balances around
+//! every payable call by hand.
+//!
+//! Once enabled, the free balances of all the accounts are compared before and after every
+//! successful call with an endowment. The value is used if the called contract kept it or
+//! forwarded it to other accounts:
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default().with_unused_value_check();
+//! session.call("deposit", NO_ARGS, Some(100))?;
+//! assert!(session.unused_value_reports().is_empty());
+//! ```
+
+use std::collections::BTreeMap;
+
+use frame_support::{
+	sp_runtime::traits::Saturating, traits::fungible::Inspect, CloneNoBound, DebugNoBound,
+	EqNoBound, PartialEqNoBound,
+};
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{BalanceOf, Session},
+};
+
+/// A call whose endowment wasn't (fully) used by the called contract.
+#[derive(CloneNoBound, DebugNoBound, PartialEqNoBound, EqNoBound)]
+pub struct UnusedValue<R: Config> {
+	/// The address of the called contract.
+	pub contract: AccountIdFor<R>,
+	/// The input of the call.
+	pub input: Vec<u8>,
+	/// The value endowed to the call.
+	pub endowment: BalanceOf<R>,
+	/// The part of the endowment kept by the contract.
+	pub kept: BalanceOf<R>,
+	/// The value received during the call by accounts other than the contract and the caller.
+	pub forwarded: BalanceOf<R>,
+}
+
+/// The free balances of all the accounts, to be compared before and after a call.
+pub(crate) struct Balances<R: Config>(BTreeMap<AccountIdFor<R>, BalanceOf<R>>);
+
+impl<R: Config> Balances<R> {
+	/// Takes the free balances of all the accounts.
+	///
+	/// Must be called within the externalities.
+	pub fn take() -> Self {
+		Self(
+			frame_system::Account::<R>::iter_keys()
+				.map(|account| {
+					let balance = <R::Currency as Inspect<AccountIdFor<R>>>::balance(&account);
+					(account, balance)
+				})
+				.collect(),
+		)
+	}
+
+	/// Returns the report of the call of `contract` by `caller` with `endowment`, if the value
+	/// wasn't used by the contract. `self` are the balances before the call.
+	///
+	/// Must be called within the externalities.
+	pub fn unused_value(
+		&self,
+		contract: &AccountIdFor<R>,
+		caller: &AccountIdFor<R>,
+		input: &[u8],
+		endowment: BalanceOf<R>,
+	) -> Option<UnusedValue<R>> {
+		let after = Self::take();
+		let gain = |account: &AccountIdFor<R>| {
+			let before = self.0.get(account).copied().unwrap_or_default();
+			let after = after.0.get(account).copied().unwrap_or_default();
+			after.saturating_sub(before)
+		};
+
+		let kept = gain(contract).min(endowment);
+		let forwarded = after
+			.0
+			.keys()
+			.filter(|account| *account != contract && *account != caller)
+			.fold(BalanceOf::<R>::default(), |forwarded, account| {
+				forwarded.saturating_add(gain(account))
+			});
+		(kept.saturating_add(forwarded) < endowment).then(|| UnusedValue {
+			contract: contract.clone(),
+			input: input.to_vec(),
+			endowment,
+			kept,
+			forwarded,
+		})
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Enables the detection of unused endowments and returns updated `self`. See
+	/// [`crate::session::unused_value`].
+	pub fn with_unused_value_check(self) -> Self {
+		Self { unused_value_reports: Some(Vec::new()), ..self }
+	}
+
+	/// Returns the calls whose endowment wasn't used by the called contract, since the detection
+	/// was enabled with `with_unused_value_check`.
+	pub fn unused_value_reports(&self) -> &[UnusedValue<T::Runtime>] {
+		self.unused_value_reports.as_deref().unwrap_or_default()
+	}
+}
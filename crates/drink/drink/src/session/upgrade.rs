@@ -0,0 +1,126 @@
+//! Testing of contract upgrades replacing the code of a contract (`set_code_hash`), including the
+//! compatibility of the new storage layout with the existing storage.
+//!
+//! ```rust, ignore
+//! upgrade_and_verify(
+//!     &mut session,
+//!     contract,
+//!     BundleProvider::V2.bundle()?,
+//!     Some(("migrate", &[])),
+//!     &[
+//!         LayoutCheck::new("total_supply", NO_ARGS),
+//!         LayoutCheck::new("balance_of", &[ALICE.to_string()]),
+//!     ],
+//! )?;
+//! ```
+
+use ink_sandbox::{api::prelude::*, AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{error::SessionError, ContractBundle, Session},
+};
+
+/// A read of the storage of a contract, by a message, which must keep working after an upgrade.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutCheck {
+	/// The name of the message.
+	pub message: String,
+	/// The arguments of the message.
+	pub args: Vec<String>,
+}
+
+impl LayoutCheck {
+	/// Creates a check calling `message` with `args`.
+	pub fn new<S: AsRef<str>>(message: &str, args: &[S]) -> Self {
+		Self {
+			message: message.to_string(),
+			args: args.iter().map(|arg| arg.as_ref().to_string()).collect(),
+		}
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Replaces the code of the contract under `address` with the code of `bundle`, like the
+	/// `set_code_hash` of the contract would, keeping its storage and balance. The contract is
+	/// called with the transcoder of `bundle` afterwards.
+	pub fn upgrade(
+		&mut self,
+		address: AccountIdFor<T::Runtime>,
+		bundle: ContractBundle,
+	) -> Result<(), SessionError> {
+		let code_hash = self.upload(bundle.wasm)?;
+		self.sandbox
+			.set_code(address.clone(), code_hash)
+			.map_err(SessionError::UpgradeFailed)?;
+		self.transcoders.register(address, &bundle.transcoder);
+		Ok(())
+	}
+
+	/// Performs a dry run of the call of `check`, returning the output of the message if it went
+	/// through (neither failed nor reverted).
+	fn read_layout(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+		check: &LayoutCheck,
+	) -> Option<Vec<u8>> {
+		let result = self.dry_run_call(address.clone(), &check.message, &check.args, None).ok()?;
+		result
+			.result
+			.ok()
+			.filter(|result| !result.did_revert())
+			.map(|result| result.data)
+	}
+}
+
+/// Upgrades the contract under `address` to the code of `new_bundle`, runs the `migration`
+/// message (if any), and asserts that the storage written by the old code is still readable by
+/// the new code.
+///
+/// Every layout check must go through after the upgrade. Checks of messages the old code has as
+/// well must also return the same output as before the upgrade, as the upgrade must not change
+/// the data.
+///
+/// Returns an error if the upgrade or the migration fails, and panics if a layout check fails.
+#[track_caller]
+pub fn upgrade_and_verify<T: Sandbox>(
+	session: &mut Session<T>,
+	address: AccountIdFor<T::Runtime>,
+	new_bundle: ContractBundle,
+	migration: Option<(&str, &[String])>,
+	layout_checks: &[LayoutCheck],
+) -> Result<(), SessionError>
+where
+	T::Runtime: Config,
+{
+	let before = layout_checks
+		.iter()
+		.map(|check| session.read_layout(&address, check))
+		.collect::<Vec<_>>();
+
+	session.upgrade(address.clone(), new_bundle)?;
+	if let Some((message, args)) = migration {
+		session.call_with_address::<_, ()>(address.clone(), message, args, None)?;
+	}
+
+	for (check, before) in layout_checks.iter().zip(before) {
+		let after = session.read_layout(&address, check).unwrap_or_else(|| {
+			panic!(
+				"Layout check `{}({:?})` failed after the upgrade: the storage is not readable by \
+				 the new code",
+				check.message, check.args
+			)
+		});
+		if let Some(before) = before {
+			assert_eq!(
+				before, after,
+				"Layout check `{}({:?})` returned a different output after the upgrade",
+				check.message, check.args
+			);
+		}
+	}
+	Ok(())
+}
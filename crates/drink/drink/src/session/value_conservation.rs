@@ -0,0 +1,93 @@
+//! A value-conservation invariant over the whole session, for catching balance-accounting bugs
+//! (e.g. value created or destroyed by a contract) in systems of many contracts, where asserting
+//! the balances of every account by hand isn't practical.
+//!
+//! Value can only enter or leave the accounts by being minted or burned, which the balances pallet
+//! accounts for in the total issuance. Once enabled, the total balances (free and reserved) of all
+//! the accounts are summed after every upload, deployment and call: their change must match the
+//! change of the total issuance since the previous operation, otherwise the session panics:
+//!
+//! ```rust, ignore
+//! let mut session = Session::<MinimalSandbox>::default().with_value_conservation_check();
+//! session.deploy_bundle(bundle, "new", NO_ARGS, NO_SALT, Some(100))?;
+//! // Panics if the call created or destroyed value without minting or burning it.
+//! session.call("withdraw", &["50"], None)?;
+//! ```
+//!
+//! Value moved outside the session (e.g. directly through the sandbox) is accounted for as well,
+//! as long as it is minted or burned through the balances pallet.
+
+use frame_support::{sp_runtime::traits::Saturating, traits::fungible::Inspect};
+use ink_sandbox::{AccountIdFor, Sandbox};
+
+use crate::{
+	pallet_contracts::Config,
+	session::{BalanceOf, Session},
+};
+
+/// The total balances of all the accounts and the total issuance, at some point of the session.
+pub(crate) struct ValueLedger<R: Config> {
+	balances: BalanceOf<R>,
+	issuance: BalanceOf<R>,
+}
+
+impl<R: Config> ValueLedger<R> {
+	/// Takes the total balances of all the accounts and the total issuance.
+	///
+	/// Must be called within the externalities.
+	pub fn take() -> Self {
+		let balances = frame_system::Account::<R>::iter_keys().fold(
+			BalanceOf::<R>::default(),
+			|balances, account| {
+				balances.saturating_add(<R::Currency as Inspect<AccountIdFor<R>>>::total_balance(
+					&account,
+				))
+			},
+		);
+		Self { balances, issuance: <R::Currency as Inspect<AccountIdFor<R>>>::total_issuance() }
+	}
+
+	/// Panics if the change of the total balances since `self` isn't explained by the change of
+	/// the total issuance, describing `operation` as the culprit.
+	pub fn assert_conserved(&self, after: &Self, operation: &str) {
+		// `before.balances + minted == after.balances + burned`, without signed arithmetic.
+		assert!(
+			self.balances.saturating_add(after.issuance) ==
+				after.balances.saturating_add(self.issuance),
+			"Value not conserved by {operation}: the total balances went from {:?} to {:?}, but \
+			 the total issuance (minted and burned value) went from {:?} to {:?}",
+			self.balances,
+			after.balances,
+			self.issuance,
+			after.issuance,
+		);
+	}
+}
+
+impl<T: Sandbox> Session<T>
+where
+	T::Runtime: Config,
+{
+	/// Enables the value-conservation check and returns updated `self`. See
+	/// [`crate::session::value_conservation`].
+	pub fn with_value_conservation_check(mut self) -> Self {
+		self.set_value_conservation_check(true);
+		self
+	}
+
+	/// Enables or disables the value-conservation check. When enabled, the value is expected to be
+	/// conserved from now on.
+	pub fn set_value_conservation_check(&mut self, enabled: bool) {
+		self.value_ledger =
+			enabled.then(|| self.sandbox.execute_with(ValueLedger::<T::Runtime>::take));
+	}
+
+	/// Checks that `operation` conserved the value, if the check is enabled.
+	pub(crate) fn check_value_conservation(&mut self, operation: &str) {
+		if let Some(before) = &self.value_ledger {
+			let after = self.sandbox.execute_with(ValueLedger::<T::Runtime>::take);
+			before.assert_conserved(&after, operation);
+			self.value_ledger = Some(after);
+		}
+	}
+}
@@ -0,0 +1,175 @@
+//! Helpers to sign payloads with dev keys, for testing contracts verifying signatures on-chain
+//! (e.g. permits or meta-transactions).
+//!
+//! Keys are derived from secret URIs, like the dev accounts (see [`crate::accounts`]). The
+//! signatures have the formats expected by ink!'s `sr25519_verify` and `ecdsa_recover`:
+//!
+//! ```rust, ignore
+//! let permit = Permit { owner: ALICE, spender: BOB, value: 10, nonce: 0 };
+//! let signature = sign_sr25519!("//Alice", permit.encode());
+//! let args = [signature_arg(&permit.encode()), signature_arg(&signature)];
+//! session.call::<_, ()>("permit", &args, None)??;
+//!
+//! let message_hash = keccak_256(b"meta-transaction");
+//! let signature = sign_ecdsa!("//Alice", message_hash);
+//! assert_eq!(ecdsa_recover(&signature, &message_hash), Some(ecdsa_public("//Alice")));
+//! ```
+
+use sp_core::{crypto::Pair as _, ecdsa, hashing::keccak_256, sr25519};
+
+use crate::hex::to_hex;
+
+/// Derives the sr25519 key pair from `seed`, e.g. `"//Alice"`.
+///
+/// # Panics
+///
+/// Panics if `seed` is not a valid secret URI.
+pub fn sr25519_pair(seed: &str) -> sr25519::Pair {
+	sr25519::Pair::from_string(seed, None)
+		.unwrap_or_else(|_| panic!("Invalid sr25519 seed `{seed}`"))
+}
+
+/// Derives the ecdsa (secp256k1) key pair from `seed`, e.g. `"//Alice"`.
+///
+/// # Panics
+///
+/// Panics if `seed` is not a valid secret URI.
+pub fn ecdsa_pair(seed: &str) -> ecdsa::Pair {
+	ecdsa::Pair::from_string(seed, None).unwrap_or_else(|_| panic!("Invalid ecdsa seed `{seed}`"))
+}
+
+/// Signs `payload` with the sr25519 key derived from `seed`.
+pub fn sign_sr25519(seed: &str, payload: &[u8]) -> [u8; 64] {
+	sr25519_pair(seed).sign(payload).0
+}
+
+/// Verifies the sr25519 `signature` of `payload` by `public`, as ink!'s `sr25519_verify` does.
+pub fn verify_sr25519(signature: &[u8; 64], payload: &[u8], public: &[u8; 32]) -> bool {
+	sr25519::Pair::verify(
+		&sr25519::Signature::from_raw(*signature),
+		payload,
+		&sr25519::Public::from_raw(*public),
+	)
+}
+
+/// Signs the 32-byte `message_hash` with the ecdsa key derived from `seed`. The signature is
+/// recoverable (the last byte is the recovery id).
+pub fn sign_ecdsa(seed: &str, message_hash: &[u8; 32]) -> [u8; 65] {
+	ecdsa_pair(seed).sign_prehashed(message_hash).0
+}
+
+/// Returns the compressed ecdsa public key derived from `seed`.
+pub fn ecdsa_public(seed: &str) -> [u8; 33] {
+	ecdsa_pair(seed).public().0
+}
+
+/// Recovers the compressed public key from the ecdsa `signature` of `message_hash`, as ink!'s
+/// `ecdsa_recover` does. Recovery ids in the Ethereum format (27 or 28) are accepted.
+pub fn ecdsa_recover(signature: &[u8; 65], message_hash: &[u8; 32]) -> Option<[u8; 33]> {
+	let mut signature = *signature;
+	if signature[64] >= 27 {
+		signature[64] -= 27;
+	}
+	ecdsa::Signature::from_raw(signature)
+		.recover_prehashed(message_hash)
+		.map(|public| public.0)
+}
+
+/// Renders a signature (or any signed payload) as a hex string, to be passed as an argument of a
+/// contract message or constructor.
+pub fn signature_arg(bytes: &[u8]) -> String {
+	to_hex(bytes)
+}
+
+/// A test vector for the recovery of ecdsa public keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcrecoverVector {
+	/// What the vector covers.
+	pub description: &'static str,
+	/// The signed message hash.
+	pub message_hash: [u8; 32],
+	/// The signature.
+	pub signature: [u8; 65],
+	/// The public key to be recovered, `None` if the recovery must fail.
+	pub expected: Option<[u8; 33]>,
+}
+
+/// Test vectors for contracts relying on `ecdsa_recover`, covering valid signatures (in both
+/// recovery id formats) and malformed signatures.
+///
+/// The signatures are deterministic (RFC 6979), so the vectors are the same on every run.
+pub fn ecrecover_vectors() -> Vec<EcrecoverVector> {
+	let message_hash = keccak_256(b"drink!");
+	let signature = sign_ecdsa("//Alice", &message_hash);
+	let alice = Some(ecdsa_public("//Alice"));
+
+	let mut ethereum_format = signature;
+	ethereum_format[64] += 27;
+	let mut invalid_recovery_id = signature;
+	invalid_recovery_id[64] = 4;
+	let mut zero_r = signature;
+	zero_r[..32].fill(0);
+
+	vec![
+		EcrecoverVector {
+			description: "valid signature",
+			message_hash,
+			signature,
+			expected: alice,
+		},
+		EcrecoverVector {
+			description: "valid signature of another signer",
+			message_hash,
+			signature: sign_ecdsa("//Bob", &message_hash),
+			expected: Some(ecdsa_public("//Bob")),
+		},
+		EcrecoverVector {
+			description: "valid signature with an Ethereum recovery id (27 or 28)",
+			message_hash,
+			signature: ethereum_format,
+			expected: alice,
+		},
+		EcrecoverVector {
+			description: "invalid recovery id",
+			message_hash,
+			signature: invalid_recovery_id,
+			expected: None,
+		},
+		EcrecoverVector {
+			description: "zero `r`",
+			message_hash,
+			signature: zero_r,
+			expected: None,
+		},
+	]
+}
+
+/// Signs a payload with the sr25519 key derived from a secret URI, returning the 64-byte
+/// signature.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let signature = sign_sr25519!("//Alice", permit.encode());
+/// ```
+#[macro_export]
+macro_rules! sign_sr25519 {
+	($seed:expr, $payload:expr $(,)?) => {
+		$crate::signatures::sign_sr25519($seed, ::core::convert::AsRef::<[u8]>::as_ref(&$payload))
+	};
+}
+
+/// Signs a 32-byte message hash with the ecdsa key derived from a secret URI, returning the
+/// 65-byte recoverable signature.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let signature = sign_ecdsa!("//Alice", keccak_256(b"meta-transaction"));
+/// ```
+#[macro_export]
+macro_rules! sign_ecdsa {
+	($seed:expr, $message_hash:expr $(,)?) => {
+		$crate::signatures::sign_ecdsa($seed, &$message_hash)
+	};
+}
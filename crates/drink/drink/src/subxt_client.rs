@@ -0,0 +1,152 @@
+//! Adapter exposing a sandbox to code written with the typed APIs of subxt.
+//!
+//! The typed API can be generated against the exact runtime of the sandbox from its exported
+//! metadata (see [`Sandbox::export_metadata`]):
+//!
+//! ```rust, ignore
+//! #[subxt::subxt(runtime_metadata_path = "metadata.scale")]
+//! pub mod sandbox_runtime {}
+//!
+//! let mut client = SubxtClient::<_, PolkadotConfig>::new(session.sandbox())?;
+//! let transfer = sandbox_runtime::tx().balances().transfer_allow_death(BOB.into(), 10);
+//! client.submit(&transfer, ALICE)?;
+//! let account = client.fetch(&sandbox_runtime::storage().system().account(BOB))?;
+//! ```
+
+use frame_support::{sp_runtime::traits::Zero, traits::Get};
+use frame_system::pallet_prelude::BlockNumberFor;
+use ink_sandbox::{api::prelude::*, AccountIdFor, DispatchError, RuntimeCall, Sandbox};
+use scale::{Decode, Encode};
+use subxt::{
+	client::RuntimeVersion, ext::subxt_core, storage::Address, tx::Payload, Metadata, OfflineClient,
+};
+use thiserror::Error;
+
+/// Error of the interactions through a [`SubxtClient`].
+#[derive(Error, Debug)]
+pub enum SubxtClientError {
+	/// Encoding a transaction or a storage address, or decoding a storage value, has failed.
+	#[error("Subxt error: {0}")]
+	Subxt(#[from] subxt::Error),
+	/// The metadata, a runtime call or the genesis hash could not be decoded.
+	#[error("Decoding failed: {0}")]
+	Decode(#[from] scale::Error),
+	/// The dispatch of a submitted transaction has failed.
+	#[error("Dispatch failed: {0:?}")]
+	Dispatch(DispatchError),
+}
+
+/// A subxt client over a sandbox.
+///
+/// Transactions built with the typed API are dispatched directly on the sandbox (without
+/// signature checks nor fees), and storage is read from its current state.
+pub struct SubxtClient<'a, S: Sandbox, C: subxt::Config> {
+	sandbox: &'a mut S,
+	client: OfflineClient<C>,
+}
+
+impl<'a, S: Sandbox, C: subxt::Config> SubxtClient<'a, S, C> {
+	/// Creates a client with the metadata, the runtime version and the genesis hash of
+	/// `sandbox`.
+	pub fn new(sandbox: &'a mut S) -> Result<Self, SubxtClientError> {
+		let metadata = Metadata::decode(&mut &S::get_metadata().encode()[..])?;
+		let (genesis_hash, version) = sandbox.execute_with(|| {
+			let genesis = BlockNumberFor::<S::Runtime>::zero();
+			(
+				frame_system::Pallet::<S::Runtime>::block_hash(genesis),
+				<S::Runtime as frame_system::Config>::Version::get(),
+			)
+		});
+		let client = OfflineClient::new(
+			C::Hash::decode(&mut &genesis_hash.encode()[..])?,
+			RuntimeVersion {
+				spec_version: version.spec_version,
+				transaction_version: version.transaction_version,
+			},
+			metadata,
+		);
+		Ok(Self { sandbox, client })
+	}
+
+	/// Returns the underlying offline client, e.g. to build transactions or storage keys.
+	pub fn client(&self) -> &OfflineClient<C> {
+		&self.client
+	}
+
+	/// Returns the underlying sandbox.
+	pub fn sandbox(&mut self) -> &mut S {
+		self.sandbox
+	}
+
+	/// Dispatches the call of `payload` on behalf of `origin`.
+	///
+	/// # Arguments
+	///
+	/// * `payload` - The call, built with the typed API.
+	/// * `origin` - The signer of the transaction.
+	pub fn submit<P: Payload>(
+		&mut self,
+		payload: &P,
+		origin: AccountIdFor<S::Runtime>,
+	) -> Result<(), SubxtClientError> {
+		let call_data = self.client.tx().call_data(payload)?;
+		let call = RuntimeCall::<S::Runtime>::decode(&mut &call_data[..])?;
+		self.sandbox
+			.runtime_call(call, S::convert_account_to_origin(origin))
+			.map(|_| ())
+			.map_err(|e| SubxtClientError::Dispatch(e.error))
+	}
+
+	/// Returns the value stored under `address`, if any.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The storage address, built with the typed API.
+	pub fn fetch<A: Address>(
+		&mut self,
+		address: &A,
+	) -> Result<Option<A::Target>, SubxtClientError> {
+		let key = self.client.storage().address_bytes(address)?;
+		let Some(value) = self.sandbox.read_raw_storage(&key) else {
+			return Ok(None);
+		};
+		let metadata = self.client.metadata();
+		let value = subxt_core::storage::decode_value(&mut &value[..], address, &metadata)
+			.map_err(subxt::Error::from)?;
+		Ok(Some(value))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ink_sandbox::{config::DEFAULT_ACCOUNT, AccountId32, DefaultSandbox};
+	use subxt::{
+		dynamic::{self, Value},
+		ext::scale_value::At,
+		PolkadotConfig,
+	};
+
+	use super::*;
+
+	const RECIPIENT: AccountId32 = AccountId32::new([2u8; 32]);
+
+	#[test]
+	fn submitted_transfer_can_be_fetched() -> Result<(), SubxtClientError> {
+		let mut sandbox = DefaultSandbox::default();
+		let mut client = SubxtClient::<_, PolkadotConfig>::new(&mut sandbox)?;
+
+		let transfer = dynamic::tx(
+			"Balances",
+			"transfer_allow_death",
+			vec![Value::unnamed_variant("Id", [Value::from_bytes(&RECIPIENT)]), Value::u128(10)],
+		);
+		client.submit(&transfer, DEFAULT_ACCOUNT)?;
+
+		let account = client
+			.fetch(&dynamic::storage("System", "Account", vec![Value::from_bytes(&RECIPIENT)]))?
+			.expect("Recipient account should exist")
+			.to_value()?;
+		assert_eq!(account.at("data").at("free").and_then(|free| free.as_u128()), Some(10));
+		Ok(())
+	}
+}
@@ -0,0 +1,50 @@
+//! Helpers to express balances in units of the sandbox runtime's native token.
+//!
+//! ```rust, ignore
+//! session.sandbox().mint_into(&BOB, units(10) + millis(5))?;
+//! assert_eq!(dot_style_format(units(10) + millis(5)), "10.005 UNIT");
+//! ```
+
+/// Number of decimals of the native token of the sandbox runtimes.
+pub const DECIMALS: u32 = 10;
+
+/// One unit of the native token.
+pub const UNIT: u128 = 10u128.pow(DECIMALS);
+
+/// One thousandth of a unit.
+pub const MILLI_UNIT: u128 = UNIT / 1_000;
+
+/// One millionth of a unit.
+pub const MICRO_UNIT: u128 = UNIT / 1_000_000;
+
+/// Symbol used when formatting balances.
+pub const SYMBOL: &str = "UNIT";
+
+/// Returns `amount` units of the native token.
+pub const fn units(amount: u128) -> u128 {
+	amount * UNIT
+}
+
+/// Returns `amount` thousandths of a unit of the native token.
+pub const fn millis(amount: u128) -> u128 {
+	amount * MILLI_UNIT
+}
+
+/// Returns `amount` millionths of a unit of the native token.
+pub const fn micros(amount: u128) -> u128 {
+	amount * MICRO_UNIT
+}
+
+/// Formats `balance` in units, the way wallets display DOT amounts (e.g. `1_500_000_000` is
+/// rendered as `0.15 UNIT`).
+///
+/// Trailing zeros of the fractional part are omitted.
+pub fn dot_style_format(balance: u128) -> String {
+	let whole = balance / UNIT;
+	let fraction = balance % UNIT;
+	if fraction == 0 {
+		return format!("{whole} {SYMBOL}");
+	}
+	let fraction = format!("{fraction:0width$}", width = DECIMALS as usize);
+	format!("{whole}.{} {SYMBOL}", fraction.trim_end_matches('0'))
+}
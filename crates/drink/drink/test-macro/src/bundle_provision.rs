@@ -31,6 +31,19 @@ impl BundleProviderGenerator {
 		Self { root_contract_name, bundles }
 	}
 
+	/// Returns the path to the bundle of the contract `name` (in any case convention), if it was
+	/// built.
+	pub fn bundle_path(&self, name: &str) -> Option<&PathBuf> {
+		self.bundles.get(&name.to_case(Case::Pascal))
+	}
+
+	/// Returns the names of all the built contracts.
+	pub fn contract_names(&self) -> Vec<&str> {
+		let mut names = self.bundles.keys().map(String::as_str).collect::<Vec<_>>();
+		names.sort();
+		names
+	}
+
 	pub fn generate_bundle_provision(&self, enum_item: ItemEnum) -> TokenStream2 {
 		let enum_name = &enum_item.ident;
 		let enum_vis = &enum_item.vis;
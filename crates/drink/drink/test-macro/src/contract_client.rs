@@ -0,0 +1,207 @@
+//! Generation of typed contract clients from the contract metadata.
+
+use convert_case::{Case, Casing};
+use darling::FromMeta;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use serde_json::Value;
+use syn::{Fields, ItemStruct};
+
+use crate::{
+	metadata::{docs, entries, label, load_metadata, TypeRegistry},
+	SynResult,
+};
+
+/// Names of the parameters of the generated functions.
+const RESERVED_NAMES: [&str; 4] = ["session", "bundle", "salt", "transferred_value"];
+
+#[derive(FromMeta)]
+pub struct ContractClientAttributes {
+	/// Path to the `.contract` bundle or the `.json` metadata, relative to the crate root.
+	path: String,
+}
+
+/// Generates the client struct in place of the (unit) `item` struct.
+pub fn generate_contract_client(
+	attributes: ContractClientAttributes,
+	item: ItemStruct,
+) -> SynResult<TokenStream2> {
+	if !item.generics.params.is_empty() || !matches!(item.fields, Fields::Unit) {
+		return Err(syn::Error::new_spanned(
+			&item,
+			"Contract client must be a unit struct without generics",
+		));
+	}
+	let error = |message: String| syn::Error::new_spanned(&item.ident, message);
+
+	let (path, metadata) = load_metadata(&attributes.path).map_err(error)?;
+
+	let types = TypeRegistry::new(&metadata);
+	let spec = &metadata["spec"];
+	let constructors = entries(&spec["constructors"])
+		.map(|constructor| generate_constructor(&types, constructor))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(error)?;
+	let messages = entries(&spec["messages"])
+		.map(|message| generate_message(&types, message))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(error)?;
+
+	let ItemStruct { attrs, vis, ident, .. } = item;
+	Ok(quote! {
+		#(#attrs)*
+		#vis struct #ident<'session, S: ::drink::Sandbox>
+		where
+			S::Runtime: ::drink::pallet_contracts::Config,
+		{
+			session: &'session mut ::drink::session::Session<S>,
+			address: ::drink::ink_sandbox::AccountIdFor<S::Runtime>,
+		}
+
+		// Makes the crate recompile whenever the metadata changes.
+		const _: &[u8] = include_bytes!(#path);
+
+		impl<'session, S: ::drink::Sandbox> #ident<'session, S>
+		where
+			S::Runtime: ::drink::pallet_contracts::Config,
+		{
+			/// Binds the client to the contract under `address`.
+			pub fn new(
+				session: &'session mut ::drink::session::Session<S>,
+				address: ::drink::ink_sandbox::AccountIdFor<S::Runtime>,
+			) -> Self {
+				Self { session, address }
+			}
+
+			/// Returns the address of the contract.
+			pub fn address(&self) -> ::drink::ink_sandbox::AccountIdFor<S::Runtime> {
+				self.address.clone()
+			}
+
+			/// Returns the session the client is bound to.
+			pub fn session(&mut self) -> &mut ::drink::session::Session<S> {
+				self.session
+			}
+
+			#(#constructors)*
+
+			#(#messages)*
+		}
+	})
+}
+
+/// Generates a `deploy_<constructor>` function, deploying the contract and binding a client to
+/// it.
+fn generate_constructor(types: &TypeRegistry, constructor: &Value) -> Result<TokenStream2, String> {
+	let label = label(constructor)?;
+	let name = format_ident!("deploy_{}", method_name(label));
+	let docs = docs(constructor);
+	let selector = selector(constructor)?;
+	let (params, encoding) = arguments(types, constructor)?;
+	let (value_param, value) = value(constructor);
+
+	Ok(quote! {
+		#(#[doc = #docs])*
+		pub fn #name(
+			session: &'session mut ::drink::session::Session<S>,
+			bundle: &::drink::session::ContractBundle,
+			#(#params,)*
+			salt: ::std::vec::Vec<u8>,
+			#value_param
+		) -> ::core::result::Result<Self, ::drink::session::error::SessionError> {
+			#[allow(unused_mut)]
+			let mut __drink_input = ::std::vec::Vec::new();
+			#(#encoding)*
+			let address = session
+				.deploy_by_selector(bundle.wasm.clone(), #selector, __drink_input, salt, #value)?;
+			session.set_transcoder(address.clone(), &bundle.transcoder);
+			::core::result::Result::Ok(Self { session, address })
+		}
+	})
+}
+
+/// Generates a method calling the message.
+fn generate_message(types: &TypeRegistry, message: &Value) -> Result<TokenStream2, String> {
+	let label = label(message)?;
+	let name = format_ident!("{}", method_name(label));
+	let docs = docs(message);
+	let selector = selector(message)?;
+	let (params, encoding) = arguments(types, message)?;
+	let (value_param, value) = value(message);
+	let return_type = match message["returnType"]["type"].as_u64() {
+		Some(id) => types.message_return_type(id)?,
+		None => quote! { () },
+	};
+
+	Ok(quote! {
+		#(#[doc = #docs])*
+		pub fn #name(
+			&mut self,
+			#(#params,)*
+			#value_param
+		) -> ::core::result::Result<
+			::drink::errors::MessageResult<#return_type>,
+			::drink::session::error::SessionError,
+		> {
+			#[allow(unused_mut)]
+			let mut __drink_input = ::std::vec::Vec::new();
+			#(#encoding)*
+			self.session.call_by_selector(self.address.clone(), #selector, __drink_input, #value)
+		}
+	})
+}
+
+/// Turns labels like `PSP22::transfer` into method names like `psp22_transfer`.
+fn method_name(label: &str) -> String {
+	label.replace("::", "_").to_case(Case::Snake)
+}
+
+fn selector(entry: &Value) -> Result<TokenStream2, String> {
+	let selector = entry["selector"].as_str().unwrap_or_default();
+	let hex = selector.strip_prefix("0x").unwrap_or(selector);
+	if hex.len() != 8 {
+		return Err(format!("Invalid selector `{selector}`"));
+	}
+	let bytes = (0..4)
+		.map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| format!("Invalid selector `{selector}`: {e}"))?;
+	Ok(quote! { [#(#bytes),*] })
+}
+
+/// Returns the parameters of the arguments and the statements encoding them into `input`.
+fn arguments(
+	types: &TypeRegistry,
+	entry: &Value,
+) -> Result<(Vec<TokenStream2>, Vec<TokenStream2>), String> {
+	let mut params = vec![];
+	let mut encoding = vec![];
+	for arg in entries(&entry["args"]) {
+		let label = label(arg)?;
+		// Keywords and the names of the generated parameters get a trailing underscore.
+		let name = syn::parse_str::<Ident>(label)
+			.ok()
+			.filter(|_| !RESERVED_NAMES.contains(&label))
+			.unwrap_or_else(|| format_ident!("{}_", label));
+		let id = arg["type"]["type"]
+			.as_u64()
+			.ok_or_else(|| format!("Missing type of `{label}`"))?;
+		let ty = types.rust_type(id)?;
+		params.push(quote! { #name: #ty });
+		encoding.push(quote! { ::drink::scale::Encode::encode_to(&#name, &mut __drink_input); });
+	}
+	Ok((params, encoding))
+}
+
+/// Returns the value parameter and the value expression, depending on the payability.
+fn value(entry: &Value) -> (TokenStream2, TokenStream2) {
+	match entry["payable"].as_bool() {
+		Some(true) => (
+			quote! {
+				transferred_value: ::core::option::Option<::drink::ink_sandbox::BalanceFor<S::Runtime>>
+			},
+			quote! { transferred_value },
+		),
+		_ => (quote! {}, quote! { ::core::option::Option::None }),
+	}
+}
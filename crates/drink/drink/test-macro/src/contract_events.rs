@@ -0,0 +1,80 @@
+//! Generation of contract event types from the contract metadata.
+
+use darling::FromMeta;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use serde_json::Value;
+use syn::ItemMod;
+
+use crate::{
+	metadata::{docs, entries, label, load_metadata, TypeRegistry},
+	SynResult,
+};
+
+#[derive(FromMeta)]
+pub struct ContractEventsAttributes {
+	/// Path to the `.contract` bundle or the `.json` metadata, relative to the crate root.
+	path: String,
+}
+
+/// Appends a struct per contract event to the (inline) module `item`.
+pub fn generate_contract_events(
+	attributes: ContractEventsAttributes,
+	item: ItemMod,
+) -> SynResult<TokenStream2> {
+	let error = |message: String| syn::Error::new_spanned(&item.ident, message);
+	let Some((_, content)) = &item.content else {
+		return Err(error("Contract events must be generated in an inline module".to_string()));
+	};
+
+	let (path, metadata) = load_metadata(&attributes.path).map_err(error)?;
+	let types = TypeRegistry::new(&metadata);
+	let events = entries(&metadata["spec"]["events"])
+		.map(|event| generate_event(&types, event))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(error)?;
+
+	let ItemMod { attrs, vis, unsafety, ident, .. } = &item;
+	Ok(quote! {
+		#(#attrs)*
+		#vis #unsafety mod #ident {
+			#(#content)*
+
+			// Makes the crate recompile whenever the metadata changes.
+			const _: &[u8] = include_bytes!(#path);
+
+			#(#events)*
+		}
+	})
+}
+
+/// Generates a struct with the fields of the event, encoded like the event emitted by the contract.
+fn generate_event(types: &TypeRegistry, event: &Value) -> Result<TokenStream2, String> {
+	let name = format_ident!("{}", label(event)?);
+	let docs = docs(event);
+	let fields = entries(&event["args"])
+		.map(|field| {
+			let label = label(field)?;
+			let name =
+				syn::parse_str::<Ident>(label).unwrap_or_else(|_| format_ident!("r#{}", label));
+			let docs = docs(field);
+			let id = field["type"]["type"]
+				.as_u64()
+				.ok_or_else(|| format!("Missing type of `{label}`"))?;
+			let ty = types.rust_type(id)?;
+			Ok(quote! {
+				#(#[doc = #docs])*
+				pub #name: #ty
+			})
+		})
+		.collect::<Result<Vec<_>, String>>()?;
+
+	Ok(quote! {
+		#(#[doc = #docs])*
+		#[derive(Clone, Debug, PartialEq, ::drink::scale::Encode, ::drink::scale::Decode)]
+		#[codec(crate = ::drink::scale)]
+		pub struct #name {
+			#(#fields,)*
+		}
+	})
+}
@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+	parse::{Parse, ParseStream},
+	punctuated::Punctuated,
+	Attribute, Expr, ExprArray, FnArg, LitStr, Pat, PatType, Token, Type,
+};
+
+use crate::{bundle_provision::BundleProviderGenerator, SynResult};
+
+/// Name of the parameter attribute requesting contract injection.
+const DEPLOY_ATTRIBUTE: &str = "deploy";
+
+/// Arguments of the `#[deploy(...)]` parameter attribute.
+///
+/// Accepted form: `#[deploy("contract_name", "constructor", [arg1, arg2, ...])]`, where the last
+/// part is optional.
+struct DeployArgs {
+	contract: LitStr,
+	constructor: LitStr,
+	args: Vec<Expr>,
+}
+
+impl Parse for DeployArgs {
+	fn parse(input: ParseStream) -> SynResult<Self> {
+		let contract = input.parse()?;
+		input.parse::<Token![,]>()?;
+		let constructor = input.parse()?;
+
+		let mut args = vec![];
+		if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+			let array = input.parse::<ExprArray>()?;
+			args = array.elems.into_iter().collect();
+			input.parse::<Option<Token![,]>>()?;
+		}
+
+		Ok(Self { contract, constructor, args })
+	}
+}
+
+/// A test function parameter that should be populated with the address of a freshly deployed
+/// contract.
+pub struct InjectedContract {
+	pat: Box<Pat>,
+	ty: Box<Type>,
+	deploy: DeployArgs,
+}
+
+/// Splits the test function parameters into the injected contracts and the rest.
+///
+/// Parameters without the `#[deploy]` attribute (e.g. `mut session: Session`) are dropped, since
+/// the session is created by the macro itself.
+pub fn extract_injected_contracts(
+	inputs: impl IntoIterator<Item = FnArg>,
+) -> SynResult<Vec<InjectedContract>> {
+	let mut injected = vec![];
+	for input in inputs {
+		let FnArg::Typed(PatType { attrs, pat, ty, .. }) = input else {
+			continue;
+		};
+		let Some(attr) = find_deploy_attribute(&attrs) else {
+			continue;
+		};
+		injected.push(InjectedContract { pat, ty, deploy: attr.parse_args()? });
+	}
+	Ok(injected)
+}
+
+fn find_deploy_attribute(attrs: &[Attribute]) -> Option<&Attribute> {
+	attrs.iter().find(|attr| attr.path().is_ident(DEPLOY_ATTRIBUTE))
+}
+
+/// Generates the deployment code for all the injected contracts.
+///
+/// Every contract is deployed with a new salt of the session (see `Session::next_salt`), so that
+/// the same contract can be injected any number of times.
+pub fn generate_deployments(
+	injected: &[InjectedContract],
+	bundles: &BundleProviderGenerator,
+) -> SynResult<TokenStream2> {
+	let deployments = injected
+		.iter()
+		.map(|InjectedContract { pat, ty, deploy }| {
+			let contract_name = deploy.contract.value();
+			let path = bundles.bundle_path(&contract_name).ok_or_else(|| {
+				syn::Error::new_spanned(
+					&deploy.contract,
+					format!(
+						"Unknown contract `{contract_name}`. Available contracts: {}",
+						bundles.contract_names().join(", ")
+					),
+				)
+			})?;
+			let path = path.to_str().expect("Invalid path");
+
+			let constructor = &deploy.constructor;
+			let args = match deploy.args.as_slice() {
+				[] => quote! { ::drink::session::NO_ARGS },
+				args => quote! { &[#(#args),*] },
+			};
+
+			Ok(quote! {
+				let #pat: #ty = {
+					let salt = session.next_salt();
+					session
+						.deploy_bundle(
+							::drink::session::ContractBundle::load(#path).expect(concat!(
+								"Failed to load the bundle of `",
+								#contract_name,
+								"`"
+							)),
+							#constructor,
+							#args,
+							salt,
+							None,
+						)
+						.expect(concat!("Failed to deploy `", #contract_name, "`"))
+				};
+			})
+		})
+		.collect::<SynResult<Vec<_>>>()?;
+
+	Ok(quote! { #(#deployments)* })
+}
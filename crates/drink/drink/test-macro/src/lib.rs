@@ -4,14 +4,23 @@
 
 mod bundle_provision;
 mod contract_building;
+mod contract_client;
+mod contract_events;
+mod deploy_injection;
+mod metadata;
 
 use darling::{ast::NestedMeta, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{ItemEnum, ItemFn};
+use syn::{ItemEnum, ItemFn, ItemMod, ItemStruct};
 
-use crate::contract_building::build_contracts;
+use crate::{
+	contract_building::build_contracts,
+	contract_client::{generate_contract_client, ContractClientAttributes},
+	contract_events::{generate_contract_events, ContractEventsAttributes},
+	deploy_injection::{extract_injected_contracts, generate_deployments},
+};
 
 type SynResult<T> = Result<T, syn::Error>;
 
@@ -52,6 +61,23 @@ type SynResult<T> = Result<T, syn::Error>;
 ///
 /// By default, the macro will use `drink::minimal::MinimalSandbox`.
 ///
+/// When the `DRINK_TEST_REPORT` environment variable is set to the path of a file, the session
+/// appends a JSON report of the test (its deployments, calls, gas, events and result) to the file
+/// (see `drink::session::test_report`).
+///
+/// The sandbox can be configured with a TOML file (see `drink::SandboxConfig`), whose path is
+/// relative to the root of the crate: `#[drink::test(config = "sandbox.toml")]`. The sandbox must
+/// then be created with `drink::create_sandbox`.
+///
+/// # Injecting deployed contracts
+///
+/// Besides the session, the testcase function can declare parameters annotated with
+/// `#[deploy("contract_name", "constructor", [args...])]` (the argument list is optional). Every
+/// such contract will be deployed (in the order of declaration) before the test body is executed,
+/// and its address will be bound to the parameter. The contract name must be one of the contracts
+/// built by the macro (see above). Each injected contract is deployed with a new salt of the
+/// session (see `Session::next_salt`), so that the same contract can be injected more than once.
+///
 /// # Example
 ///
 /// ```rust, ignore
@@ -61,6 +87,14 @@ type SynResult<T> = Result<T, syn::Error>;
 ///         .deploy_bundle(&get_bundle(), "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)
 ///         .unwrap();
 /// }
+///
+/// #[drink::test]
+/// fn with_injection(
+///     mut session: Session<MinimalSandbox>,
+///     #[deploy("flipper", "new", ["true"])] flipper: AccountId32,
+/// ) {
+///     session.call_with_address::<_, bool>(flipper, "get", NO_ARGS, NO_ENDOWMENT).unwrap();
+/// }
 /// ```
 #[proc_macro_attribute]
 pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -73,6 +107,7 @@ pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
 #[derive(FromMeta)]
 struct TestAttributes {
 	sandbox: Option<syn::Path>,
+	config: Option<String>,
 }
 
 /// Auxiliary function to enter ?-based error propagation.
@@ -80,7 +115,9 @@ fn test_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStrea
 	let item_fn = syn::parse2::<ItemFn>(item)?;
 	let macro_args = TestAttributes::from_list(&NestedMeta::parse_meta_list(attr)?)?;
 
-	build_contracts();
+	let bundles = build_contracts();
+	let injected_contracts = extract_injected_contracts(item_fn.sig.inputs)?;
+	let deployments = generate_deployments(&injected_contracts, &bundles)?;
 
 	let fn_vis = item_fn.vis;
 	let fn_attrs = item_fn.attrs;
@@ -95,12 +132,24 @@ fn test_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStrea
 	let sandbox = macro_args
 		.sandbox
 		.unwrap_or(syn::parse2(quote! { ::drink::minimal::MinimalSandbox })?);
+	let session = match macro_args.config {
+		Some(config) => quote! {
+			Session::<#sandbox>::with_sandbox(<#sandbox>::with_config(
+				&::drink::SandboxConfig::load(
+					::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(#config),
+				)
+				.expect("Failed to load the sandbox configuration"),
+			))
+		},
+		None => quote! { Session::<#sandbox>::default() },
+	};
 
 	Ok(quote! {
 		#[test]
 		#(#fn_attrs)*
 		#fn_vis #fn_async #fn_const #fn_unsafety fn #fn_name #fn_generics () #fn_output {
-			let mut session = Session::<#sandbox>::default();
+			let mut session = #session.with_test_report();
+			#deployments
 			#fn_block
 		}
 	})
@@ -180,3 +229,94 @@ fn parse_bundle_enum(item: TokenStream2) -> SynResult<ItemEnum> {
 
 	Ok(enum_item)
 }
+
+/// Generates a typed client of a contract from its metadata.
+///
+/// # Requirements
+///
+/// - Your crate must have `drink` in its dependencies (and it shouldn't be renamed).
+/// - The attributed struct must be a unit struct without generics.
+/// - The `path` to the `.contract` bundle or the `.json` metadata is relative to the crate root.
+/// - Custom types used by the messages (structs and enums) must be in scope, e.g. imported from the
+///   contract crate. Account ids are mapped to `drink::AccountId32`, hashes to `[u8; 32]`.
+///
+/// # Impact
+///
+/// The struct receives a lifetime and a sandbox parameter, becoming a client bound to a
+/// `Session` and a contract address. For every message, it gets a method with the message
+/// arguments and return type (labels like `PSP22::transfer` become `psp22_transfer`), returning
+/// `Result<MessageResult<T>, SessionError>`. For every constructor, it gets a `deploy_<name>`
+/// function deploying the contract from a bundle and returning the client. Payable messages and
+/// constructors take an additional `transferred_value` argument.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// #[drink::contract_client(path = "target/ink/flipper.contract")]
+/// struct Flipper;
+///
+/// #[drink::test]
+/// fn flipping(mut session: Session<MinimalSandbox>) {
+///     let bundle = BundleProvider::local().unwrap();
+///     let mut flipper = Flipper::deploy_new(&mut session, &bundle, false, NO_SALT).unwrap();
+///     flipper.flip().unwrap().unwrap();
+///     assert_eq!(flipper.get().unwrap(), Ok(true));
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn contract_client(attr: TokenStream, item: TokenStream) -> TokenStream {
+	match contract_client_internal(attr.into(), item.into()) {
+		Ok(ts) => ts.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+/// Auxiliary function to enter ?-based error propagation.
+fn contract_client_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStream2> {
+	let attributes = ContractClientAttributes::from_list(&NestedMeta::parse_meta_list(attr)?)?;
+	generate_contract_client(attributes, syn::parse2::<ItemStruct>(item)?)
+}
+
+/// Generates the types of the events of a contract from its metadata.
+///
+/// # Requirements
+///
+/// - Your crate must have `drink` in its dependencies (and it shouldn't be renamed).
+/// - The attributed item must be an inline module. Its content is preserved, so it can import the
+///   custom types used by the events (e.g. from the contract crate).
+/// - The `path` to the `.contract` bundle or the `.json` metadata is relative to the crate root.
+///
+/// # Impact
+///
+/// For every event of the contract, the module receives a struct with the same name and fields,
+/// deriving `Encode`, `Decode`, `Debug` and `PartialEq`. The structs are encoded exactly like the
+/// events emitted by the contract, so they can be compared with the recorded contract events,
+/// e.g. with `drink::assert_last_event!`. Since the types are regenerated from the metadata on
+/// every build, they cannot silently drift from the contract.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// #[drink::contract_events(path = "target/ink/psp22.contract")]
+/// mod events {}
+///
+/// #[drink::test]
+/// fn transfer_emits_event(mut session: Session<MinimalSandbox>) {
+///     // ...
+///     session.call::<_, ()>("PSP22::transfer", &[BOB.to_string(), "10", "[]"], None)??;
+///     assert_last_event!(session, events::Transfer { from: Some(ALICE), to: Some(BOB), value: 10 });
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn contract_events(attr: TokenStream, item: TokenStream) -> TokenStream {
+	match contract_events_internal(attr.into(), item.into()) {
+		Ok(ts) => ts.into(),
+		Err(e) => e.to_compile_error().into(),
+	}
+}
+
+/// Auxiliary function to enter ?-based error propagation.
+fn contract_events_internal(attr: TokenStream2, item: TokenStream2) -> SynResult<TokenStream2> {
+	let attributes = ContractEventsAttributes::from_list(&NestedMeta::parse_meta_list(attr)?)?;
+	generate_contract_events(attributes, syn::parse2::<ItemMod>(item)?)
+}
@@ -0,0 +1,146 @@
+//! Parsing of the contract metadata shared by the code generating macros.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use serde_json::Value;
+
+/// Reads the contract metadata (a `.contract` bundle or a `.json` metadata file) under `path`,
+/// relative to the root of the crate being compiled. Returns the absolute path and the metadata.
+pub fn load_metadata(path: &str) -> Result<(String, Value), String> {
+	let mut absolute = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+	absolute.push(path);
+	let metadata = std::fs::read_to_string(&absolute)
+		.map_err(|e| format!("Failed to read the contract metadata {absolute:?}: {e}"))?;
+	let metadata = serde_json::from_str(&metadata)
+		.map_err(|e| format!("Failed to parse the contract metadata {absolute:?}: {e}"))?;
+	let absolute = absolute.to_str().ok_or_else(|| format!("Invalid path {absolute:?}"))?;
+	Ok((absolute.to_string(), metadata))
+}
+
+pub fn entries(value: &Value) -> impl Iterator<Item = &Value> {
+	value.as_array().into_iter().flatten()
+}
+
+pub fn label(entry: &Value) -> Result<&str, String> {
+	entry["label"].as_str().ok_or_else(|| format!("Missing label in {entry}"))
+}
+
+pub fn docs(entry: &Value) -> Vec<&str> {
+	entries(&entry["docs"]).filter_map(Value::as_str).collect()
+}
+
+/// Maps the types of the metadata registry to Rust types.
+pub struct TypeRegistry<'a> {
+	types: HashMap<u64, &'a Value>,
+}
+
+impl<'a> TypeRegistry<'a> {
+	pub fn new(metadata: &'a Value) -> Self {
+		let types = entries(&metadata["types"])
+			.filter_map(|ty| Some((ty["id"].as_u64()?, &ty["type"])))
+			.collect();
+		Self { types }
+	}
+
+	fn get(&self, id: u64) -> Result<&'a Value, String> {
+		self.types.get(&id).copied().ok_or_else(|| format!("Unknown type id {id}"))
+	}
+
+	fn path(&self, id: u64) -> Result<Vec<&'a str>, String> {
+		Ok(entries(&self.get(id)?["path"]).filter_map(Value::as_str).collect())
+	}
+
+	fn params(&self, id: u64) -> Result<Vec<u64>, String> {
+		entries(&self.get(id)?["params"])
+			.map(|param| {
+				param["type"]
+					.as_u64()
+					.ok_or_else(|| format!("Unresolved parameter of type {id}"))
+			})
+			.collect()
+	}
+
+	/// Messages return `Result<T, LangError>`, of which only `T` is decoded by the session.
+	pub fn message_return_type(&self, id: u64) -> Result<TokenStream2, String> {
+		let params = self.params(id)?;
+		match (self.path(id)?.last(), params.as_slice()) {
+			(Some(&"Result"), [ok, error]) if self.path(*error)?.last() == Some(&"LangError") =>
+				self.rust_type(*ok),
+			_ => self.rust_type(id),
+		}
+	}
+
+	pub fn rust_type(&self, id: u64) -> Result<TokenStream2, String> {
+		let ty = self.get(id)?;
+		let path = self.path(id)?;
+		let params = self
+			.params(id)?
+			.into_iter()
+			.map(|param| self.rust_type(param))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		match (path.last(), params.as_slice()) {
+			(Some(&"Option"), [some]) => return Ok(quote! { ::core::option::Option<#some> }),
+			(Some(&"Result"), [ok, error]) =>
+				return Ok(quote! { ::core::result::Result<#ok, #error> }),
+			(Some(&"AccountId"), _) => return Ok(quote! { ::drink::AccountId32 }),
+			(Some(&"Hash"), _) => return Ok(quote! { [u8; 32] }),
+			(Some(&"LangError"), _) => return Ok(quote! { ::drink::errors::LangError }),
+			_ => {},
+		}
+
+		let def = &ty["def"];
+		if let Some(primitive) = def["primitive"].as_str() {
+			return match primitive {
+				"str" => Ok(quote! { ::std::string::String }),
+				"bool" | "char" | "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" |
+				"i32" | "i64" | "i128" => {
+					let primitive = Ident::new(primitive, Span::call_site());
+					Ok(quote! { #primitive })
+				},
+				_ => Err(format!("Unsupported primitive type `{primitive}`")),
+			};
+		}
+		if let Some(inner) = def["sequence"]["type"].as_u64() {
+			let inner = self.rust_type(inner)?;
+			return Ok(quote! { ::std::vec::Vec<#inner> });
+		}
+		if let (Some(len), Some(inner)) =
+			(def["array"]["len"].as_u64(), def["array"]["type"].as_u64())
+		{
+			let inner = self.rust_type(inner)?;
+			let len = len as usize;
+			return Ok(quote! { [#inner; #len] });
+		}
+		if let Some(elements) = def["tuple"].as_array() {
+			let elements = elements
+				.iter()
+				.map(|element| {
+					let id = element.as_u64().ok_or("Invalid tuple element")?;
+					self.rust_type(id)
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			return Ok(quote! { (#(#elements,)*) });
+		}
+		if let Some(inner) = def["compact"]["type"].as_u64() {
+			let inner = self.rust_type(inner)?;
+			return Ok(quote! { ::drink::scale::Compact<#inner> });
+		}
+
+		// Custom types (composites and enums) are expected to be in scope, e.g. imported from the
+		// contract crate.
+		match path.last() {
+			Some(name) => {
+				let name = Ident::new(name, Span::call_site());
+				Ok(if params.is_empty() {
+					quote! { #name }
+				} else {
+					quote! { #name<#(#params),*> }
+				})
+			},
+			None => Err(format!("Unsupported anonymous type {id}")),
+		}
+	}
+}
@@ -71,4 +71,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[drink::test]
+    fn call_snapshots(mut session: Session) -> Result<(), Box<dyn Error>> {
+        use drink::{
+            sandbox_api::prelude::SystemAPI,
+            session::snapshot::{assert_snapshot, SnapshotMode},
+        };
+
+        let path = std::env::temp_dir()
+            .join(format!("flipper-snapshots-{}", std::process::id()))
+            .join("flip.snap");
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", &["true"], NO_SALT, NO_ENDOWMENT)?;
+
+        // The first assertion writes the snapshot, the next ones compare against it.
+        let (_, flip) =
+            session.snapshot_call(|session| session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT));
+        assert_snapshot(&path, &flip, SnapshotMode::Compare);
+        assert_eq!(std::fs::read_to_string(&path)?, flip);
+        assert_snapshot(&path, &flip, SnapshotMode::Compare);
+
+        // A different call doesn't match the snapshot...
+        let (_, get) =
+            session.snapshot_call(|session| session.call::<_, bool>("get", NO_ARGS, NO_ENDOWMENT));
+        assert_ne!(get, flip);
+        assert!(
+            std::panic::catch_unwind(|| assert_snapshot(&path, &get, SnapshotMode::Compare))
+                .is_err()
+        );
+
+        // ...unless the snapshots are updated.
+        assert_snapshot(&path, &get, SnapshotMode::Update);
+        assert_eq!(std::fs::read_to_string(&path)?, get);
+        assert_snapshot(&path, &get, SnapshotMode::Compare);
+
+        // An action building a block snapshots the events emitted since the block was built.
+        let (_, flip_in_new_block) = session.snapshot_call(|session| {
+            session.sandbox().build_block();
+            session.call::<_, ()>("flip", NO_ARGS, NO_ENDOWMENT)
+        });
+        assert!(flip_in_new_block.contains("Contracts(Called"));
+
+        // An action without a contract call (here, building a block) doesn't snapshot the
+        // previous call.
+        let (_, block) = session.snapshot_call(|session| session.sandbox().build_block());
+        assert!(block.starts_with("result: no contract call\n"));
+
+        std::fs::remove_dir_all(path.parent().unwrap())?;
+        Ok(())
+    }
 }
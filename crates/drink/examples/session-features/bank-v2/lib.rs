@@ -0,0 +1,123 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod bank {
+    use ink::storage::Mapping;
+
+    /// Errors of the bank.
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// The caller didn't deposit enough.
+        InsufficientBalance,
+        /// Transferring the withdrawn value failed.
+        TransferFailed,
+        /// The caller isn't the owner of the bank.
+        NotOwner,
+        /// The zero account can't own the bank.
+        ZeroOwner,
+    }
+
+    /// A deposit was made.
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// The maximum deposit of an account.
+    pub const MAX_DEPOSIT: Balance = 1_000_000;
+
+    #[ink(storage)]
+    pub struct Bank {
+        balances: Mapping<AccountId, Balance>,
+        owner: AccountId,
+    }
+
+    impl Bank {
+        /// Creates a bank owned by the caller.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                balances: Mapping::default(),
+                owner: Self::env().caller(),
+            }
+        }
+
+        /// Creates a bank owned by `owner`, which can't be the zero account.
+        #[ink(constructor)]
+        pub fn with_owner(owner: AccountId) -> Result<Self, Error> {
+            if owner == AccountId::from([0; 32]) {
+                return Err(Error::ZeroOwner);
+            }
+            Ok(Self {
+                balances: Mapping::default(),
+                owner,
+            })
+        }
+
+        /// Credits the transferred value to the caller, up to `MAX_DEPOSIT`. The rest is sent
+        /// back.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) {
+            let caller = self.env().caller();
+            let (amount, excess) = self.split_deposit(caller);
+            self.balances
+                .insert(caller, &(self.balance_of(caller) + amount));
+            if excess > 0 {
+                self.env()
+                    .transfer(caller, excess)
+                    .expect("The excess was just transferred");
+            }
+            self.env().emit_event(Deposited {
+                account: caller,
+                amount,
+            });
+        }
+
+        /// Sends `amount` of the deposit of the caller back to it.
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let balance = self.balance_of(caller);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            self.balances.insert(caller, &(balance - amount));
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// Returns the deposit of `account`.
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Balance {
+            self.balances.get(account).unwrap_or_default()
+        }
+
+        /// Returns the owner of the bank.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Hands the bank over to `new_owner`. Only the owner can do it.
+        #[ink(message)]
+        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.owner = new_owner;
+            Ok(())
+        }
+
+        /// Splits the transferred value into the amount credited to `account` and the excess
+        /// beyond `MAX_DEPOSIT`.
+        fn split_deposit(&self, account: AccountId) -> (Balance, Balance) {
+            let room = MAX_DEPOSIT.saturating_sub(self.balance_of(account));
+            let value = self.env().transferred_value();
+            (value.min(room), value.saturating_sub(room))
+        }
+    }
+}
@@ -0,0 +1,718 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod bank {
+    use ink::storage::Mapping;
+
+    /// Errors of the bank.
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// The caller didn't deposit enough.
+        InsufficientBalance,
+        /// Transferring the withdrawn value failed.
+        TransferFailed,
+        /// The caller isn't the owner of the bank.
+        NotOwner,
+        /// The zero account can't own the bank.
+        ZeroOwner,
+    }
+
+    /// The maximum deposit of an account.
+    pub const MAX_DEPOSIT: Balance = 1_000_000;
+
+    #[ink(storage)]
+    pub struct Bank {
+        balances: Mapping<AccountId, Balance>,
+        owner: AccountId,
+    }
+
+    impl Bank {
+        /// Creates a bank owned by the caller.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                balances: Mapping::default(),
+                owner: Self::env().caller(),
+            }
+        }
+
+        /// Creates a bank owned by `owner`, which can't be the zero account.
+        #[ink(constructor)]
+        pub fn with_owner(owner: AccountId) -> Result<Self, Error> {
+            if owner == AccountId::from([0; 32]) {
+                return Err(Error::ZeroOwner);
+            }
+            Ok(Self {
+                balances: Mapping::default(),
+                owner,
+            })
+        }
+
+        /// Credits the transferred value to the caller, up to `MAX_DEPOSIT`. The rest is sent
+        /// back.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) {
+            let caller = self.env().caller();
+            let (amount, excess) = self.split_deposit(caller);
+            self.balances
+                .insert(caller, &(self.balance_of(caller) + amount));
+            if excess > 0 {
+                self.env()
+                    .transfer(caller, excess)
+                    .expect("The excess was just transferred");
+            }
+        }
+
+        /// Sends `amount` of the deposit of the caller back to it.
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let balance = self.balance_of(caller);
+            if balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            self.balances.insert(caller, &(balance - amount));
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| Error::TransferFailed)
+        }
+
+        /// Returns the deposit of `account`.
+        #[ink(message)]
+        pub fn balance_of(&self, account: AccountId) -> Balance {
+            self.balances.get(account).unwrap_or_default()
+        }
+
+        /// Returns the owner of the bank.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Hands the bank over to `new_owner`. Only the owner can do it.
+        #[ink(message)]
+        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            self.owner = new_owner;
+            Ok(())
+        }
+
+        /// Splits the transferred value into the amount credited to `account` and the excess
+        /// beyond `MAX_DEPOSIT`.
+        fn split_deposit(&self, account: AccountId) -> (Balance, Balance) {
+            let room = MAX_DEPOSIT.saturating_sub(self.balance_of(account));
+            let value = self.env().transferred_value();
+            (value.min(room), value.saturating_sub(room))
+        }
+
+        /// Never returns, until it runs out of gas.
+        #[ink(message)]
+        pub fn spin(&self) {
+            loop {
+                let _ = self.env().block_number();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        error::Error,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use drink::{
+        access_matrix,
+        accounts::{derive_account, fund_accounts, BOB},
+        assert_asset_supply_invariant, assert_balance_changes, assert_event_topic,
+        frame_support::weights::Weight,
+        minimal::{MinimalSandbox, MinimalSandboxRuntime, UNIT},
+        repl::{Repl, ReplError},
+        sandbox_api::prelude::*,
+        scale::Decode,
+        session::{
+            differential::{Aspect, DiffHarness},
+            endowment::EndowmentPolicy,
+            error::SessionError,
+            event_coverage::EventCoverage,
+            fuzz::ConstructorOutcome,
+            gas_profile::GasProfile,
+            interceptors::{CallOutcome, CallParameters, Interceptor},
+            replay::Replay,
+            test_report::{Outcome, TestReport},
+            upgrade::{upgrade_and_verify, LayoutCheck},
+            Session, NO_ARGS, NO_ENDOWMENT, NO_SALT,
+        },
+        AccountId32,
+    };
+
+    use crate::bank;
+
+    #[drink::contract_bundle_provider]
+    enum BundleProvider {}
+
+    /// Withdraws `amount` from the bank, failing if the bank refuses it.
+    fn withdraw(session: &mut Session<MinimalSandbox>, amount: u128) -> Result<(), Box<dyn Error>> {
+        let result: Result<(), bank::Error> =
+            session.call("withdraw", &[amount.to_string()], NO_ENDOWMENT)??;
+        result.map_err(|error| format!("Failed to withdraw {amount}: {error:?}").into())
+    }
+
+    #[drink::test]
+    fn recorded_sessions_can_be_replayed(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        let transcoder = contract.transcoder.clone();
+        let address = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        // A withdrawal beyond the deposit is reverted, and so is its replay.
+        let error =
+            session.call_and_expect_error::<_, bank::Error>("withdraw", &["2000"], NO_ENDOWMENT)?;
+        assert_eq!(error, bank::Error::InsufficientBalance);
+        withdraw(&mut session, 400)?;
+
+        let path = std::env::temp_dir().join(format!("bank-session-{}.json", std::process::id()));
+        session.record().export_json(&path)?;
+        let mut replayed = Replay::load(&path)?.run::<MinimalSandbox>()?;
+        std::fs::remove_file(&path)?;
+
+        // The replayed session ends up in the same state.
+        let actor = session.get_actor();
+        for account in [&actor, &address] {
+            assert_eq!(
+                replayed.sandbox().free_balance(account),
+                session.sandbox().free_balance(account)
+            );
+        }
+        replayed.set_transcoder(address.clone(), &transcoder);
+        for session in [&mut session, &mut replayed] {
+            let deposit: u128 = session.call_with_address(
+                address.clone(),
+                "balance_of",
+                &[actor.to_string()],
+                NO_ENDOWMENT,
+            )??;
+            assert_eq!(deposit, 600);
+        }
+
+        Ok(())
+    }
+
+    fn timed_out<V>(result: Result<V, SessionError>) -> bool {
+        matches!(result, Err(SessionError::ExecutionTimeout(_)))
+    }
+
+    #[drink::test]
+    fn looping_calls_time_out(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract.clone(), "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        // Enough gas to spin for much longer than the timeout.
+        session.set_gas_limit(Weight::from_parts(u64::MAX, u64::MAX));
+        session.set_execution_timeout(Some(Duration::from_millis(500)));
+
+        let result = session.call::<_, ()>("spin", NO_ARGS, NO_ENDOWMENT);
+        assert!(timed_out(result));
+        assert_eq!(session.timed_out(), Some(Duration::from_millis(500)));
+
+        // The state of the session was lost with the execution, so it can't be used anymore, even
+        // without a timeout.
+        session.set_execution_timeout(None);
+        let actor = session.get_actor().to_string();
+        let result = session.call::<_, u128>("balance_of", &[actor], NO_ENDOWMENT);
+        assert!(timed_out(result));
+        let result = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT);
+        assert!(timed_out(result));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn repl_deploys_and_calls_contracts(session: Session) -> Result<(), Box<dyn Error>> {
+        // Tests run in the directory of the package, where the contract is built (as
+        // `session_features`).
+        let mut repl = Repl::new(session);
+        let output = repl.execute(r#"new-account "//Bank/savings account""#)?;
+        let customer = derive_account("//Bank/savings account")?;
+        assert!(output.starts_with(&format!("Account //Bank/savings account added: {customer}")));
+        repl.execute("set-actor '//Bank/savings account'")?;
+        assert_eq!(repl.session().get_actor(), customer);
+
+        repl.execute("deploy --yes")?;
+        repl.execute("deploy --alias other")?;
+        let result = repl.execute("deploy --alias other");
+        assert!(matches!(result, Err(ReplError::AliasTaken(alias)) if alias == "other"));
+        let current = |repl: &Repl<MinimalSandbox>| {
+            repl.contracts().current_contract().unwrap().alias.clone()
+        };
+        assert_eq!(current(&repl), "other");
+        repl.execute("use session_features")?;
+        assert_eq!(current(&repl), "session_features");
+
+        let output = repl.execute(&format!("call balance_of {customer}"))?;
+        assert!(output.starts_with("Result: Ok(())"));
+        repl.execute(&format!("call --contract other balance_of {customer}"))?;
+        let result = repl.execute(&format!("call --contract missing balance_of {customer}"));
+        assert!(matches!(result, Err(ReplError::UnknownAlias(alias)) if alias == "missing"));
+        let result = repl.execute("call 'balance_of");
+        assert!(matches!(result, Err(ReplError::InvalidCommand(_))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn repl_restores_snapshots(session: Session) -> Result<(), Box<dyn Error>> {
+        let mut repl = Repl::new(session);
+        repl.execute("deploy")?;
+        let block = repl.session().sandbox().block_number();
+        repl.execute("snapshot save deployed")?;
+
+        repl.execute("next-block 3")?;
+        repl.execute("deploy --alias other")?;
+        repl.execute("snapshot restore deployed")?;
+
+        // Both the state and the deployed contracts are the ones of the snapshot again.
+        assert_eq!(repl.session().sandbox().block_number(), block);
+        let result = repl.execute("use other");
+        assert!(matches!(result, Err(ReplError::UnknownAlias(_))));
+        repl.execute("use session_features")?;
+        let result = repl.execute("snapshot restore missing");
+        assert!(matches!(result, Err(ReplError::UnknownSnapshot(name)) if name == "missing"));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn injected_contracts_use_the_salts_of_the_session(
+        mut session: Session,
+        #[deploy("session_features", "new")] first: AccountId32,
+        #[deploy("session_features", "new")] second: AccountId32,
+    ) -> Result<(), Box<dyn Error>> {
+        assert_ne!(first, second);
+        // The injected contracts were deployed with the first salts of the session, like they are
+        // by any session with the same seed.
+        let mut replica = Session::<MinimalSandbox>::default();
+        for injected in [first, second] {
+            let salt = replica.next_salt();
+            let contract = BundleProvider::local()?;
+            let address = replica.deploy_bundle(contract, "new", NO_ARGS, salt, NO_ENDOWMENT)?;
+            assert_eq!(address, injected);
+        }
+        // So deploying with the next salt doesn't collide with them.
+        let salt = session.next_salt();
+        session.deploy_bundle(BundleProvider::local()?, "new", NO_ARGS, salt, NO_ENDOWMENT)?;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn only_the_owner_can_hand_over_the_bank(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        let owner = session.get_actor();
+        fund_accounts(&mut session, &[BOB], 1_000 * UNIT).unwrap();
+
+        // Anyone can deposit and check a deposit, but only the owner can hand the bank over.
+        access_matrix!(session,
+            actors: [owner, BOB],
+            messages: [
+                "deposit"() => [true, true],
+                "balance_of"(BOB) => [true, true],
+                "set_owner"(BOB) => [true, false],
+            ]
+        );
+
+        // The audit only dry-ran the calls, so the bank still has its owner.
+        let current: AccountId32 = session.call("owner", NO_ARGS, NO_ENDOWMENT)??;
+        assert_eq!(current, owner);
+
+        Ok(())
+    }
+
+    #[test]
+    fn the_next_version_only_differs_by_its_events() -> Result<(), Box<dyn Error>> {
+        let mut harness = DiffHarness::<MinimalSandbox>::new(
+            BundleProvider::local()?,
+            BundleProvider::BankV2.bundle()?,
+        );
+        let (session, _) = harness.sessions();
+        let actor = session.get_actor().to_string();
+        harness
+            .deploy("new", NO_ARGS, NO_SALT, NO_ENDOWMENT)
+            .call("deposit", NO_ARGS, Some(1_000))
+            .call("withdraw", &["2000"], NO_ENDOWMENT)
+            .call("withdraw", &["400"], NO_ENDOWMENT)
+            .call("balance_of", &[actor], NO_ENDOWMENT);
+
+        // Only the deposit tells the versions apart, as the next version announces it.
+        let divergences = harness.divergences();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].aspect, Aspect::Events);
+        assert!(divergences[0].action.starts_with("call deposit"));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn banks_can_be_upgraded_to_the_next_version(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        let address = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        assert!(session
+            .record()
+            .last_event_batch()
+            .contract_events()
+            .is_empty());
+
+        // The deposits and the owner are still there after the upgrade.
+        let actor = session.get_actor().to_string();
+        upgrade_and_verify(
+            &mut session,
+            address,
+            BundleProvider::BankV2.bundle()?,
+            None,
+            &[
+                LayoutCheck::new("balance_of", &[actor]),
+                LayoutCheck::new("owner", NO_ARGS),
+            ],
+        )?;
+
+        // And the deposits are announced from now on.
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        assert_eq!(
+            session.record().last_event_batch().contract_events().len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn constructors_never_trap(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let report = session.fuzz_constructors(&BundleProvider::local()?, 50);
+        report.assert_no_violation();
+
+        // `new` can't fail, whereas `with_owner` may reject the owner with its declared error.
+        for case in &report.cases {
+            match case.constructor.as_str() {
+                "new" => assert_eq!(case.outcome, ConstructorOutcome::Deployed),
+                _ => assert!(matches!(
+                    case.outcome,
+                    ConstructorOutcome::Deployed | ConstructorOutcome::Reverted
+                )),
+            }
+        }
+        // The constructor calls were only dry-run.
+        assert!(session.record().deploy_returns().is_empty());
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn breakpoints_see_the_state_before_the_message(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        let address = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        let balance = session.sandbox().free_balance(&address);
+
+        let hits = Arc::new(Mutex::new(Vec::new()));
+        let recorded = hits.clone();
+        session.set_breakpoint_on_message("withdraw", move |hit| {
+            let amount = u128::decode(&mut &hit.input[..]).unwrap();
+            recorded.lock().unwrap().push((
+                hit.contract.clone(),
+                amount,
+                hit.balance(&hit.contract),
+            ));
+        })?;
+        let result = session.set_breakpoint_on_message("steal", |_| {});
+        assert!(matches!(result, Err(SessionError::Encoding(_))));
+
+        // The breakpoint is hit before the withdrawal is sent.
+        withdraw(&mut session, 400)?;
+        assert_eq!(*hits.lock().unwrap(), [(address.clone(), 400, balance)]);
+        assert_eq!(session.sandbox().free_balance(&address), balance - 400);
+
+        session.clear_breakpoints();
+        withdraw(&mut session, 100)?;
+        assert_eq!(hits.lock().unwrap().len(), 1);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn non_deterministic_code_is_rejected(session: Session) -> Result<(), Box<dyn Error>> {
+        let mut session = session.with_determinism_audit();
+        // The bank only uses deterministic instructions.
+        session.upload_bundle(BundleProvider::local()?)?;
+
+        let floats = wat::parse_str(
+            r#"
+            (module
+                (import "env" "memory" (memory 1 1))
+                (func (export "deploy"))
+                (func (export "call")
+                    (drop (f32.add (f32.const 1) (f32.const 2)))
+                )
+            )
+            "#,
+        )?;
+        let result = session.upload(floats);
+        assert!(matches!(result, Err(SessionError::NonDeterministicCode)));
+        // Code which is invalid anyway isn't reported as non-deterministic.
+        let result = session.upload(vec![0, 1, 2]);
+        assert!(matches!(result, Err(SessionError::UploadFailed(_))));
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn deposits_beyond_the_maximum_are_sent_back(session: Session) -> Result<(), Box<dyn Error>> {
+        let mut session = session.with_unused_value_check();
+        let contract = BundleProvider::local()?;
+        let address = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        assert!(session.unused_value_reports().is_empty());
+
+        // Only the room left below the maximum is kept, the rest of the value is unused.
+        session.call::<_, ()>("deposit", NO_ARGS, Some(bank::MAX_DEPOSIT))??;
+        let reports = session.unused_value_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].contract, address);
+        assert_eq!(reports[0].endowment, bank::MAX_DEPOSIT);
+        assert_eq!(reports[0].kept, bank::MAX_DEPOSIT - 1_000);
+        assert_eq!(reports[0].forwarded, 0);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn the_bank_conserves_value(session: Session) -> Result<(), Box<dyn Error>> {
+        // The session panics as soon as an operation creates or destroys value.
+        let mut session = session.with_value_conservation_check();
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(bank::MAX_DEPOSIT))??;
+        withdraw(&mut session, 400)?;
+        session.call_and_expect_error::<_, bank::Error>("withdraw", &["2000000"], NO_ENDOWMENT)?;
+
+        // Value minted directly through the sandbox is accounted for.
+        fund_accounts(&mut session, &[BOB], 1_000 * UNIT).unwrap();
+        session.set_actor(BOB);
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        withdraw(&mut session, 1_000)?;
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn withdrawals_move_the_value_back(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        let address = session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+
+        // Bob isn't involved in the withdrawal, and a refused withdrawal moves nothing.
+        let actor = session.get_actor();
+        let error = assert_balance_changes!(session, { actor: +400, address: -400, BOB: 0 }, {
+            withdraw(&mut session, 400)?;
+            session.call_and_expect_error::<_, bank::Error>("withdraw", &["1000"], NO_ENDOWMENT)?
+        });
+        assert_eq!(error, bank::Error::InsufficientBalance);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn the_supply_of_assets_is_held_by_their_holders(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        const TOKEN: u32 = 1;
+
+        let owner = session.get_actor();
+        fund_accounts(&mut session, &[BOB], 1_000 * UNIT).unwrap();
+        AssetsAPI::<MinimalSandbox>::create(session.sandbox(), &TOKEN, &owner, 1).unwrap();
+        AssetsAPI::<MinimalSandbox>::mint_into(session.sandbox(), &TOKEN, &owner, 1_000).unwrap();
+        AssetsAPI::<MinimalSandbox>::mint_into(session.sandbox(), &TOKEN, &BOB, 500).unwrap();
+
+        // The asset was minted outside of the contract interactions of the session, so its holders
+        // must be listed.
+        assert_asset_supply_invariant!(session, TOKEN, [owner, BOB]);
+        let supply = session.asset_supply(&TOKEN, &[owner]);
+        assert!(!supply.is_accounted_for());
+        assert_eq!(supply.total_supply, 1_500);
+        assert_eq!(supply.holders_balance, 1_000);
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn gas_profiles_cover_the_calls_of_the_contract(
+        mut session: Session,
+    ) -> Result<(), Box<dyn Error>> {
+        let bundle = BundleProvider::local()?;
+        session.deploy_bundle(bundle.clone(), "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        withdraw(&mut session, 400)?;
+        // The calls of contracts with another code aren't part of the profile.
+        let next_version = BundleProvider::BankV2.bundle()?;
+        session.deploy_bundle(next_version, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, AccountId32>("owner", NO_ARGS, NO_ENDOWMENT)??;
+
+        // The gas consumption depends on the compiler, so instead of verifying it against a
+        // committed profile (with `verify_gas_profile`), only check what the profile covers.
+        let profile = session.gas_profile(&bundle);
+        assert_eq!(profile.constructors.keys().collect::<Vec<_>>(), ["new"]);
+        assert_eq!(
+            profile.messages.keys().collect::<Vec<_>>(),
+            ["deposit", "withdraw"]
+        );
+
+        let path = std::env::temp_dir().join(format!("bank-{}.gas.toml", std::process::id()));
+        profile.save(&path)?;
+        let loaded = GasProfile::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(loaded, Some(profile));
+
+        Ok(())
+    }
+
+    /// Makes Bob the caller of every message, and records whether the calls went through.
+    struct CallAsBob(Arc<Mutex<Vec<bool>>>);
+
+    impl Interceptor<MinimalSandboxRuntime> for CallAsBob {
+        fn before(&mut self, params: &mut CallParameters<MinimalSandboxRuntime>) {
+            if !params.is_deployment() {
+                params.actor = BOB;
+            }
+        }
+
+        fn after(&mut self, _: &CallParameters<MinimalSandboxRuntime>, outcome: &CallOutcome) {
+            let succeeded = outcome
+                .result
+                .as_ref()
+                .is_ok_and(|result| !result.did_revert());
+            self.0.lock().unwrap().push(succeeded);
+        }
+    }
+
+    #[drink::test]
+    fn interceptors_can_call_as_another_actor(mut session: Session) -> Result<(), Box<dyn Error>> {
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        let owner = session.get_actor();
+        fund_accounts(&mut session, &[BOB], 1_000 * UNIT).unwrap();
+
+        let outcomes = Arc::new(Mutex::new(Vec::new()));
+        session.add_interceptor(CallAsBob(outcomes.clone()));
+        let error = session.call_and_expect_error::<_, bank::Error>(
+            "set_owner",
+            &[BOB.to_string()],
+            NO_ENDOWMENT,
+        )?;
+        assert_eq!(error, bank::Error::NotOwner);
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        assert_eq!(*outcomes.lock().unwrap(), [false, true]);
+
+        // The deposit was made by Bob, though the actor of the session is still the owner.
+        session.clear_interceptors();
+        assert_eq!(session.get_actor(), owner);
+        for (account, deposit) in [(BOB, 1_000), (owner, 0)] {
+            let balance: u128 =
+                session.call("balance_of", &[account.to_string()], NO_ENDOWMENT)??;
+            assert_eq!(balance, deposit);
+        }
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn asserted_events_are_covered(session: Session) -> Result<(), Box<dyn Error>> {
+        let coverage = EventCoverage::new();
+        let mut session = session.with_event_coverage(&coverage);
+        let bundle = BundleProvider::BankV2.bundle()?;
+        session.deploy_bundle(bundle.clone(), "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        assert_eq!(coverage.report(&bundle).untested, ["Deposited"]);
+
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        let actor = session.get_actor();
+        assert_event_topic!(session, actor);
+
+        // Asserting the topic of the deposit covers the only event of the next version.
+        let reports = coverage.print_reports(&[bundle]);
+        assert_eq!(reports[0].asserted, ["Deposited"]);
+        assert!(reports[0].is_complete());
+
+        Ok(())
+    }
+
+    #[test]
+    fn interactions_are_reported() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("bank-report-{}.jsonl", std::process::id()));
+        let mut session = Session::<MinimalSandbox>::default().with_test_report_to(&path);
+
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(1_000))??;
+        session.call_and_expect_error::<_, bank::Error>("withdraw", &["2000"], NO_ENDOWMENT)?;
+        // The report is written when the session is dropped.
+        drop(session);
+
+        let reports = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let report = reports
+            .lines()
+            .map(serde_json::from_str::<TestReport>)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .find(|report| report.test.ends_with("interactions_are_reported"))
+            .expect("The test should be reported");
+        assert!(report.passed);
+        assert_eq!(report.deploys.len(), 1);
+        assert_eq!(report.deploys[0].message.as_deref(), Some("new"));
+        let calls: Vec<_> = report
+            .calls
+            .iter()
+            .map(|call| (call.message.as_deref(), &call.outcome))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (Some("deposit"), &Outcome::Success),
+                (Some("withdraw"), &Outcome::Reverted)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[drink::test]
+    fn deposits_get_the_default_endowment(session: Session) -> Result<(), Box<dyn Error>> {
+        let mut session = session.with_default_endowment(EndowmentPolicy::Fixed(500));
+        let contract = BundleProvider::local()?;
+        session.deploy_bundle(contract, "new", NO_ARGS, NO_SALT, NO_ENDOWMENT)?;
+
+        // Only the payable `deposit` is endowed by default, and an explicit endowment prevails.
+        session.call::<_, ()>("deposit", NO_ARGS, NO_ENDOWMENT)??;
+        withdraw(&mut session, 200)?;
+        session.call::<_, ()>("deposit", NO_ARGS, Some(100))??;
+        let actor = session.get_actor().to_string();
+        let deposit: u128 = session.call("balance_of", &[actor], NO_ENDOWMENT)??;
+        assert_eq!(deposit, 400);
+
+        // Payable messages missing from a policy per message can't be called without endowment.
+        session.set_default_endowment(EndowmentPolicy::PerMessage(BTreeMap::new()));
+        let result = session.call::<_, ()>("deposit", NO_ARGS, NO_ENDOWMENT);
+        assert!(matches!(result, Err(SessionError::NoEndowment(message)) if message == "deposit"));
+
+        Ok(())
+    }
+}
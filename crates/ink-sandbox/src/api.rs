@@ -1,13 +1,27 @@
 pub mod assets_api;
+pub mod authorship_api;
 pub mod balances_api;
 pub mod contracts_api;
+pub mod foreign_assets_api;
+pub mod governance_api;
+pub mod identity_api;
 pub mod nfts_api;
+pub mod parachain_api;
+pub mod referenda_api;
+pub mod staking_api;
+pub mod storage_api;
 pub mod system_api;
 pub mod timestamp_api;
+pub mod tx_pause_api;
+pub mod xcm_api;
 
 pub mod prelude {
 	pub use super::{
-		assets_api::AssetsAPI, balances_api::BalanceAPI, contracts_api::ContractAPI,
-		nfts_api::NftsAPI, system_api::SystemAPI, timestamp_api::TimestampAPI,
+		assets_api::AssetsAPI, authorship_api::AuthorshipAPI, balances_api::BalanceAPI,
+		contracts_api::ContractAPI, foreign_assets_api::ForeignAssetsAPI,
+		governance_api::GovernanceAPI, identity_api::IdentityAPI, nfts_api::NftsAPI,
+		parachain_api::ParachainAPI, referenda_api::ReferendaAPI, staking_api::StakingAPI,
+		storage_api::StorageAPI, system_api::SystemAPI, timestamp_api::TimestampAPI,
+		tx_pause_api::TxPauseAPI, xcm_api::XcmAPI,
 	};
 }
@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use frame_support::{
 	sp_runtime::{traits::Dispatchable, DispatchError},
 	traits::fungibles::{
@@ -9,15 +11,25 @@ use pallet_assets::Instance1;
 
 use crate::{AccountIdFor, RuntimeCall, Sandbox};
 
-type AssetIdOf<T> = <AssetsOf<T> as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
-type AssetsOf<T> = pallet_assets::Pallet<T, Instance1>;
-type BalanceOf<T> = <AssetsOf<T> as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+type AssetIdOf<T, I> = <AssetsOf<T, I> as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+type AssetsOf<T, I> = pallet_assets::Pallet<T, I>;
+type BalanceOf<T, I> = <AssetsOf<T, I> as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
 
 /// Assets API for the sandbox.
-pub trait AssetsAPI<T: Sandbox>
+///
+/// Generic over the instance `I` of the assets pallet. Sandboxes implement it for the trust-backed
+/// assets (`Instance1`), and the other instances (e.g. the foreign assets of the parachain sandbox,
+/// under `Instance2`) are reached through an [`InstanceAssets`] view:
+///
+/// ```rust, ignore
+/// sandbox.balance_of(&asset, &owner);
+/// sandbox.foreign_assets().balance_of(&asset, &owner);
+/// InstanceAssets::<_, Instance2>::new(&mut sandbox).balance_of(&asset, &owner);
+/// ```
+pub trait AssetsAPI<T: Sandbox, I: 'static = Instance1>
 where
 	T: Sandbox,
-	T::Runtime: pallet_assets::Config<Instance1>,
+	T::Runtime: pallet_assets::Config<I>,
 {
 	/// Creates `value` amount of tokens and assigns them to `account`, increasing the total supply.
 	///
@@ -27,16 +39,16 @@ where
 	/// * `min_balance` - The asset amount one account need at least.
 	fn create(
 		&mut self,
-		id: &AssetIdOf<T::Runtime>,
+		id: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
-		min_balance: BalanceOf<T::Runtime>,
+		min_balance: BalanceOf<T::Runtime, I>,
 	) -> Result<(), DispatchError>;
 
 	/// Start the destruction an existing fungible asset.
 	///
 	/// # Arguments
 	/// * `asset` - ID of the asset.
-	fn start_destroy(&mut self, asset: &AssetIdOf<T::Runtime>) -> Result<(), DispatchError>;
+	fn start_destroy(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> Result<(), DispatchError>;
 
 	/// Start the destruction an existing fungible asset.
 	///
@@ -48,7 +60,7 @@ where
 	fn set_metadata<Origin: Into<<RuntimeCall<T::Runtime> as Dispatchable>::RuntimeOrigin>>(
 		&mut self,
 		origin: Origin,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		name: Vec<u8>,
 		symbol: Vec<u8>,
 		decimals: u8,
@@ -64,10 +76,10 @@ where
 	/// * `value` - The number of tokens to approve.
 	fn approve(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
 		delegate: &AccountIdFor<T::Runtime>,
-		amount: BalanceOf<T::Runtime>,
+		amount: BalanceOf<T::Runtime, I>,
 	) -> Result<(), DispatchError>;
 
 	/// Creates `value` amount of tokens and assigns them to `account`, increasing the total supply.
@@ -78,10 +90,10 @@ where
 	/// * `value` - The number of tokens to mint.
 	fn mint_into(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		account: &AccountIdFor<T::Runtime>,
-		value: BalanceOf<T::Runtime>,
-	) -> Result<BalanceOf<T::Runtime>, DispatchError>;
+		value: BalanceOf<T::Runtime, I>,
+	) -> Result<BalanceOf<T::Runtime, I>, DispatchError>;
 
 	/// Returns the account balance for the specified `owner`.
 	///
@@ -89,15 +101,15 @@ where
 	/// * `owner` - The account whose balance is being queried.
 	fn balance_of(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
-	) -> BalanceOf<T::Runtime>;
+	) -> BalanceOf<T::Runtime, I>;
 
 	/// Returns the total supply of the `asset`.
 	///
 	/// # Arguments
 	/// * `asset` - ID of the asset.
-	fn total_supply(&mut self, asset: &AssetIdOf<T::Runtime>) -> BalanceOf<T::Runtime>;
+	fn total_supply(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> BalanceOf<T::Runtime, I>;
 
 	/// Returns the allowance for a `spender` approved by an `owner`.
 	///
@@ -107,16 +119,16 @@ where
 	/// * `spender` - The account that is allowed to spend the tokens.
 	fn allowance(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
 		delegate: &AccountIdFor<T::Runtime>,
-	) -> BalanceOf<T::Runtime>;
+	) -> BalanceOf<T::Runtime, I>;
 
 	/// Check if the asset exists.
 	///
 	/// # Arguments
 	/// * `asset` - ID of the asset.
-	fn asset_exists(&mut self, asset: &AssetIdOf<T::Runtime>) -> bool;
+	fn asset_exists(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> bool;
 }
 
 impl<T> AssetsAPI<T> for T
@@ -126,27 +138,134 @@ where
 {
 	fn create(
 		&mut self,
-		id: &AssetIdOf<T::Runtime>,
+		id: &AssetIdOf<T::Runtime, Instance1>,
+		owner: &AccountIdFor<T::Runtime>,
+		min_balance: BalanceOf<T::Runtime, Instance1>,
+	) -> Result<(), DispatchError> {
+		InstanceAssets::<T, Instance1>::new(self).create(id, owner, min_balance)
+	}
+
+	fn start_destroy(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+	) -> Result<(), DispatchError> {
+		InstanceAssets::<T, Instance1>::new(self).start_destroy(asset)
+	}
+
+	fn set_metadata<Origin: Into<<RuntimeCall<T::Runtime> as Dispatchable>::RuntimeOrigin>>(
+		&mut self,
+		origin: Origin,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+		name: Vec<u8>,
+		symbol: Vec<u8>,
+		decimals: u8,
+	) -> Result<(), DispatchError> {
+		InstanceAssets::<T, Instance1>::new(self)
+			.set_metadata(origin, asset, name, symbol, decimals)
+	}
+
+	fn mint_into(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+		account: &AccountIdFor<T::Runtime>,
+		value: BalanceOf<T::Runtime, Instance1>,
+	) -> Result<BalanceOf<T::Runtime, Instance1>, DispatchError> {
+		InstanceAssets::<T, Instance1>::new(self).mint_into(asset, account, value)
+	}
+
+	fn approve(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+		owner: &AccountIdFor<T::Runtime>,
+		delegate: &AccountIdFor<T::Runtime>,
+		amount: BalanceOf<T::Runtime, Instance1>,
+	) -> Result<(), DispatchError> {
+		InstanceAssets::<T, Instance1>::new(self).approve(asset, owner, delegate, amount)
+	}
+
+	fn balance_of(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+		owner: &AccountIdFor<T::Runtime>,
+	) -> BalanceOf<T::Runtime, Instance1> {
+		InstanceAssets::<T, Instance1>::new(self).balance_of(asset, owner)
+	}
+
+	fn total_supply(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+	) -> BalanceOf<T::Runtime, Instance1> {
+		InstanceAssets::<T, Instance1>::new(self).total_supply(asset)
+	}
+
+	fn allowance(
+		&mut self,
+		asset: &AssetIdOf<T::Runtime, Instance1>,
+		owner: &AccountIdFor<T::Runtime>,
+		delegate: &AccountIdFor<T::Runtime>,
+	) -> BalanceOf<T::Runtime, Instance1> {
+		InstanceAssets::<T, Instance1>::new(self).allowance(asset, owner, delegate)
+	}
+
+	fn asset_exists(&mut self, asset: &AssetIdOf<T::Runtime, Instance1>) -> bool {
+		InstanceAssets::<T, Instance1>::new(self).asset_exists(asset)
+	}
+}
+
+/// A view of the instance `I` of the assets pallet of a sandbox, implementing [`AssetsAPI`] for
+/// that instance.
+pub struct InstanceAssets<'a, T, I> {
+	sandbox: &'a mut T,
+	_instance: PhantomData<I>,
+}
+
+impl<'a, T, I> InstanceAssets<'a, T, I> {
+	/// Creates the view of the instance `I` of the assets pallet of `sandbox`.
+	pub fn new(sandbox: &'a mut T) -> Self {
+		Self { sandbox, _instance: PhantomData }
+	}
+}
+
+impl<T, I: 'static> AssetsAPI<T, I> for InstanceAssets<'_, T, I>
+where
+	T: Sandbox,
+	T::Runtime: pallet_assets::Config<I>,
+{
+	fn create(
+		&mut self,
+		id: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
-		min_balance: BalanceOf<T::Runtime>,
+		min_balance: BalanceOf<T::Runtime, I>,
 	) -> Result<(), DispatchError> {
-		self.execute_with(|| <pallet_assets::Pallet::<T::Runtime, Instance1> as Create<AccountIdFor<T::Runtime>>>::create(id.clone(), owner.clone(), true, min_balance))
+		self.sandbox.execute_with(|| {
+			<AssetsOf<T::Runtime, I> as Create<AccountIdFor<T::Runtime>>>::create(
+				id.clone(),
+				owner.clone(),
+				true,
+				min_balance,
+			)
+		})
 	}
 
-	fn start_destroy(&mut self, asset: &AssetIdOf<T::Runtime>) -> Result<(), DispatchError> {
-		self.execute_with(|| <pallet_assets::Pallet::<T::Runtime, Instance1> as Destroy<AccountIdFor<T::Runtime>>>::start_destroy(asset.clone(), None))
+	fn start_destroy(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> Result<(), DispatchError> {
+		self.sandbox.execute_with(|| {
+			<AssetsOf<T::Runtime, I> as Destroy<AccountIdFor<T::Runtime>>>::start_destroy(
+				asset.clone(),
+				None,
+			)
+		})
 	}
 
 	fn set_metadata<Origin: Into<<RuntimeCall<T::Runtime> as Dispatchable>::RuntimeOrigin>>(
 		&mut self,
 		origin: Origin,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		name: Vec<u8>,
 		symbol: Vec<u8>,
 		decimals: u8,
 	) -> Result<(), DispatchError> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::set_metadata(
+		self.sandbox.execute_with(|| {
+			AssetsOf::<T::Runtime, I>::set_metadata(
 				origin.into(),
 				asset.clone().into(),
 				name,
@@ -158,74 +277,60 @@ where
 
 	fn mint_into(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		account: &AccountIdFor<T::Runtime>,
-		value: BalanceOf<T::Runtime>,
-	) -> Result<BalanceOf<T::Runtime>, DispatchError> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::mint_into(asset.clone(), account, value)
-		})
+		value: BalanceOf<T::Runtime, I>,
+	) -> Result<BalanceOf<T::Runtime, I>, DispatchError> {
+		self.sandbox
+			.execute_with(|| AssetsOf::<T::Runtime, I>::mint_into(asset.clone(), account, value))
 	}
 
 	fn approve(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
 		delegate: &AccountIdFor<T::Runtime>,
-		amount: BalanceOf<T::Runtime>,
+		amount: BalanceOf<T::Runtime, I>,
 	) -> Result<(), DispatchError> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::approve(
-				asset.clone(),
-				owner,
-				delegate,
-				amount,
-			)
+		self.sandbox.execute_with(|| {
+			AssetsOf::<T::Runtime, I>::approve(asset.clone(), owner, delegate, amount)
 		})
 	}
 
 	fn balance_of(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
-	) -> BalanceOf<T::Runtime> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::balance(asset.clone(), owner)
-		})
+	) -> BalanceOf<T::Runtime, I> {
+		self.sandbox
+			.execute_with(|| AssetsOf::<T::Runtime, I>::balance(asset.clone(), owner))
 	}
 
-	fn total_supply(&mut self, asset: &AssetIdOf<T::Runtime>) -> BalanceOf<T::Runtime> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::total_supply(asset.clone())
-		})
+	fn total_supply(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> BalanceOf<T::Runtime, I> {
+		self.sandbox
+			.execute_with(|| AssetsOf::<T::Runtime, I>::total_supply(asset.clone()))
 	}
 
 	fn allowance(
 		&mut self,
-		asset: &AssetIdOf<T::Runtime>,
+		asset: &AssetIdOf<T::Runtime, I>,
 		owner: &AccountIdFor<T::Runtime>,
 		delegate: &AccountIdFor<T::Runtime>,
-	) -> BalanceOf<T::Runtime> {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::allowance(
-				asset.clone(),
-				owner,
-				delegate,
-			)
-		})
+	) -> BalanceOf<T::Runtime, I> {
+		self.sandbox
+			.execute_with(|| AssetsOf::<T::Runtime, I>::allowance(asset.clone(), owner, delegate))
 	}
 
-	fn asset_exists(&mut self, asset: &AssetIdOf<T::Runtime>) -> bool {
-		self.execute_with(|| {
-			pallet_assets::Pallet::<T::Runtime, Instance1>::asset_exists(asset.clone())
-		})
+	fn asset_exists(&mut self, asset: &AssetIdOf<T::Runtime, I>) -> bool {
+		self.sandbox
+			.execute_with(|| AssetsOf::<T::Runtime, I>::asset_exists(asset.clone()))
 	}
 }
 
 #[cfg(test)]
 mod test {
 	use super::*;
-	use crate::DefaultSandbox;
+	use crate::{DefaultSandbox, ParachainSandbox};
 	#[test]
 	fn api_works() {
 		let mut sandbox = DefaultSandbox::default();
@@ -239,4 +344,19 @@ mod test {
 
 		assert!(sandbox.asset_exists(&token));
 	}
+
+	#[test]
+	fn instances_are_independent() {
+		let mut sandbox = ParachainSandbox::default();
+		let token = 1;
+		let actor = ParachainSandbox::default_actor();
+
+		let mut foreign_assets = InstanceAssets::<_, pallet_assets::Instance2>::new(&mut sandbox);
+		foreign_assets.create(&token, &actor, 1).unwrap();
+		foreign_assets.mint_into(&token, &actor, 100).unwrap();
+		assert_eq!(foreign_assets.total_supply(&token), 100);
+
+		assert!(!sandbox.asset_exists(&token));
+		assert_eq!(sandbox.balance_of(&token, &actor), 0);
+	}
 }
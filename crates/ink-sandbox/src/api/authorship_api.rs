@@ -0,0 +1,66 @@
+use frame_support::{
+	storage::{storage_prefix, unhashed},
+	traits::PalletInfoAccess,
+};
+
+use crate::{AccountIdFor, Sandbox};
+
+type AuthorshipOf<T> = pallet_authorship::Pallet<T>;
+
+/// Authorship API for the sandbox, controlling the block author seen by the runtime.
+///
+/// Contracts reading the block author (e.g. through a chain extension) can be tested with a
+/// controlled author. See [`crate::api::prelude::SystemAPI::deposit_digest_item`] for injecting
+/// the consensus digests the author is usually derived from.
+pub trait AuthorshipAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_authorship::Config,
+{
+	/// Returns the author of the current block, if known.
+	fn block_author(&mut self) -> Option<AccountIdFor<T::Runtime>>;
+
+	/// Sets the author of the current block.
+	///
+	/// # Arguments
+	/// * `author` - The new block author.
+	fn set_block_author(&mut self, author: &AccountIdFor<T::Runtime>);
+}
+
+impl<T> AuthorshipAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_authorship::Config,
+{
+	fn block_author(&mut self) -> Option<AccountIdFor<T::Runtime>> {
+		self.execute_with(AuthorshipOf::<T::Runtime>::author)
+	}
+
+	fn set_block_author(&mut self, author: &AccountIdFor<T::Runtime>) {
+		// The author is cached in the (private) `Author` storage value of the pallet.
+		self.execute_with(|| {
+			let key = storage_prefix(
+				<AuthorshipOf<T::Runtime> as PalletInfoAccess>::name().as_bytes(),
+				b"Author",
+			);
+			unhashed::put(&key, author)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::AccountId32;
+
+	use crate::{api::prelude::*, ParachainSandbox};
+
+	#[test]
+	fn block_author_can_be_set() {
+		let mut sandbox = ParachainSandbox::default();
+		let author = AccountId32::new([7u8; 32]);
+		assert_eq!(sandbox.block_author(), None);
+
+		sandbox.set_block_author(&author);
+		assert_eq!(sandbox.block_author(), Some(author));
+	}
+}
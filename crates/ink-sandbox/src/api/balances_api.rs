@@ -1,9 +1,28 @@
-use frame_support::{sp_runtime::DispatchError, traits::fungible::Mutate};
+use frame_support::{
+	sp_runtime::{traits::Saturating, DispatchError},
+	traits::{fungible::Mutate, Get, StoredMap},
+};
 
 use crate::{AccountIdFor, Sandbox};
 
 type BalanceOf<R> = <R as pallet_balances::Config>::Balance;
 
+/// The balances and reference counters of an account, e.g. of a contract, to assert that a
+/// contract paying out funds never kills its own account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractAccountInfo<Balance> {
+	/// The free balance, including the frozen part.
+	pub free: Balance,
+	/// The reserved balance (including the balance on hold, e.g. the storage deposit).
+	pub reserved: Balance,
+	/// The part of the free balance which can't be spent.
+	pub frozen: Balance,
+	/// The number of modules relying on the account to exist.
+	pub consumers: u32,
+	/// The number of modules keeping the account alive. The account is dead once it reaches 0.
+	pub providers: u32,
+}
+
 /// Balances API for the sandbox.
 pub trait BalanceAPI<T: Sandbox>
 where
@@ -28,6 +47,30 @@ where
 	///
 	/// * `address` - The address of the account to query.
 	fn free_balance(&mut self, address: &AccountIdFor<T::Runtime>) -> BalanceOf<T::Runtime>;
+
+	/// Return the balances and reference counters of an account, e.g. of a contract.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the account to query.
+	fn contract_account_info(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> ContractAccountInfo<BalanceOf<T::Runtime>>;
+
+	/// Return the existential deposit: the minimum free balance keeping an account alive.
+	fn existential_deposit(&mut self) -> BalanceOf<T::Runtime>;
+
+	/// Lower the free balance of an account down to the existential deposit, e.g. for testing
+	/// that a contract paying out funds doesn't kill its own account. Return the balance removed.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the account to drain.
+	fn drain_to_existential_deposit(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> Result<BalanceOf<T::Runtime>, DispatchError>;
 }
 
 impl<T> BalanceAPI<T> for T
@@ -46,6 +89,44 @@ where
 	fn free_balance(&mut self, address: &AccountIdFor<T::Runtime>) -> BalanceOf<T::Runtime> {
 		self.execute_with(|| pallet_balances::Pallet::<T::Runtime>::free_balance(address))
 	}
+
+	fn contract_account_info(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> ContractAccountInfo<BalanceOf<T::Runtime>> {
+		self.execute_with(|| {
+			let data =
+				<<T::Runtime as pallet_balances::Config>::AccountStore as StoredMap<_, _>>::get(
+					address,
+				);
+			ContractAccountInfo {
+				free: data.free,
+				reserved: data.reserved,
+				frozen: data.frozen,
+				consumers: frame_system::Pallet::<T::Runtime>::consumers(address),
+				providers: frame_system::Pallet::<T::Runtime>::providers(address),
+			}
+		})
+	}
+
+	fn existential_deposit(&mut self) -> BalanceOf<T::Runtime> {
+		self.execute_with(<T::Runtime as pallet_balances::Config>::ExistentialDeposit::get)
+	}
+
+	fn drain_to_existential_deposit(
+		&mut self,
+		address: &AccountIdFor<T::Runtime>,
+	) -> Result<BalanceOf<T::Runtime>, DispatchError> {
+		let existential_deposit = self.existential_deposit();
+		self.execute_with(|| {
+			let free = pallet_balances::Pallet::<T::Runtime>::free_balance(address);
+			if free <= existential_deposit {
+				return Ok(Default::default());
+			}
+			pallet_balances::Pallet::<T::Runtime>::set_balance(address, existential_deposit);
+			Ok(free.saturating_sub(existential_deposit))
+		})
+	}
 }
 
 #[cfg(test)]
@@ -61,4 +142,22 @@ mod test {
 
 		assert_eq!(sandbox.free_balance(&DefaultSandbox::default_actor()), balance + 100);
 	}
+
+	#[test]
+	fn accounts_can_be_drained_to_the_existential_deposit() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let info = sandbox.contract_account_info(&actor);
+		assert_eq!(info.free, sandbox.free_balance(&actor));
+		assert_eq!(info.providers, 1);
+
+		let existential_deposit = sandbox.existential_deposit();
+		assert_eq!(
+			sandbox.drain_to_existential_deposit(&actor),
+			Ok(info.free - existential_deposit)
+		);
+		let drained = sandbox.contract_account_info(&actor);
+		assert_eq!(drained, ContractAccountInfo { free: existential_deposit, ..info });
+		assert_eq!(sandbox.drain_to_existential_deposit(&actor), Ok(0));
+	}
 }
@@ -1,14 +1,27 @@
 use std::ops::Not;
 
-use frame_support::{traits::fungible::Inspect, weights::Weight};
-use frame_system::Config as SysConfig;
+use frame_support::{
+	sp_runtime::{
+		traits::{StaticLookup, Zero},
+		DispatchError,
+	},
+	storage::storage_prefix,
+	traits::{fungible::Inspect, PalletInfoAccess},
+	weights::Weight,
+	Blake2_128Concat, StorageHasher, Twox64Concat,
+};
+use frame_system::{Config as SysConfig, RawOrigin};
 use pallet_contracts::{
 	Code, CodeUploadResult, CollectEvents, ContractInstantiateResult, DebugInfo, Determinism,
+	StorageDeposit,
 };
-use scale::Decode as _;
+use scale::{Decode, Encode};
 
 use crate::{
-	AccountIdFor, ContractExecResultFor, ContractInstantiateResultFor, EventRecordOf, Sandbox,
+	api::storage_api::StorageAPI, call_chain, code_cache, delegate, delegate::DelegateContext,
+	determinism::DeterminismAudit, failures, failures::FailureMode, host_fns,
+	host_fns::CallMetrics, runtime_calls, AccountIdFor, ContractExecResultFor,
+	ContractInstantiateResultFor, EventRecordOf, Sandbox,
 };
 
 type BalanceOf<R> =
@@ -101,6 +114,158 @@ pub trait ContractAPI {
 		storage_deposit_limit: Option<BalanceOf<Self::T>>,
 		determinism: Determinism,
 	) -> ContractExecResultFor<Self::T>;
+
+	/// Return the value stored under `key` in the storage of a contract, if any.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the contract.
+	/// * `key` - The (unhashed) storage key, as used by the contract.
+	fn contract_storage_get(
+		&mut self,
+		address: &AccountIdFor<Self::T>,
+		key: &[u8],
+	) -> Option<Vec<u8>>;
+
+	/// Return all the entries of the storage (child trie) of a contract, in the order of their
+	/// hashed keys. The keys are returned unhashed, as used by the contract.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the contract.
+	fn contract_storage_iter(&mut self, address: &AccountIdFor<Self::T>)
+		-> Vec<(Vec<u8>, Vec<u8>)>;
+
+	/// Replace all the entries of the storage (child trie) of a contract with `entries`, the keys
+	/// being unhashed, as returned by [`ContractAPI::contract_storage_iter`]. Returns `false` if
+	/// there is no contract at `address`.
+	///
+	/// The storage deposit of the contract isn't adjusted to the new entries.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the contract.
+	/// * `entries` - The new entries of the storage.
+	fn replace_contract_storage(
+		&mut self,
+		address: &AccountIdFor<Self::T>,
+		entries: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> bool;
+
+	/// Interface for `remove_code` contract call. Returns the upload deposit released to the owner
+	/// of the code.
+	///
+	/// Fails with `CodeNotFound` if no code is stored under `code_hash`, and with `CodeInUse` if
+	/// contracts are still instantiated from it.
+	///
+	/// # Arguments
+	///
+	/// * `code_hash` - The hash of the code to remove.
+	/// * `origin` - The owner of the code, i.e. its uploader.
+	fn remove_code(
+		&mut self,
+		code_hash: <Self::T as SysConfig>::Hash,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<BalanceOf<Self::T>, DispatchError>;
+
+	/// Return the number of contracts instantiated from the code stored under `code_hash`, or
+	/// `None` if no code is stored under it.
+	///
+	/// # Arguments
+	///
+	/// * `code_hash` - The hash of the code.
+	fn code_refcount(&mut self, code_hash: &<Self::T as SysConfig>::Hash) -> Option<u64>;
+
+	/// Interface for the root `set_code` contract call, replacing the code of a contract while
+	/// keeping its storage and balance.
+	///
+	/// # Arguments
+	///
+	/// * `address` - The address of the contract.
+	/// * `code_hash` - The hash of the new code, which must be uploaded already.
+	fn set_code(
+		&mut self,
+		address: AccountIdFor<Self::T>,
+		code_hash: <Self::T as SysConfig>::Hash,
+	) -> Result<(), DispatchError>;
+
+	/// Return the runtime calls dispatched by contracts via `call_runtime` since the last time
+	/// they were taken, in the order of dispatch.
+	///
+	/// The calls are recorded by the call filter of the contracts pallet (see
+	/// [`crate::runtime_calls::RecordRuntimeCalls`]), so calls rejected by the runtime are
+	/// recorded as well.
+	fn take_runtime_calls(&mut self) -> Vec<<Self::T as SysConfig>::RuntimeCall>;
+
+	/// Inject a failure into the execution of contracts, until the failures are cleared.
+	///
+	/// # Arguments
+	///
+	/// * `failure` - The failure to inject.
+	fn inject_failure(&mut self, failure: FailureMode);
+
+	/// Clear the failures injected into the execution of contracts.
+	fn clear_failures(&mut self);
+
+	/// Return the metrics of the host functions called by contracts since the last time they
+	/// were taken. See [`crate::host_fns`].
+	fn take_call_metrics(&mut self) -> CallMetrics;
+
+	/// Deploy a chain of `depth` contracts forwarding calls down to `target`, and return the
+	/// address of its entry (`target` itself if `depth` is 0). See [`crate::call_chain`].
+	///
+	/// # Arguments
+	///
+	/// * `target` - The address of the contract called at the end of the chain.
+	/// * `depth` - The number of forwarding contracts.
+	/// * `origin` - The deployer of the forwarding contracts.
+	fn deploy_call_chain(
+		&mut self,
+		target: AccountIdFor<Self::T>,
+		depth: u32,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<AccountIdFor<Self::T>, DispatchError>;
+
+	/// Deploy a proxy delegating every call to the library stored under `library`, and return its
+	/// address. See [`crate::delegate`].
+	///
+	/// The library only needs to be uploaded. It isn't a dependency of the proxy, so it can be
+	/// removed while the proxy is deployed, making the proxy trap.
+	///
+	/// # Arguments
+	///
+	/// * `library` - The code hash of the library.
+	/// * `origin` - The deployer of the proxy.
+	fn deploy_delegate_proxy(
+		&mut self,
+		library: <Self::T as SysConfig>::Hash,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<AccountIdFor<Self::T>, DispatchError>;
+
+	/// Return the context recorded by the probe library (see [`crate::delegate::probe_code`]) in
+	/// the storage of `contract`, or `None` if it never ran in the storage of `contract`.
+	///
+	/// # Arguments
+	///
+	/// * `contract` - The address of the contract delegating to the probe library.
+	fn delegate_context(
+		&mut self,
+		contract: &AccountIdFor<Self::T>,
+	) -> Option<DelegateContext<AccountIdFor<Self::T>>>;
+
+	/// Audit whether `contract_bytes` can be uploaded with `Determinism::Enforced`, e.g. to be
+	/// delegated to, telling non-deterministic code apart from invalid code. See
+	/// [`crate::determinism`]. Nothing is uploaded.
+	///
+	/// # Arguments
+	///
+	/// * `contract_bytes` - The contract code.
+	/// * `origin` - The account uploading the code (paying for the storage deposit).
+	fn audit_determinism(
+		&mut self,
+		contract_bytes: Vec<u8>,
+		origin: AccountIdFor<Self::T>,
+	) -> DeterminismAudit;
 }
 
 impl<T> ContractAPI for T
@@ -125,17 +290,50 @@ where
 			 storage_deposit_limit={storage_deposit_limit:?}"
 		);
 		self.execute_with(|| {
-			pallet_contracts::Pallet::<Self::T>::bare_instantiate(
+			let code_hash = code_cache::code_hash::<Self::T>(&contract_bytes);
+			// With the code cached, it is stored right away and the contract instantiated from it,
+			// like the contracts pallet does after validating the code.
+			let (code, upload_deposit) = match code_cache::upload::<Self::T>(
+				&code_hash,
+				&origin,
+				storage_deposit_limit,
+				Determinism::Enforced,
+			) {
+				Some(Ok(upload)) => (Code::Existing(code_hash), Some(upload.deposit)),
+				Some(Err(error)) =>
+					return ContractInstantiateResult {
+						gas_consumed: Weight::zero(),
+						gas_required: Weight::zero(),
+						storage_deposit: Default::default(),
+						debug_message: vec![],
+						result: Err(error.into()),
+						events: None,
+					},
+				None => (Code::Upload(contract_bytes), None),
+			};
+
+			let mut result = pallet_contracts::Pallet::<Self::T>::bare_instantiate(
 				origin,
 				value,
 				gas_limit,
-				storage_deposit_limit,
-				Code::Upload(contract_bytes),
+				storage_deposit_limit
+					.map(|limit| limit.saturating_sub(upload_deposit.unwrap_or_default())),
+				code,
 				data,
 				salt,
 				DebugInfo::UnsafeDebug,
 				CollectEvents::UnsafeCollect,
-			)
+			);
+			match upload_deposit {
+				Some(deposit) =>
+					result.storage_deposit =
+						StorageDeposit::Charge(deposit).saturating_add(&result.storage_deposit),
+				None if result.result.is_ok() =>
+					code_cache::store::<Self::T>(&code_hash, Determinism::Enforced),
+				None => {},
+			}
+			host_fns::record_proof_size(result.gas_consumed);
+			result
 		})
 	}
 
@@ -156,7 +354,7 @@ where
 		);
 		let mut code_hash = &code_hash[..];
 		self.execute_with(|| {
-			pallet_contracts::Pallet::<Self::T>::bare_instantiate(
+			let result = pallet_contracts::Pallet::<Self::T>::bare_instantiate(
 				origin,
 				value,
 				gas_limit,
@@ -169,7 +367,9 @@ where
 				salt,
 				DebugInfo::UnsafeDebug,
 				CollectEvents::UnsafeCollect,
-			)
+			);
+			host_fns::record_proof_size(result.gas_consumed);
+			result
 		})
 	}
 
@@ -181,12 +381,26 @@ where
 		determinism: Determinism,
 	) -> CodeUploadResult<<Self::T as frame_system::Config>::Hash, BalanceOf<Self::T>> {
 		self.execute_with(|| {
-			pallet_contracts::Pallet::<Self::T>::bare_upload_code(
+			let code_hash = code_cache::code_hash::<Self::T>(&contract_bytes);
+			if let Some(result) = code_cache::upload::<Self::T>(
+				&code_hash,
+				&origin,
+				storage_deposit_limit,
+				determinism,
+			) {
+				return result.map_err(Into::into);
+			}
+
+			let result = pallet_contracts::Pallet::<Self::T>::bare_upload_code(
 				origin,
 				contract_bytes,
 				storage_deposit_limit,
 				determinism,
-			)
+			);
+			if result.is_ok() {
+				code_cache::store::<Self::T>(&code_hash, determinism);
+			}
+			result
 		})
 	}
 
@@ -201,7 +415,7 @@ where
 		determinism: Determinism,
 	) -> ContractExecResultFor<Self::T> {
 		self.execute_with(|| {
-			pallet_contracts::Pallet::<Self::T>::bare_call(
+			let result = pallet_contracts::Pallet::<Self::T>::bare_call(
 				origin,
 				address,
 				value,
@@ -211,9 +425,215 @@ where
 				DebugInfo::UnsafeDebug,
 				CollectEvents::UnsafeCollect,
 				determinism,
+			);
+			host_fns::record_proof_size(result.gas_consumed);
+			result
+		})
+	}
+
+	fn contract_storage_get(
+		&mut self,
+		address: &AccountIdFor<Self::T>,
+		key: &[u8],
+	) -> Option<Vec<u8>> {
+		self.execute_with(|| {
+			pallet_contracts::Pallet::<Self::T>::get_storage(address.clone(), key.to_vec())
+				.ok()
+				.flatten()
+		})
+	}
+
+	fn contract_storage_iter(
+		&mut self,
+		address: &AccountIdFor<Self::T>,
+	) -> Vec<(Vec<u8>, Vec<u8>)> {
+		let Some(trie_id) = trie_id(self, address) else {
+			return vec![];
+		};
+
+		// Contract storage keys are hashed with `Blake2_128Concat`.
+		self.child_storage_pairs(&trie_id, &[], None, None)
+			.into_iter()
+			.filter_map(|(key, value)| Some((key.get(16..)?.to_vec(), value)))
+			.collect()
+	}
+
+	fn replace_contract_storage(
+		&mut self,
+		address: &AccountIdFor<Self::T>,
+		entries: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> bool {
+		let Some(trie_id) = trie_id(self, address) else {
+			return false;
+		};
+
+		let stale = self.child_storage_pairs(&trie_id, &[], None, None);
+		self.execute_with(|| {
+			for (key, _) in stale {
+				sp_io::default_child_storage::clear(&trie_id, &key);
+			}
+			for (key, value) in entries {
+				sp_io::default_child_storage::set(&trie_id, &Blake2_128Concat::hash(&key), &value);
+			}
+		});
+		true
+	}
+
+	fn remove_code(
+		&mut self,
+		code_hash: <Self::T as SysConfig>::Hash,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<BalanceOf<Self::T>, DispatchError> {
+		self.execute_with(|| {
+			let deposit = code_cache::code_info::<Self::T>(&code_hash)
+				.map(|info| info.deposit)
+				.unwrap_or_default();
+			pallet_contracts::Pallet::<Self::T>::remove_code(
+				RawOrigin::Signed(origin).into(),
+				code_hash,
+			)
+			.map_err(|e| e.error)?;
+			Ok(deposit)
+		})
+	}
+
+	fn code_refcount(&mut self, code_hash: &<Self::T as SysConfig>::Hash) -> Option<u64> {
+		self.execute_with(|| code_cache::code_info::<Self::T>(code_hash).map(|info| info.refcount))
+	}
+
+	fn set_code(
+		&mut self,
+		address: AccountIdFor<Self::T>,
+		code_hash: <Self::T as SysConfig>::Hash,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_contracts::Pallet::<Self::T>::set_code(
+				RawOrigin::Root.into(),
+				<Self::T as SysConfig>::Lookup::unlookup(address),
+				code_hash,
+			)
+		})
+	}
+
+	fn take_runtime_calls(&mut self) -> Vec<<Self::T as SysConfig>::RuntimeCall> {
+		self.execute_with(runtime_calls::take)
+			.into_iter()
+			.map(|call| {
+				<Self::T as SysConfig>::RuntimeCall::decode(&mut &call[..])
+					.expect("Recorded runtime calls are runtime calls")
+			})
+			.collect()
+	}
+
+	fn inject_failure(&mut self, failure: FailureMode) {
+		self.execute_with(|| failures::update(|failures| failures.push(failure)))
+	}
+
+	fn clear_failures(&mut self) {
+		self.execute_with(|| failures::update(Vec::clear))
+	}
+
+	fn take_call_metrics(&mut self) -> CallMetrics {
+		self.execute_with(host_fns::take)
+	}
+
+	fn deploy_call_chain(
+		&mut self,
+		target: AccountIdFor<Self::T>,
+		depth: u32,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<AccountIdFor<Self::T>, DispatchError> {
+		let code = call_chain::forwarder_code();
+		let gas_limit = self.configured_gas_limit();
+		(0..depth).try_fold(target, |callee, _| {
+			self.deploy_contract(
+				code.clone(),
+				Zero::zero(),
+				callee.encode(),
+				// Chains of different depths to the same target don't share their contracts.
+				depth.encode(),
+				origin.clone(),
+				gas_limit,
+				None,
 			)
+			.result
+			.map(|result| result.account_id)
+		})
+	}
+
+	fn deploy_delegate_proxy(
+		&mut self,
+		library: <Self::T as SysConfig>::Hash,
+		origin: AccountIdFor<Self::T>,
+	) -> Result<AccountIdFor<Self::T>, DispatchError> {
+		let gas_limit = self.configured_gas_limit();
+		self.deploy_contract(
+			delegate::proxy_code(),
+			Zero::zero(),
+			library.encode(),
+			// Proxies to different libraries don't share their address.
+			library.encode(),
+			origin,
+			gas_limit,
+			None,
+		)
+		.result
+		.map(|result| result.account_id)
+	}
+
+	fn delegate_context(
+		&mut self,
+		contract: &AccountIdFor<Self::T>,
+	) -> Option<DelegateContext<AccountIdFor<Self::T>>> {
+		let caller = self.contract_storage_get(contract, delegate::PROBE_CALLER_KEY)?;
+		let input = self.contract_storage_get(contract, delegate::PROBE_INPUT_KEY)?;
+		Some(DelegateContext {
+			caller: AccountIdFor::<Self::T>::decode(&mut &caller[..])
+				.expect("The probe records the caller as an account"),
+			input,
 		})
 	}
+
+	fn audit_determinism(
+		&mut self,
+		contract_bytes: Vec<u8>,
+		origin: AccountIdFor<Self::T>,
+	) -> DeterminismAudit {
+		let mut upload = |determinism| {
+			self.dry_run(|sandbox| {
+				sandbox.upload_contract(contract_bytes.clone(), origin.clone(), None, determinism)
+			})
+		};
+		match upload(Determinism::Enforced) {
+			Ok(_) => DeterminismAudit::Deterministic,
+			Err(error) => match upload(Determinism::Relaxed) {
+				Ok(_) => DeterminismAudit::NonDeterministic,
+				Err(_) => DeterminismAudit::Invalid(error),
+			},
+		}
+	}
+}
+
+/// Returns the id of the child trie storing the storage of the contract at `address`, if any.
+fn trie_id<T>(sandbox: &mut T, address: &AccountIdFor<T::Runtime>) -> Option<Vec<u8>>
+where
+	T: Sandbox,
+	T::Runtime: pallet_contracts::Config,
+{
+	// `ContractInfoOf` is private to the contracts pallet, so the trie id (the first field of the
+	// contract info) is read from the raw storage.
+	let info_key = [
+		storage_prefix(
+			<pallet_contracts::Pallet<T::Runtime> as PalletInfoAccess>::name().as_bytes(),
+			b"ContractInfoOf",
+		)
+		.as_slice(),
+		&Twox64Concat::hash(&address.encode()),
+	]
+	.concat();
+	sandbox
+		.read_raw_storage(&info_key)
+		.and_then(|info| Vec::<u8>::decode(&mut &info[..]).ok())
 }
 
 /// Converts bytes to a '\n'-split string, ignoring empty lines.
@@ -231,7 +651,10 @@ mod tests {
 	use pallet_contracts::Origin;
 
 	use super::*;
-	use crate::{api::prelude::*, DefaultSandbox, RuntimeEventOf, RuntimeOf};
+	use crate::{
+		api::prelude::*, macros::CALL_STACK_DEPTH, DefaultSandbox, RuntimeEventOf, RuntimeOf,
+		ScheduleParameters,
+	};
 
 	fn compile_module(contract_name: &str) -> Vec<u8> {
 		let path = [
@@ -262,6 +685,71 @@ mod tests {
 		assert_eq!(hash, result.unwrap().code_hash);
 	}
 
+	#[test]
+	fn cached_code_is_uploaded_to_other_sandboxes() {
+		let wasm_binary = compile_module("storage");
+		let upload = |sandbox: &mut DefaultSandbox| {
+			sandbox
+				.upload_contract(
+					wasm_binary.clone(),
+					DefaultSandbox::default_actor(),
+					None,
+					Determinism::Enforced,
+				)
+				.expect("Code should be uploaded")
+		};
+
+		let first = upload(&mut DefaultSandbox::default());
+		let mut sandbox = DefaultSandbox::default();
+		let second = upload(&mut sandbox);
+		assert_eq!(first.code_hash, second.code_hash);
+		assert_eq!(first.deposit, second.deposit);
+
+		let result = sandbox.instantiate_contract(
+			second.code_hash.encode(),
+			0,
+			vec![],
+			vec![],
+			DefaultSandbox::default_actor(),
+			DefaultSandbox::default_gas_limit(),
+			None,
+		);
+		assert!(result.result.is_ok());
+	}
+
+	#[test]
+	fn cached_code_is_validated_against_the_schedule() {
+		// The code may use up to 32 memory pages, which only the lenient schedule allows.
+		let wasm_binary = wat::parse_str(
+			r#"(module
+				(import "env" "memory" (memory 1 32))
+				(func (export "call"))
+				(func (export "deploy"))
+			)"#,
+		)
+		.unwrap();
+		let upload = |sandbox: &mut DefaultSandbox| {
+			sandbox
+				.upload_contract(
+					wasm_binary.clone(),
+					DefaultSandbox::default_actor(),
+					None,
+					Determinism::Enforced,
+				)
+				.map(|_| ())
+		};
+
+		let mut lenient = DefaultSandbox::default();
+		lenient.update_parameters(|parameters| {
+			parameters.schedule = ScheduleParameters::development_lenient()
+		});
+		assert!(upload(&mut lenient).is_ok());
+		assert_eq!(
+			upload(&mut DefaultSandbox::default()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::CodeRejected.into())
+		);
+	}
+
 	#[test]
 	fn can_deploy_contract() {
 		let mut sandbox = DefaultSandbox::default();
@@ -300,6 +788,173 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn can_inspect_contract_storage() {
+		let mut sandbox = DefaultSandbox::default();
+		let result = sandbox.deploy_contract(
+			compile_module("storage"),
+			0,
+			vec![],
+			vec![],
+			DefaultSandbox::default_actor(),
+			DefaultSandbox::default_gas_limit(),
+			None,
+		);
+		let contract_address = result.result.expect("Contract should be deployed").account_id;
+
+		assert_eq!(
+			sandbox.contract_storage_get(&contract_address, &[1, 2, 3, 4]),
+			Some(42u32.encode())
+		);
+		assert_eq!(sandbox.contract_storage_get(&contract_address, &[5, 6, 7, 8]), None);
+		assert_eq!(
+			sandbox.contract_storage_iter(&contract_address),
+			vec![(vec![1, 2, 3, 4], 42u32.encode())]
+		);
+	}
+
+	#[test]
+	fn host_fn_calls_are_counted() {
+		let mut sandbox = DefaultSandbox::default();
+		let result = sandbox.deploy_contract(
+			compile_module("storage"),
+			0,
+			vec![],
+			vec![],
+			DefaultSandbox::default_actor(),
+			DefaultSandbox::default_gas_limit(),
+			None,
+		);
+		let gas_consumed = result.gas_consumed;
+		let contract_address = result.result.expect("Contract should be deployed").account_id;
+		assert_eq!(
+			sandbox.take_call_metrics(),
+			CallMetrics {
+				storage_writes: 1,
+				proof_size_consumed: gas_consumed.proof_size(),
+				..Default::default()
+			}
+		);
+
+		let result = sandbox.call_contract(
+			contract_address,
+			0,
+			vec![],
+			DefaultSandbox::default_actor(),
+			DefaultSandbox::default_gas_limit(),
+			None,
+			Determinism::Enforced,
+		);
+		assert!(result.result.is_ok());
+		assert_eq!(
+			sandbox.take_call_metrics(),
+			CallMetrics {
+				proof_size_consumed: result.gas_consumed.proof_size(),
+				..Default::default()
+			}
+		);
+	}
+
+	#[test]
+	fn non_deterministic_code_is_audited() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let floats = wat::parse_str(
+			r#"
+			(module
+				(import "env" "memory" (memory 1 1))
+				(func (export "deploy"))
+				(func (export "call")
+					(drop (f32.add (f32.const 1) (f32.const 2)))
+				)
+			)
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			sandbox.audit_determinism(compile_module("dummy"), actor.clone()),
+			DeterminismAudit::Deterministic
+		);
+		assert_eq!(
+			sandbox.audit_determinism(floats.clone(), actor.clone()),
+			DeterminismAudit::NonDeterministic
+		);
+		assert!(matches!(
+			sandbox.audit_determinism(vec![0, 1, 2], actor.clone()),
+			DeterminismAudit::Invalid(_)
+		));
+		// Nothing was uploaded.
+		assert_eq!(
+			sandbox.code_refcount(
+				&<<RuntimeOf<DefaultSandbox> as frame_system::Config>::Hashing>::hash(&floats)
+			),
+			None
+		);
+	}
+
+	#[test]
+	fn can_set_code() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let old_code_hash = <<RuntimeOf<DefaultSandbox> as frame_system::Config>::Hashing>::hash(
+			&compile_module("storage"),
+		);
+		let contract = sandbox
+			.deploy_contract(
+				compile_module("storage"),
+				0,
+				vec![],
+				vec![],
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+			.result
+			.expect("Contract should be deployed")
+			.account_id;
+		let new_code_hash = sandbox
+			.upload_contract(compile_module("dummy"), actor, None, Determinism::Enforced)
+			.expect("Code should be uploaded")
+			.code_hash;
+
+		assert_eq!(sandbox.set_code(contract.clone(), new_code_hash), Ok(()));
+		assert_eq!(sandbox.code_refcount(&old_code_hash), Some(0));
+		assert_eq!(sandbox.code_refcount(&new_code_hash), Some(1));
+		// The storage is kept.
+		assert_eq!(sandbox.contract_storage_get(&contract, &[1, 2, 3, 4]), Some(42u32.encode()));
+	}
+
+	#[test]
+	fn can_replace_contract_storage() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let contract = sandbox
+			.deploy_contract(
+				compile_module("storage"),
+				0,
+				vec![],
+				vec![],
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+			.result
+			.expect("Contract should be deployed")
+			.account_id;
+		assert_eq!(
+			sandbox.contract_storage_iter(&contract),
+			vec![(vec![1, 2, 3, 4], 42u32.encode())]
+		);
+
+		assert!(sandbox.replace_contract_storage(&contract, vec![(b"key".to_vec(), vec![7])]));
+		assert_eq!(sandbox.contract_storage_get(&contract, &[1, 2, 3, 4]), None);
+		assert_eq!(sandbox.contract_storage_get(&contract, b"key"), Some(vec![7]));
+		assert_eq!(sandbox.contract_storage_iter(&contract), vec![(b"key".to_vec(), vec![7])]);
+
+		assert!(!sandbox.replace_contract_storage(&actor, vec![]));
+	}
+
 	#[test]
 	fn can_call_contract() {
 		let mut sandbox = DefaultSandbox::default();
@@ -355,4 +1010,290 @@ mod tests {
 			}),
 		);
 	}
+
+	#[test]
+	fn can_remove_unused_code() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let upload = sandbox
+			.upload_contract(compile_module("dummy"), actor.clone(), None, Determinism::Enforced)
+			.expect("Code should be uploaded");
+		assert_eq!(sandbox.code_refcount(&upload.code_hash), Some(0));
+
+		let instantiate = |sandbox: &mut DefaultSandbox, salt: Vec<u8>| {
+			sandbox.instantiate_contract(
+				upload.code_hash.encode(),
+				0,
+				vec![],
+				salt,
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+		};
+		assert!(instantiate(&mut sandbox, vec![1]).result.is_ok());
+		assert_eq!(sandbox.code_refcount(&upload.code_hash), Some(1));
+		assert_eq!(
+			sandbox.remove_code(upload.code_hash, actor.clone()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::CodeInUse.into())
+		);
+
+		let mut sandbox = DefaultSandbox::default();
+		let upload = sandbox
+			.upload_contract(compile_module("dummy"), actor.clone(), None, Determinism::Enforced)
+			.expect("Code should be uploaded");
+		let free_balance = sandbox.free_balance(&actor);
+		assert_eq!(sandbox.remove_code(upload.code_hash, actor.clone()), Ok(upload.deposit));
+		assert_eq!(sandbox.free_balance(&actor), free_balance + upload.deposit);
+		assert_eq!(sandbox.code_refcount(&upload.code_hash), None);
+		assert_eq!(
+			sandbox.remove_code(upload.code_hash, actor.clone()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::CodeNotFound.into())
+		);
+		assert_eq!(
+			instantiate(&mut sandbox, vec![2]).result.map(|_| ()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::CodeNotFound.into())
+		);
+	}
+
+	#[test]
+	fn code_length_is_limited() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.update_parameters(|parameters| parameters.max_code_len = 8);
+
+		assert_eq!(
+			sandbox
+				.upload_contract(
+					compile_module("dummy"),
+					DefaultSandbox::default_actor(),
+					None,
+					Determinism::Enforced,
+				)
+				.map(|_| ()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::CodeTooLarge.into())
+		);
+	}
+
+	#[test]
+	fn host_functions_can_be_made_to_fail() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let contract = sandbox
+			.deploy_contract(
+				compile_module("dummy"),
+				0,
+				vec![],
+				vec![],
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+			.result
+			.expect("Contract should be deployed")
+			.account_id;
+		let call = |sandbox: &mut DefaultSandbox| {
+			sandbox
+				.call_contract(
+					contract.clone(),
+					0,
+					vec![],
+					actor.clone(),
+					DefaultSandbox::default_gas_limit(),
+					None,
+					Determinism::Enforced,
+				)
+				.result
+				.map(|_| ())
+		};
+
+		sandbox.inject_failure(FailureMode::OutOfGasAtHostFn("seal_deposit_event"));
+		assert_eq!(
+			call(&mut sandbox),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::OutOfGas.into())
+		);
+
+		sandbox.clear_failures();
+		assert_eq!(call(&mut sandbox), Ok(()));
+	}
+
+	#[test]
+	fn executions_can_be_aborted() {
+		use crate::failures::AbortSignal;
+
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let contract = sandbox
+			.deploy_contract(
+				compile_module("dummy"),
+				0,
+				vec![],
+				vec![],
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+			.result
+			.expect("Contract should be deployed")
+			.account_id;
+		let signal = AbortSignal::default();
+		sandbox.register_extension(signal.clone());
+		let mut call = || {
+			sandbox
+				.call_contract(
+					contract.clone(),
+					0,
+					vec![],
+					actor.clone(),
+					DefaultSandbox::default_gas_limit(),
+					None,
+					Determinism::Enforced,
+				)
+				.result
+				.map(|_| ())
+		};
+
+		assert_eq!(call(), Ok(()));
+		// E.g. raised by the thread which gave up on the execution.
+		signal.raise();
+		assert_eq!(
+			call(),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::OutOfGas.into())
+		);
+	}
+
+	#[test]
+	fn call_chains_reach_the_maximum_depth() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let target = sandbox
+			.deploy_contract(
+				compile_module("dummy"),
+				0,
+				vec![],
+				vec![],
+				actor.clone(),
+				DefaultSandbox::default_gas_limit(),
+				None,
+			)
+			.result
+			.expect("Contract should be deployed")
+			.account_id;
+		let call_at_depth = |sandbox: &mut DefaultSandbox, depth: usize| {
+			let entry = sandbox
+				.deploy_call_chain(target.clone(), depth as u32, actor.clone())
+				.expect("Call chain should be deployed");
+			sandbox
+				.call_contract(
+					entry,
+					0,
+					vec![],
+					actor.clone(),
+					DefaultSandbox::default_gas_limit(),
+					None,
+					Determinism::Enforced,
+				)
+				.result
+				.map(|result| result.data)
+		};
+
+		assert_eq!(call_at_depth(&mut sandbox, CALL_STACK_DEPTH), Ok(vec![0; 4]));
+		// The last forwarder fails to call the target, and the forwarders trap in turn.
+		assert_eq!(
+			call_at_depth(&mut sandbox, CALL_STACK_DEPTH + 1),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::ContractTrapped.into())
+		);
+	}
+
+	#[test]
+	fn delegate_calls_run_in_the_context_of_the_caller() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let library = sandbox
+			.upload_contract(delegate::probe_code(), actor.clone(), None, Determinism::Enforced)
+			.expect("Library should be uploaded");
+		let proxy = sandbox
+			.deploy_delegate_proxy(library.code_hash, actor.clone())
+			.expect("Proxy should be deployed");
+		let call = |sandbox: &mut DefaultSandbox,
+		            contract: &AccountIdFor<RuntimeOf<DefaultSandbox>>| {
+			sandbox
+				.call_contract(
+					contract.clone(),
+					0,
+					vec![1, 2, 3],
+					actor.clone(),
+					DefaultSandbox::default_gas_limit(),
+					None,
+					Determinism::Enforced,
+				)
+				.result
+				.map(|result| result.data)
+		};
+		assert_eq!(sandbox.delegate_context(&proxy), None);
+
+		assert_eq!(call(&mut sandbox, &proxy), Ok(vec![1, 2, 3]));
+		assert_eq!(
+			sandbox.delegate_context(&proxy),
+			Some(DelegateContext { caller: actor.clone(), input: vec![1, 2, 3] })
+		);
+
+		// Called by another contract, the library sees that contract as its caller.
+		let entry = sandbox
+			.deploy_call_chain(proxy.clone(), 1, actor.clone())
+			.expect("Call chain should be deployed");
+		assert_eq!(call(&mut sandbox, &entry), Ok(vec![1, 2, 3]));
+		assert_eq!(
+			sandbox.delegate_context(&proxy).map(|context| context.caller),
+			Some(entry.clone())
+		);
+		// The library only writes to the storage of the proxy.
+		assert_eq!(sandbox.delegate_context(&entry), None);
+	}
+
+	#[test]
+	fn delegate_calls_to_missing_code_trap() {
+		let mut sandbox = DefaultSandbox::default();
+		let actor = DefaultSandbox::default_actor();
+		let library = sandbox
+			.upload_contract(delegate::probe_code(), actor.clone(), None, Determinism::Enforced)
+			.expect("Library should be uploaded");
+		let call = |sandbox: &mut DefaultSandbox,
+		            proxy: AccountIdFor<RuntimeOf<DefaultSandbox>>| {
+			sandbox
+				.call_contract(
+					proxy,
+					0,
+					vec![],
+					actor.clone(),
+					DefaultSandbox::default_gas_limit(),
+					None,
+					Determinism::Enforced,
+				)
+				.result
+				.map(|_| ())
+		};
+
+		// The library was never uploaded.
+		let missing =
+			<RuntimeOf<DefaultSandbox> as frame_system::Config>::Hashing::hash(b"missing");
+		let proxy = sandbox
+			.deploy_delegate_proxy(missing, actor.clone())
+			.expect("Proxy should be deployed");
+		assert_eq!(
+			call(&mut sandbox, proxy.clone()),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::ContractTrapped.into())
+		);
+		assert_eq!(sandbox.delegate_context(&proxy), None);
+
+		// The library was removed after the proxy was deployed.
+		let proxy = sandbox
+			.deploy_delegate_proxy(library.code_hash, actor.clone())
+			.expect("Proxy should be deployed");
+		assert_eq!(call(&mut sandbox, proxy.clone()), Ok(()));
+		assert_eq!(sandbox.remove_code(library.code_hash, actor.clone()), Ok(library.deposit));
+		assert_eq!(
+			call(&mut sandbox, proxy),
+			Err(pallet_contracts::Error::<RuntimeOf<DefaultSandbox>>::ContractTrapped.into())
+		);
+	}
 }
@@ -0,0 +1,226 @@
+use frame_support::{
+	sp_runtime::DispatchError,
+	storage::unhashed,
+	traits::fungibles::{Create, Inspect, Mutate},
+};
+use pallet_assets::Instance2;
+use scale::Encode;
+use sp_io::hashing::blake2_256;
+use xcm::latest::{Junction, Location};
+
+use crate::{api::assets_api::InstanceAssets, AccountIdFor, Sandbox};
+
+type AssetIdOf<T> = <AssetsOf<T> as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+type AssetsOf<T> = pallet_assets::Pallet<T, Instance2>;
+type BalanceOf<T> = <AssetsOf<T> as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Locations of well-known foreign assets, as seen from a parachain.
+pub mod locations {
+	use super::*;
+
+	/// Id of the AssetHub parachain.
+	pub const ASSET_HUB_PARA_ID: u32 = 1000;
+	/// Index of the assets pallet on AssetHub.
+	pub const ASSET_HUB_ASSETS_PALLET: u8 = 50;
+	/// Id of USDC in the assets pallet of AssetHub.
+	pub const USDC_ASSET_ID: u128 = 1337;
+
+	/// The location of AssetHub.
+	pub fn asset_hub() -> Location {
+		Location::new(1, [Junction::Parachain(ASSET_HUB_PARA_ID)])
+	}
+
+	/// The location of the relay chain token (e.g. DOT).
+	pub fn relay_token() -> Location {
+		Location::parent()
+	}
+
+	/// The location of USDC, reserved on AssetHub.
+	pub fn usdc() -> Location {
+		Location::new(
+			1,
+			[
+				Junction::Parachain(ASSET_HUB_PARA_ID),
+				Junction::PalletInstance(ASSET_HUB_ASSETS_PALLET),
+				Junction::GeneralIndex(USDC_ASSET_ID),
+			],
+		)
+	}
+}
+
+/// Foreign assets API for the sandbox, emulating cross-chain transfers of assets reserved on other
+/// chains (e.g. DOT or USDC on AssetHub).
+///
+/// A foreign asset is represented by an asset of the foreign assets instance of the assets pallet
+/// (`Instance2`), registered under its location.
+/// Incoming transfers mint the representation to the beneficiary, emitting the same `Issued` event
+/// as the XCM executor does, so contracts handling bridged assets can be tested without a
+/// multi-chain setup.
+pub trait ForeignAssetsAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_assets::Config<Instance2>,
+{
+	/// Creates the (sufficient) asset `id` representing the foreign asset under `location`.
+	///
+	/// # Arguments
+	/// * `id` - ID of the asset representing the foreign asset.
+	/// * `location` - The location of the foreign asset.
+	/// * `owner` - The owner of the created asset.
+	/// * `min_balance` - The asset amount one account need at least.
+	fn register_foreign_asset(
+		&mut self,
+		id: &AssetIdOf<T::Runtime>,
+		location: &Location,
+		owner: &AccountIdFor<T::Runtime>,
+		min_balance: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Returns the ID of the asset representing the foreign asset under `location`, if registered.
+	///
+	/// # Arguments
+	/// * `location` - The location of the foreign asset.
+	fn foreign_asset_id(&mut self, location: &Location) -> Option<AssetIdOf<T::Runtime>>;
+
+	/// Emulates an incoming reserve transfer of `amount` of the foreign asset under `location` to
+	/// `beneficiary`.
+	///
+	/// # Arguments
+	/// * `location` - The location of the foreign asset.
+	/// * `beneficiary` - The account credited with the transferred assets.
+	/// * `amount` - The number of tokens transferred.
+	fn receive_reserve_transfer(
+		&mut self,
+		location: &Location,
+		beneficiary: &AccountIdFor<T::Runtime>,
+		amount: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Emulates an incoming teleport of `amount` of the foreign asset under `location` to
+	/// `beneficiary`.
+	///
+	/// # Arguments
+	/// * `location` - The location of the foreign asset.
+	/// * `beneficiary` - The account credited with the teleported assets.
+	/// * `amount` - The number of tokens teleported.
+	fn receive_teleport(
+		&mut self,
+		location: &Location,
+		beneficiary: &AccountIdFor<T::Runtime>,
+		amount: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Returns a view of the foreign assets instance, to use the [`crate::api::prelude::AssetsAPI`]
+	/// on the representations of the foreign assets.
+	fn foreign_assets(&mut self) -> InstanceAssets<'_, T, Instance2>;
+}
+
+impl<T> ForeignAssetsAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_assets::Config<Instance2>,
+{
+	fn register_foreign_asset(
+		&mut self,
+		id: &AssetIdOf<T::Runtime>,
+		location: &Location,
+		owner: &AccountIdFor<T::Runtime>,
+		min_balance: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			<AssetsOf<T::Runtime> as Create<AccountIdFor<T::Runtime>>>::create(
+				id.clone(),
+				owner.clone(),
+				true,
+				min_balance,
+			)?;
+			unhashed::put(&registry_key(location), id);
+			Ok(())
+		})
+	}
+
+	fn foreign_asset_id(&mut self, location: &Location) -> Option<AssetIdOf<T::Runtime>> {
+		self.execute_with(|| unhashed::get(&registry_key(location)))
+	}
+
+	fn receive_reserve_transfer(
+		&mut self,
+		location: &Location,
+		beneficiary: &AccountIdFor<T::Runtime>,
+		amount: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		// The reserve keeps the real assets, the derivative is minted on the destination.
+		receive::<T>(self, location, beneficiary, amount)
+	}
+
+	fn receive_teleport(
+		&mut self,
+		location: &Location,
+		beneficiary: &AccountIdFor<T::Runtime>,
+		amount: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		// The assets are burnt on the source, and minted on the destination.
+		receive::<T>(self, location, beneficiary, amount)
+	}
+
+	fn foreign_assets(&mut self) -> InstanceAssets<'_, T, Instance2> {
+		InstanceAssets::new(self)
+	}
+}
+
+/// Mints the representation of the foreign asset under `location`, as the XCM executor deposits
+/// received assets.
+fn receive<T: Sandbox>(
+	sandbox: &mut T,
+	location: &Location,
+	beneficiary: &AccountIdFor<T::Runtime>,
+	amount: BalanceOf<T::Runtime>,
+) -> Result<(), DispatchError>
+where
+	T::Runtime: pallet_assets::Config<Instance2>,
+{
+	let id = sandbox
+		.foreign_asset_id(location)
+		.ok_or(DispatchError::Other("Foreign asset is not registered"))?;
+	sandbox.execute_with(|| {
+		<AssetsOf<T::Runtime> as Mutate<AccountIdFor<T::Runtime>>>::mint_into(
+			id,
+			beneficiary,
+			amount,
+		)
+		.map(|_| ())
+	})
+}
+
+/// The key under which the asset representing the foreign asset under `location` is registered.
+fn registry_key(location: &Location) -> [u8; 32] {
+	blake2_256(&(b":drink:foreign_asset:", location).encode())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox};
+
+	#[test]
+	fn reserve_transfers_mint_the_registered_asset() {
+		let mut sandbox = ParachainSandbox::default();
+		let actor = ParachainSandbox::default_actor();
+		let usdc = locations::usdc();
+
+		assert_eq!(
+			sandbox.receive_reserve_transfer(&usdc, &actor, 100),
+			Err(DispatchError::Other("Foreign asset is not registered"))
+		);
+
+		sandbox.register_foreign_asset(&7, &usdc, &actor, 1).unwrap();
+		assert_eq!(sandbox.foreign_asset_id(&usdc), Some(7));
+		assert_eq!(sandbox.foreign_asset_id(&locations::relay_token()), None);
+
+		sandbox.receive_reserve_transfer(&usdc, &actor, 100).unwrap();
+		sandbox.receive_teleport(&usdc, &actor, 50).unwrap();
+		assert_eq!(sandbox.foreign_assets().balance_of(&7, &actor), 150);
+		assert_eq!(sandbox.foreign_assets().total_supply(&7), 150);
+		assert!(!sandbox.asset_exists(&7));
+	}
+}
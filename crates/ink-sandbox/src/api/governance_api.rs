@@ -0,0 +1,422 @@
+use frame_support::{
+	sp_runtime::{
+		traits::{Hash, StaticLookup},
+		DispatchError,
+	},
+	traits::tokens::Pay,
+	weights::Weight,
+};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use pallet_collective::{
+	MemberCount, Members, ProposalCount, ProposalIndex, Proposals, Votes, Voting,
+};
+use pallet_treasury::{SpendIndex, Spends};
+
+use crate::{AccountIdFor, OriginFor, Sandbox};
+
+type AssetBalanceOf<T> = <<T as pallet_treasury::Config>::Paymaster as Pay>::Balance;
+type AssetKindOf<T> = <T as pallet_treasury::Config>::AssetKind;
+type BeneficiaryLookupOf<T> =
+	<<T as pallet_treasury::Config>::BeneficiaryLookup as StaticLookup>::Source;
+type HashOf<T> = <T as frame_system::Config>::Hash;
+type ProposalOf<T, I> = <T as pallet_collective::Config<I>>::Proposal;
+type VotesOf<T> = Votes<AccountIdFor<T>, BlockNumberFor<T>>;
+
+/// Governance API for the sandbox.
+///
+/// Built on top of a collective (e.g. Pop's council). Privileged calls can be dispatched with a
+/// collective origin by proposing them and getting them approved: treasury spends, or any call
+/// wrapped in a motion of the motion pallet, which dispatches it as root once a simple majority, a
+/// super majority or all of the council approve it. Both pallets are part of
+/// [`crate::ParachainSandbox`].
+pub trait GovernanceAPI<T: Sandbox, I: 'static = ()>
+where
+	T: Sandbox,
+	T::Runtime: pallet_collective::Config<I>,
+{
+	/// Sets the members (and optionally the prime member) of the collective, using the root
+	/// origin.
+	///
+	/// # Arguments
+	/// * `members` - The new members of the collective.
+	/// * `prime` - The prime member, whose vote acts as the default vote.
+	fn set_members(
+		&mut self,
+		members: Vec<AccountIdFor<T::Runtime>>,
+		prime: Option<AccountIdFor<T::Runtime>>,
+	) -> Result<(), DispatchError>;
+
+	/// Proposes `proposal` to the collective. Returns the index and the hash of the proposal.
+	///
+	/// Note: With a `threshold` lower than 2, the proposal is executed right away and the returned
+	/// index is not assigned to any proposal.
+	///
+	/// # Arguments
+	/// * `origin` - The proposing member.
+	/// * `threshold` - The number of approvals required to execute the proposal.
+	/// * `proposal` - The call to be dispatched with the collective origin.
+	fn propose(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		threshold: MemberCount,
+		proposal: ProposalOf<T::Runtime, I>,
+	) -> Result<(ProposalIndex, HashOf<T::Runtime>), DispatchError>;
+
+	/// Votes on an active proposal.
+	///
+	/// # Arguments
+	/// * `origin` - The voting member.
+	/// * `proposal_hash` - The hash of the proposal.
+	/// * `index` - The index of the proposal.
+	/// * `approve` - Whether to vote aye or nay.
+	fn vote(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_hash: HashOf<T::Runtime>,
+		index: ProposalIndex,
+		approve: bool,
+	) -> Result<(), DispatchError>;
+
+	/// Closes the voting on a proposal, executing it if approved.
+	///
+	/// # Arguments
+	/// * `origin` - The closing account.
+	/// * `proposal_hash` - The hash of the proposal.
+	/// * `index` - The index of the proposal.
+	fn close(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_hash: HashOf<T::Runtime>,
+		index: ProposalIndex,
+	) -> Result<(), DispatchError>;
+
+	/// Dispatches `proposal` with the origin of a single member of the collective.
+	///
+	/// # Arguments
+	/// * `origin` - The executing member.
+	/// * `proposal` - The call to be dispatched.
+	fn execute(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal: ProposalOf<T::Runtime, I>,
+	) -> Result<(), DispatchError>;
+
+	/// Proposes to the collective a treasury spend of `amount` of `asset_kind` to `beneficiary`.
+	/// Returns the index and the hash of the proposal.
+	///
+	/// Once the proposal is approved, the spend can be paid out with [`GovernanceAPI::payout`].
+	///
+	/// # Arguments
+	/// * `origin` - The proposing member.
+	/// * `threshold` - The number of approvals required to execute the proposal.
+	/// * `asset_kind` - The kind of asset to spend.
+	/// * `amount` - The amount to spend.
+	/// * `beneficiary` - The account receiving the spend.
+	fn propose_spend(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		threshold: MemberCount,
+		asset_kind: AssetKindOf<T::Runtime>,
+		amount: AssetBalanceOf<T::Runtime>,
+		beneficiary: BeneficiaryLookupOf<T::Runtime>,
+	) -> Result<(ProposalIndex, HashOf<T::Runtime>), DispatchError>
+	where
+		T::Runtime: pallet_treasury::Config,
+		ProposalOf<T::Runtime, I>: From<pallet_treasury::Call<T::Runtime>>;
+
+	/// Pays out an approved treasury spend.
+	///
+	/// # Arguments
+	/// * `origin` - The account triggering the payout.
+	/// * `index` - The index of the spend.
+	fn payout(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		index: SpendIndex,
+	) -> Result<(), DispatchError>
+	where
+		T::Runtime: pallet_treasury::Config;
+
+	/// Returns the indices of the approved treasury spends.
+	fn spends(&mut self) -> Vec<SpendIndex>
+	where
+		T::Runtime: pallet_treasury::Config;
+
+	/// Returns the members of the collective.
+	fn members(&mut self) -> Vec<AccountIdFor<T::Runtime>>;
+
+	/// Returns the hashes of the active proposals.
+	fn proposals(&mut self) -> Vec<HashOf<T::Runtime>>;
+
+	/// Returns the votes on an active proposal, if any.
+	///
+	/// # Arguments
+	/// * `proposal_hash` - The hash of the proposal.
+	fn voting(&mut self, proposal_hash: &HashOf<T::Runtime>) -> Option<VotesOf<T::Runtime>>;
+}
+
+impl<T, I> GovernanceAPI<T, I> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_collective::Config<I>,
+	I: 'static,
+{
+	fn set_members(
+		&mut self,
+		members: Vec<AccountIdFor<T::Runtime>>,
+		prime: Option<AccountIdFor<T::Runtime>>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			let old_count = Members::<T::Runtime, I>::get().len() as MemberCount;
+			pallet_collective::Pallet::<T::Runtime, I>::set_members(
+				RawOrigin::Root.into(),
+				members,
+				prime,
+				old_count,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn propose(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		threshold: MemberCount,
+		proposal: ProposalOf<T::Runtime, I>,
+	) -> Result<(ProposalIndex, HashOf<T::Runtime>), DispatchError> {
+		self.execute_with(|| {
+			let index = ProposalCount::<T::Runtime, I>::get();
+			let hash = <T::Runtime as frame_system::Config>::Hashing::hash_of(&proposal);
+			pallet_collective::Pallet::<T::Runtime, I>::propose(
+				origin.into(),
+				threshold,
+				Box::new(proposal),
+				u32::MAX,
+			)
+			.map(|_| (index, hash))
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn vote(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_hash: HashOf<T::Runtime>,
+		index: ProposalIndex,
+		approve: bool,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_collective::Pallet::<T::Runtime, I>::vote(
+				origin.into(),
+				proposal_hash,
+				index,
+				approve,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn close(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_hash: HashOf<T::Runtime>,
+		index: ProposalIndex,
+	) -> Result<(), DispatchError> {
+		// The bounds only protect against underestimating the proposal; in the sandbox we can
+		// always afford the maximum.
+		self.execute_with(|| {
+			pallet_collective::Pallet::<T::Runtime, I>::close(
+				origin.into(),
+				proposal_hash,
+				index,
+				Weight::MAX,
+				u32::MAX,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn execute(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal: ProposalOf<T::Runtime, I>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_collective::Pallet::<T::Runtime, I>::execute(
+				origin.into(),
+				Box::new(proposal),
+				u32::MAX,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn propose_spend(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		threshold: MemberCount,
+		asset_kind: AssetKindOf<T::Runtime>,
+		amount: AssetBalanceOf<T::Runtime>,
+		beneficiary: BeneficiaryLookupOf<T::Runtime>,
+	) -> Result<(ProposalIndex, HashOf<T::Runtime>), DispatchError>
+	where
+		T::Runtime: pallet_treasury::Config,
+		ProposalOf<T::Runtime, I>: From<pallet_treasury::Call<T::Runtime>>,
+	{
+		let spend = pallet_treasury::Call::<T::Runtime>::spend {
+			asset_kind: Box::new(asset_kind),
+			amount,
+			beneficiary: Box::new(beneficiary),
+			valid_from: None,
+		};
+		<Self as GovernanceAPI<T, I>>::propose(self, origin, threshold, spend.into())
+	}
+
+	fn payout(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		index: SpendIndex,
+	) -> Result<(), DispatchError>
+	where
+		T::Runtime: pallet_treasury::Config,
+	{
+		self.execute_with(|| {
+			pallet_treasury::Pallet::<T::Runtime>::payout(origin.into(), index)
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn spends(&mut self) -> Vec<SpendIndex>
+	where
+		T::Runtime: pallet_treasury::Config,
+	{
+		self.execute_with(|| Spends::<T::Runtime>::iter_keys().collect())
+	}
+
+	fn members(&mut self) -> Vec<AccountIdFor<T::Runtime>> {
+		self.execute_with(Members::<T::Runtime, I>::get)
+	}
+
+	fn proposals(&mut self) -> Vec<HashOf<T::Runtime>> {
+		self.execute_with(|| Proposals::<T::Runtime, I>::get().into_inner())
+	}
+
+	fn voting(&mut self, proposal_hash: &HashOf<T::Runtime>) -> Option<VotesOf<T::Runtime>> {
+		self.execute_with(|| Voting::<T::Runtime, I>::get(proposal_hash))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pallet_contracts::test_utils::{ALICE, BOB, CHARLIE, DJANGO};
+
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox, RuntimeCall, RuntimeEventOf, RuntimeOf};
+
+	fn remark() -> RuntimeCall<RuntimeOf<ParachainSandbox>> {
+		RuntimeCall::<RuntimeOf<ParachainSandbox>>::System(frame_system::Call::remark_with_event {
+			remark: vec![42],
+		})
+	}
+
+	#[test]
+	fn set_members_works() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+
+		sandbox.set_members(vec![BOB, ALICE], None)?;
+		assert_eq!(sandbox.members(), vec![ALICE, BOB]);
+		Ok(())
+	}
+
+	#[test]
+	fn approved_proposal_is_executed() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.set_members(vec![ALICE, BOB, CHARLIE], None)?;
+
+		let (index, hash) = sandbox.propose(Some(ALICE), 2, remark())?;
+		assert_eq!(sandbox.proposals(), vec![hash]);
+
+		sandbox.vote(Some(BOB), hash, index, true)?;
+		sandbox.vote(Some(CHARLIE), hash, index, true)?;
+		let votes = sandbox.voting(&hash).expect("Proposal should be active");
+		assert_eq!(votes.ayes, vec![BOB, CHARLIE]);
+
+		sandbox.reset_events();
+		sandbox.close(Some(ALICE), hash, index)?;
+		assert!(sandbox.proposals().is_empty());
+		assert!(sandbox.events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEventOf::<ParachainSandbox>::Council(pallet_collective::Event::Executed {
+				result: Ok(()),
+				..
+			})
+		)));
+		Ok(())
+	}
+
+	#[test]
+	fn council_can_approve_treasury_spends() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.set_members(vec![ALICE, BOB, CHARLIE], None)?;
+		let treasury = sandbox
+			.execute_with(pallet_treasury::Pallet::<RuntimeOf<ParachainSandbox>>::account_id);
+		BalanceAPI::mint_into(&mut sandbox, &treasury, 1_000).unwrap();
+		let balance = sandbox.free_balance(&DJANGO);
+
+		let (index, hash) = sandbox.propose_spend(Some(ALICE), 2, (), 100, DJANGO)?;
+		sandbox.vote(Some(BOB), hash, index, true)?;
+		sandbox.vote(Some(CHARLIE), hash, index, true)?;
+		sandbox.close(Some(ALICE), hash, index)?;
+		assert_eq!(sandbox.spends(), vec![0]);
+
+		sandbox.payout(Some(ALICE), 0)?;
+		assert_eq!(sandbox.free_balance(&DJANGO), balance + 100);
+		Ok(())
+	}
+
+	fn set_balance(new_free: u128) -> RuntimeCall<RuntimeOf<ParachainSandbox>> {
+		RuntimeCall::<RuntimeOf<ParachainSandbox>>::Balances(
+			pallet_balances::Call::force_set_balance { who: DJANGO.into(), new_free },
+		)
+	}
+
+	#[test]
+	fn council_can_dispatch_motions() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.set_members(vec![ALICE, BOB, CHARLIE], None)?;
+
+		// Two members out of three are a simple majority...
+		let motion = RuntimeCall::<RuntimeOf<ParachainSandbox>>::Motion(
+			pallet_motion::Call::simple_majority { call: Box::new(set_balance(42)) },
+		);
+		let (index, hash) = sandbox.propose(Some(ALICE), 2, motion)?;
+		sandbox.vote(Some(BOB), hash, index, true)?;
+		sandbox.close(Some(ALICE), hash, index)?;
+		assert_eq!(sandbox.free_balance(&DJANGO), 42);
+
+		// ...but not a unanimous one.
+		let motion =
+			RuntimeCall::<RuntimeOf<ParachainSandbox>>::Motion(pallet_motion::Call::unanimous {
+				call: Box::new(set_balance(100)),
+			});
+		let (index, hash) = sandbox.propose(Some(ALICE), 2, motion)?;
+		sandbox.vote(Some(BOB), hash, index, true)?;
+		sandbox.close(Some(ALICE), hash, index)?;
+		assert_eq!(sandbox.free_balance(&DJANGO), 42);
+		Ok(())
+	}
+
+	#[test]
+	fn non_member_cannot_propose() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.set_members(vec![ALICE], None)?;
+
+		assert!(sandbox.propose(Some(BOB), 1, remark()).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,220 @@
+use frame_support::sp_runtime::{
+	traits::{Hash, StaticLookup},
+	DispatchError,
+};
+use frame_system::RawOrigin;
+use pallet_identity::{BalanceOf, IdentityOf, Judgement, RegistrarIndex, Registrars, Registration};
+
+use crate::{AccountIdFor, OriginFor, Sandbox};
+
+type IdentityInformationOf<T> = <T as pallet_identity::Config>::IdentityInformation;
+type RegistrationOf<T> = Registration<
+	BalanceOf<T>,
+	<T as pallet_identity::Config>::MaxRegistrars,
+	IdentityInformationOf<T>,
+>;
+
+/// Identity API for the sandbox.
+pub trait IdentityAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_identity::Config,
+{
+	/// Sets the identity of the origin.
+	///
+	/// # Arguments
+	/// * `origin` - The account whose identity is set.
+	/// * `info` - The identity information.
+	fn set_identity(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		info: IdentityInformationOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Adds a registrar, using the root origin. Returns the index of the registrar.
+	///
+	/// # Arguments
+	/// * `account` - The account of the registrar.
+	fn add_registrar(
+		&mut self,
+		account: AccountIdFor<T::Runtime>,
+	) -> Result<RegistrarIndex, DispatchError>;
+
+	/// Requests a judgement from a registrar.
+	///
+	/// # Arguments
+	/// * `origin` - The account requesting the judgement.
+	/// * `reg_index` - The index of the registrar.
+	/// * `max_fee` - The maximum fee that may be paid to the registrar.
+	fn request_judgement(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		reg_index: RegistrarIndex,
+		max_fee: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Provides a judgement on the current identity of `target`.
+	///
+	/// # Arguments
+	/// * `origin` - The registrar.
+	/// * `reg_index` - The index of the registrar.
+	/// * `target` - The account whose identity is judged.
+	/// * `judgement` - The judgement.
+	fn provide_judgement(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		reg_index: RegistrarIndex,
+		target: &AccountIdFor<T::Runtime>,
+		judgement: Judgement<BalanceOf<T::Runtime>>,
+	) -> Result<(), DispatchError>;
+
+	/// Returns the identity of `who`, including its judgements, if any.
+	///
+	/// # Arguments
+	/// * `who` - The account.
+	fn identity_of(&mut self, who: &AccountIdFor<T::Runtime>)
+		-> Option<RegistrationOf<T::Runtime>>;
+}
+
+impl<T> IdentityAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_identity::Config,
+{
+	fn set_identity(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		info: IdentityInformationOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_identity::Pallet::<T::Runtime>::set_identity(origin.into(), Box::new(info))
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn add_registrar(
+		&mut self,
+		account: AccountIdFor<T::Runtime>,
+	) -> Result<RegistrarIndex, DispatchError> {
+		self.execute_with(|| {
+			let index = Registrars::<T::Runtime>::get().len() as RegistrarIndex;
+			pallet_identity::Pallet::<T::Runtime>::add_registrar(
+				RawOrigin::Root.into(),
+				<T::Runtime as frame_system::Config>::Lookup::unlookup(account),
+			)
+			.map(|_| index)
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn request_judgement(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		reg_index: RegistrarIndex,
+		max_fee: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_identity::Pallet::<T::Runtime>::request_judgement(
+				origin.into(),
+				reg_index,
+				max_fee,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn provide_judgement(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		reg_index: RegistrarIndex,
+		target: &AccountIdFor<T::Runtime>,
+		judgement: Judgement<BalanceOf<T::Runtime>>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			// The judgement has to refer to the current identity of the target.
+			let identity = IdentityOf::<T::Runtime>::get(target)
+				.map(|registration| {
+					<T::Runtime as frame_system::Config>::Hashing::hash_of(&registration.info)
+				})
+				.ok_or(pallet_identity::Error::<T::Runtime>::InvalidTarget)?;
+			pallet_identity::Pallet::<T::Runtime>::provide_judgement(
+				origin.into(),
+				reg_index,
+				<T::Runtime as frame_system::Config>::Lookup::unlookup(target.clone()),
+				judgement,
+				identity,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn identity_of(
+		&mut self,
+		who: &AccountIdFor<T::Runtime>,
+	) -> Option<RegistrationOf<T::Runtime>> {
+		self.execute_with(|| IdentityOf::<T::Runtime>::get(who))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pallet_contracts::test_utils::{ALICE, BOB};
+	use pallet_identity::{legacy::IdentityInfo, Data};
+
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox};
+
+	fn identity(display: &[u8]) -> IdentityInfo<frame_support::traits::ConstU32<100>> {
+		IdentityInfo {
+			additional: Default::default(),
+			display: Data::Raw(display.to_vec().try_into().unwrap()),
+			legal: Data::None,
+			web: Data::None,
+			riot: Data::None,
+			email: Data::None,
+			pgp_fingerprint: None,
+			image: Data::None,
+			twitter: Data::None,
+		}
+	}
+
+	#[test]
+	fn set_identity_works() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.mint_into(&ALICE, 1_000)?;
+
+		sandbox.set_identity(Some(ALICE), identity(b"alice"))?;
+		let registration = sandbox.identity_of(&ALICE).expect("Identity should be set");
+		assert_eq!(registration.info, identity(b"alice"));
+		assert!(registration.judgements.is_empty());
+		assert!(sandbox.identity_of(&BOB).is_none());
+		Ok(())
+	}
+
+	#[test]
+	fn provide_judgement_works() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.mint_into(&ALICE, 1_000)?;
+		let registrar = sandbox.add_registrar(BOB)?;
+
+		sandbox.set_identity(Some(ALICE), identity(b"alice"))?;
+		sandbox.provide_judgement(Some(BOB), registrar, &ALICE, Judgement::Reasonable)?;
+		let registration = sandbox.identity_of(&ALICE).expect("Identity should be set");
+		assert_eq!(registration.judgements.into_inner(), vec![(registrar, Judgement::Reasonable)]);
+		Ok(())
+	}
+
+	#[test]
+	fn provide_judgement_fails_without_identity() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		let registrar = sandbox.add_registrar(BOB)?;
+
+		assert!(sandbox
+			.provide_judgement(Some(BOB), registrar, &ALICE, Judgement::Reasonable)
+			.is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,129 @@
+use cumulus_pallet_parachain_system::{LastRelayChainBlockNumber, ValidationData};
+use cumulus_primitives_core::{
+	relay_chain::BlockNumber as RelayChainBlockNumber, ParaId, PersistedValidationData,
+};
+use frame_support::{storage_alias, traits::Get};
+
+use crate::Sandbox;
+
+type ParachainSystemOf<T> = cumulus_pallet_parachain_system::Pallet<T>;
+type ParachainInfoOf<T> = staging_parachain_info::Pallet<T>;
+
+/// The id of the parachain, which the parachain info pallet only exposes for reading.
+#[storage_alias(pallet_name)]
+type ParachainId<T: staging_parachain_info::Config> =
+	StorageValue<ParachainInfoOf<T>, ParaId, ValueQuery>;
+
+/// Parachain API for the sandbox, simulating the parachain context of Pop.
+///
+/// Mocks the state that the parachain system pallet derives from the relay chain (and its
+/// inherents), so contracts reading relay-chain-anchored data behave as on the real parachain.
+pub trait ParachainAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: cumulus_pallet_parachain_system::Config + staging_parachain_info::Config,
+{
+	/// Returns the id of the parachain.
+	fn para_id(&mut self) -> ParaId;
+
+	/// Sets the id of the parachain.
+	///
+	/// # Arguments
+	/// * `para_id` - The new parachain id.
+	fn set_para_id(&mut self, para_id: ParaId);
+
+	/// Returns the number of the relay chain block the current parachain block is anchored to.
+	fn relay_block_number(&mut self) -> RelayChainBlockNumber;
+
+	/// Anchors the current parachain block to the relay chain block `number`, as the validation
+	/// data inherent would do.
+	///
+	/// # Arguments
+	/// * `number` - The relay chain block number.
+	fn set_relay_block_number(&mut self, number: RelayChainBlockNumber);
+
+	/// Advances the relay chain by `blocks` blocks and returns the new relay chain block number.
+	///
+	/// # Arguments
+	/// * `blocks` - The number of relay chain blocks to advance.
+	fn advance_relay_blocks(&mut self, blocks: RelayChainBlockNumber) -> RelayChainBlockNumber;
+}
+
+impl<T> ParachainAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: cumulus_pallet_parachain_system::Config + staging_parachain_info::Config,
+{
+	fn para_id(&mut self) -> ParaId {
+		self.execute_with(<ParachainInfoOf<T::Runtime> as Get<ParaId>>::get)
+	}
+
+	fn set_para_id(&mut self, para_id: ParaId) {
+		self.execute_with(|| ParachainId::<T::Runtime>::put(para_id))
+	}
+
+	fn relay_block_number(&mut self) -> RelayChainBlockNumber {
+		self.execute_with(|| {
+			ParachainSystemOf::<T::Runtime>::validation_data()
+				.map(|data| data.relay_parent_number)
+				.unwrap_or_else(LastRelayChainBlockNumber::<T::Runtime>::get)
+		})
+	}
+
+	fn set_relay_block_number(&mut self, number: RelayChainBlockNumber) {
+		self.execute_with(|| {
+			ValidationData::<T::Runtime>::put(PersistedValidationData {
+				relay_parent_number: number,
+				..ParachainSystemOf::<T::Runtime>::validation_data().unwrap_or_default()
+			});
+			LastRelayChainBlockNumber::<T::Runtime>::put(number);
+		})
+	}
+
+	fn advance_relay_blocks(&mut self, blocks: RelayChainBlockNumber) -> RelayChainBlockNumber {
+		let number = self.relay_block_number().saturating_add(blocks);
+		self.set_relay_block_number(number);
+		number
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use cumulus_pallet_parachain_system::RelaychainDataProvider;
+	use frame_support::sp_runtime::traits::BlockNumberProvider;
+
+	use super::*;
+	use crate::{ParachainSandbox, RuntimeOf};
+
+	type Runtime = RuntimeOf<ParachainSandbox>;
+
+	#[test]
+	fn relay_blocks_can_be_advanced() {
+		let mut sandbox = ParachainSandbox::default();
+		assert_eq!(sandbox.relay_block_number(), 0);
+
+		sandbox.set_relay_block_number(100);
+		assert_eq!(sandbox.advance_relay_blocks(5), 105);
+		assert_eq!(sandbox.relay_block_number(), 105);
+		sandbox.execute_with(|| {
+			assert_eq!(
+				ParachainSystemOf::<Runtime>::validation_data()
+					.map(|data| data.relay_parent_number),
+				Some(105)
+			);
+			assert_eq!(LastRelayChainBlockNumber::<Runtime>::get(), 105);
+			assert_eq!(RelaychainDataProvider::<Runtime>::current_block_number(), 105);
+		});
+	}
+
+	#[test]
+	fn para_id_can_be_set() {
+		let mut sandbox = ParachainSandbox::default();
+		sandbox.set_para_id(ParaId::from(4001));
+
+		assert_eq!(sandbox.para_id(), ParaId::from(4001));
+		sandbox.execute_with(|| {
+			assert_eq!(ParachainInfoOf::<Runtime>::parachain_id(), ParaId::from(4001))
+		});
+	}
+}
@@ -0,0 +1,333 @@
+use frame_support::{
+	sp_runtime::{
+		traits::{Hash, Saturating},
+		DispatchError, Perbill,
+	},
+	traits::{
+		schedule::{v3::Named as ScheduleNamed, DispatchTime},
+		Hooks, StorePreimage, VoteTally,
+	},
+};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use pallet_referenda::{
+	BoundedCallOf, PalletsOriginOf, ReferendumCount, ReferendumIndex, ReferendumInfo,
+	ReferendumInfoFor, ReferendumInfoOf,
+};
+use scale::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+use crate::{api::system_api::SystemAPI, OriginFor, Sandbox};
+
+type CallOf<T, I> = <T as pallet_referenda::Config<I>>::RuntimeCall;
+type HashOf<T> = <T as frame_system::Config>::Hash;
+type TallyOf<T, I> = <T as pallet_referenda::Config<I>>::Tally;
+
+/// A tally of the votes on a referendum, for the runtimes without a voting pallet, like the
+/// sandboxes created with [`crate::create_parachain_sandbox`]. Set with
+/// [`ReferendaAPI::set_tally`].
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	Default,
+	PartialEq,
+	Eq,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+pub struct Tally {
+	/// The number of votes in favour.
+	pub ayes: u32,
+	/// The number of votes against.
+	pub nays: u32,
+	/// The share of the electorate voting in favour.
+	pub support: Perbill,
+}
+
+impl<Class> VoteTally<u32, Class> for Tally {
+	fn new(_: Class) -> Self {
+		Self::default()
+	}
+
+	fn ayes(&self, _: Class) -> u32 {
+		self.ayes
+	}
+
+	fn support(&self, _: Class) -> Perbill {
+		self.support
+	}
+
+	fn approval(&self, _: Class) -> Perbill {
+		match self.ayes.saturating_add(self.nays) {
+			0 => Perbill::zero(),
+			votes => Perbill::from_rational(self.ayes, votes),
+		}
+	}
+}
+
+/// Referenda API for the sandbox.
+///
+/// Allows to note preimages, submit referenda and fast-forward them through their track up to
+/// enactment, so that OpenGov-style governance can be tested end-to-end.
+pub trait ReferendaAPI<T: Sandbox, I: 'static = ()>
+where
+	T: Sandbox,
+	T::Runtime: pallet_referenda::Config<I> + pallet_preimage::Config + pallet_scheduler::Config,
+{
+	/// Notes a preimage and returns its hash.
+	///
+	/// # Arguments
+	/// * `origin` - The account noting the preimage.
+	/// * `bytes` - The preimage.
+	fn note_preimage(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		bytes: Vec<u8>,
+	) -> Result<HashOf<T::Runtime>, DispatchError>;
+
+	/// Submits a referendum on `proposal` and returns its index.
+	///
+	/// The proposal is bounded automatically, i.e. its preimage is noted if it's too large to be
+	/// inlined.
+	///
+	/// # Arguments
+	/// * `origin` - The account submitting the referendum.
+	/// * `proposal_origin` - The origin with which the proposal is dispatched if approved.
+	/// * `proposal` - The call to be dispatched if approved.
+	/// * `enactment` - When the proposal should be enacted once approved.
+	fn submit(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_origin: PalletsOriginOf<T::Runtime>,
+		proposal: CallOf<T::Runtime, I>,
+		enactment: DispatchTime<BlockNumberFor<T::Runtime>>,
+	) -> Result<ReferendumIndex, DispatchError>;
+
+	/// Places the decision deposit of a referendum, allowing it to enter the decision phase.
+	///
+	/// # Arguments
+	/// * `origin` - The account placing the deposit.
+	/// * `index` - The index of the referendum.
+	fn place_decision_deposit(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		index: ReferendumIndex,
+	) -> Result<(), DispatchError>;
+
+	/// Overrides the tally of an ongoing referendum, as if the votes were cast.
+	///
+	/// # Arguments
+	/// * `index` - The index of the referendum.
+	/// * `tally` - The new tally.
+	fn set_tally(
+		&mut self,
+		index: ReferendumIndex,
+		tally: TallyOf<T::Runtime, I>,
+	) -> Result<(), DispatchError>;
+
+	/// Builds blocks until the referendum is concluded and, if approved, its proposal is
+	/// enacted. Returns the final state of the referendum.
+	///
+	/// # Arguments
+	/// * `index` - The index of the referendum.
+	/// * `max_blocks` - The maximum number of blocks to build.
+	fn fast_forward(
+		&mut self,
+		index: ReferendumIndex,
+		max_blocks: u32,
+	) -> Result<ReferendumInfoOf<T::Runtime, I>, DispatchError>;
+
+	/// Returns the state of a referendum, if any.
+	///
+	/// # Arguments
+	/// * `index` - The index of the referendum.
+	fn referendum(&mut self, index: ReferendumIndex) -> Option<ReferendumInfoOf<T::Runtime, I>>;
+}
+
+impl<T, I> ReferendaAPI<T, I> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_referenda::Config<I> + pallet_preimage::Config + pallet_scheduler::Config,
+	I: 'static,
+{
+	fn note_preimage(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		bytes: Vec<u8>,
+	) -> Result<HashOf<T::Runtime>, DispatchError> {
+		self.execute_with(|| {
+			let hash = <T::Runtime as frame_system::Config>::Hashing::hash(&bytes);
+			pallet_preimage::Pallet::<T::Runtime>::note_preimage(origin.into(), bytes)
+				.map(|_| hash)
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn submit(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		proposal_origin: PalletsOriginOf<T::Runtime>,
+		proposal: CallOf<T::Runtime, I>,
+		enactment: DispatchTime<BlockNumberFor<T::Runtime>>,
+	) -> Result<ReferendumIndex, DispatchError> {
+		self.execute_with(|| {
+			let index = ReferendumCount::<T::Runtime, I>::get();
+			let proposal: BoundedCallOf<T::Runtime, I> =
+				<T::Runtime as pallet_referenda::Config<I>>::Preimages::bound(proposal)?;
+			pallet_referenda::Pallet::<T::Runtime, I>::submit(
+				origin.into(),
+				Box::new(proposal_origin),
+				proposal,
+				enactment,
+			)
+			.map(|_| index)
+		})
+	}
+
+	fn place_decision_deposit(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		index: ReferendumIndex,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			pallet_referenda::Pallet::<T::Runtime, I>::place_decision_deposit(origin.into(), index)
+		})
+	}
+
+	fn set_tally(
+		&mut self,
+		index: ReferendumIndex,
+		tally: TallyOf<T::Runtime, I>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			ReferendumInfoFor::<T::Runtime, I>::try_mutate(index, |info| match info {
+				Some(ReferendumInfo::Ongoing(status)) => {
+					status.tally = tally;
+					Ok(())
+				},
+				_ => Err(pallet_referenda::Error::<T::Runtime, I>::NotOngoing.into()),
+			})
+		})
+	}
+
+	fn fast_forward(
+		&mut self,
+		index: ReferendumIndex,
+		max_blocks: u32,
+	) -> Result<ReferendumInfoOf<T::Runtime, I>, DispatchError> {
+		// The referenda pallet schedules the enactment under this name (see
+		// `pallet_referenda::Pallet::schedule_enactment`).
+		let enactment_task =
+			(*b"assembly", "enactment", index).using_encoded(sp_io::hashing::blake2_256);
+		let mut blocks = 0u32;
+		loop {
+			let info = <Self as ReferendaAPI<T, I>>::referendum(self, index)
+				.ok_or(pallet_referenda::Error::<T::Runtime, I>::BadReferendum)?;
+			let pending_enactment = matches!(info, ReferendumInfo::Approved(..)) &&
+				self.execute_with(|| {
+					<<T::Runtime as pallet_referenda::Config<I>>::Scheduler as ScheduleNamed<
+						BlockNumberFor<T::Runtime>,
+						CallOf<T::Runtime, I>,
+						PalletsOriginOf<T::Runtime>,
+					>>::next_dispatch_time(enactment_task)
+					.is_ok()
+				});
+			if !matches!(info, ReferendumInfo::Ongoing(..)) && !pending_enactment {
+				return Ok(info);
+			}
+			if blocks == max_blocks {
+				return Err(DispatchError::Other(
+					"Referendum has not been enacted within the given number of blocks",
+				));
+			}
+
+			self.build_block();
+			blocks.saturating_inc();
+			// The blocks of the sandbox don't run the scheduler, which dispatches the alarms and
+			// the enactment of the referenda.
+			self.execute_with(|| {
+				pallet_scheduler::Pallet::<T::Runtime>::on_initialize(frame_system::Pallet::<
+					T::Runtime,
+				>::block_number())
+			});
+			if matches!(
+				<Self as ReferendaAPI<T, I>>::referendum(self, index),
+				Some(ReferendumInfo::Ongoing(..))
+			) {
+				// Advance the referendum right away instead of waiting for its alarm.
+				self.execute_with(|| {
+					pallet_referenda::Pallet::<T::Runtime, I>::nudge_referendum(
+						RawOrigin::Root.into(),
+						index,
+					)
+				})?;
+			}
+		}
+	}
+
+	fn referendum(&mut self, index: ReferendumIndex) -> Option<ReferendumInfoOf<T::Runtime, I>> {
+		self.execute_with(|| ReferendumInfoFor::<T::Runtime, I>::get(index))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use pallet_contracts::test_utils::{ALICE, DJANGO};
+
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox, RuntimeCall, RuntimeOf};
+
+	type Runtime = RuntimeOf<ParachainSandbox>;
+
+	#[test]
+	fn approved_referenda_are_enacted() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		BalanceAPI::mint_into(&mut sandbox, &ALICE, 1_000)?;
+		let proposal = RuntimeCall::<Runtime>::Balances(pallet_balances::Call::force_set_balance {
+			who: DJANGO.into(),
+			new_free: 42,
+		});
+
+		let index = sandbox.submit(
+			Some(ALICE),
+			RawOrigin::Root.into(),
+			proposal,
+			DispatchTime::After(1),
+		)?;
+		sandbox.place_decision_deposit(Some(ALICE), index)?;
+		sandbox.set_tally(index, Tally { ayes: 1, nays: 0, support: Perbill::one() })?;
+		assert_eq!(sandbox.free_balance(&DJANGO), 0);
+
+		let info = sandbox.fast_forward(index, 10)?;
+		assert!(matches!(info, ReferendumInfo::Approved(..)));
+		assert_eq!(sandbox.free_balance(&DJANGO), 42);
+		Ok(())
+	}
+
+	#[test]
+	fn rejected_referenda_are_not_enacted() -> Result<(), DispatchError> {
+		let mut sandbox = ParachainSandbox::default();
+		BalanceAPI::mint_into(&mut sandbox, &ALICE, 1_000)?;
+		let proposal = RuntimeCall::<Runtime>::Balances(pallet_balances::Call::force_set_balance {
+			who: DJANGO.into(),
+			new_free: 42,
+		});
+
+		let index = sandbox.submit(
+			Some(ALICE),
+			RawOrigin::Root.into(),
+			proposal,
+			DispatchTime::After(1),
+		)?;
+		sandbox.place_decision_deposit(Some(ALICE), index)?;
+		sandbox.set_tally(index, Tally { ayes: 0, nays: 1, support: Perbill::zero() })?;
+
+		let info = sandbox.fast_forward(index, 20)?;
+		assert!(matches!(info, ReferendumInfo::Rejected(..)));
+		assert_eq!(sandbox.free_balance(&DJANGO), 0);
+		Ok(())
+	}
+}
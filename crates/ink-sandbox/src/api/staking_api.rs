@@ -0,0 +1,380 @@
+use frame_support::{
+	ensure,
+	sp_runtime::{traits::Saturating, DispatchError},
+	storage::with_storage_layer,
+	traits::{Get, ReservableCurrency},
+};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use pallet_authorship::EventHandler;
+use pallet_collator_selection::{
+	BalanceOf, CandidacyBond, CandidateInfo, CandidateList, DesiredCandidates, Error,
+	Invulnerables, LastAuthoredBlock,
+};
+use pallet_session::SessionManager;
+
+use crate::{AccountIdFor, OriginFor, Sandbox};
+
+type CollatorSelectionOf<T> = pallet_collator_selection::Pallet<T>;
+
+/// Staking API for the sandbox, built on top of the collator selection pallet used by Pop.
+///
+/// Allows to set up collators and their stakes and to simulate reward rounds (block authoring and
+/// session rotation).
+pub trait StakingAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_collator_selection::Config,
+{
+	/// Sets the invulnerable collators, using the root origin.
+	///
+	/// # Arguments
+	/// * `invulnerables` - The new set of invulnerable collators.
+	fn set_invulnerables(
+		&mut self,
+		invulnerables: Vec<AccountIdFor<T::Runtime>>,
+	) -> Result<(), DispatchError>;
+
+	/// Sets the desired number of candidates, using the root origin.
+	///
+	/// # Arguments
+	/// * `max` - The desired number of candidates.
+	fn set_desired_candidates(&mut self, max: u32) -> Result<(), DispatchError>;
+
+	/// Sets the minimum bond required to become a candidate, using the root origin.
+	///
+	/// # Arguments
+	/// * `bond` - The new candidacy bond.
+	fn set_candidacy_bond(&mut self, bond: BalanceOf<T::Runtime>) -> Result<(), DispatchError>;
+
+	/// Registers the origin as a candidate, reserving the candidacy bond.
+	///
+	/// Note: The candidate must have its session keys registered.
+	///
+	/// # Arguments
+	/// * `origin` - The account that wants to become a candidate.
+	fn register_as_candidate(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+	) -> Result<(), DispatchError>;
+
+	/// Adds `who` as a candidate with the given `deposit`, bypassing the session keys check.
+	///
+	/// The deposit is reserved from the account of the candidate. Fails, without any change, if
+	/// `who` is already a candidate or an invulnerable, or if the deposit can't be reserved.
+	///
+	/// # Arguments
+	/// * `who` - The new candidate.
+	/// * `deposit` - The stake of the candidate.
+	fn add_candidate(
+		&mut self,
+		who: &AccountIdFor<T::Runtime>,
+		deposit: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Updates the stake of the origin, which must be a candidate.
+	///
+	/// # Arguments
+	/// * `origin` - The candidate.
+	/// * `new_deposit` - The new stake of the candidate.
+	fn update_bond(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		new_deposit: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError>;
+
+	/// Notes `author` as the author of the current block, paying out the block reward from the
+	/// pot.
+	///
+	/// # Arguments
+	/// * `author` - The block author.
+	fn note_author(&mut self, author: &AccountIdFor<T::Runtime>);
+
+	/// Starts a new session (reward round), returning the new set of collators, if any.
+	///
+	/// # Arguments
+	/// * `index` - The index of the new session.
+	fn new_session(&mut self, index: u32) -> Option<Vec<AccountIdFor<T::Runtime>>>;
+
+	/// Returns the account of the pot, from which the block rewards are paid.
+	fn pot_account(&mut self) -> AccountIdFor<T::Runtime>;
+
+	/// Returns the invulnerable collators.
+	fn invulnerables(&mut self) -> Vec<AccountIdFor<T::Runtime>>;
+
+	/// Returns the candidates together with their stakes, sorted by the stake (ascending).
+	fn candidates(&mut self) -> Vec<(AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>)>;
+
+	/// Returns the desired number of candidates.
+	fn desired_candidates(&mut self) -> u32;
+
+	/// Returns the minimum bond required to become a candidate.
+	fn candidacy_bond(&mut self) -> BalanceOf<T::Runtime>;
+
+	/// Returns the last block authored by `who`.
+	///
+	/// # Arguments
+	/// * `who` - The collator.
+	fn last_authored_block(&mut self, who: &AccountIdFor<T::Runtime>)
+		-> BlockNumberFor<T::Runtime>;
+}
+
+impl<T> StakingAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_collator_selection::Config,
+{
+	fn set_invulnerables(
+		&mut self,
+		invulnerables: Vec<AccountIdFor<T::Runtime>>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			CollatorSelectionOf::<T::Runtime>::set_invulnerables(
+				RawOrigin::Root.into(),
+				invulnerables,
+			)
+			.map(|_| ())
+			.map_err(|e| e.error)
+		})
+	}
+
+	fn set_desired_candidates(&mut self, max: u32) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			CollatorSelectionOf::<T::Runtime>::set_desired_candidates(RawOrigin::Root.into(), max)
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn set_candidacy_bond(&mut self, bond: BalanceOf<T::Runtime>) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			CollatorSelectionOf::<T::Runtime>::set_candidacy_bond(RawOrigin::Root.into(), bond)
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn register_as_candidate(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			CollatorSelectionOf::<T::Runtime>::register_as_candidate(origin.into())
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn add_candidate(
+		&mut self,
+		who: &AccountIdFor<T::Runtime>,
+		deposit: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			ensure!(
+				!Invulnerables::<T::Runtime>::get().contains(who),
+				Error::<T::Runtime>::AlreadyInvulnerable
+			);
+			ensure!(
+				!CandidateList::<T::Runtime>::get().iter().any(|candidate| &candidate.who == who),
+				Error::<T::Runtime>::AlreadyCandidate
+			);
+			// Don't keep the deposit reserved if the candidate can't be inserted.
+			with_storage_layer(|| {
+				<T::Runtime as pallet_collator_selection::Config>::Currency::reserve(who, deposit)?;
+				CandidateList::<T::Runtime>::try_mutate(|candidates| {
+					let position = candidates
+						.iter()
+						.position(|candidate| candidate.deposit > deposit)
+						.unwrap_or(candidates.len());
+					candidates
+						.try_insert(position, CandidateInfo { who: who.clone(), deposit })
+						.map_err(|_| DispatchError::from(Error::<T::Runtime>::TooManyCandidates))
+				})?;
+				LastAuthoredBlock::<T::Runtime>::insert(
+					who,
+					frame_system::Pallet::<T::Runtime>::block_number().saturating_add(
+						<T::Runtime as pallet_collator_selection::Config>::KickThreshold::get(),
+					),
+				);
+				Ok(())
+			})
+		})
+	}
+
+	fn update_bond(
+		&mut self,
+		origin: impl Into<OriginFor<T>>,
+		new_deposit: BalanceOf<T::Runtime>,
+	) -> Result<(), DispatchError> {
+		self.execute_with(|| {
+			CollatorSelectionOf::<T::Runtime>::update_bond(origin.into(), new_deposit)
+				.map(|_| ())
+				.map_err(|e| e.error)
+		})
+	}
+
+	fn note_author(&mut self, author: &AccountIdFor<T::Runtime>) {
+		self.execute_with(|| {
+			<CollatorSelectionOf<T::Runtime> as EventHandler<_, _>>::note_author(author.clone())
+		})
+	}
+
+	fn new_session(&mut self, index: u32) -> Option<Vec<AccountIdFor<T::Runtime>>> {
+		self.execute_with(|| {
+			<CollatorSelectionOf<T::Runtime> as SessionManager<_>>::new_session(index)
+		})
+	}
+
+	fn pot_account(&mut self) -> AccountIdFor<T::Runtime> {
+		self.execute_with(CollatorSelectionOf::<T::Runtime>::account_id)
+	}
+
+	fn invulnerables(&mut self) -> Vec<AccountIdFor<T::Runtime>> {
+		self.execute_with(|| Invulnerables::<T::Runtime>::get().into_inner())
+	}
+
+	fn candidates(&mut self) -> Vec<(AccountIdFor<T::Runtime>, BalanceOf<T::Runtime>)> {
+		self.execute_with(|| {
+			CandidateList::<T::Runtime>::get()
+				.into_iter()
+				.map(|candidate| (candidate.who, candidate.deposit))
+				.collect()
+		})
+	}
+
+	fn desired_candidates(&mut self) -> u32 {
+		self.execute_with(DesiredCandidates::<T::Runtime>::get)
+	}
+
+	fn candidacy_bond(&mut self) -> BalanceOf<T::Runtime> {
+		self.execute_with(CandidacyBond::<T::Runtime>::get)
+	}
+
+	fn last_authored_block(
+		&mut self,
+		who: &AccountIdFor<T::Runtime>,
+	) -> BlockNumberFor<T::Runtime> {
+		self.execute_with(|| LastAuthoredBlock::<T::Runtime>::get(who))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::testing::UintAuthorityId;
+	use pallet_contracts::test_utils::{ALICE, BOB, CHARLIE};
+
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox, RuntimeOf};
+
+	type Runtime = RuntimeOf<ParachainSandbox>;
+
+	fn funded_sandbox() -> ParachainSandbox {
+		let mut sandbox = ParachainSandbox::default();
+		for account in [ALICE, BOB, CHARLIE] {
+			BalanceAPI::mint_into(&mut sandbox, &account, 1_000).unwrap();
+		}
+		sandbox
+	}
+
+	fn set_keys(sandbox: &mut ParachainSandbox, who: AccountIdFor<Runtime>, key: u64) {
+		sandbox
+			.execute_with(|| {
+				pallet_session::Pallet::<Runtime>::set_keys(
+					Some(who).into(),
+					UintAuthorityId(key),
+					vec![],
+				)
+			})
+			.unwrap();
+	}
+
+	#[test]
+	fn candidates_are_sorted_by_stake() {
+		let mut sandbox = funded_sandbox();
+		set_keys(&mut sandbox, CHARLIE, 3);
+		sandbox.register_as_candidate(Some(CHARLIE)).unwrap();
+		sandbox.add_candidate(&ALICE, 100).unwrap();
+		sandbox.add_candidate(&BOB, 50).unwrap();
+
+		assert_eq!(sandbox.candidates(), vec![(CHARLIE, 0), (BOB, 50), (ALICE, 100)]);
+		assert_eq!(sandbox.contract_account_info(&ALICE).reserved, 100);
+	}
+
+	#[test]
+	fn existing_collators_cannot_be_added() {
+		let mut sandbox = funded_sandbox();
+		set_keys(&mut sandbox, CHARLIE, 3);
+		sandbox.set_invulnerables(vec![CHARLIE]).unwrap();
+		sandbox.add_candidate(&ALICE, 100).unwrap();
+
+		assert_eq!(
+			sandbox.add_candidate(&ALICE, 200),
+			Err(Error::<Runtime>::AlreadyCandidate.into())
+		);
+		assert_eq!(
+			sandbox.add_candidate(&CHARLIE, 100),
+			Err(Error::<Runtime>::AlreadyInvulnerable.into())
+		);
+		assert_eq!(sandbox.candidates(), vec![(ALICE, 100)]);
+		assert_eq!(sandbox.contract_account_info(&ALICE).reserved, 100);
+		assert_eq!(sandbox.contract_account_info(&CHARLIE).reserved, 0);
+	}
+
+	#[test]
+	fn failed_additions_do_not_reserve_the_deposit() {
+		let mut sandbox = funded_sandbox();
+		sandbox.execute_with(|| {
+			CandidateList::<Runtime>::put(frame_support::BoundedVec::<_, _>::truncate_from(
+				(100..200u8)
+					.map(|i| CandidateInfo {
+						who: AccountIdFor::<Runtime>::new([i; 32]),
+						deposit: 0,
+					})
+					.collect(),
+			))
+		});
+
+		assert_eq!(
+			sandbox.add_candidate(&ALICE, 100),
+			Err(Error::<Runtime>::TooManyCandidates.into())
+		);
+		assert_eq!(sandbox.contract_account_info(&ALICE).reserved, 0);
+		assert_eq!(sandbox.last_authored_block(&ALICE), 0);
+	}
+
+	#[test]
+	fn bonds_can_be_updated() {
+		let mut sandbox = funded_sandbox();
+		sandbox.set_candidacy_bond(10).unwrap();
+		sandbox.add_candidate(&ALICE, 100).unwrap();
+		sandbox.add_candidate(&BOB, 50).unwrap();
+
+		sandbox.update_bond(Some(BOB), 200).unwrap();
+		assert_eq!(sandbox.candidates(), vec![(ALICE, 100), (BOB, 200)]);
+		assert_eq!(sandbox.contract_account_info(&BOB).reserved, 200);
+		assert!(sandbox.update_bond(Some(ALICE), 5).is_err());
+		assert!(sandbox.update_bond(Some(CHARLIE), 100).is_err());
+	}
+
+	#[test]
+	fn reward_rounds_pay_the_authors_and_rotate_the_collators() {
+		let mut sandbox = funded_sandbox();
+		sandbox.set_desired_candidates(1).unwrap();
+		sandbox.add_candidate(&ALICE, 100).unwrap();
+		sandbox.add_candidate(&BOB, 50).unwrap();
+
+		// Half of the pot (above the existential deposit) is paid to the author of each block.
+		let pot = sandbox.pot_account();
+		BalanceAPI::mint_into(&mut sandbox, &pot, 1_001).unwrap();
+		let balance = sandbox.free_balance(&BOB);
+		sandbox.note_author(&BOB);
+		assert_eq!(sandbox.free_balance(&BOB), balance + 500);
+		assert_eq!(sandbox.free_balance(&pot), 501);
+		assert_eq!(sandbox.last_authored_block(&BOB), sandbox.block_number());
+
+		// Only the desired number of candidates, with the highest stakes, become collators.
+		assert_eq!(sandbox.new_session(1), Some(vec![ALICE]));
+		sandbox.set_desired_candidates(2).unwrap();
+		assert_eq!(sandbox.new_session(2), Some(vec![ALICE, BOB]));
+	}
+}
@@ -0,0 +1,358 @@
+use frame_support::storage::{StorageDoubleMap, StorageMap, StorageValue};
+use scale::{FullCodec, FullEncode};
+
+use crate::Sandbox;
+
+/// Storage API for the sandbox.
+///
+/// Allows to read and write arbitrary storage items, e.g. to set up or corrupt state that is hard
+/// to reach through dispatchables. Items are addressed by their storage type, like
+/// `pallet_assets::Asset<Runtime, Instance1>`.
+pub trait StorageAPI {
+	/// Read a storage value.
+	fn read_storage_value<Item, V>(&mut self) -> Item::Query
+	where
+		Item: StorageValue<V>,
+		V: FullCodec;
+
+	/// Write a storage value.
+	///
+	/// # Arguments
+	///
+	/// * `value` - The new value.
+	fn write_storage_value<Item, V>(&mut self, value: V)
+	where
+		Item: StorageValue<V>,
+		V: FullCodec;
+
+	/// Read an entry of a storage map.
+	///
+	/// # Arguments
+	///
+	/// * `key` - The key of the entry.
+	fn read_storage<Item, K, V>(&mut self, key: K) -> Item::Query
+	where
+		Item: StorageMap<K, V>,
+		K: FullEncode,
+		V: FullCodec;
+
+	/// Write an entry of a storage map.
+	///
+	/// # Arguments
+	///
+	/// * `key` - The key of the entry.
+	/// * `value` - The new value.
+	fn write_storage<Item, K, V>(&mut self, key: K, value: V)
+	where
+		Item: StorageMap<K, V>,
+		K: FullEncode,
+		V: FullCodec;
+
+	/// Read an entry of a storage double map.
+	///
+	/// # Arguments
+	///
+	/// * `key1` - The first key of the entry.
+	/// * `key2` - The second key of the entry.
+	fn read_storage_double<Item, K1, K2, V>(&mut self, key1: K1, key2: K2) -> Item::Query
+	where
+		Item: StorageDoubleMap<K1, K2, V>,
+		K1: FullEncode,
+		K2: FullEncode,
+		V: FullCodec;
+
+	/// Write an entry of a storage double map.
+	///
+	/// # Arguments
+	///
+	/// * `key1` - The first key of the entry.
+	/// * `key2` - The second key of the entry.
+	/// * `value` - The new value.
+	fn write_storage_double<Item, K1, K2, V>(&mut self, key1: K1, key2: K2, value: V)
+	where
+		Item: StorageDoubleMap<K1, K2, V>,
+		K1: FullEncode,
+		K2: FullEncode,
+		V: FullCodec;
+
+	/// Return the raw (SCALE-encoded) value stored under `key`, if any.
+	///
+	/// # Arguments
+	///
+	/// * `key` - The full storage key.
+	fn read_raw_storage(&mut self, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Store the raw (SCALE-encoded) `value` under `key`.
+	///
+	/// # Arguments
+	///
+	/// * `key` - The full storage key.
+	/// * `value` - The raw value.
+	fn write_raw_storage(&mut self, key: &[u8], value: &[u8]);
+
+	/// Remove the value stored under `key`.
+	///
+	/// # Arguments
+	///
+	/// * `key` - The full storage key.
+	fn clear_raw_storage(&mut self, key: &[u8]);
+
+	/// Return the raw key-value pairs of the top-level storage whose keys start with `prefix`, in
+	/// lexicographic order of the keys.
+	///
+	/// # Arguments
+	///
+	/// * `prefix` - The prefix of the keys, e.g. the prefix of a pallet or a storage item.
+	/// * `start_key` - If given, only the keys strictly after it are returned (for pagination).
+	/// * `limit` - The maximum number of pairs to return.
+	fn storage_pairs(
+		&mut self,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		limit: Option<usize>,
+	) -> Vec<(Vec<u8>, Vec<u8>)>;
+
+	/// Return the raw key-value pairs of a (default) child trie whose keys start with `prefix`, in
+	/// lexicographic order of the keys.
+	///
+	/// # Arguments
+	///
+	/// * `storage_key` - The storage key of the child trie, e.g. the trie id of a contract.
+	/// * `prefix` - The prefix of the keys.
+	/// * `start_key` - If given, only the keys strictly after it are returned (for pagination).
+	/// * `limit` - The maximum number of pairs to return.
+	fn child_storage_pairs(
+		&mut self,
+		storage_key: &[u8],
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		limit: Option<usize>,
+	) -> Vec<(Vec<u8>, Vec<u8>)>;
+}
+
+impl<T> StorageAPI for T
+where
+	T: Sandbox,
+{
+	fn read_storage_value<Item, V>(&mut self) -> Item::Query
+	where
+		Item: StorageValue<V>,
+		V: FullCodec,
+	{
+		self.execute_with(Item::get)
+	}
+
+	fn write_storage_value<Item, V>(&mut self, value: V)
+	where
+		Item: StorageValue<V>,
+		V: FullCodec,
+	{
+		self.execute_with(|| Item::put(value))
+	}
+
+	fn read_storage<Item, K, V>(&mut self, key: K) -> Item::Query
+	where
+		Item: StorageMap<K, V>,
+		K: FullEncode,
+		V: FullCodec,
+	{
+		self.execute_with(|| Item::get(key))
+	}
+
+	fn write_storage<Item, K, V>(&mut self, key: K, value: V)
+	where
+		Item: StorageMap<K, V>,
+		K: FullEncode,
+		V: FullCodec,
+	{
+		self.execute_with(|| Item::insert(key, value))
+	}
+
+	fn read_storage_double<Item, K1, K2, V>(&mut self, key1: K1, key2: K2) -> Item::Query
+	where
+		Item: StorageDoubleMap<K1, K2, V>,
+		K1: FullEncode,
+		K2: FullEncode,
+		V: FullCodec,
+	{
+		self.execute_with(|| Item::get(key1, key2))
+	}
+
+	fn write_storage_double<Item, K1, K2, V>(&mut self, key1: K1, key2: K2, value: V)
+	where
+		Item: StorageDoubleMap<K1, K2, V>,
+		K1: FullEncode,
+		K2: FullEncode,
+		V: FullCodec,
+	{
+		self.execute_with(|| Item::insert(key1, key2, value))
+	}
+
+	fn read_raw_storage(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		self.execute_with(|| sp_io::storage::get(key).map(|value| value.to_vec()))
+	}
+
+	fn write_raw_storage(&mut self, key: &[u8], value: &[u8]) {
+		self.execute_with(|| sp_io::storage::set(key, value))
+	}
+
+	fn clear_raw_storage(&mut self, key: &[u8]) {
+		self.execute_with(|| sp_io::storage::clear(key))
+	}
+
+	fn storage_pairs(
+		&mut self,
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		limit: Option<usize>,
+	) -> Vec<(Vec<u8>, Vec<u8>)> {
+		self.execute_with(|| {
+			collect_pairs(prefix, start_key, limit, sp_io::storage::next_key, |key| {
+				sp_io::storage::get(key).map(|value| value.to_vec())
+			})
+		})
+	}
+
+	fn child_storage_pairs(
+		&mut self,
+		storage_key: &[u8],
+		prefix: &[u8],
+		start_key: Option<&[u8]>,
+		limit: Option<usize>,
+	) -> Vec<(Vec<u8>, Vec<u8>)> {
+		self.execute_with(|| {
+			collect_pairs(
+				prefix,
+				start_key,
+				limit,
+				|key| sp_io::default_child_storage::next_key(storage_key, key),
+				|key| sp_io::default_child_storage::get(storage_key, key),
+			)
+		})
+	}
+}
+
+/// Walks the keys starting with `prefix` (after `start_key`, if given) and collects at most `limit`
+/// key-value pairs.
+fn collect_pairs(
+	prefix: &[u8],
+	start_key: Option<&[u8]>,
+	limit: Option<usize>,
+	next_key: impl Fn(&[u8]) -> Option<Vec<u8>>,
+	get: impl Fn(&[u8]) -> Option<Vec<u8>>,
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+	let limit = limit.unwrap_or(usize::MAX);
+	let mut pairs = vec![];
+
+	// `next_key` is exclusive, so without a start key the prefix itself has to be checked first.
+	let mut key = match start_key {
+		Some(start_key) if start_key >= prefix => start_key.to_vec(),
+		_ => {
+			if let Some(value) = get(prefix) {
+				pairs.push((prefix.to_vec(), value));
+			}
+			prefix.to_vec()
+		},
+	};
+
+	while pairs.len() < limit {
+		let Some(next) = next_key(&key).filter(|next| next.starts_with(prefix)) else {
+			break;
+		};
+		if let Some(value) = get(&next) {
+			pairs.push((next.clone(), value));
+		}
+		key = next;
+	}
+	pairs.truncate(limit);
+	pairs
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::AccountId32;
+	use scale::Encode;
+
+	use crate::{api::prelude::*, DefaultSandbox, RuntimeOf, Sandbox};
+
+	type Runtime = RuntimeOf<DefaultSandbox>;
+	const ACCOUNT: AccountId32 = AccountId32::new([2u8; 32]);
+
+	#[test]
+	fn storage_value_works() {
+		let mut sandbox = DefaultSandbox::default();
+
+		sandbox.write_storage_value::<pallet_timestamp::Now<Runtime>, _>(42);
+		assert_eq!(sandbox.read_storage_value::<pallet_timestamp::Now<Runtime>, _>(), 42);
+		assert_eq!(sandbox.get_timestamp(), 42);
+	}
+
+	#[test]
+	fn storage_map_works() {
+		let mut sandbox = DefaultSandbox::default();
+		let mut account = sandbox.read_storage::<frame_system::Account<Runtime>, _, _>(ACCOUNT);
+		assert_eq!(account.data.free, 0);
+
+		account.data.free = 100;
+		sandbox.write_storage::<frame_system::Account<Runtime>, _, _>(ACCOUNT, account);
+		assert_eq!(sandbox.free_balance(&ACCOUNT), 100);
+	}
+
+	#[test]
+	fn raw_storage_works() {
+		let mut sandbox = DefaultSandbox::default();
+		let key = frame_system::Account::<Runtime>::hashed_key_for(ACCOUNT);
+		assert_eq!(sandbox.read_raw_storage(&key), None);
+
+		sandbox.mint_into(&ACCOUNT, 100).unwrap();
+		let account = sandbox.read_storage::<frame_system::Account<Runtime>, _, _>(ACCOUNT);
+		assert_eq!(sandbox.read_raw_storage(&key), Some(account.encode()));
+
+		sandbox.clear_raw_storage(&key);
+		assert_eq!(sandbox.free_balance(&ACCOUNT), 0);
+
+		sandbox.write_raw_storage(&key, &account.encode());
+		assert_eq!(sandbox.free_balance(&ACCOUNT), 100);
+	}
+
+	#[test]
+	fn storage_pairs_works() {
+		let mut sandbox = DefaultSandbox::default();
+		for (key, value) in [(&b"ab"[..], 1u8), (b"abc", 2), (b"abd", 3), (b"ac", 4)] {
+			sandbox.write_raw_storage(key, &[value]);
+		}
+
+		assert_eq!(
+			sandbox.storage_pairs(b"ab", None, None),
+			vec![(b"ab".to_vec(), vec![1]), (b"abc".to_vec(), vec![2]), (b"abd".to_vec(), vec![3])]
+		);
+		assert_eq!(
+			sandbox.storage_pairs(b"ab", None, Some(2)),
+			vec![(b"ab".to_vec(), vec![1]), (b"abc".to_vec(), vec![2])]
+		);
+		assert_eq!(
+			sandbox.storage_pairs(b"ab", Some(b"abc"), Some(2)),
+			vec![(b"abd".to_vec(), vec![3])]
+		);
+		assert!(sandbox.storage_pairs(b"ab", None, Some(0)).is_empty());
+	}
+
+	#[test]
+	fn child_storage_pairs_works() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.execute_with(|| {
+			sp_io::default_child_storage::set(b"child", b"k1", b"v1");
+			sp_io::default_child_storage::set(b"child", b"k2", b"v2");
+			sp_io::default_child_storage::set(b"other", b"k3", b"v3");
+		});
+
+		assert_eq!(
+			sandbox.child_storage_pairs(b"child", b"k", None, None),
+			vec![(b"k1".to_vec(), b"v1".to_vec()), (b"k2".to_vec(), b"v2".to_vec())]
+		);
+		assert_eq!(
+			sandbox.child_storage_pairs(b"child", b"", Some(b"k1"), None),
+			vec![(b"k2".to_vec(), b"v2".to_vec())]
+		);
+	}
+}
@@ -1,10 +1,12 @@
+use std::ops::RangeBounds;
+
 use frame_support::sp_runtime::{
 	traits::{Dispatchable, Saturating},
-	DispatchResultWithInfo,
+	Digest, DigestItem, DispatchResultWithInfo,
 };
-use frame_system::pallet_prelude::BlockNumberFor;
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
 
-use crate::{EventRecordOf, RuntimeCall, Sandbox};
+use crate::{indexer, indexer::BlockEvents, AccountIdFor, EventRecordOf, RuntimeCall, Sandbox};
 
 /// System API for the sandbox.
 pub trait SystemAPI {
@@ -21,6 +23,31 @@ pub trait SystemAPI {
 	/// * `n` - The number of blocks to build.
 	fn build_blocks(&mut self, n: u32) -> BlockNumberFor<Self::T>;
 
+	/// Jump `n` blocks ahead and return the new height.
+	///
+	/// Unlike [`SystemAPI::build_blocks`], the intermediate blocks are not built, so no hooks are
+	/// executed for them. Only the current block is finalized and the new one initialized, which
+	/// makes waiting for a large number of blocks cheap.
+	///
+	/// # Arguments
+	///
+	/// * `n` - The number of blocks to skip.
+	fn skip_blocks(&mut self, n: u32) -> BlockNumberFor<Self::T>;
+
+	/// The same as [`SystemAPI::skip_blocks`], but calls `on_skipped` for every intermediate
+	/// block, with the block number already set. It allows to run only the hooks (or other
+	/// per-block logic, like advancing the timestamp) that a test relies on.
+	///
+	/// # Arguments
+	///
+	/// * `n` - The number of blocks to skip.
+	/// * `on_skipped` - The logic to run for every intermediate block.
+	fn skip_blocks_with(
+		&mut self,
+		n: u32,
+		on_skipped: impl FnMut(BlockNumberFor<Self::T>),
+	) -> BlockNumberFor<Self::T>;
+
 	/// Return the current height of the chain.
 	fn block_number(&mut self) -> BlockNumberFor<Self::T>;
 
@@ -30,6 +57,37 @@ pub trait SystemAPI {
 	/// Reset the events of the current block.
 	fn reset_events(&mut self);
 
+	/// Keep the events of the last `blocks` finalized blocks, to be exported with
+	/// [`SystemAPI::export_block_events`]. Archiving is disabled by default (and if `blocks` is
+	/// zero), as keeping the events costs memory in long tests. See [`crate::indexer`].
+	///
+	/// # Arguments
+	///
+	/// * `blocks` - The number of blocks to keep.
+	fn set_event_retention(&mut self, blocks: usize);
+
+	/// Return the events of the archived finalized blocks within `blocks`, in the format consumed
+	/// by indexers. See [`crate::indexer`] and [`SystemAPI::set_event_retention`].
+	///
+	/// # Arguments
+	///
+	/// * `blocks` - The heights of the blocks to export, e.g. `start..`.
+	fn export_block_events(
+		&mut self,
+		blocks: impl RangeBounds<BlockNumberFor<Self::T>>,
+	) -> Vec<BlockEvents<Self::T>>;
+
+	/// Return the digest of the current block.
+	fn digest(&mut self) -> Digest;
+
+	/// Deposit `item` into the digest of the current block, e.g. a pre-runtime digest of a
+	/// consensus engine (`DigestItem::PreRuntime`).
+	///
+	/// # Arguments
+	///
+	/// * `item` - The digest item to deposit.
+	fn deposit_digest_item(&mut self, item: DigestItem);
+
 	/// Execute a runtime call (dispatchable).
 	///
 	/// # Arguments
@@ -41,6 +99,19 @@ pub trait SystemAPI {
 		call: RuntimeCall<Self::T>,
 		origin: Origin,
 	) -> DispatchResultWithInfo<<RuntimeCall<Self::T> as Dispatchable>::PostInfo>;
+
+	/// Execute a runtime call (dispatchable) with the root origin.
+	///
+	/// Useful for test setup requiring privileged calls that are not covered by the dedicated API
+	/// traits. See [`crate::CallBuilder`] for building calls of arbitrary pallets.
+	///
+	/// # Arguments
+	///
+	/// * `call` - The runtime call to execute.
+	fn dispatch_root(
+		&mut self,
+		call: RuntimeCall<Self::T>,
+	) -> DispatchResultWithInfo<<RuntimeCall<Self::T> as Dispatchable>::PostInfo>;
 }
 
 impl<T> SystemAPI for T
@@ -51,13 +122,15 @@ where
 	type T = T::Runtime;
 
 	fn build_block(&mut self) -> BlockNumberFor<Self::T> {
-		self.execute_with(|| {
+		let block = self.execute_with(|| {
 			let mut current_block = frame_system::Pallet::<Self::T>::block_number();
 			let block_hash = T::finalize_block(current_block);
 			current_block.saturating_inc();
 			T::initialize_block(current_block, block_hash);
 			current_block
-		})
+		});
+		self.on_block_initialized();
+		block
 	}
 
 	fn build_blocks(&mut self, n: u32) -> BlockNumberFor<Self::T> {
@@ -68,6 +141,34 @@ where
 		last_block.unwrap_or_else(|| self.block_number())
 	}
 
+	fn skip_blocks(&mut self, n: u32) -> BlockNumberFor<Self::T> {
+		self.skip_blocks_with(n, |_| {})
+	}
+
+	fn skip_blocks_with(
+		&mut self,
+		n: u32,
+		mut on_skipped: impl FnMut(BlockNumberFor<Self::T>),
+	) -> BlockNumberFor<Self::T> {
+		if n == 0 {
+			return self.block_number();
+		}
+		let block = self.execute_with(|| {
+			let mut current_block = frame_system::Pallet::<Self::T>::block_number();
+			let block_hash = T::finalize_block(current_block);
+			for _ in 1..n {
+				current_block.saturating_inc();
+				frame_system::Pallet::<Self::T>::set_block_number(current_block);
+				on_skipped(current_block);
+			}
+			current_block.saturating_inc();
+			T::initialize_block(current_block, block_hash);
+			current_block
+		});
+		self.on_block_initialized();
+		block
+	}
+
 	fn block_number(&mut self) -> BlockNumberFor<Self::T> {
 		self.execute_with(frame_system::Pallet::<Self::T>::block_number)
 	}
@@ -80,6 +181,25 @@ where
 		self.execute_with(frame_system::Pallet::<Self::T>::reset_events)
 	}
 
+	fn set_event_retention(&mut self, blocks: usize) {
+		self.execute_with(|| indexer::set_retention(blocks))
+	}
+
+	fn export_block_events(
+		&mut self,
+		blocks: impl RangeBounds<BlockNumberFor<Self::T>>,
+	) -> Vec<BlockEvents<Self::T>> {
+		self.execute_with(|| indexer::export::<T>(blocks))
+	}
+
+	fn digest(&mut self) -> Digest {
+		self.execute_with(frame_system::Pallet::<Self::T>::digest)
+	}
+
+	fn deposit_digest_item(&mut self, item: DigestItem) {
+		self.execute_with(|| frame_system::Pallet::<Self::T>::deposit_log(item))
+	}
+
 	fn runtime_call<Origin: Into<<RuntimeCall<Self::T> as Dispatchable>::RuntimeOrigin>>(
 		&mut self,
 		call: RuntimeCall<Self::T>,
@@ -87,11 +207,20 @@ where
 	) -> DispatchResultWithInfo<<RuntimeCall<Self::T> as Dispatchable>::PostInfo> {
 		self.execute_with(|| call.dispatch(origin.into()))
 	}
+
+	fn dispatch_root(
+		&mut self,
+		call: RuntimeCall<Self::T>,
+	) -> DispatchResultWithInfo<<RuntimeCall<Self::T> as Dispatchable>::PostInfo> {
+		self.runtime_call(call, RawOrigin::<AccountIdFor<Self::T>>::Root)
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use frame_support::sp_runtime::{traits::Dispatchable, AccountId32, DispatchResultWithInfo};
+	use frame_support::sp_runtime::{
+		traits::Dispatchable, AccountId32, DigestItem, DispatchResultWithInfo,
+	};
 
 	use crate::{
 		api::prelude::{BalanceAPI, SystemAPI},
@@ -135,6 +264,21 @@ mod tests {
 		assert_eq!(sandbox.free_balance(&actor), initial_balance);
 	}
 
+	#[test]
+	fn skip_blocks_works() {
+		let mut sandbox = DefaultSandbox::default();
+		let initial_block = sandbox.block_number();
+
+		assert_eq!(sandbox.skip_blocks(0), initial_block);
+		assert_eq!(sandbox.skip_blocks(100_000), initial_block + 100_000);
+		assert_eq!(sandbox.block_number(), initial_block + 100_000);
+
+		let mut skipped = vec![];
+		sandbox.skip_blocks_with(3, |block| skipped.push(block));
+		assert_eq!(skipped, vec![initial_block + 100_001, initial_block + 100_002]);
+		assert_eq!(sandbox.block_number(), initial_block + 100_003);
+	}
+
 	#[test]
 	fn runtime_call_works() {
 		let mut sandbox = DefaultSandbox::default();
@@ -149,6 +293,24 @@ mod tests {
 		assert_eq!(sandbox.free_balance(&RECIPIENT), expected_balance);
 	}
 
+	#[test]
+	fn dispatch_root_works() {
+		let mut sandbox = DefaultSandbox::default();
+		const RECIPIENT: AccountId32 = AccountId32::new([2u8; 32]);
+		let call = RuntimeCall::<RuntimeOf<DefaultSandbox>>::Balances(pallet_balances::Call::<
+			RuntimeOf<DefaultSandbox>,
+		>::force_set_balance {
+			who: RECIPIENT.into(),
+			new_free: 100,
+		});
+
+		assert!(sandbox
+			.runtime_call(call.clone(), Some(DefaultSandbox::default_actor()))
+			.is_err());
+		assert!(sandbox.dispatch_root(call).is_ok());
+		assert_eq!(sandbox.free_balance(&RECIPIENT), 100);
+	}
+
 	#[test]
 	fn current_events() {
 		let mut sandbox = DefaultSandbox::default();
@@ -181,4 +343,13 @@ mod tests {
 		make_transfer(&mut sandbox, RECIPIENT, 1).expect("Failed to make transfer");
 		assert!(!sandbox.events().is_empty());
 	}
+
+	#[test]
+	fn digest_items_can_be_deposited() {
+		let mut sandbox = DefaultSandbox::default();
+		let item = DigestItem::PreRuntime(*b"aura", 42u64.to_le_bytes().to_vec());
+
+		sandbox.deposit_digest_item(item.clone());
+		assert!(sandbox.digest().logs.contains(&item));
+	}
 }
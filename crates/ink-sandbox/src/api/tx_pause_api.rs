@@ -0,0 +1,115 @@
+use frame_support::{sp_runtime::DispatchError, BoundedVec};
+use frame_system::RawOrigin;
+use pallet_tx_pause::RuntimeCallNameOf;
+
+use crate::Sandbox;
+
+/// Transaction pause API for the sandbox, simulating a chain pausing calls during an incident.
+///
+/// Paused calls are rejected by the call filter of the runtime, i.e. when dispatched by a
+/// transaction (e.g. through [`crate::api::prelude::SystemAPI::runtime_call`]) or by a contract
+/// (through `call_runtime` or a chain extension). Calls are identified by the name of their pallet
+/// in the runtime and their own name, e.g. `("Balances", "transfer_allow_death")`.
+///
+/// Note that calls made directly through [`crate::api::prelude::ContractAPI`] bypass the call
+/// filter. Dispatch `Contracts::call` as a transaction to test a chain pausing contract calls.
+pub trait TxPauseAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_tx_pause::Config,
+{
+	/// Pauses the call `call` of the pallet `pallet`.
+	///
+	/// # Arguments
+	/// * `pallet` - The name of the pallet in the runtime.
+	/// * `call` - The name of the call.
+	fn pause_call(&mut self, pallet: &str, call: &str) -> Result<(), DispatchError>;
+
+	/// Unpauses the call `call` of the pallet `pallet`.
+	///
+	/// # Arguments
+	/// * `pallet` - The name of the pallet in the runtime.
+	/// * `call` - The name of the call.
+	fn unpause_call(&mut self, pallet: &str, call: &str) -> Result<(), DispatchError>;
+
+	/// Check if the call `call` of the pallet `pallet` is paused.
+	///
+	/// # Arguments
+	/// * `pallet` - The name of the pallet in the runtime.
+	/// * `call` - The name of the call.
+	fn is_call_paused(&mut self, pallet: &str, call: &str) -> bool;
+}
+
+impl<T> TxPauseAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_tx_pause::Config,
+{
+	fn pause_call(&mut self, pallet: &str, call: &str) -> Result<(), DispatchError> {
+		let full_name = full_name::<T::Runtime>(pallet, call)?;
+		self.execute_with(|| {
+			pallet_tx_pause::Pallet::<T::Runtime>::pause(RawOrigin::Root.into(), full_name)
+		})
+	}
+
+	fn unpause_call(&mut self, pallet: &str, call: &str) -> Result<(), DispatchError> {
+		let full_name = full_name::<T::Runtime>(pallet, call)?;
+		self.execute_with(|| {
+			pallet_tx_pause::Pallet::<T::Runtime>::unpause(RawOrigin::Root.into(), full_name)
+		})
+	}
+
+	fn is_call_paused(&mut self, pallet: &str, call: &str) -> bool {
+		self.execute_with(|| {
+			pallet_tx_pause::Pallet::<T::Runtime>::is_paused_unbound(
+				pallet.as_bytes().to_vec(),
+				call.as_bytes().to_vec(),
+			)
+		})
+	}
+}
+
+/// The full name of the call `call` of the pallet `pallet`, as identified by the pallet.
+fn full_name<R: pallet_tx_pause::Config>(
+	pallet: &str,
+	call: &str,
+) -> Result<RuntimeCallNameOf<R>, DispatchError> {
+	let bounded = |name: &str| {
+		BoundedVec::try_from(name.as_bytes().to_vec())
+			.map_err(|_| DispatchError::Other("Call name is too long"))
+	};
+	Ok((bounded(pallet)?, bounded(call)?))
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::AccountId32;
+
+	use super::*;
+	use crate::{api::prelude::*, ParachainSandbox, RuntimeCall, RuntimeOf};
+
+	#[test]
+	fn paused_calls_are_filtered() {
+		let mut sandbox = ParachainSandbox::default();
+		let transfer = RuntimeCall::<RuntimeOf<ParachainSandbox>>::Balances(
+			pallet_balances::Call::transfer_allow_death {
+				dest: AccountId32::new([2u8; 32]).into(),
+				value: 100,
+			},
+		);
+
+		sandbox.pause_call("Balances", "transfer_allow_death").unwrap();
+		assert!(sandbox.is_call_paused("Balances", "transfer_allow_death"));
+		assert!(!sandbox.is_call_paused("Balances", "transfer_keep_alive"));
+		assert_eq!(
+			sandbox
+				.runtime_call(transfer.clone(), Some(ParachainSandbox::default_actor()))
+				.map_err(|e| e.error),
+			Err(frame_system::Error::<RuntimeOf<ParachainSandbox>>::CallFiltered.into())
+		);
+
+		sandbox.unpause_call("Balances", "transfer_allow_death").unwrap();
+		assert!(!sandbox.is_call_paused("Balances", "transfer_allow_death"));
+		assert!(sandbox.runtime_call(transfer, Some(ParachainSandbox::default_actor())).is_ok());
+	}
+}
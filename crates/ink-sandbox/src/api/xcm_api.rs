@@ -0,0 +1,175 @@
+use frame_support::{sp_runtime::DispatchError, weights::Weight};
+use pallet_xcm::QueryStatus;
+use sp_externalities::{decl_extension, ExternalitiesExt};
+use xcm::latest::{Location, QueryId, Response, XcmContext};
+use xcm_executor::traits::OnResponse;
+
+use crate::Sandbox;
+
+/// A response delivery, bound to the runtime it was injected into.
+type Delivery = Box<dyn FnOnce() + Send>;
+
+decl_extension! {
+	/// The XCM responses injected into the sandbox, waiting for the next block.
+	pub(crate) struct PendingXcmResponses(Vec<Delivery>);
+}
+
+/// XCM API for the sandbox, simulating the responses of other chains to XCM queries.
+///
+/// Contracts awaiting a `QueryResponse` (e.g. after sending a query through pallet-xcm) can be
+/// tested by injecting the response, which is delivered at the start of the next block, as the
+/// response of the responder of the query would be.
+pub trait XcmAPI<T: Sandbox>
+where
+	T: Sandbox,
+	T::Runtime: pallet_xcm::Config,
+{
+	/// Injects the response to the query `query_id`, to be delivered at the start of the next
+	/// block.
+	///
+	/// Fails if the query is not pending, or if the sandbox wasn't created with
+	/// [`crate::create_parachain_sandbox`].
+	///
+	/// # Arguments
+	/// * `query_id` - The id of the query.
+	/// * `response` - The response to the query.
+	fn inject_xcm_response(
+		&mut self,
+		query_id: QueryId,
+		response: Response,
+	) -> Result<(), DispatchError>;
+
+	/// Delivers the injected responses right away, instead of at the start of the next block.
+	fn deliver_xcm_responses(&mut self);
+}
+
+impl<T> XcmAPI<T> for T
+where
+	T: Sandbox,
+	T::Runtime: pallet_xcm::Config,
+{
+	fn inject_xcm_response(
+		&mut self,
+		query_id: QueryId,
+		response: Response,
+	) -> Result<(), DispatchError> {
+		let Some((responder, querier)) =
+			self.execute_with(|| pending_query::<T::Runtime>(query_id))
+		else {
+			return Err(DispatchError::Other("Query is not pending"));
+		};
+
+		let delivery: Delivery = Box::new(move || {
+			let context = XcmContext::with_message_id([0; 32]);
+			<pallet_xcm::Pallet<T::Runtime> as OnResponse>::on_response(
+				&responder,
+				query_id,
+				querier.as_ref(),
+				response,
+				Weight::MAX,
+				&context,
+			);
+		});
+		self.execute_with(|| {
+			sp_externalities::with_externalities(|ext| {
+				ext.extension::<PendingXcmResponses>().map(|pending| pending.0.push(delivery))
+			})
+			.flatten()
+			.ok_or(DispatchError::Other("XCM responses can't be injected into this sandbox"))
+		})
+	}
+
+	fn deliver_xcm_responses(&mut self) {
+		self.execute_with(deliver_pending_responses)
+	}
+}
+
+/// Delivers the injected XCM responses. Called at the start of every block.
+///
+/// Must be called within the externalities.
+pub(crate) fn deliver_pending_responses() {
+	let deliveries = sp_externalities::with_externalities(|ext| {
+		ext.extension::<PendingXcmResponses>()
+			.map(|pending| std::mem::take(&mut pending.0))
+			.unwrap_or_default()
+	})
+	.unwrap_or_default();
+	deliveries.into_iter().for_each(|delivery| delivery());
+}
+
+/// Returns the responder and the expected querier of the query, if it is pending.
+fn pending_query<R: pallet_xcm::Config>(query_id: QueryId) -> Option<(Location, Option<Location>)> {
+	match pallet_xcm::Pallet::<R>::query(&query_id)? {
+		QueryStatus::Pending { responder, maybe_match_querier, .. } => Some((
+			Location::try_from(responder).ok()?,
+			maybe_match_querier.and_then(|querier| Location::try_from(querier).ok()),
+		)),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use xcm_executor::traits::QueryHandler;
+
+	use super::*;
+	use crate::{api::prelude::SystemAPI, ParachainSandbox, RuntimeOf};
+
+	type Runtime = RuntimeOf<ParachainSandbox>;
+
+	const RESPONSE: Response = Response::ExecutionResult(None);
+
+	fn new_query(sandbox: &mut ParachainSandbox) -> QueryId {
+		sandbox.execute_with(|| {
+			<pallet_xcm::Pallet<Runtime> as QueryHandler>::new_query(
+				Location::parent(),
+				100,
+				Location::here(),
+			)
+		})
+	}
+
+	fn is_ready(sandbox: &mut ParachainSandbox, query_id: QueryId) -> bool {
+		sandbox.execute_with(|| {
+			matches!(
+				pallet_xcm::Pallet::<Runtime>::query(&query_id),
+				Some(QueryStatus::Ready { response, .. }) if Response::try_from(response) == Ok(RESPONSE)
+			)
+		})
+	}
+
+	#[test]
+	fn injected_responses_are_delivered_on_the_next_block() {
+		let mut sandbox = ParachainSandbox::default();
+		let query_id = new_query(&mut sandbox);
+
+		assert_eq!(sandbox.inject_xcm_response(query_id, RESPONSE), Ok(()));
+		assert!(!is_ready(&mut sandbox, query_id));
+
+		sandbox.build_block();
+		assert!(is_ready(&mut sandbox, query_id));
+	}
+
+	#[test]
+	fn injected_responses_can_be_delivered_right_away() {
+		let mut sandbox = ParachainSandbox::default();
+		let query_id = new_query(&mut sandbox);
+
+		assert_eq!(sandbox.inject_xcm_response(query_id, RESPONSE), Ok(()));
+		sandbox.deliver_xcm_responses();
+		assert!(is_ready(&mut sandbox, query_id));
+	}
+
+	#[test]
+	fn only_pending_queries_can_be_answered() {
+		let mut sandbox = ParachainSandbox::default();
+		let not_pending = Err(DispatchError::Other("Query is not pending"));
+
+		assert_eq!(sandbox.inject_xcm_response(42, RESPONSE), not_pending);
+
+		let query_id = new_query(&mut sandbox);
+		assert_eq!(sandbox.inject_xcm_response(query_id, RESPONSE), Ok(()));
+		sandbox.deliver_xcm_responses();
+		assert_eq!(sandbox.inject_xcm_response(query_id, RESPONSE), not_pending);
+	}
+}
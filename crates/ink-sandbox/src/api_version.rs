@@ -0,0 +1,78 @@
+//! The API version of the contracts pallet of a sandbox, i.e. the revision of the stable host
+//! functions it provides, and the versions required by the contracts of the ink! releases.
+//!
+//! A contract importing a host function the pallet doesn't provide is only rejected (with an
+//! opaque `CodeRejected`) once deployed, so the bundles are checked against the version of the
+//! sandbox first:
+//!
+//! ```rust, ignore
+//! let ink = InkVersion::new(5, 1, 0);
+//! assert!(required_api_version(&ink) <= contracts_api_version::<MinimalRuntime>());
+//! ```
+
+use std::fmt;
+
+use frame_support::traits::Get;
+use scale::{Decode, Encode};
+
+/// A release of ink!, as recorded in the metadata of the contracts built with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InkVersion {
+	/// The major version.
+	pub major: u64,
+	/// The minor version.
+	pub minor: u64,
+	/// The patch version.
+	pub patch: u64,
+}
+
+impl InkVersion {
+	/// Creates the version `major.minor.patch`.
+	pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+		Self { major, minor, patch }
+	}
+}
+
+impl fmt::Display for InkVersion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+	}
+}
+
+/// The compatibility matrix: the first release of ink! of every API version of the contracts
+/// pallet required by its contracts, in order. A release requires the version of the latest entry
+/// not after it.
+pub const INK_API_VERSIONS: &[(InkVersion, u16)] = &[
+	(InkVersion::new(4, 0, 0), 1),
+	// `xcm_execute` and `xcm_send`.
+	(InkVersion::new(5, 1, 0), 2),
+];
+
+/// Returns the API version of the contracts pallet of the runtime `R`.
+pub fn contracts_api_version<R: pallet_contracts::Config>() -> u16 {
+	let version = <R::ApiVersion as Get<pallet_contracts::ApiVersion>>::get();
+	u16::decode(&mut &version.encode()[..]).expect("The API version is encoded as a `u16`")
+}
+
+/// Returns the API version of the contracts pallet required by the contracts built with `ink`.
+pub fn required_api_version(ink: &InkVersion) -> u16 {
+	INK_API_VERSIONS
+		.iter()
+		.take_while(|(release, _)| release <= ink)
+		.last()
+		.map_or(0, |(_, version)| *version)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{required_api_version, InkVersion};
+
+	#[test]
+	fn releases_require_the_version_of_their_latest_entry() {
+		assert_eq!(required_api_version(&InkVersion::new(3, 4, 0)), 0);
+		assert_eq!(required_api_version(&InkVersion::new(4, 3, 0)), 1);
+		assert_eq!(required_api_version(&InkVersion::new(5, 0, 1)), 1);
+		assert_eq!(required_api_version(&InkVersion::new(5, 1, 0)), 2);
+		assert_eq!(required_api_version(&InkVersion::new(5, 1, 1)), 2);
+	}
+}
@@ -0,0 +1,177 @@
+//! Building runtime calls of arbitrary pallets from their names.
+
+use std::fmt;
+
+use frame_metadata::RuntimeMetadata;
+use scale::{DecodeAll, Encode};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef};
+
+use crate::{RuntimeCall, Sandbox};
+
+/// Error returned when a call cannot be built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallBuilderError {
+	/// The runtime metadata version is not supported.
+	UnsupportedMetadata,
+	/// There is no pallet with the given name in the runtime.
+	PalletNotFound(String),
+	/// The pallet has no call with the given name.
+	CallNotFound { pallet: String, call: String },
+	/// The arguments don't match the call's parameters.
+	InvalidArguments(String),
+}
+
+impl fmt::Display for CallBuilderError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnsupportedMetadata => write!(f, "Unsupported runtime metadata version"),
+			Self::PalletNotFound(pallet) => write!(f, "Pallet `{pallet}` not found in the runtime"),
+			Self::CallNotFound { pallet, call } =>
+				write!(f, "Call `{call}` not found in pallet `{pallet}`"),
+			Self::InvalidArguments(error) => write!(f, "Invalid call arguments: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for CallBuilderError {}
+
+/// Builder for runtime calls of any pallet of the sandbox runtime.
+///
+/// The call is looked up by the pallet and call names in the runtime metadata, so any pallet
+/// function can be invoked (e.g. with [`crate::api::system_api::SystemAPI::dispatch_root`])
+/// without a dedicated API trait. Arguments must be provided in the order of the call
+/// parameters.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let call = CallBuilder::new("Balances", "force_set_balance")
+///     .arg(MultiAddress::<AccountId32, ()>::Id(BOB))
+///     .arg(Compact(100u128))
+///     .build::<DefaultSandbox>()?;
+/// sandbox.dispatch_root(call)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CallBuilder {
+	pallet: String,
+	call: String,
+	args: Vec<u8>,
+}
+
+impl CallBuilder {
+	/// Starts building the call `call` of the pallet `pallet`.
+	pub fn new(pallet: &str, call: &str) -> Self {
+		Self { pallet: pallet.to_string(), call: call.to_string(), args: vec![] }
+	}
+
+	/// Appends the next argument of the call.
+	pub fn arg(mut self, arg: impl Encode) -> Self {
+		arg.encode_to(&mut self.args);
+		self
+	}
+
+	/// Builds the runtime call for the sandbox `S`.
+	pub fn build<S: Sandbox>(self) -> Result<RuntimeCall<S::Runtime>, CallBuilderError> {
+		let metadata = S::get_metadata();
+		let (types, pallet) =
+			match &metadata.1 {
+				RuntimeMetadata::V14(metadata) => (
+					&metadata.types,
+					metadata.pallets.iter().find(|pallet| pallet.name == self.pallet).map(
+						|pallet| (pallet.index, pallet.calls.as_ref().map(|calls| calls.ty.id)),
+					),
+				),
+				RuntimeMetadata::V15(metadata) => (
+					&metadata.types,
+					metadata.pallets.iter().find(|pallet| pallet.name == self.pallet).map(
+						|pallet| (pallet.index, pallet.calls.as_ref().map(|calls| calls.ty.id)),
+					),
+				),
+				_ => return Err(CallBuilderError::UnsupportedMetadata),
+			};
+
+		let (pallet_index, calls) =
+			pallet.ok_or_else(|| CallBuilderError::PalletNotFound(self.pallet.clone()))?;
+		let call_index =
+			calls.and_then(|calls| call_index(types, calls, &self.call)).ok_or_else(|| {
+				CallBuilderError::CallNotFound {
+					pallet: self.pallet.clone(),
+					call: self.call.clone(),
+				}
+			})?;
+
+		let encoded = [vec![pallet_index, call_index], self.args].concat();
+		<RuntimeCall<S::Runtime> as DecodeAll>::decode_all(&mut &encoded[..])
+			.map_err(|e| CallBuilderError::InvalidArguments(e.to_string()))
+	}
+}
+
+fn call_index(types: &PortableRegistry, calls: u32, call: &str) -> Option<u8> {
+	match &types.resolve(calls)?.type_def {
+		TypeDef::<PortableForm>::Variant(calls) => calls
+			.variants
+			.iter()
+			.find(|variant| variant.name == call)
+			.map(|variant| variant.index),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::{AccountId32, MultiAddress};
+	use scale::Compact;
+
+	use super::*;
+	use crate::{
+		api::prelude::{BalanceAPI, SystemAPI},
+		DefaultSandbox, RuntimeOf,
+	};
+
+	const RECIPIENT: AccountId32 = AccountId32::new([2u8; 32]);
+
+	#[test]
+	fn build_works() {
+		let call = CallBuilder::new("Balances", "force_set_balance")
+			.arg(MultiAddress::<AccountId32, ()>::Id(RECIPIENT))
+			.arg(Compact(100u128))
+			.build::<DefaultSandbox>();
+
+		assert_eq!(
+			call,
+			Ok(RuntimeCall::<RuntimeOf<DefaultSandbox>>::Balances(pallet_balances::Call::<
+				RuntimeOf<DefaultSandbox>,
+			>::force_set_balance {
+				who: RECIPIENT.into(),
+				new_free: 100,
+			}))
+		);
+
+		let mut sandbox = DefaultSandbox::default();
+		assert!(sandbox.dispatch_root(call.unwrap()).is_ok());
+		assert_eq!(sandbox.free_balance(&RECIPIENT), 100);
+	}
+
+	#[test]
+	fn unknown_names_are_rejected() {
+		assert_eq!(
+			CallBuilder::new("Unknown", "call").build::<DefaultSandbox>(),
+			Err(CallBuilderError::PalletNotFound("Unknown".to_string()))
+		);
+		assert_eq!(
+			CallBuilder::new("Balances", "unknown").build::<DefaultSandbox>(),
+			Err(CallBuilderError::CallNotFound {
+				pallet: "Balances".to_string(),
+				call: "unknown".to_string()
+			})
+		);
+	}
+
+	#[test]
+	fn invalid_arguments_are_rejected() {
+		let call = CallBuilder::new("Balances", "force_set_balance")
+			.arg(MultiAddress::<AccountId32, ()>::Id(RECIPIENT))
+			.build::<DefaultSandbox>();
+		assert!(matches!(call, Err(CallBuilderError::InvalidArguments(_))));
+	}
+}
@@ -0,0 +1,70 @@
+//! Chains of nested cross-contract calls, for testing contracts at and beyond the maximum call
+//! depth of the sandbox (see `CALL_STACK_DEPTH` in [`crate::create_sandbox`]).
+//!
+//! A chain is made of forwarder contracts, each forwarding every call (with its input) to the
+//! next one, down to the target contract. Calling the entry of a chain of `n` forwarders calls the
+//! target at depth `n`:
+//!
+//! ```rust, ignore
+//! let entry = sandbox.deploy_call_chain(contract, CALL_STACK_DEPTH as u32, ALICE)?;
+//! // The call to `contract` is the last nested call the sandbox allows.
+//! sandbox.call_contract(entry, 0, input, ALICE, gas_limit, None, Determinism::Enforced);
+//! ```
+
+/// Contract storing the address of its callee (the input of its constructor), and forwarding
+/// every call to it. Returns the output of the callee, and traps if the callee failed.
+const FORWARDER: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal1" "get_storage" (func $seal_get_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal1" "call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4): the storage key of the callee ([0, 0, 0, 0])
+	;; [16, 32): the transferred value (0)
+	;; [32, 64): the address of the callee
+	;; [64, 68): the length of the address of the callee
+	;; [68, 72): the length of the output of the callee
+	;; [96, ..): the output of the callee
+
+	(func (export "deploy")
+		(i32.store (i32.const 64) (i32.const 32))
+		(call $seal_input (i32.const 32) (i32.const 64))
+		(drop
+			(call $seal_set_storage (i32.const 0) (i32.const 4) (i32.const 32) (i32.const 32))
+		)
+	)
+
+	(func (export "call")
+		(i32.store (i32.const 64) (i32.const 32))
+		(drop
+			(call $seal_get_storage (i32.const 0) (i32.const 4) (i32.const 32) (i32.const 64))
+		)
+
+		(i32.store (i32.const 68) (i32.const 0x8000))
+		(if
+			(call $seal_call
+				(i32.const 1)	;; flags: forward the input
+				(i32.const 32)	;; the callee
+				(i64.const 0)	;; all the gas left
+				(i32.const 16)	;; the transferred value
+				(i32.const 0)	;; the input (forwarded)
+				(i32.const 0)	;; the length of the input (forwarded)
+				(i32.const 96)	;; the output
+				(i32.const 68)	;; the length of the output
+			)
+			(then unreachable)
+		)
+
+		(call $seal_return (i32.const 0) (i32.const 96) (i32.load (i32.const 68)))
+	)
+)
+"#;
+
+/// Returns the code of the forwarder contracts making up the call chains.
+pub fn forwarder_code() -> Vec<u8> {
+	wat::parse_str(FORWARDER).expect("The forwarder is valid WAT")
+}
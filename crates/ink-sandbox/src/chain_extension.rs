@@ -0,0 +1,85 @@
+//! Tracing of the chain-extension calls of contracts, e.g. for reporting which functions of a
+//! chain-extension API (like the Pop API) a test suite exercised.
+//!
+//! The sandboxes created with [`crate::create_sandbox`] wrap their chain extension into
+//! [`TracingChainExtension`], which notes the identifier of every call before delegating it. Other
+//! runtimes can use the wrapper as their chain extension as well. The calls are noted for the whole
+//! process, since a coverage report spans all the tests (and sandboxes) of a test binary:
+//!
+//! ```rust, ignore
+//! session.call::<_, ()>("transfer", &[BOB.to_string(), "10"], None)??;
+//! assert!(traced_calls().contains(&ChainExtensionCall::from_id(TRANSFER)));
+//! ```
+
+use std::{
+	collections::BTreeSet,
+	sync::{Mutex, PoisonError},
+};
+
+use pallet_contracts::{
+	chain_extension::{ChainExtension, Environment, Ext, InitState, RetVal},
+	Config,
+};
+
+/// The chain-extension calls of all the sandboxes of the process.
+static TRACED_CALLS: Mutex<BTreeSet<ChainExtensionCall>> = Mutex::new(BTreeSet::new());
+
+/// The identifier of a chain-extension call, as passed by the contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChainExtensionCall {
+	/// The identifier of the chain extension (the upper 16 bits of the identifier).
+	pub ext_id: u16,
+	/// The identifier of the function (the lower 16 bits of the identifier).
+	pub func_id: u16,
+}
+
+impl ChainExtensionCall {
+	/// Splits the 32-bit identifier of a chain-extension method, as declared by ink! contracts.
+	pub fn from_id(id: u32) -> Self {
+		Self { ext_id: (id >> 16) as u16, func_id: id as u16 }
+	}
+
+	/// Returns the 32-bit identifier of the chain-extension method.
+	pub fn id(&self) -> u32 {
+		(u32::from(self.ext_id) << 16) | u32::from(self.func_id)
+	}
+}
+
+/// Chain extension noting every call (see [`traced_calls`]) before delegating it to `E`.
+#[derive(Default)]
+pub struct TracingChainExtension<E>(E);
+
+impl<T: Config, E: ChainExtension<T>> ChainExtension<T> for TracingChainExtension<E> {
+	fn call<X: Ext<T = T>>(
+		&mut self,
+		env: Environment<X, InitState>,
+	) -> pallet_contracts::chain_extension::Result<RetVal> {
+		TRACED_CALLS
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(ChainExtensionCall { ext_id: env.ext_id(), func_id: env.func_id() });
+		self.0.call(env)
+	}
+
+	fn enabled() -> bool {
+		E::enabled()
+	}
+}
+
+/// Returns the chain-extension calls of all the sandboxes of the process so far, whether they
+/// succeeded or not.
+pub fn traced_calls() -> BTreeSet<ChainExtensionCall> {
+	TRACED_CALLS.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ChainExtensionCall;
+
+	#[test]
+	fn identifiers_are_split() {
+		let call = ChainExtensionCall::from_id(u32::from_le_bytes([1, 0, 150, 3]));
+		assert_eq!(call, ChainExtensionCall { ext_id: 0x0396, func_id: 0x0001 });
+		assert_eq!(call.id(), u32::from_le_bytes([1, 0, 150, 3]));
+	}
+}
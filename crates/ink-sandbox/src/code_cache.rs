@@ -0,0 +1,157 @@
+//! Process-wide cache of validated contract code.
+//!
+//! Uploading a contract validates and instruments its wasm blob, which is by far the most
+//! expensive part of a deployment. Test suites usually deploy the same bundles in every test, so
+//! the resulting code entries are cached and written directly to the storage of any other sandbox
+//! instance uploading the same code, skipping its validation.
+//!
+//! The entries are therefore keyed on every input of the validation and of the upload deposit:
+//! - the runtime, which fixes its chain extension,
+//! - the code hash and the determinism of the upload,
+//! - the `DepositPerByte`, `DepositPerItem`, `MaxCodeLen`, `Schedule` and `UnsafeUnstableInterface`
+//!   of the contracts pallet, which sandboxes may configure per instance.
+
+use std::{
+	any::TypeId,
+	collections::HashMap,
+	sync::{Mutex, OnceLock, PoisonError},
+};
+
+use frame_support::{
+	sp_runtime::traits::{Bounded, Hash},
+	storage::{storage_prefix, unhashed},
+	traits::{fungible::MutateHold, Get, PalletInfoAccess},
+};
+use pallet_contracts::{CodeUploadReturnValue, Config, Determinism, Error, Event, HoldReason};
+use scale::{Decode, Encode, HasCompact};
+use sp_io::hashing::blake2_256;
+
+use crate::{AccountIdFor, BalanceFor};
+
+/// Mirror of the (private) `CodeInfo` of the contracts pallet.
+#[derive(Encode, Decode)]
+pub(crate) struct CodeInfo<AccountId, Balance: HasCompact> {
+	pub(crate) owner: AccountId,
+	#[codec(compact)]
+	pub(crate) deposit: Balance,
+	#[codec(compact)]
+	pub(crate) refcount: u64,
+	pub(crate) determinism: Determinism,
+	pub(crate) code_len: u32,
+}
+
+/// The raw storage entries of an uploaded code.
+#[derive(Clone)]
+struct CachedCode {
+	pristine_code: Vec<u8>,
+	code_info: Vec<u8>,
+}
+
+type CacheKey = (TypeId, [u8; 32]);
+
+static CODE_CACHE: OnceLock<Mutex<HashMap<CacheKey, CachedCode>>> = OnceLock::new();
+
+fn cache() -> std::sync::MutexGuard<'static, HashMap<CacheKey, CachedCode>> {
+	CODE_CACHE
+		.get_or_init(Default::default)
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the key of the code under `code_hash` in the current externalities, see the module
+/// documentation for its inputs.
+fn cache_key<T: Config>(code_hash: &T::Hash, determinism: Determinism) -> CacheKey {
+	let key = (
+		code_hash,
+		determinism,
+		<T as Config>::DepositPerByte::get(),
+		<T as Config>::DepositPerItem::get(),
+		<T as Config>::MaxCodeLen::get(),
+		<T as Config>::Schedule::get(),
+		<T as Config>::UnsafeUnstableInterface::get(),
+	);
+	(TypeId::of::<T>(), blake2_256(&key.encode()))
+}
+
+fn storage_key<T: Config>(item: &[u8], code_hash: &T::Hash) -> Vec<u8> {
+	[
+		storage_prefix(<pallet_contracts::Pallet<T> as PalletInfoAccess>::name().as_bytes(), item)
+			.as_slice(),
+		&code_hash.encode(),
+	]
+	.concat()
+}
+
+/// Returns the hash under which the contracts pallet stores `code`.
+pub(crate) fn code_hash<T: Config>(code: &[u8]) -> T::Hash {
+	T::Hashing::hash(code)
+}
+
+/// Returns the info of the code stored under `code_hash` in the current externalities, if any.
+///
+/// Must be called within the externalities.
+pub(crate) fn code_info<T: Config>(
+	code_hash: &T::Hash,
+) -> Option<CodeInfo<AccountIdFor<T>, BalanceFor<T>>> {
+	unhashed::get(&storage_key::<T>(b"CodeInfoOf", code_hash))
+}
+
+/// Caches the code stored under `code_hash` in the current externalities, if any.
+///
+/// Must be called within the externalities.
+pub(crate) fn store<T: Config>(code_hash: &T::Hash, determinism: Determinism) {
+	let (Some(pristine_code), Some(code_info)) = (
+		unhashed::get_raw(&storage_key::<T>(b"PristineCode", code_hash)),
+		unhashed::get_raw(&storage_key::<T>(b"CodeInfoOf", code_hash)),
+	) else {
+		return;
+	};
+	cache()
+		.entry(cache_key::<T>(code_hash, determinism))
+		.or_insert(CachedCode { pristine_code, code_info });
+}
+
+/// Uploads the cached code under `code_hash` on behalf of `origin`, skipping its validation.
+///
+/// Returns `None` if the code isn't cached or is already stored in the current externalities, in
+/// which case the regular upload has to be performed. Otherwise, the upload deposit is held from
+/// `origin` and the `CodeStored` event is emitted, as the contracts pallet would do.
+///
+/// Must be called within the externalities.
+pub(crate) fn upload<T: Config>(
+	code_hash: &T::Hash,
+	origin: &AccountIdFor<T>,
+	storage_deposit_limit: Option<BalanceFor<T>>,
+	determinism: Determinism,
+) -> Option<Result<CodeUploadReturnValue<T::Hash, BalanceFor<T>>, Error<T>>> {
+	let info_key = storage_key::<T>(b"CodeInfoOf", code_hash);
+	if unhashed::exists(&info_key) {
+		return None;
+	}
+	let cached = cache().get(&cache_key::<T>(code_hash, determinism))?.clone();
+	let mut code_info =
+		CodeInfo::<AccountIdFor<T>, BalanceFor<T>>::decode(&mut &cached.code_info[..]).ok()?;
+
+	let deposit = code_info.deposit;
+	if deposit > storage_deposit_limit.unwrap_or_else(Bounded::max_value) {
+		return Some(Err(Error::<T>::StorageDepositLimitExhausted));
+	}
+	if T::Currency::hold(&HoldReason::CodeUploadDepositReserve.into(), origin, deposit).is_err() {
+		return Some(Err(Error::<T>::StorageDepositNotEnoughFunds));
+	}
+
+	code_info.owner = origin.clone();
+	code_info.refcount = 0;
+	unhashed::put_raw(&storage_key::<T>(b"PristineCode", code_hash), &cached.pristine_code);
+	unhashed::put_raw(&info_key, &code_info.encode());
+
+	let event: <T as Config>::RuntimeEvent = Event::<T>::CodeStored {
+		code_hash: *code_hash,
+		deposit_held: deposit,
+		uploader: origin.clone(),
+	}
+	.into();
+	frame_system::Pallet::<T>::deposit_event(event);
+
+	Some(Ok(CodeUploadReturnValue { code_hash: *code_hash, deposit }))
+}
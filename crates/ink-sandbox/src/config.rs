@@ -0,0 +1,300 @@
+//! Configuration of the sandboxes created with [`crate::create_sandbox`]: the genesis balances and
+//! assets, the production of blocks, the default gas limit and the schedule of the contracts
+//! pallet.
+//!
+//! The configuration is serializable, so the same file can configure a sandbox in code
+//! (`with_config`), in `drink-cli --config <file>` and in `#[drink::test(config = "<file>")]`:
+//!
+//! ```toml
+//! schedule = "development-lenient"
+//! wall_clock = false
+//! balances = [["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", 1000000000000000]]
+//!
+//! [default_gas_limit]
+//! ref_time = 500000000000
+//! proof_size = 4194304
+//!
+//! [block]
+//! genesis_timestamp = 1700000000
+//! block_time = 6
+//!
+//! [[assets]]
+//! id = 1
+//! owner = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! accounts = [["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", 1000]]
+//! ```
+//!
+//! Omitted fields keep their default value (see [`SandboxConfig::default`]).
+
+use std::{fmt, path::Path, time::SystemTime};
+
+use frame_support::{sp_runtime::AccountId32, weights::Weight};
+use scale::Encode;
+use serde::{Deserialize, Serialize};
+use sp_externalities::{decl_extension, ExternalitiesExt};
+
+use crate::{DefaultSandbox, Sandbox, SandboxParameters, ScheduleParameters};
+
+/// Unit base for balances.
+pub const UNIT: u128 = 10_000_000_000;
+/// Default initial balance for the default account.
+pub const INIT_AMOUNT: u128 = 100_000_000 * UNIT;
+/// Default account.
+pub const DEFAULT_ACCOUNT: AccountId32 = AccountId32::new([1u8; 32]);
+
+/// Error returned when a sandbox configuration cannot be loaded or applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+	/// The configuration file cannot be read.
+	Io(String),
+	/// The configuration is malformed.
+	Invalid(String),
+	/// There is no schedule preset with the given name.
+	UnknownSchedule(String),
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(error) => write!(f, "Failed to read the sandbox configuration: {error}"),
+			Self::Invalid(error) => write!(f, "Invalid sandbox configuration: {error}"),
+			Self::UnknownSchedule(name) => write!(
+				f,
+				"Unknown schedule preset `{name}`, expected one of {:?}",
+				ScheduleParameters::PRESETS
+			),
+		}
+	}
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The configuration of a sandbox. See [`crate::config`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SandboxConfig {
+	/// The genesis balances, the one of the default account included.
+	pub balances: Vec<(AccountId32, u128)>,
+	/// The trust-backed assets existing at genesis.
+	pub assets: Vec<AssetConfig>,
+	/// The timestamps of the blocks, if they don't follow the wall clock.
+	pub block: BlockConfig,
+	/// The gas limit of the contract interactions for which none is specified.
+	pub default_gas_limit: Weight,
+	/// The name of the schedule preset of the contracts pallet, one of
+	/// [`ScheduleParameters::PRESETS`].
+	pub schedule: String,
+	/// Whether the timestamps of the blocks follow the wall clock, or `block` (for reproducible
+	/// timestamps).
+	pub wall_clock: bool,
+}
+
+impl Default for SandboxConfig {
+	fn default() -> Self {
+		Self {
+			balances: vec![(DEFAULT_ACCOUNT, INIT_AMOUNT)],
+			assets: vec![],
+			block: BlockConfig::default(),
+			default_gas_limit: DefaultSandbox::default_gas_limit(),
+			schedule: "default".to_string(),
+			wall_clock: true,
+		}
+	}
+}
+
+impl SandboxConfig {
+	/// Loads the configuration from the TOML file under `path`.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+		let toml = std::fs::read_to_string(path).map_err(|e| ConfigError::Io(e.to_string()))?;
+		Self::from_toml(&toml)
+	}
+
+	/// Parses the configuration from `toml`, checking its schedule preset.
+	pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+		let config: Self = toml::from_str(toml).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+		config.schedule_parameters()?;
+		Ok(config)
+	}
+
+	/// Returns the schedule preset of the configuration.
+	pub fn schedule_parameters(&self) -> Result<ScheduleParameters, ConfigError> {
+		ScheduleParameters::preset(&self.schedule)
+			.ok_or_else(|| ConfigError::UnknownSchedule(self.schedule.clone()))
+	}
+
+	/// Returns the runtime parameters of the configuration, the others being the default ones.
+	pub fn parameters(&self) -> Result<SandboxParameters, ConfigError> {
+		Ok(SandboxParameters::default()
+			.schedule(self.schedule_parameters()?)
+			.default_gas_limit(self.default_gas_limit))
+	}
+
+	/// Returns the timestamps of the blocks, if they don't follow the wall clock.
+	pub(crate) fn timestamps(&self) -> Option<BlockConfig> {
+		(!self.wall_clock).then(|| self.block.clone())
+	}
+}
+
+/// A trust-backed asset existing at genesis.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetConfig {
+	/// The identifier of the asset.
+	pub id: u32,
+	/// The owner of the asset.
+	pub owner: AccountId32,
+	/// Whether the asset is sufficient, i.e. whether holding it is enough for an account to exist.
+	/// Holders of non-sufficient assets must have a balance.
+	#[serde(default)]
+	pub is_sufficient: bool,
+	/// The minimum balance of the asset.
+	#[serde(default = "AssetConfig::default_min_balance")]
+	pub min_balance: u128,
+	/// The genesis balances of the asset.
+	#[serde(default)]
+	pub accounts: Vec<(AccountId32, u128)>,
+}
+
+impl AssetConfig {
+	fn default_min_balance() -> u128 {
+		1
+	}
+}
+
+/// The timestamps of the blocks, when they don't follow the wall clock. Like the wall-clock ones,
+/// they are in seconds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockConfig {
+	/// The timestamp of the first block.
+	pub genesis_timestamp: u64,
+	/// The time between two blocks.
+	pub block_time: u64,
+}
+
+impl Default for BlockConfig {
+	fn default() -> Self {
+		Self { genesis_timestamp: 0, block_time: 6 }
+	}
+}
+
+decl_extension! {
+	/// The timestamps of the blocks, if they don't follow the wall clock.
+	pub struct BlockTimestamps(Option<BlockConfig>);
+}
+
+/// Returns the timestamp of the block `height`: according to the configuration of the sandbox if
+/// the timestamps don't follow the wall clock, the current time otherwise.
+pub(crate) fn timestamp_of(height: u64) -> u64 {
+	let timestamps = sp_externalities::with_externalities(|ext| {
+		ext.extension::<BlockTimestamps>().and_then(|timestamps| timestamps.0.clone())
+	})
+	.flatten();
+	match timestamps {
+		Some(block) => block
+			.genesis_timestamp
+			.saturating_add(height.saturating_sub(1).saturating_mul(block.block_time)),
+		None => SystemTime::now()
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.expect("Time went backwards")
+			.as_secs(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::weights::Weight;
+	use pallet_assets::Instance1;
+
+	use super::{AssetConfig, ConfigError, SandboxConfig, DEFAULT_ACCOUNT};
+	use crate::{
+		api::prelude::*, AccountId32, DefaultSandbox, Sandbox, ScheduleParameters, Ss58Codec,
+	};
+
+	const BOB: AccountId32 = AccountId32::new([2u8; 32]);
+
+	#[test]
+	fn config_is_parsed_from_toml() {
+		let config = SandboxConfig::from_toml(&format!(
+			r#"
+			schedule = "development-lenient"
+			wall_clock = false
+
+			[default_gas_limit]
+			ref_time = 1000
+			proof_size = 100
+
+			[[assets]]
+			id = 1
+			owner = "{}"
+			"#,
+			DEFAULT_ACCOUNT.to_ss58check()
+		))
+		.unwrap();
+		assert_eq!(
+			config,
+			SandboxConfig {
+				schedule: "development-lenient".to_string(),
+				wall_clock: false,
+				default_gas_limit: Weight::from_parts(1000, 100),
+				assets: vec![AssetConfig {
+					id: 1,
+					owner: DEFAULT_ACCOUNT,
+					is_sufficient: false,
+					min_balance: 1,
+					accounts: vec![],
+				}],
+				..Default::default()
+			}
+		);
+
+		assert_eq!(
+			SandboxConfig::from_toml(r#"schedule = "unknown""#),
+			Err(ConfigError::UnknownSchedule("unknown".to_string()))
+		);
+		assert!(matches!(
+			SandboxConfig::from_toml("unknown_field = 1"),
+			Err(ConfigError::Invalid(_))
+		));
+	}
+
+	#[test]
+	fn sandbox_is_created_from_config() {
+		let gas_limit = Weight::from_parts(1_000_000, 1024);
+		let config = SandboxConfig {
+			balances: vec![(BOB, 1_000)],
+			assets: vec![AssetConfig {
+				id: 1,
+				owner: BOB,
+				is_sufficient: false,
+				min_balance: 1,
+				accounts: vec![(BOB, 100)],
+			}],
+			default_gas_limit: gas_limit,
+			schedule: "development-lenient".to_string(),
+			..Default::default()
+		};
+		let mut sandbox = DefaultSandbox::with_config(&config);
+
+		assert_eq!(sandbox.free_balance(&BOB), 1_000);
+		assert_eq!(sandbox.free_balance(&DefaultSandbox::default_actor()), 0);
+		assert_eq!(AssetsAPI::<_, Instance1>::balance_of(&mut sandbox, &1, &BOB), 100);
+		assert_eq!(sandbox.configured_gas_limit(), gas_limit);
+		assert_eq!(
+			sandbox.parameters().schedule,
+			ScheduleParameters::preset("development-lenient").unwrap()
+		);
+	}
+
+	#[test]
+	fn timestamps_can_be_reproducible() {
+		let mut config = SandboxConfig { wall_clock: false, ..Default::default() };
+		config.block.genesis_timestamp = 1_000;
+		config.block.block_time = 10;
+		let mut sandbox = DefaultSandbox::with_config(&config);
+
+		assert_eq!(sandbox.get_timestamp(), 1_000);
+		sandbox.build_blocks(2);
+		assert_eq!(sandbox.get_timestamp(), 1_020);
+	}
+}
@@ -0,0 +1,117 @@
+//! Reading the constants of the pallets of a sandbox runtime from its metadata, so tests can
+//! compute expected values from the runtime configuration (e.g. the existential deposit or the
+//! deposit per byte) instead of duplicating magic numbers.
+//!
+//! ```rust, ignore
+//! let existential_deposit: u128 = sandbox.pallet_constant("Balances", "ExistentialDeposit")?;
+//! ```
+
+use std::fmt;
+
+use frame_metadata::RuntimeMetadata;
+use scale::{Decode, DecodeAll};
+
+use crate::Sandbox;
+
+/// Error returned when a pallet constant cannot be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstantError {
+	/// The runtime metadata version is not supported.
+	UnsupportedMetadata,
+	/// There is no pallet with the given name in the runtime.
+	PalletNotFound(String),
+	/// The pallet has no constant with the given name.
+	ConstantNotFound { pallet: String, constant: String },
+	/// The value of the constant doesn't decode as the requested type.
+	InvalidType(String),
+}
+
+impl fmt::Display for ConstantError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnsupportedMetadata => write!(f, "Unsupported runtime metadata version"),
+			Self::PalletNotFound(pallet) => write!(f, "Pallet `{pallet}` not found in the runtime"),
+			Self::ConstantNotFound { pallet, constant } =>
+				write!(f, "Constant `{constant}` not found in pallet `{pallet}`"),
+			Self::InvalidType(error) => write!(f, "Invalid constant type: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for ConstantError {}
+
+/// Returns the value of the constant `constant` of the pallet `pallet` of the sandbox runtime,
+/// decoded as `V`.
+///
+/// The metadata is built within the externalities of the sandbox, so the constants overridden per
+/// instance (see [`crate::SandboxParameters`]) have their current value.
+pub(crate) fn pallet_constant<S: Sandbox, V: Decode>(
+	sandbox: &mut S,
+	pallet: &str,
+	constant: &str,
+) -> Result<V, ConstantError> {
+	let metadata = sandbox.execute_with(S::get_metadata);
+	let constants = match &metadata.1 {
+		RuntimeMetadata::V14(metadata) => metadata
+			.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.map(|p| p.constants.iter().map(|c| (&c.name, &c.value)).collect::<Vec<_>>()),
+		RuntimeMetadata::V15(metadata) => metadata
+			.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.map(|p| p.constants.iter().map(|c| (&c.name, &c.value)).collect::<Vec<_>>()),
+		_ => return Err(ConstantError::UnsupportedMetadata),
+	}
+	.ok_or_else(|| ConstantError::PalletNotFound(pallet.to_string()))?;
+
+	let (_, value) =
+		constants.into_iter().find(|(name, _)| *name == constant).ok_or_else(|| {
+			ConstantError::ConstantNotFound {
+				pallet: pallet.to_string(),
+				constant: constant.to_string(),
+			}
+		})?;
+	V::decode_all(&mut &value[..]).map_err(|e| ConstantError::InvalidType(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ConstantError;
+	use crate::{DefaultSandbox, Sandbox};
+
+	#[test]
+	fn pallet_constants_are_read() {
+		let mut sandbox = DefaultSandbox::default();
+		assert_eq!(sandbox.pallet_constant::<u128>("Balances", "ExistentialDeposit"), Ok(1));
+		assert_eq!(sandbox.pallet_constant::<u128>("Contracts", "DepositPerByte"), Ok(1));
+	}
+
+	#[test]
+	fn pallet_constants_follow_the_parameters_of_the_sandbox() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.update_parameters(|parameters| parameters.deposit_per_byte = 10);
+		assert_eq!(sandbox.pallet_constant::<u128>("Contracts", "DepositPerByte"), Ok(10));
+	}
+
+	#[test]
+	fn unknown_or_mistyped_constants_are_reported() {
+		let mut sandbox = DefaultSandbox::default();
+		assert_eq!(
+			sandbox.pallet_constant::<u128>("Unknown", "ExistentialDeposit"),
+			Err(ConstantError::PalletNotFound("Unknown".to_string()))
+		);
+		assert_eq!(
+			sandbox.pallet_constant::<u128>("Balances", "Unknown"),
+			Err(ConstantError::ConstantNotFound {
+				pallet: "Balances".to_string(),
+				constant: "Unknown".to_string()
+			})
+		);
+		assert!(matches!(
+			sandbox.pallet_constant::<u8>("Balances", "ExistentialDeposit"),
+			Err(ConstantError::InvalidType(_))
+		));
+	}
+}
@@ -0,0 +1,236 @@
+//! Hooks into the execution of contracts, for custom observability (metrics, logging, assertions)
+//! without writing a whole `Debug` type for the contracts pallet.
+//!
+//! The hooks are implemented with [`DebugHooks`], and plugged into a sandbox with [`HookedDebug`]
+//! as its `Debug` type:
+//!
+//! ```rust, ignore
+//! struct Logging;
+//!
+//! impl DebugHooks for Logging {
+//!     fn before_call<R: Config>(contract: &AccountIdFor<R>, _: ExportedFunction, input: &[u8]) {
+//!         println!("Calling {contract:?} with {input:?}");
+//!     }
+//!
+//!     fn on_storage_access(access: StorageAccess) {
+//!         println!("Storage access: {access:?}");
+//!     }
+//! }
+//!
+//! create_sandbox!(LoggingSandbox, (), HookedDebug<Logging>);
+//! ```
+//!
+//! The hooks have no state of their own: state shared with the tests can be kept in thread-local
+//! or static variables, or in extensions of the externalities.
+
+use core::marker::PhantomData;
+
+use pallet_contracts::{
+	debug::{CallInterceptor, CallSpan, ExecResult, ExportedFunction},
+	Config, ExecReturnValue, Tracing,
+};
+
+use crate::AccountIdFor;
+
+/// An access of a contract to its storage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageAccess {
+	/// A read of a value, or of its presence (`seal_get_storage`, `seal_contains_storage`).
+	Read,
+	/// A write or a removal of a value (`seal_set_storage`, `seal_clear_storage`).
+	Write,
+	/// A removal of a value, returning it (`seal_take_storage`).
+	Take,
+}
+
+impl StorageAccess {
+	/// Returns the storage access of the host function `name`, whose weight was charged for the
+	/// `charges`-th time (if counted).
+	///
+	/// The storage host functions charge their weight twice: upfront for the largest value, then
+	/// again to refund the difference with the actual value. Only the upfront charges are accesses.
+	pub(crate) fn charged(name: &str, charges: Option<u32>) -> Option<Self> {
+		let access = match name {
+			"seal_get_storage" | "seal_contains_storage" => Self::Read,
+			"seal_set_storage" | "seal_clear_storage" => Self::Write,
+			"seal_take_storage" => Self::Take,
+			_ => return None,
+		};
+		(charges.unwrap_or(1) % 2 == 1).then_some(access)
+	}
+}
+
+/// Hooks into the execution of contracts. All of them do nothing by default.
+///
+/// The `Debug` types passed to [`crate::create_sandbox`] must implement it (with no hooks at all,
+/// if they observe the execution of contracts otherwise).
+pub trait DebugHooks: 'static {
+	/// Called right before a contract is called or instantiated (`entry_point`) with `input`,
+	/// including by another contract.
+	fn before_call<R: Config>(
+		_contract: &AccountIdFor<R>,
+		_entry_point: ExportedFunction,
+		_input: &[u8],
+	) {
+	}
+
+	/// Called right after a contract was called or instantiated, with the `output` of the call.
+	fn after_call<R: Config>(
+		_contract: &AccountIdFor<R>,
+		_entry_point: ExportedFunction,
+		_input: &[u8],
+		_output: &ExecReturnValue,
+	) {
+	}
+
+	/// Called whenever a contract accesses its storage.
+	fn on_storage_access(_access: StorageAccess) {}
+}
+
+impl DebugHooks for () {}
+
+/// A `Debug` type for the contracts pallet, calling the hooks `H`.
+pub struct HookedDebug<H>(PhantomData<H>);
+
+impl<H: DebugHooks> DebugHooks for HookedDebug<H> {
+	fn before_call<R: Config>(
+		contract: &AccountIdFor<R>,
+		entry_point: ExportedFunction,
+		input: &[u8],
+	) {
+		H::before_call::<R>(contract, entry_point, input)
+	}
+
+	fn after_call<R: Config>(
+		contract: &AccountIdFor<R>,
+		entry_point: ExportedFunction,
+		input: &[u8],
+		output: &ExecReturnValue,
+	) {
+		H::after_call::<R>(contract, entry_point, input, output)
+	}
+
+	fn on_storage_access(access: StorageAccess) {
+		H::on_storage_access(access)
+	}
+}
+
+impl<R: Config, H: DebugHooks> Tracing<R> for HookedDebug<H> {
+	type CallSpan = HookedCallSpan<R, H>;
+
+	fn new_call_span(
+		contract_address: &AccountIdFor<R>,
+		entry_point: ExportedFunction,
+		input_data: &[u8],
+	) -> Self::CallSpan {
+		H::before_call::<R>(contract_address, entry_point, input_data);
+		HookedCallSpan {
+			contract_address: contract_address.clone(),
+			entry_point,
+			input_data: input_data.to_vec(),
+			hooks: PhantomData,
+		}
+	}
+}
+
+impl<R: Config, H: DebugHooks> CallInterceptor<R> for HookedDebug<H> {
+	fn intercept_call(
+		_contract_address: &AccountIdFor<R>,
+		_entry_point: &ExportedFunction,
+		_input_data: &[u8],
+	) -> Option<ExecResult> {
+		None
+	}
+}
+
+/// The span of a contract call, calling the `after_call` hook of `H` when the call is done.
+pub struct HookedCallSpan<R: Config, H> {
+	contract_address: AccountIdFor<R>,
+	entry_point: ExportedFunction,
+	input_data: Vec<u8>,
+	hooks: PhantomData<H>,
+}
+
+impl<R: Config, H: DebugHooks> CallSpan for HookedCallSpan<R, H> {
+	fn after_call(self, output: &ExecReturnValue) {
+		H::after_call::<R>(&self.contract_address, self.entry_point, &self.input_data, output);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+
+	use pallet_contracts::{debug::ExportedFunction, Config, Determinism, ExecReturnValue};
+
+	use super::{DebugHooks, StorageAccess};
+	use crate::{api::prelude::*, AccountIdFor, Sandbox};
+
+	thread_local! {
+		static OBSERVED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+	}
+
+	pub struct Recording;
+
+	impl DebugHooks for Recording {
+		fn before_call<R: Config>(_: &AccountIdFor<R>, entry_point: ExportedFunction, _: &[u8]) {
+			OBSERVED.with_borrow_mut(|observed| observed.push(format!("before {entry_point:?}")));
+		}
+
+		fn after_call<R: Config>(
+			_: &AccountIdFor<R>,
+			entry_point: ExportedFunction,
+			_: &[u8],
+			_: &ExecReturnValue,
+		) {
+			OBSERVED.with_borrow_mut(|observed| observed.push(format!("after {entry_point:?}")));
+		}
+
+		fn on_storage_access(access: StorageAccess) {
+			OBSERVED.with_borrow_mut(|observed| observed.push(format!("{access:?}")));
+		}
+	}
+
+	mod hooked {
+		crate::create_sandbox!(
+			HookedSandbox,
+			(),
+			crate::debug_hooks::HookedDebug<crate::debug_hooks::tests::Recording>
+		);
+	}
+	use hooked::HookedSandbox;
+
+	#[test]
+	fn hooks_observe_the_execution_of_contracts() {
+		let mut sandbox = HookedSandbox::default();
+		let code =
+			wat::parse_file([env!("CARGO_MANIFEST_DIR"), "/test-resources/storage.wat"].concat())
+				.unwrap();
+		let result = sandbox.deploy_contract(
+			code,
+			0,
+			vec![],
+			vec![],
+			HookedSandbox::default_actor(),
+			HookedSandbox::default_gas_limit(),
+			None,
+		);
+		let address = result.result.expect("Contract should be deployed").account_id;
+		assert_eq!(
+			OBSERVED.take(),
+			["before Constructor", "Write", "after Constructor"].map(String::from)
+		);
+
+		let result = sandbox.call_contract(
+			address,
+			0,
+			vec![],
+			HookedSandbox::default_actor(),
+			HookedSandbox::default_gas_limit(),
+			None,
+			Determinism::Enforced,
+		);
+		assert!(result.result.is_ok());
+		assert_eq!(OBSERVED.take(), ["before Call", "after Call"].map(String::from));
+	}
+}
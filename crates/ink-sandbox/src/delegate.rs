@@ -0,0 +1,144 @@
+//! Contracts for testing `delegate_call` flows, e.g. proxies and diamonds running library code in
+//! their own storage context.
+//!
+//! A proxy stores the code hash of a library (the input of its constructor), and delegates every
+//! call to it. The probe library records the context it runs in (its caller and its input) in the
+//! storage of the contract delegating to it, so the context can be asserted after a call:
+//!
+//! ```rust, ignore
+//! let library = sandbox.upload_contract(probe_code(), ALICE, None, Determinism::Enforced)?;
+//! let proxy = sandbox.deploy_delegate_proxy(library.code_hash, ALICE)?;
+//! sandbox.call_contract(proxy.clone(), 0, input, BOB, gas_limit, None, Determinism::Enforced);
+//! // The library ran in the storage of the proxy, called by the caller of the proxy.
+//! assert_eq!(sandbox.delegate_context(&proxy), Some(DelegateContext { caller: BOB, input }));
+//! ```
+//!
+//! Delegating to code that isn't uploaded (or was removed since) makes the proxy trap.
+
+/// The storage key under which the probe library records its caller.
+pub const PROBE_CALLER_KEY: &[u8] = b"delegate_probe:caller";
+
+/// The storage key under which the probe library records its input.
+pub const PROBE_INPUT_KEY: &[u8] = b"delegate_probe:input";
+
+/// Contract storing the code hash of a library (the input of its constructor), and delegating
+/// every call to it. Returns the output of the library, reverts if the library reverted, and traps
+/// if the library trapped or isn't found.
+const PROXY: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "delegate_call" (func $seal_delegate_call (param i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal1" "get_storage" (func $seal_get_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4): the storage key of the code hash of the library ([0, 0, 0, 0])
+	;; [32, 64): the code hash of the library
+	;; [64, 68): the length of the code hash of the library
+	;; [68, 72): the length of the output of the library
+	;; [96, ..): the output of the library
+
+	(func (export "deploy")
+		(i32.store (i32.const 64) (i32.const 32))
+		(call $seal_input (i32.const 32) (i32.const 64))
+		(drop
+			(call $seal_set_storage (i32.const 0) (i32.const 4) (i32.const 32) (i32.const 32))
+		)
+	)
+
+	(func (export "call")
+		(local $code i32)
+		(i32.store (i32.const 64) (i32.const 32))
+		(drop
+			(call $seal_get_storage (i32.const 0) (i32.const 4) (i32.const 32) (i32.const 64))
+		)
+
+		(i32.store (i32.const 68) (i32.const 0x8000))
+		(local.set $code
+			(call $seal_delegate_call
+				(i32.const 1)	;; flags: forward the input
+				(i32.const 32)	;; the code hash of the library
+				(i32.const 0)	;; the input (forwarded)
+				(i32.const 0)	;; the length of the input (forwarded)
+				(i32.const 96)	;; the output
+				(i32.const 68)	;; the length of the output
+			)
+		)
+		;; `CalleeReverted`
+		(if (i32.eq (local.get $code) (i32.const 2))
+			(then (call $seal_return (i32.const 1) (i32.const 96) (i32.load (i32.const 68))))
+		)
+		(if (local.get $code)
+			(then unreachable)
+		)
+
+		(call $seal_return (i32.const 0) (i32.const 96) (i32.load (i32.const 68)))
+	)
+)
+"#;
+
+/// Library recording its caller under [`PROBE_CALLER_KEY`] and its input under
+/// [`PROBE_INPUT_KEY`], and returning its input.
+const PROBE: &str = r#"
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4): the length of the caller
+	;; [4, 8): the length of the input
+	;; [32, 64): the caller
+	;; [64, 85): the storage key of the caller
+	;; [96, 116): the storage key of the input
+	;; [128, ..): the input
+
+	(data (i32.const 64) "delegate_probe:caller")
+	(data (i32.const 96) "delegate_probe:input")
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(i32.store (i32.const 0) (i32.const 32))
+		(call $seal_caller (i32.const 32) (i32.const 0))
+		(i32.store (i32.const 4) (i32.const 0x4000))
+		(call $seal_input (i32.const 128) (i32.const 4))
+
+		(drop
+			(call $seal_set_storage (i32.const 64) (i32.const 21) (i32.const 32) (i32.const 32))
+		)
+		(drop
+			(call $seal_set_storage
+				(i32.const 96) (i32.const 20) (i32.const 128) (i32.load (i32.const 4))
+			)
+		)
+
+		(call $seal_return (i32.const 0) (i32.const 128) (i32.load (i32.const 4)))
+	)
+)
+"#;
+
+/// The context the probe library ran in, as recorded in the storage of the contract delegating to
+/// it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegateContext<AccountId> {
+	/// The caller seen by the library, i.e. the caller of the delegating contract.
+	pub caller: AccountId,
+	/// The input of the library, i.e. the input of the delegating contract.
+	pub input: Vec<u8>,
+}
+
+/// Returns the code of the proxy contracts, see
+/// [`ContractAPI::deploy_delegate_proxy`](crate::api::contracts_api::ContractAPI::deploy_delegate_proxy).
+pub fn proxy_code() -> Vec<u8> {
+	wat::parse_str(PROXY).expect("The proxy is valid WAT")
+}
+
+/// Returns the code of the probe library, to be uploaded and delegated to.
+pub fn probe_code() -> Vec<u8> {
+	wat::parse_str(PROBE).expect("The probe is valid WAT")
+}
@@ -0,0 +1,27 @@
+//! Auditing the determinism of contract code, e.g. of contracts meant to be used as dependencies
+//! of other contracts (through `delegate_call`), which must be deterministic.
+//!
+//! All the host functions of the contracts pallet are deterministic, but some wasm instructions
+//! (floating point ones) are not. Code using them is rejected with an opaque `CodeRejected` when
+//! uploaded with `Determinism::Enforced`, and can only be uploaded with `Determinism::Relaxed`
+//! (for off-chain use). [`crate::api::prelude::ContractAPI::audit_determinism`] tells these cases
+//! apart:
+//!
+//! ```rust, ignore
+//! assert_eq!(sandbox.audit_determinism(code, ALICE), DeterminismAudit::Deterministic);
+//! ```
+
+use frame_support::sp_runtime::DispatchError;
+
+/// The result of auditing the determinism of contract code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeterminismAudit {
+	/// The code can be uploaded with `Determinism::Enforced`.
+	Deterministic,
+	/// The code uses non-deterministic instructions: it can only be uploaded with
+	/// `Determinism::Relaxed`.
+	NonDeterministic,
+	/// The code can't be uploaded at all, e.g. because it isn't valid wasm or imports unknown host
+	/// functions.
+	Invalid(DispatchError),
+}
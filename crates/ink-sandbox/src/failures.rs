@@ -0,0 +1,194 @@
+//! Injection of failures into the execution of contracts, for robustness testing.
+//!
+//! The sandbox runtime uses [`FailureInjectingWeights`] as the weights of the contracts pallet,
+//! which are charged by every host function a contract calls. Failures injected with
+//! [`crate::api::prelude::ContractAPI::inject_failure`] are kept in an extension of the
+//! externalities, and make the targeted host functions fail:
+//!
+//! ```rust, ignore
+//! sandbox.inject_failure(FailureMode::OutOfGasAtHostFn("seal_transfer"));
+//! // Any contract transferring funds now runs out of gas.
+//! sandbox.clear_failures();
+//! ```
+//!
+//! An execution can also be aborted from another thread by raising an [`AbortSignal`] registered
+//! as an extension: every host function called afterwards runs out of gas.
+
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc,
+};
+
+use frame_support::weights::Weight;
+use pallet_contracts::WeightInfo;
+use sp_externalities::{decl_extension, ExternalitiesExt};
+
+use crate::debug_hooks::{DebugHooks, StorageAccess};
+
+/// A failure of the runtime, injected into the execution of contracts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureMode {
+	/// Every call of the host function (e.g. `"seal_transfer"`) runs out of gas.
+	OutOfGasAtHostFn(&'static str),
+}
+
+decl_extension! {
+	/// The failures injected into the execution of contracts.
+	pub struct InjectedFailures(Vec<FailureMode>);
+}
+
+decl_extension! {
+	/// The signal aborting the execution of contracts, e.g. one which isn't awaited anymore: once
+	/// raised (by any of its clones), every host function runs out of gas.
+	#[derive(Clone, Default)]
+	pub struct AbortSignal(Arc<AtomicBool>);
+}
+
+impl AbortSignal {
+	/// Raises the signal.
+	pub fn raise(&self) {
+		self.0.store(true, Ordering::Relaxed)
+	}
+
+	/// Returns whether the signal was raised.
+	pub fn is_raised(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+/// Returns whether the host function `name` must run out of gas.
+fn out_of_gas_at(name: &str) -> bool {
+	sp_externalities::with_externalities(|ext| {
+		if ext.extension::<AbortSignal>().is_some_and(|signal| signal.is_raised()) {
+			return true;
+		}
+		ext.extension::<InjectedFailures>().is_some_and(|failures| {
+			failures.0.iter().any(|failure| match failure {
+				FailureMode::OutOfGasAtHostFn(host_fn) => *host_fn == name,
+			})
+		})
+	})
+	.unwrap_or_default()
+}
+
+/// Runs `update` on the injected failures.
+///
+/// Must be called within the externalities.
+pub(crate) fn update(update: impl FnOnce(&mut Vec<FailureMode>)) {
+	sp_externalities::with_externalities(|ext| {
+		if let Some(failures) = ext.extension::<InjectedFailures>() {
+			update(&mut failures.0)
+		}
+	});
+}
+
+/// Weights of the contracts pallet, charging the whole gas left to the host functions which must
+/// run out of gas. Otherwise the same as the weights `W`.
+///
+/// Every charge is also counted for the [`crate::host_fns::CallMetrics`], and reported to the
+/// storage hook of `D` (the `Debug` type of the sandbox) if it is a storage access.
+pub struct FailureInjectingWeights<W = (), D = ()>(core::marker::PhantomData<(W, D)>);
+
+macro_rules! failure_injecting_weights {
+	($( $name:ident ( $( $arg:ident ),* ) ),* $(,)?) => {
+		impl<W: WeightInfo, D: DebugHooks> WeightInfo for FailureInjectingWeights<W, D> {
+			$(
+				fn $name($( $arg: u32 ),*) -> Weight {
+					let charges = crate::host_fns::record(stringify!($name));
+					if let Some(access) = StorageAccess::charged(stringify!($name), charges) {
+						D::on_storage_access(access);
+					}
+					if out_of_gas_at(stringify!($name)) {
+						Weight::MAX
+					} else {
+						W::$name($( $arg ),*)
+					}
+				}
+			)*
+		}
+	};
+}
+
+failure_injecting_weights! {
+	on_process_deletion_queue_batch(),
+	on_initialize_per_trie_key(k),
+	v9_migration_step(c),
+	v10_migration_step(),
+	v11_migration_step(k),
+	v12_migration_step(c),
+	v13_migration_step(),
+	v14_migration_step(),
+	v15_migration_step(),
+	v16_migration_step(),
+	migration_noop(),
+	migrate(),
+	on_runtime_upgrade_noop(),
+	on_runtime_upgrade_in_progress(),
+	on_runtime_upgrade(),
+	call_with_code_per_byte(c),
+	instantiate_with_code(c, i, s),
+	instantiate(i, s),
+	call(),
+	upload_code_determinism_enforced(c),
+	upload_code_determinism_relaxed(c),
+	remove_code(),
+	set_code(),
+	noop_host_fn(r),
+	seal_caller(),
+	seal_is_contract(),
+	seal_code_hash(),
+	seal_own_code_hash(),
+	seal_caller_is_origin(),
+	seal_caller_is_root(),
+	seal_address(),
+	seal_gas_left(),
+	seal_balance(),
+	seal_value_transferred(),
+	seal_minimum_balance(),
+	seal_block_number(),
+	seal_now(),
+	seal_weight_to_fee(),
+	seal_input(n),
+	seal_return(n),
+	seal_terminate(n),
+	seal_random(),
+	seal_deposit_event(t, n),
+	seal_debug_message(i),
+	get_storage_empty(),
+	get_storage_full(),
+	set_storage_empty(),
+	set_storage_full(),
+	seal_set_storage(n, o),
+	seal_clear_storage(n),
+	seal_get_storage(n),
+	seal_contains_storage(n),
+	seal_take_storage(n),
+	set_transient_storage_empty(),
+	set_transient_storage_full(),
+	get_transient_storage_empty(),
+	get_transient_storage_full(),
+	rollback_transient_storage(),
+	seal_set_transient_storage(n, o),
+	seal_clear_transient_storage(n),
+	seal_get_transient_storage(n),
+	seal_contains_transient_storage(n),
+	seal_take_transient_storage(n),
+	seal_transfer(),
+	seal_call(t, i),
+	seal_delegate_call(),
+	seal_instantiate(i, s),
+	seal_hash_sha2_256(n),
+	seal_hash_keccak_256(n),
+	seal_hash_blake2_256(n),
+	seal_hash_blake2_128(n),
+	seal_sr25519_verify(n),
+	seal_ecdsa_recover(),
+	seal_ecdsa_to_eth_address(),
+	seal_set_code_hash(),
+	lock_delegate_dependency(),
+	unlock_delegate_dependency(),
+	seal_reentrance_count(),
+	seal_account_reentrance_count(),
+	seal_instantiation_nonce(),
+	instr_i64_load_store(r),
+}
@@ -0,0 +1,150 @@
+//! Simulated chain reorganizations.
+//!
+//! A sandbox created with [`crate::create_sandbox`] keeps the state of its recent blocks, so a
+//! new branch of the chain can be forked off any of them. Branches share their history up to the
+//! fork point, and the sandbox can switch between them to emulate a reorg:
+//!
+//! ```rust, ignore
+//! let fork_point = sandbox.block_number();
+//! sandbox.build_blocks(3);
+//! let fork = sandbox.fork_at(fork_point)?;
+//! sandbox.switch_fork(fork)?;
+//! // The blocks built after `fork_point` are not part of the chain anymore.
+//! ```
+//!
+//! The same history serves the queries of the state of past blocks (see [`crate::history`]).
+
+use std::{collections::VecDeque, fmt};
+
+use sp_core::Blake2Hasher;
+use sp_state_machine::InMemoryBackend;
+
+/// The number of recent blocks whose state is kept by default, i.e. which a branch can be forked
+/// off and whose state can be queried.
+pub const MAX_REORG_DEPTH: usize = 64;
+
+/// Identifier of a branch of the chain. The original branch is `0`.
+pub type ForkId = usize;
+
+type Backend = InMemoryBackend<Blake2Hasher>;
+
+/// Error of the fork operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForkError {
+	/// The state of the block is not known, e.g. it is older than the retained blocks (see
+	/// [`MAX_REORG_DEPTH`]) or not built yet.
+	UnknownBlock(u64),
+	/// There is no branch with the given identifier.
+	UnknownFork(ForkId),
+}
+
+impl fmt::Display for ForkError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnknownBlock(height) => write!(f, "The state of block {height} is not known"),
+			Self::UnknownFork(fork) => write!(f, "Fork {fork} does not exist"),
+		}
+	}
+}
+
+impl std::error::Error for ForkError {}
+
+/// A branch of the chain.
+#[derive(Default)]
+struct Branch {
+	/// The latest known state of the recent blocks, by height.
+	history: VecDeque<(u64, Backend)>,
+	/// The latest state of the branch, while it is not the current one.
+	head: Option<Backend>,
+}
+
+/// The branches of the chain of a sandbox.
+pub struct Forks {
+	current: ForkId,
+	branches: Vec<Branch>,
+	/// The number of recent blocks whose state is kept, per branch.
+	retention: usize,
+}
+
+impl Default for Forks {
+	fn default() -> Self {
+		Self { current: 0, branches: vec![Branch::default()], retention: MAX_REORG_DEPTH }
+	}
+}
+
+impl Forks {
+	/// Returns the identifier of the current branch.
+	pub fn current(&self) -> ForkId {
+		self.current
+	}
+
+	/// Records the state of the block `height` of the current branch, replacing the state of the
+	/// blocks at or above `height`.
+	pub fn note_block(&mut self, height: u64, state: Backend) {
+		let history = &mut self.branches[self.current].history;
+		// Blocks built again at the same height (e.g. after a `dry_run`) replace the old ones.
+		while history.back().is_some_and(|(last, _)| *last >= height) {
+			history.pop_back();
+		}
+		history.push_back((height, state));
+		if history.len() > self.retention {
+			history.pop_front();
+		}
+	}
+
+	/// Keeps the state of the last `blocks` blocks (at least one) of every branch, dropping the
+	/// older ones.
+	pub fn set_retention(&mut self, blocks: usize) {
+		self.retention = blocks.max(1);
+		for branch in &mut self.branches {
+			branch.history.drain(..branch.history.len().saturating_sub(self.retention));
+		}
+	}
+
+	/// Returns the state at the end of the block `height` of the current branch, whose latest
+	/// block is `latest`: the state of the next block when it was initialized, or `None` if
+	/// `height` is the latest block, whose state is the current one.
+	pub fn state_after(&self, height: u64, latest: u64) -> Result<Option<&Backend>, ForkError> {
+		if height == latest {
+			return Ok(None);
+		}
+		let history = &self.branches[self.current].history;
+		if height > latest || !history.front().is_some_and(|(oldest, _)| *oldest <= height) {
+			return Err(ForkError::UnknownBlock(height));
+		}
+		// Blocks above `latest` may be left by a `dry_run`, and aren't part of the branch.
+		history
+			.iter()
+			.find(|(block, _)| (height + 1..=latest).contains(block))
+			.map(|(_, state)| Some(state))
+			.ok_or(ForkError::UnknownBlock(height))
+	}
+
+	/// Creates a new branch, forked off the block `height` of the current branch, and returns its
+	/// identifier. The new branch shares the history of the current one up to `height`.
+	pub fn fork_at(&mut self, height: u64) -> Result<ForkId, ForkError> {
+		let history: VecDeque<_> = self.branches[self.current]
+			.history
+			.iter()
+			.take_while(|(block, _)| *block <= height)
+			.cloned()
+			.collect();
+		let head = match history.back() {
+			Some((block, state)) if *block == height => state.clone(),
+			_ => return Err(ForkError::UnknownBlock(height)),
+		};
+		self.branches.push(Branch { history, head: Some(head) });
+		Ok(self.branches.len() - 1)
+	}
+
+	/// Makes `fork` the current branch. `state` is the latest state of the current branch, and the
+	/// latest state of `fork` is returned.
+	pub fn switch(&mut self, fork: ForkId, state: Backend) -> Result<Backend, ForkError> {
+		if fork >= self.branches.len() {
+			return Err(ForkError::UnknownFork(fork));
+		}
+		self.branches[self.current].head = Some(state);
+		self.current = fork;
+		Ok(self.branches[fork].head.take().expect("Other branches have a head"))
+	}
+}
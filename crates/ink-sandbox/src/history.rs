@@ -0,0 +1,101 @@
+//! Queries of the state of past blocks, e.g. for testing the snapshots of voting power the way
+//! indexers and light clients see them.
+//!
+//! A sandbox created with [`crate::create_sandbox`] keeps the state of its recent blocks (see
+//! [`crate::forks`]), and returns a view of the state at the end of any of them. The view is a
+//! sandbox itself, so the whole API can be used to read it:
+//!
+//! ```rust, ignore
+//! let snapshot_block = sandbox.block_number();
+//! sandbox.build_blocks(3);
+//! let balance = sandbox.at_block(snapshot_block)?.free_balance(&ALICE);
+//! ```
+//!
+//! The view is read-only: changes made through it are discarded with it. The blocks skipped over
+//! (see [`crate::api::prelude::SystemAPI::skip_blocks`]) share the state at the end of the skip.
+
+use std::{any::Any, marker::PhantomData};
+
+use frame_support::sp_runtime::traits::Dispatchable;
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_core::Blake2Hasher;
+use sp_state_machine::InMemoryBackend;
+
+use crate::{
+	AccountIdFor, Extension, RuntimeCall, RuntimeMetadataPrefixed, Sandbox, TestExternalities,
+	Weight,
+};
+
+/// The state at the end of a past block of a sandbox `S`. See [`crate::history`].
+pub struct BlockState<S> {
+	height: u64,
+	ext: TestExternalities,
+	_sandbox: PhantomData<S>,
+}
+
+impl<S> BlockState<S> {
+	/// Creates the view of `state`, the state at the end of the block `height`. Used by the
+	/// sandboxes created with [`crate::create_sandbox`].
+	#[doc(hidden)]
+	pub fn new(height: u64, state: InMemoryBackend<Blake2Hasher>) -> Self {
+		let mut ext = TestExternalities::default();
+		ext.backend = state;
+		Self { height, ext, _sandbox: PhantomData }
+	}
+
+	/// Returns the number of the block.
+	pub fn height(&self) -> u64 {
+		self.height
+	}
+}
+
+impl<S: Sandbox> Sandbox for BlockState<S> {
+	type Runtime = S::Runtime;
+
+	fn execute_with<T>(&mut self, execute: impl FnOnce() -> T) -> T {
+		self.ext.execute_with(execute)
+	}
+
+	fn dry_run<T>(&mut self, action: impl FnOnce(&mut Self) -> T) -> T {
+		let backend_backup = self.ext.as_backend();
+		let result = action(self);
+		self.ext.commit_all().expect("Failed to commit changes");
+		self.ext.backend = backend_backup;
+		result
+	}
+
+	fn register_extension<E: Any + Extension>(&mut self, ext: E) {
+		self.ext.register_extension(ext);
+	}
+
+	fn initialize_block(
+		height: BlockNumberFor<Self::Runtime>,
+		parent_hash: <Self::Runtime as frame_system::Config>::Hash,
+	) {
+		S::initialize_block(height, parent_hash)
+	}
+
+	fn finalize_block(
+		height: BlockNumberFor<Self::Runtime>,
+	) -> <Self::Runtime as frame_system::Config>::Hash {
+		S::finalize_block(height)
+	}
+
+	fn default_actor() -> AccountIdFor<Self::Runtime> {
+		S::default_actor()
+	}
+
+	fn default_gas_limit() -> Weight {
+		S::default_gas_limit()
+	}
+
+	fn get_metadata() -> RuntimeMetadataPrefixed {
+		S::get_metadata()
+	}
+
+	fn convert_account_to_origin(
+		account: AccountIdFor<Self::Runtime>,
+	) -> <RuntimeCall<Self::Runtime> as Dispatchable>::RuntimeOrigin {
+		S::convert_account_to_origin(account)
+	}
+}
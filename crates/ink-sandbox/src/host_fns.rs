@@ -0,0 +1,124 @@
+//! Counting of the host functions called by contracts, for asserting the algorithmic behavior of
+//! a contract (e.g. at most one storage write per transfer) rather than only its gas consumption.
+//!
+//! The debugging hooks of the contracts pallet only see calls between contracts, not the host
+//! functions a contract calls. Every host function charges its weight though, so the weights of
+//! the sandbox runtime ([`crate::failures::FailureInjectingWeights`]) count how often each of them
+//! is charged, in an extension of the externalities. The counts are taken with
+//! [`crate::api::prelude::ContractAPI::take_call_metrics`]:
+//!
+//! ```rust, ignore
+//! sandbox.take_call_metrics();
+//! sandbox.call_contract(token, 0, transfer, ALICE, gas_limit, None, Determinism::Enforced);
+//! assert!(sandbox.take_call_metrics().storage_writes <= 1);
+//! ```
+//!
+//! The metrics also sum the proof size consumed by the contract interactions, as reported by the
+//! contracts pallet, since the blocks of a parachain are bounded by their PoV size as well.
+
+use std::collections::BTreeMap;
+
+use frame_support::weights::Weight;
+use sp_externalities::{decl_extension, ExternalitiesExt};
+
+/// The host functions reading the storage of a contract.
+const STORAGE_READS: &[&str] = &["seal_get_storage", "seal_contains_storage", "seal_take_storage"];
+
+/// The host functions writing the storage of a contract.
+const STORAGE_WRITES: &[&str] = &["seal_set_storage", "seal_clear_storage", "seal_take_storage"];
+
+/// The host functions hashing data.
+const HASHES: &[&str] =
+	&["seal_hash_sha2_256", "seal_hash_keccak_256", "seal_hash_blake2_256", "seal_hash_blake2_128"];
+
+decl_extension! {
+	/// The number of times the weight of each host function was charged, since last taken.
+	pub struct HostFnCounts(BTreeMap<&'static str, u32>);
+}
+
+decl_extension! {
+	/// The proof size consumed by the contract interactions, since last taken.
+	pub struct ProofSizeConsumed(u64);
+}
+
+/// The host functions called by contracts, since the metrics were last taken.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallMetrics {
+	/// The number of reads of contract storage (`seal_take_storage` counts as both a read and a
+	/// write).
+	pub storage_reads: u32,
+	/// The number of writes (including removals) of contract storage.
+	pub storage_writes: u32,
+	/// The number of `seal_transfer` calls. Value transferred along with calls to other contracts
+	/// isn't counted.
+	pub transfers: u32,
+	/// The number of `seal_hash_*` calls.
+	pub hashes: u32,
+	/// The proof size (in bytes) consumed by the deployments and calls, i.e. their contribution to
+	/// the PoV of a block.
+	pub proof_size_consumed: u64,
+}
+
+impl CallMetrics {
+	fn from_counts(counts: &BTreeMap<&'static str, u32>, proof_size_consumed: u64) -> Self {
+		// The storage host functions charge their weight twice: upfront for the largest value, then
+		// again to refund the difference with the actual value.
+		let sum = |host_fns: &[&str], charges_per_call: u32| {
+			host_fns
+				.iter()
+				.map(|host_fn| counts.get(host_fn).copied().unwrap_or_default())
+				.map(|charges| charges.div_ceil(charges_per_call))
+				.sum()
+		};
+		Self {
+			storage_reads: sum(STORAGE_READS, 2),
+			storage_writes: sum(STORAGE_WRITES, 2),
+			transfers: sum(&["seal_transfer"], 1),
+			hashes: sum(HASHES, 1),
+			proof_size_consumed,
+		}
+	}
+}
+
+/// Counts a charge of the weight of the host function `name`, and returns the number of charges
+/// so far.
+///
+/// Does nothing outside the externalities, or if they don't count host functions.
+pub(crate) fn record(name: &'static str) -> Option<u32> {
+	sp_externalities::with_externalities(|ext| {
+		let counts = ext.extension::<HostFnCounts>()?;
+		let count = counts.0.entry(name).or_default();
+		*count += 1;
+		Some(*count)
+	})
+	.flatten()
+}
+
+/// Adds the proof size of `gas_consumed` by a contract interaction to the metrics.
+///
+/// Does nothing outside the externalities, or if they don't sum the proof size.
+pub(crate) fn record_proof_size(gas_consumed: Weight) {
+	sp_externalities::with_externalities(|ext| {
+		if let Some(consumed) = ext.extension::<ProofSizeConsumed>() {
+			consumed.0 = consumed.0.saturating_add(gas_consumed.proof_size());
+		}
+	});
+}
+
+/// Takes the metrics of the host functions called so far, resetting the counts.
+///
+/// Must be called within the externalities.
+pub(crate) fn take() -> CallMetrics {
+	sp_externalities::with_externalities(|ext| {
+		let proof_size_consumed = ext
+			.extension::<ProofSizeConsumed>()
+			.map(|consumed| std::mem::take(&mut consumed.0))
+			.unwrap_or_default();
+		ext.extension::<HostFnCounts>()
+			.map(|counts| {
+				CallMetrics::from_counts(&std::mem::take(&mut counts.0), proof_size_consumed)
+			})
+			.unwrap_or_default()
+	})
+	.unwrap_or_default()
+}
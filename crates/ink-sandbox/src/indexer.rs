@@ -0,0 +1,321 @@
+//! Export of the events of the blocks built by the sandbox, for testing indexer mappings (e.g.
+//! Subsquid or SubQuery) against events produced by real contracts.
+//!
+//! The events of a block only live in the state until the next block is initialized, so the
+//! sandboxes created with [`crate::create_sandbox`] can archive them in an extension of the
+//! externalities whenever a block is finalized. Archiving is opt-in and bounded: it starts once a
+//! number of blocks to keep is set with [`crate::api::prelude::SystemAPI::set_event_retention`],
+//! and only the last blocks are kept. They can be exported afterwards with
+//! [`crate::api::prelude::SystemAPI::export_block_events`], in a format close to the blocks
+//! consumed by indexers:
+//!
+//! ```rust, ignore
+//! sandbox.set_event_retention(100);
+//! let start = sandbox.block_number();
+//! sandbox.call_contract(token, 0, transfer, ALICE, gas_limit, None, Determinism::Enforced);
+//! sandbox.build_block();
+//! let blocks = sandbox.export_block_events(start..);
+//! std::fs::write("blocks.json", serde_json::to_string(&blocks)?)?;
+//! ```
+//!
+//! Only finalized blocks are exported: the events of the current block are not.
+
+use std::ops::RangeBounds;
+
+use frame_metadata::RuntimeMetadata;
+use frame_support::sp_runtime::SaturatedConversion;
+use frame_system::{pallet_prelude::BlockNumberFor, Phase};
+use scale::{Decode, Encode};
+use serde::{Serialize, Serializer};
+use sp_externalities::{decl_extension, ExternalitiesExt};
+
+use crate::{EventRecordOf, Sandbox};
+
+decl_extension! {
+	/// The archive of the events of the finalized blocks.
+	pub struct ArchivedBlocks(Archive);
+}
+
+/// The (SCALE-encoded) headers and events of the last finalized blocks, with their heights.
+#[derive(Default)]
+pub struct Archive {
+	/// The number of blocks to keep, none (i.e. archiving is disabled) by default.
+	retention: usize,
+	/// The archived blocks, by increasing height.
+	blocks: Vec<(u64, Vec<u8>)>,
+}
+
+/// The events of a finalized block, as consumed by indexers.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", bound = "")]
+pub struct BlockEvents<R: frame_system::Config> {
+	/// The height of the block.
+	pub height: u64,
+	/// The hash of the block.
+	#[serde(serialize_with = "encoded_hex")]
+	pub hash: R::Hash,
+	/// The timestamp of the block, as set in the timestamp pallet.
+	pub timestamp: u64,
+	/// The events of the block, in the order of emission.
+	pub events: Vec<IndexedEvent<R>>,
+}
+
+/// An event of a block, named after its pallet and variant (e.g. `Contracts.ContractEmitted`).
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", bound = "")]
+pub struct IndexedEvent<R: frame_system::Config> {
+	/// The index of the event in the block.
+	pub index: u32,
+	/// The name of the event: the name of its pallet and the name of its variant, separated by a
+	/// dot.
+	pub name: String,
+	/// The phase of the block during which the event was emitted.
+	pub phase: EventPhase,
+	/// The index of the extrinsic which emitted the event, if emitted while applying one.
+	pub extrinsic_index: Option<u32>,
+	/// The topics of the event, e.g. the topics of a contract event.
+	#[serde(serialize_with = "encoded_hex_list")]
+	pub topics: Vec<R::Hash>,
+	/// The SCALE-encoded fields of the event, e.g. the address of the contract and the data of a
+	/// `Contracts.ContractEmitted` event.
+	#[serde(serialize_with = "hex")]
+	pub args: Vec<u8>,
+	/// The event itself.
+	#[serde(skip)]
+	pub event: R::RuntimeEvent,
+}
+
+/// The phase of a block during which an event was emitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum EventPhase {
+	/// While initializing the block.
+	Initialization,
+	/// While applying an extrinsic.
+	ApplyExtrinsic,
+	/// While finalizing the block.
+	Finalization,
+}
+
+/// Archives the events of a finalized block, if archiving is enabled.
+///
+/// Must be called within the externalities. The blocks archived at the same or a greater height
+/// (e.g. on another branch of the chain) are discarded, and so are the oldest ones beyond the
+/// retention.
+pub(crate) fn archive<R: frame_system::Config>(
+	height: BlockNumberFor<R>,
+	hash: R::Hash,
+	timestamp: u64,
+	events: Vec<EventRecordOf<R>>,
+) {
+	let height = height.saturated_into::<u64>();
+	sp_externalities::with_externalities(|ext| {
+		let Some(archive) = ext.extension::<ArchivedBlocks>() else { return };
+		if archive.retention == 0 {
+			return;
+		}
+		archive.blocks.retain(|(archived, _)| *archived < height);
+		archive.blocks.push((height, (hash, timestamp, events).encode()));
+		let excess = archive.blocks.len().saturating_sub(archive.retention);
+		archive.blocks.drain(..excess);
+	});
+}
+
+/// Keeps the events of the last `blocks` finalized blocks, discarding the older ones. Archiving
+/// is disabled if `blocks` is zero.
+///
+/// Must be called within the externalities.
+pub(crate) fn set_retention(blocks: usize) {
+	sp_externalities::with_externalities(|ext| {
+		if let Some(archive) = ext.extension::<ArchivedBlocks>() {
+			archive.retention = blocks;
+			let excess = archive.blocks.len().saturating_sub(blocks);
+			archive.blocks.drain(..excess);
+		}
+	});
+}
+
+/// Returns the events of the archived blocks within `blocks`, with the names of the events
+/// resolved from the metadata of the runtime of `S`.
+///
+/// Must be called within the externalities.
+pub(crate) fn export<S: Sandbox>(
+	blocks: impl RangeBounds<BlockNumberFor<S::Runtime>>,
+) -> Vec<BlockEvents<S::Runtime>> {
+	let archived = sp_externalities::with_externalities(|ext| {
+		ext.extension::<ArchivedBlocks>()
+			.map(|archive| archive.blocks.clone())
+			.unwrap_or_default()
+	})
+	.unwrap_or_default();
+	let names = EventNames::new(S::get_metadata().1);
+
+	archived
+		.into_iter()
+		.filter(|(height, _)| {
+			let height: BlockNumberFor<S::Runtime> = (*height).saturated_into();
+			blocks.contains(&height)
+		})
+		.map(|(height, encoded)| {
+			let (hash, timestamp, events) = <(
+				<S::Runtime as frame_system::Config>::Hash,
+				u64,
+				Vec<EventRecordOf<S::Runtime>>,
+			)>::decode(&mut &encoded[..])
+			.expect("Archived blocks are valid");
+			let events = events
+				.into_iter()
+				.enumerate()
+				.map(|(index, record)| {
+					let encoded = record.event.encode();
+					let (phase, extrinsic_index) = match record.phase {
+						Phase::Initialization => (EventPhase::Initialization, None),
+						Phase::ApplyExtrinsic(index) => (EventPhase::ApplyExtrinsic, Some(index)),
+						Phase::Finalization => (EventPhase::Finalization, None),
+					};
+					IndexedEvent {
+						index: index as u32,
+						name: names.name_of(&encoded),
+						phase,
+						extrinsic_index,
+						topics: record.topics,
+						args: encoded.get(2..).unwrap_or_default().to_vec(),
+						event: record.event,
+					}
+				})
+				.collect();
+			BlockEvents { height, hash, timestamp, events }
+		})
+		.collect()
+}
+
+/// The names of the events of a runtime, by pallet index and variant index.
+struct EventNames(Vec<(u8, String, Vec<(u8, String)>)>);
+
+impl EventNames {
+	fn new(metadata: RuntimeMetadata) -> Self {
+		macro_rules! event_names {
+			($metadata:expr) => {
+				$metadata
+					.pallets
+					.iter()
+					.filter_map(|pallet| {
+						let ty = $metadata.types.resolve(pallet.event.as_ref()?.ty.id)?;
+						let variants = match &ty.type_def {
+							scale_info::TypeDef::Variant(events) => events
+								.variants
+								.iter()
+								.map(|variant| (variant.index, variant.name.clone()))
+								.collect(),
+							_ => vec![],
+						};
+						Some((pallet.index, pallet.name.clone(), variants))
+					})
+					.collect()
+			};
+		}
+		Self(match metadata {
+			RuntimeMetadata::V14(metadata) => event_names!(metadata),
+			RuntimeMetadata::V15(metadata) => event_names!(metadata),
+			_ => vec![],
+		})
+	}
+
+	/// Returns the name of the SCALE-encoded runtime event `event`, or `Unknown` if it isn't
+	/// described by the metadata.
+	fn name_of(&self, event: &[u8]) -> String {
+		let (Some(pallet_index), Some(variant_index)) = (event.first(), event.get(1)) else {
+			return "Unknown".to_string();
+		};
+		self.0
+			.iter()
+			.find(|(index, ..)| index == pallet_index)
+			.and_then(|(_, pallet, variants)| {
+				let (_, variant) = variants.iter().find(|(index, _)| index == variant_index)?;
+				Some(format!("{pallet}.{variant}"))
+			})
+			.unwrap_or_else(|| "Unknown".to_string())
+	}
+}
+
+/// Renders `bytes` as a `0x`-prefixed hex string.
+fn to_hex(bytes: &[u8]) -> String {
+	let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+	format!("0x{hex}")
+}
+
+fn hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(&to_hex(bytes))
+}
+
+fn encoded_hex<S: Serializer, T: Encode>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(&to_hex(&value.encode()))
+}
+
+// `serialize_with` passes the field itself, so the list can't be taken as a slice.
+#[allow(clippy::ptr_arg)]
+fn encoded_hex_list<S: Serializer, T: Encode>(
+	values: &Vec<T>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	serializer.collect_seq(values.iter().map(|value| to_hex(&value.encode())))
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::AccountId32;
+
+	use crate::{
+		api::prelude::{BalanceAPI, SystemAPI},
+		DefaultSandbox, RuntimeCall, RuntimeOf, Sandbox,
+	};
+
+	#[test]
+	fn events_of_finalized_blocks_are_exported() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.set_event_retention(10);
+		let recipient = AccountId32::new([2u8; 32]);
+		let start = sandbox.build_block();
+		let call = RuntimeCall::<RuntimeOf<DefaultSandbox>>::Balances(
+			pallet_balances::Call::transfer_allow_death {
+				dest: recipient.clone().into(),
+				value: 100,
+			},
+		);
+		sandbox.runtime_call(call, Some(DefaultSandbox::default_actor())).unwrap();
+		assert!(sandbox.export_block_events(start..).is_empty());
+
+		sandbox.build_block();
+		let blocks = sandbox.export_block_events(start..);
+		assert_eq!(blocks.len(), 1);
+		assert_eq!(blocks[0].height, 2);
+		assert!(blocks[0].events.iter().any(|event| event.name == "Balances.Transfer"));
+		assert_eq!(sandbox.free_balance(&recipient), 100);
+
+		let json = serde_json::to_value(&blocks).unwrap();
+		assert_eq!(json[0]["height"], 2);
+		assert!(json[0]["hash"].as_str().unwrap().starts_with("0x"));
+		assert!(json[0]["events"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.any(|event| event["name"] == "Balances.Transfer" && event["index"].is_u64()));
+	}
+
+	#[test]
+	fn archiving_is_opt_in_and_bounded() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.build_blocks(3);
+		assert!(sandbox.export_block_events(..).is_empty());
+
+		sandbox.set_event_retention(2);
+		sandbox.build_blocks(3);
+		let heights: Vec<_> =
+			sandbox.export_block_events(..).iter().map(|block| block.height).collect();
+		assert_eq!(heights, vec![5, 6]);
+
+		sandbox.set_event_retention(1);
+		assert_eq!(sandbox.export_block_events(..).len(), 1);
+		sandbox.set_event_retention(0);
+		assert!(sandbox.export_block_events(..).is_empty());
+	}
+}
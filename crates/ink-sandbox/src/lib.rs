@@ -1,8 +1,31 @@
 use core::any::Any;
+use std::path::Path;
 
 pub mod api;
+pub mod api_version;
+pub mod call_builder;
+pub mod call_chain;
+pub mod chain_extension;
+mod code_cache;
+pub mod config;
+pub mod constants;
+pub mod debug_hooks;
+pub mod delegate;
+pub mod determinism;
+pub mod failures;
+pub mod forks;
+pub mod history;
+pub mod host_fns;
+pub mod indexer;
 pub mod macros;
-
+pub mod runtime_calls;
+pub mod seed;
+pub mod shared;
+pub mod snapshot;
+
+pub use call_builder::{CallBuilder, CallBuilderError};
+pub use config::{ConfigError, SandboxConfig};
+pub use constants::ConstantError;
 pub use frame_metadata::RuntimeMetadataPrefixed;
 pub use frame_support::weights::Weight;
 use frame_support::{
@@ -10,20 +33,29 @@ use frame_support::{
 	traits::fungible::Inspect,
 };
 use frame_system::{pallet_prelude::BlockNumberFor, EventRecord};
-pub use macros::{BlockBuilder, DefaultSandbox};
+pub use macros::{
+	BlockBuilder, DefaultSandbox, ParachainSandbox, SandboxParameters, ScheduleParameters,
+};
 use pallet_contracts::{ContractExecResult, ContractInstantiateResult};
-/// Export pallets that are used in [`crate::create_sandbox`]
+use scale::{Decode, Encode};
+pub use shared::SharedSandbox;
+/// Export pallets that are used in [`crate::create_sandbox`] and
+/// [`crate::create_parachain_sandbox`]
 pub use {
+	cumulus_pallet_parachain_system,
 	frame_support::{
 		self,
 		sp_runtime::{AccountId32, DispatchError},
 	},
-	frame_system, pallet_assets, pallet_balances, pallet_contracts, pallet_nfts, pallet_timestamp,
-	paste,
+	frame_system, pallet_assets, pallet_authorship, pallet_balances, pallet_collator_selection,
+	pallet_collective, pallet_contracts, pallet_identity, pallet_motion, pallet_nfts,
+	pallet_preimage, pallet_referenda, pallet_scheduler, pallet_session, pallet_timestamp,
+	pallet_treasury, pallet_tx_pause, pallet_xcm, paste,
 	sp_core::crypto::Ss58Codec,
 	sp_externalities::{self, Extension},
 	sp_io::TestExternalities,
 	sp_runtime_interface::{self},
+	staging_parachain_info, xcm, xcm_builder, xcm_executor,
 };
 
 /// Alias for the account ID lookup source.
@@ -90,16 +122,56 @@ pub trait Sandbox {
 		Default::default()
 	}
 
+	/// Hook called once a new block has been initialized by [`crate::api::prelude::SystemAPI`],
+	/// e.g. to keep the state of the recent blocks (see [`crate::forks`]).
+	fn on_block_initialized(&mut self) {}
+
 	/// Default actor for the sandbox.
 	fn default_actor() -> AccountIdFor<Self::Runtime>;
 
+	/// Default gas limit of the contract interactions for which none is specified.
 	fn default_gas_limit() -> Weight {
 		Weight::from_parts(100_000_000_000, 3 * 1024 * 1024)
 	}
 
+	/// Gas limit of the contract interactions for which none is specified, as configured for this
+	/// instance (see [`crate::SandboxParameters::default_gas_limit`]). `default_gas_limit` unless
+	/// overridden.
+	fn configured_gas_limit(&mut self) -> Weight {
+		Self::default_gas_limit()
+	}
+
 	/// Metadata of the runtime.
 	fn get_metadata() -> RuntimeMetadataPrefixed;
 
+	/// Write the SCALE-encoded metadata of the runtime to `path`, so that external tooling (e.g.
+	/// `subxt codegen`) can be used against the exact runtime of the sandbox.
+	///
+	/// # Arguments
+	///
+	/// * `path` - The path of the metadata file.
+	fn export_metadata<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+		std::fs::write(path, Self::get_metadata().encode())
+	}
+
+	/// Read the constant `constant` of the pallet `pallet` from the runtime metadata, decoded as
+	/// `V`. See [`crate::constants`].
+	///
+	/// # Arguments
+	///
+	/// * `pallet` - The name of the pallet, e.g. `Balances`.
+	/// * `constant` - The name of the constant, e.g. `ExistentialDeposit`.
+	fn pallet_constant<V: Decode>(
+		&mut self,
+		pallet: &str,
+		constant: &str,
+	) -> Result<V, ConstantError>
+	where
+		Self: Sized,
+	{
+		constants::pallet_constant(self, pallet, constant)
+	}
+
 	/// Convert an account to an call origin.
 	fn convert_account_to_origin(
 		account: AccountIdFor<Self::Runtime>,
@@ -1,14 +1,21 @@
-use std::time::SystemTime;
+use std::{
+	any::TypeId,
+	collections::HashMap,
+	sync::{Mutex, OnceLock, PoisonError},
+};
 
 use frame_support::{
 	sp_runtime::{
 		traits::{Header, One},
-		BuildStorage,
+		AccountId32, BuildStorage, Perbill, SaturatedConversion, Storage,
 	},
 	traits::Hooks,
+	weights::{Weight, WeightToFeeCoefficient, WeightToFeePolynomial},
 };
 use frame_system::pallet_prelude::BlockNumberFor;
-use sp_io::TestExternalities;
+use pallet_assets::Instance1;
+use scale::{Decode, Encode};
+use sp_io::{hashing::blake2_256, TestExternalities};
 
 /// A helper struct for initializing and finalizing blocks.
 pub struct BlockBuilder<T>(std::marker::PhantomData<T>);
@@ -18,19 +25,60 @@ impl<
 	> BlockBuilder<T>
 {
 	/// Create a new externalities with the given balances.
+	///
+	/// The genesis storage is built only once per runtime and set of balances, and cloned for
+	/// every subsequent externalities.
 	pub fn new_ext(balances: Vec<(T::AccountId, T::Balance)>) -> TestExternalities {
-		let mut storage = frame_system::GenesisConfig::<T>::default().build_storage().unwrap();
-
-		pallet_balances::GenesisConfig::<T> { balances, ..Default::default() }
-			.assimilate_storage(&mut storage)
-			.unwrap();
+		Self::build_ext(Self::genesis_storage(balances, &[], |_| {}), None)
+	}
 
+	/// Create a new externalities with the given genesis storage and timestamps of the blocks.
+	fn build_ext(
+		storage: Storage,
+		timestamps: Option<crate::config::BlockConfig>,
+	) -> TestExternalities {
 		let mut ext = TestExternalities::new(storage);
+		ext.register_extension(crate::runtime_calls::RecordedRuntimeCalls(Vec::new()));
+		ext.register_extension(crate::failures::InjectedFailures(Vec::new()));
+		ext.register_extension(crate::host_fns::HostFnCounts(Default::default()));
+		ext.register_extension(crate::host_fns::ProofSizeConsumed(0));
+		ext.register_extension(crate::indexer::ArchivedBlocks(Default::default()));
+		ext.register_extension(crate::config::BlockTimestamps(timestamps));
+		ext.register_extension(crate::api::xcm_api::PendingXcmResponses(Vec::new()));
 
 		ext.execute_with(|| Self::initialize_block(BlockNumberFor::<T>::one(), Default::default()));
 		ext
 	}
 
+	/// Return the genesis storage with the given balances, extended by `extend` (identified by
+	/// `extension`), building it only if it isn't cached yet.
+	fn genesis_storage(
+		balances: Vec<(T::AccountId, T::Balance)>,
+		extension: &[u8],
+		extend: impl FnOnce(&mut Storage),
+	) -> Storage {
+		static GENESIS_CACHE: OnceLock<Mutex<HashMap<(TypeId, [u8; 32]), Storage>>> =
+			OnceLock::new();
+
+		let key = (TypeId::of::<T>(), blake2_256(&(balances.encode(), extension).encode()));
+		let mut cache = GENESIS_CACHE
+			.get_or_init(Default::default)
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+		cache
+			.entry(key)
+			.or_insert_with(|| {
+				let mut storage =
+					frame_system::GenesisConfig::<T>::default().build_storage().unwrap();
+				pallet_balances::GenesisConfig::<T> { balances, ..Default::default() }
+					.assimilate_storage(&mut storage)
+					.unwrap();
+				extend(&mut storage);
+				storage
+			})
+			.clone()
+	}
+
 	/// Initialize a new block at particular height.
 	pub fn initialize_block(
 		height: frame_system::pallet_prelude::BlockNumberFor<T>,
@@ -39,15 +87,13 @@ impl<
 		frame_system::Pallet::<T>::reset_events();
 		frame_system::Pallet::<T>::initialize(&height, &parent_hash, &Default::default());
 		pallet_balances::Pallet::<T>::on_initialize(height);
-		pallet_timestamp::Pallet::<T>::set_timestamp(
-			SystemTime::now()
-				.duration_since(SystemTime::UNIX_EPOCH)
-				.expect("Time went backwards")
-				.as_secs(),
-		);
+		pallet_timestamp::Pallet::<T>::set_timestamp(crate::config::timestamp_of(
+			height.saturated_into(),
+		));
 		pallet_timestamp::Pallet::<T>::on_initialize(height);
 		pallet_contracts::Pallet::<T>::on_initialize(height);
 		frame_system::Pallet::<T>::note_finished_initialize();
+		crate::api::xcm_api::deliver_pending_responses();
 	}
 
 	/// Finalize a block at particular height.
@@ -57,14 +103,270 @@ impl<
 		pallet_contracts::Pallet::<T>::on_finalize(height);
 		pallet_timestamp::Pallet::<T>::on_finalize(height);
 		pallet_balances::Pallet::<T>::on_finalize(height);
-		frame_system::Pallet::<T>::finalize().hash()
+		let events = frame_system::Pallet::<T>::events();
+		let timestamp = pallet_timestamp::Pallet::<T>::get();
+		let hash = frame_system::Pallet::<T>::finalize().hash();
+		crate::indexer::archive::<T>(height, hash, timestamp, events);
+		hash
+	}
+}
+
+impl<T> BlockBuilder<T>
+where
+	T: frame_system::Config<AccountId = AccountId32>
+		+ pallet_balances::Config<Balance = u128>
+		+ pallet_timestamp::Config<Moment = u64>
+		+ pallet_contracts::Config
+		+ pallet_assets::Config<Instance1, AssetId = u32, Balance = u128>,
+{
+	/// Create a new externalities with the genesis balances and assets, and the timestamps of the
+	/// blocks of `config`.
+	pub fn new_ext_with_config(config: &crate::SandboxConfig) -> TestExternalities {
+		let storage =
+			Self::genesis_storage(config.balances.clone(), &config.assets.encode(), |storage| {
+				pallet_assets::GenesisConfig::<T, Instance1> {
+					assets: config
+						.assets
+						.iter()
+						.map(|asset| {
+							(asset.id, asset.owner.clone(), asset.is_sufficient, asset.min_balance)
+						})
+						.collect(),
+					accounts: config
+						.assets
+						.iter()
+						.flat_map(|asset| {
+							asset
+								.accounts
+								.iter()
+								.map(|(account, balance)| (asset.id, account.clone(), *balance))
+						})
+						.collect(),
+					..Default::default()
+				}
+				.assimilate_storage(storage)
+				.unwrap();
+			});
+		Self::build_ext(storage, config.timestamps())
+	}
+}
+
+/// Runtime parameters of a sandbox created with [`crate::create_sandbox`] that can be overridden
+/// per instance, e.g. to mirror the deposit economics of a production runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SandboxParameters {
+	/// The balance a contract needs to deposit per byte of storage.
+	pub deposit_per_byte: u128,
+	/// The balance a contract needs to deposit per storage item.
+	pub deposit_per_item: u128,
+	/// The storage deposit limit used when none is specified for a call.
+	pub default_deposit_limit: u128,
+	/// The percentage of the code deposit locked when a contract depends on a code hash.
+	pub code_hash_lockup_deposit_percent: Perbill,
+	/// The polynomial converting weight (`ref_time`) to fees, used to price contract gas.
+	pub weight_to_fee: Vec<WeightToFeeCoefficient<u128>>,
+	/// The fee per byte of an extrinsic.
+	pub transaction_byte_fee: u128,
+	/// The seed of the randomness exposed to contracts, [`crate::seed::suite_seed`] by default.
+	pub seed: u64,
+	/// The maximum length of a contract code, in bytes.
+	pub max_code_len: u32,
+	/// The maximum number of codes a contract can depend on (through `lock_delegate_dependency`).
+	pub max_delegate_dependencies: u32,
+	/// The balance reserved for creating an asset, in every assets instance of the sandbox.
+	pub asset_deposit: u128,
+	/// The balance reserved for holding an asset, in every assets instance of the sandbox.
+	pub asset_account_deposit: u128,
+	/// The balance reserved for an approval of an asset, in every assets instance of the sandbox.
+	pub asset_approval_deposit: u128,
+	/// The schedule of the contracts pallet: the cost of wasm instructions and the limits of
+	/// contracts.
+	pub schedule: ScheduleParameters,
+	/// The gas limit of the contract interactions for which none is specified, e.g. by a session.
+	pub default_gas_limit: Weight,
+}
+
+impl SandboxParameters {
+	/// Uses the fee model of a production runtime, e.g. `WeightToFee` and `TransactionByteFee` of
+	/// Pop.
+	///
+	/// # Arguments
+	///
+	/// * `transaction_byte_fee` - The fee per byte of an extrinsic.
+	pub fn with_fees<W: WeightToFeePolynomial<Balance = u128>>(
+		self,
+		transaction_byte_fee: u128,
+	) -> Self {
+		Self { weight_to_fee: W::polynomial().to_vec(), transaction_byte_fee, ..self }
+	}
+
+	/// Fixes the seed of the randomness exposed to contracts.
+	///
+	/// # Arguments
+	///
+	/// * `seed` - The seed, e.g. printed by a failing run of the test suite.
+	pub fn seed(self, seed: u64) -> Self {
+		Self { seed, ..self }
+	}
+
+	/// Uses the schedule `schedule`, e.g. one of the presets of [`ScheduleParameters`].
+	///
+	/// # Arguments
+	///
+	/// * `schedule` - The schedule of the contracts pallet.
+	pub fn schedule(self, schedule: ScheduleParameters) -> Self {
+		Self { schedule, ..self }
+	}
+
+	/// Uses `default_gas_limit` for the contract interactions for which no gas limit is specified,
+	/// e.g. raised for suites of heavy messages or lowered for suites of tight limits.
+	///
+	/// # Arguments
+	///
+	/// * `default_gas_limit` - The default gas limit.
+	pub fn default_gas_limit(self, default_gas_limit: Weight) -> Self {
+		Self { default_gas_limit, ..self }
+	}
+}
+
+impl Default for SandboxParameters {
+	fn default() -> Self {
+		Self {
+			deposit_per_byte: 1,
+			deposit_per_item: 1,
+			default_deposit_limit: 10_000_000,
+			code_hash_lockup_deposit_percent: Perbill::from_percent(0),
+			// Identity: the fee equals the `ref_time`.
+			weight_to_fee: vec![WeightToFeeCoefficient {
+				coeff_integer: 1,
+				coeff_frac: Perbill::zero(),
+				negative: false,
+				degree: 1,
+			}],
+			transaction_byte_fee: 0,
+			seed: crate::seed::suite_seed(),
+			max_code_len: 123 * 1024,
+			max_delegate_dependencies: 32,
+			asset_deposit: 1,
+			asset_account_deposit: 10,
+			asset_approval_deposit: 1,
+			schedule: ScheduleParameters::default(),
+			default_gas_limit: <crate::DefaultSandbox as crate::Sandbox>::default_gas_limit(),
+		}
+	}
+}
+
+/// The schedule of the contracts pallet (`pallet_contracts::Schedule`) of a sandbox, i.e. the
+/// cost of wasm instructions and the limits of contracts, so gas-sensitive contracts can be
+/// tested against the schedule of the chain they are deployed to. By default, it is the default
+/// schedule of the contracts pallet, with the benchmarked instruction weight.
+///
+/// ```rust, ignore
+/// let parameters = SandboxParameters::default()
+///     .schedule(ScheduleParameters::preset("development-lenient").unwrap());
+/// let sandbox = DefaultSandbox::with_parameters(parameters);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct ScheduleParameters {
+	/// The `ref_time` charged per wasm instruction, or `None` for the weight benchmarked for the
+	/// contracts pallet.
+	pub instruction_weight: Option<u32>,
+	/// The maximum number of topics of an event.
+	pub event_topics: u32,
+	/// The maximum number of memory pages of a contract.
+	pub memory_pages: u32,
+	/// The maximum length of the subject of `seal_random`, in bytes.
+	pub subject_len: u32,
+	/// The maximum size of a storage value or an event payload, in bytes.
+	pub payload_len: u32,
+	/// The total memory available to the runtime, in bytes.
+	pub runtime_memory: u32,
+	/// The total memory available to the runtime of validators, in bytes.
+	pub validator_runtime_memory: u32,
+	/// The `ref_time` charged per byte of an event.
+	pub event_ref_time: u64,
+}
+
+impl ScheduleParameters {
+	/// The names of the presets, see [`Self::preset`].
+	pub const PRESETS: [&'static str; 2] = ["default", "development-lenient"];
+
+	/// Returns the preset `name`, one of [`Self::PRESETS`].
+	pub fn preset(name: &str) -> Option<Self> {
+		match name {
+			"default" => Some(Self::default()),
+			"development-lenient" => Some(Self::development_lenient()),
+			_ => None,
+		}
+	}
+
+	/// A schedule for development: wasm instructions and event bytes are free, and contracts can
+	/// use more memory, emit events with more topics and store larger values than by default.
+	pub fn development_lenient() -> Self {
+		Self {
+			instruction_weight: Some(0),
+			event_topics: 16,
+			memory_pages: 32,
+			payload_len: 64 * 1024,
+			event_ref_time: 0,
+			..Self::default()
+		}
+	}
+
+	/// Returns the schedule of the contracts pallet of the runtime `T`.
+	pub fn to_schedule<T: pallet_contracts::Config>(&self) -> pallet_contracts::Schedule<T> {
+		let mut schedule = pallet_contracts::Schedule::<T>::default();
+		if let Some(instruction_weight) = self.instruction_weight {
+			schedule.instruction_weights.base = instruction_weight;
+		}
+		schedule.limits.event_topics = self.event_topics;
+		schedule.limits.memory_pages = self.memory_pages;
+		schedule.limits.subject_len = self.subject_len;
+		schedule.limits.payload_len = self.payload_len;
+		schedule.limits.runtime_memory = self.runtime_memory;
+		schedule.limits.validator_runtime_memory = self.validator_runtime_memory;
+		schedule.limits.event_ref_time = self.event_ref_time;
+		schedule
 	}
 }
 
-// Macro that implements the sandbox trait on the provided runtime.
+impl Default for ScheduleParameters {
+	fn default() -> Self {
+		let limits = pallet_contracts::Limits::default();
+		Self {
+			instruction_weight: None,
+			event_topics: limits.event_topics,
+			memory_pages: limits.memory_pages,
+			subject_len: limits.subject_len,
+			payload_len: limits.payload_len,
+			runtime_memory: limits.runtime_memory,
+			validator_runtime_memory: limits.validator_runtime_memory,
+			event_ref_time: limits.event_ref_time,
+		}
+	}
+}
+
+/// Evaluates the weight to fee polynomial given by `coefficients`, the same way as
+/// `WeightToFeePolynomial` does.
+pub fn weight_to_fee(coefficients: &[WeightToFeeCoefficient<u128>], weight: Weight) -> u128 {
+	let ref_time = u128::from(weight.ref_time());
+	coefficients.iter().fold(0, |fee, coefficient| {
+		let power = ref_time.saturating_pow(coefficient.degree.into());
+		let term = (coefficient.coeff_frac * power)
+			.saturating_add(coefficient.coeff_integer.saturating_mul(power));
+		if coefficient.negative {
+			fee.saturating_sub(term)
+		} else {
+			fee.saturating_add(term)
+		}
+	})
+}
+
+// Macro that implements the sandbox trait on the provided runtime. The sandboxes created with
+// `create_sandbox!` also pass their forks and the `Get<Weight>` of their configured gas limit.
 #[macro_export]
 macro_rules! impl_sandbox {
-    ($sandbox:ident, $runtime:ident, $account:ident) => {
+    ($sandbox:ident, $runtime:ident, $account:ident $(, $forks:ident $(, $gas_limit:ty)?)?) => {
         use $crate::macros::BlockBuilder;
 
         impl $crate::Sandbox for $sandbox {
@@ -104,10 +406,24 @@ macro_rules! impl_sandbox {
                 BlockBuilder::<Self::Runtime>::finalize_block(height)
             }
 
+            $(
+            fn on_block_initialized(&mut self) {
+                let height = self.ext.execute_with($crate::frame_system::Pallet::<Self::Runtime>::block_number);
+                let state = self.ext.as_backend();
+                self.$forks.note_block(height.into(), state);
+            }
+            )?
+
             fn default_actor() -> $crate::AccountIdFor<Self::Runtime> {
                 $account
             }
 
+            $($(
+            fn configured_gas_limit(&mut self) -> $crate::Weight {
+                self.ext.execute_with(<$gas_limit as $crate::frame_support::traits::Get<$crate::Weight>>::get)
+            }
+            )?)?
+
             fn get_metadata() -> $crate::RuntimeMetadataPrefixed {
                 Self::Runtime::metadata()
             }
@@ -121,9 +437,49 @@ macro_rules! impl_sandbox {
     };
 }
 
+/// The default maximum number of nested contract calls of the sandboxes.
+pub const DEFAULT_CALL_STACK_DEPTH: usize = 5;
+
+// Macro returning the given call stack depth, or the default one.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! call_stack_depth {
+	() => {
+		$crate::macros::DEFAULT_CALL_STACK_DEPTH
+	};
+	($depth:expr) => {
+		$depth
+	};
+}
+
 /// Macro creating a minimal runtime with the given name. Optionally can take a chain
 /// extension type as a second argument.
 ///
+/// The runtime only has the system, assets, balances, timestamp, contracts and nfts pallets. The
+/// pallets of a parachain (governance, staking, xcm, ...) are opt-in, see
+/// [`crate::create_parachain_sandbox`].
+///
+/// The chain extension is wrapped into [`crate::chain_extension::TracingChainExtension`], so its
+/// calls can be reported.
+///
+/// The maximum depth of nested contract calls can be set after the additional pallets, and is
+/// exported as `CALL_STACK_DEPTH`:
+///
+/// ```rust, ignore
+/// create_sandbox!(DeepSandbox, (), (), {}, call_stack_depth = 16);
+/// ```
+///
+/// The `Debug` type of the contracts pallet (the third argument) must implement
+/// [`crate::debug_hooks::DebugHooks`]. Hooks can be plugged in with
+/// [`crate::debug_hooks::HookedDebug`]:
+///
+/// ```rust, ignore
+/// create_sandbox!(LoggingSandbox, (), HookedDebug<Logging>);
+/// ```
+///
+/// The sandboxes are created from a [`crate::SandboxConfig`] with `with_config`, the default one
+/// by `Default`.
+///
 /// The new macro will automatically implement `crate::Sandbox`.
 #[macro_export]
 macro_rules! create_sandbox {
@@ -137,16 +493,23 @@ macro_rules! create_sandbox {
             $crate::create_sandbox!($name, [<$name Runtime>], $chain_extension, $debug, {});
         }
     };
-    ($name:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? }) => {
+    ($name:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? } $(, call_stack_depth = $depth:expr)?) => {
         $crate::paste::paste! {
             $crate::create_sandbox!($name, [<$name Runtime>], $chain_extension, $debug, {
                 $(
                     $pallet_name : $pallet,
                 )*
-            });
+            } $(, call_stack_depth = $depth)?);
         }
     };
-    ($sandbox:ident, $runtime:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? }) => {
+    ($sandbox:ident, $runtime:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? } $(, call_stack_depth = $depth:expr)?) => {
+        $crate::create_sandbox!(@minimal $sandbox, $runtime, $chain_extension, $debug, {
+            $(
+                $pallet_name : $pallet,
+            )*
+        } $(, call_stack_depth = $depth)?);
+    };
+    (@$kind:ident $sandbox:ident, $runtime:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? } $(, call_stack_depth = $depth:expr)?) => {
 
 
 // Put all the boilerplate into an auxiliary module
@@ -171,19 +534,14 @@ mod construct_runtime {
     use scale_info::TypeInfo;
 
     // Define the runtime type as a collection of pallets
-    construct_runtime!(
-        pub enum $runtime {
-            System: $crate::frame_system,
-            Assets: $crate::pallet_assets::<Instance1>,
-            Balances: $crate::pallet_balances,
-            Timestamp: $crate::pallet_timestamp,
-            Contracts: $crate::pallet_contracts,
-            Nfts: $crate::pallet_nfts::<Instance1>,
-            $(
-                $pallet_name: $pallet,
-            )*
-        }
-    );
+    $crate::sandbox_pallets!(@runtime $kind, $runtime, {
+        $(
+            $pallet_name: $pallet,
+        )*
+    });
+
+    // Configure the pallets of the parachain sandboxes, if any
+    $crate::sandbox_pallets!(@config $kind, $runtime);
 
     // Configure pallet system
     #[derive_impl($crate::frame_system::config_preludes::SolochainDefaultConfig as $crate::frame_system::DefaultConfig)]
@@ -192,14 +550,23 @@ mod construct_runtime {
         type Version = ();
         type BlockHashCount = ConstU32<250>;
         type AccountData = $crate::pallet_balances::AccountData<<$runtime as $crate::pallet_balances::Config>::Balance>;
+        type BaseCallFilter = SandboxCallFilter;
+        type OnSetCode = SandboxOnSetCode;
+    }
+
+    // Assets deposits, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage AssetsDeposit: u128 = 1;
+        pub storage AssetsAccountDeposit: u128 = 10;
+        pub storage AssetsApprovalDeposit: u128 = 1;
     }
 
     // Configure pallet assets
     type AssetsInstance = $crate::pallet_assets::Instance1;
     impl $crate::pallet_assets::Config<AssetsInstance> for $runtime {
-        type ApprovalDeposit = ConstU128<1>;
-        type AssetAccountDeposit = ConstU128<10>;
-        type AssetDeposit = ConstU128<1>;
+        type ApprovalDeposit = AssetsApprovalDeposit;
+        type AssetAccountDeposit = AssetsAccountDeposit;
+        type AssetDeposit = AssetsDeposit;
         type AssetId = u32;
         type AssetIdParameter = u32;
         type Balance = u128;
@@ -244,30 +611,75 @@ mod construct_runtime {
         type WeightInfo = ();
     }
 
+    // Randomness derived from the seed of the sandbox (see `SandboxParameters::seed`), so runs
+    // with the same seed are reproducible.
     pub enum SandboxRandomness {}
     impl Randomness<H256, u32> for SandboxRandomness {
-        fn random(_subject: &[u8]) -> (H256, u32) {
-            unreachable!("No randomness")
+        fn random(subject: &[u8]) -> (H256, u32) {
+            let block_number = System::block_number();
+            let random = $crate::seed::derive(RandomnessSeed::get(), (subject, block_number));
+            (H256::from(random), block_number)
         }
     }
 
     type BalanceOf = <Balances as Currency<AccountId32>>::Balance;
     impl Convert<Weight, BalanceOf> for $runtime {
         fn convert(w: Weight) -> BalanceOf {
-            w.ref_time().into()
+            $crate::macros::weight_to_fee(&WeightToFee::get(), w)
         }
     }
 
     parameter_types! {
-        pub SandboxSchedule: $crate::pallet_contracts::Schedule<$runtime> = {
-            <$crate::pallet_contracts::Schedule<$runtime>>::default()
-        };
         pub DeletionWeightLimit: Weight = Weight::zero();
-        pub DefaultDepositLimit: BalanceOf = 10_000_000;
-        pub CodeHashLockupDepositPercent: Perbill = Perbill::from_percent(0);
-        pub MaxDelegateDependencies: u32 = 32;
     }
 
+    // Schedule, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage ContractsSchedule: $crate::ScheduleParameters =
+            <$crate::ScheduleParameters as Default>::default();
+    }
+
+    pub enum SandboxSchedule {}
+    impl $crate::frame_support::traits::Get<$crate::pallet_contracts::Schedule<$runtime>> for SandboxSchedule {
+        fn get() -> $crate::pallet_contracts::Schedule<$runtime> {
+            ContractsSchedule::get().to_schedule()
+        }
+    }
+
+    // Instantiation limits, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage ContractsMaxCodeLen: u32 = 123 * 1024;
+        pub storage MaxDelegateDependencies: u32 = 32;
+    }
+
+    // Deposit related constants, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage ContractsDepositPerByte: BalanceOf = 1;
+        pub storage ContractsDepositPerItem: BalanceOf = 1;
+        pub storage DefaultDepositLimit: BalanceOf = 10_000_000;
+        pub storage CodeHashLockupDepositPercent: Perbill = Perbill::from_percent(0);
+    }
+
+    // Fee related constants, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage WeightToFee: Vec<$crate::frame_support::weights::WeightToFeeCoefficient<BalanceOf>> =
+            <$crate::SandboxParameters as Default>::default().weight_to_fee;
+        pub storage TransactionByteFee: BalanceOf = 0;
+    }
+
+    // Randomness related constants, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage RandomnessSeed: u64 = <$crate::SandboxParameters as Default>::default().seed;
+    }
+
+    // Default gas limit, overridable per sandbox instance (see `SandboxParameters`).
+    parameter_types! {
+        pub storage DefaultGasLimit: Weight = <$sandbox as $crate::Sandbox>::default_gas_limit();
+    }
+
+    /// The maximum number of nested contract calls, beyond the outermost one.
+    pub const CALL_STACK_DEPTH: usize = $crate::call_stack_depth!($($depth)?);
+
     // Configure pallet contracts
     impl $crate::pallet_contracts::Config for $runtime {
         type Time = Timestamp;
@@ -275,16 +687,16 @@ mod construct_runtime {
         type Currency = Balances;
         type RuntimeEvent = RuntimeEvent;
         type RuntimeCall = RuntimeCall;
-        type CallFilter = ();
+        type CallFilter = $crate::runtime_calls::RecordRuntimeCalls;
         type WeightPrice = Self;
-        type WeightInfo = ();
-        type ChainExtension = $chain_extension;
+        type WeightInfo = $crate::failures::FailureInjectingWeights<(), $debug>;
+        type ChainExtension = $crate::chain_extension::TracingChainExtension<$chain_extension>;
         type Schedule = SandboxSchedule;
-        type CallStack = [$crate::pallet_contracts::Frame<Self>; 5];
-        type DepositPerByte = ConstU128<1>;
-        type DepositPerItem = ConstU128<1>;
+        type CallStack = [$crate::pallet_contracts::Frame<Self>; CALL_STACK_DEPTH];
+        type DepositPerByte = ContractsDepositPerByte;
+        type DepositPerItem = ContractsDepositPerItem;
         type AddressGenerator = $crate::pallet_contracts::DefaultAddressGenerator;
-        type MaxCodeLen = ConstU32<{ 123 * 1024 }>;
+        type MaxCodeLen = ContractsMaxCodeLen;
         type MaxStorageKeyLen = ConstU32<128>;
         type MaxTransientStorageSize = ConstU32<{ 1024 * 1024 }>;
         type UnsafeUnstableInterface = ConstBool<false>;
@@ -361,35 +773,993 @@ mod construct_runtime {
     }
 
     /// Unit base for balances.
-    pub const UNIT: u128 = 10_000_000_000;
+    pub const UNIT: u128 = $crate::config::UNIT;
     /// Default initial balance for the default account.
-    pub const INIT_AMOUNT: u128 = 100_000_000 * UNIT;
+    pub const INIT_AMOUNT: u128 = $crate::config::INIT_AMOUNT;
     /// Default account.
-    pub const DEFAULT_ACCOUNT: AccountId32 = AccountId32::new([1u8; 32]);
+    pub const DEFAULT_ACCOUNT: AccountId32 = $crate::config::DEFAULT_ACCOUNT;
 
     /// The sandbox.
     pub struct $sandbox {
         ext: $crate::TestExternalities,
+        forks: $crate::forks::Forks,
     }
 
     impl ::std::default::Default for $sandbox {
         fn default() -> Self {
-            let ext = BlockBuilder::<$runtime>::new_ext(vec![(DEFAULT_ACCOUNT, INIT_AMOUNT)]);
-            Self { ext }
+            Self::with_config(&$crate::SandboxConfig::default())
+        }
+    }
+
+    impl $sandbox {
+        /// Creates a new sandbox with the given configuration (see `SandboxConfig`).
+        ///
+        /// Panics if the schedule preset of the configuration is unknown.
+        pub fn with_config(config: &$crate::SandboxConfig) -> Self {
+            let parameters = config.parameters().expect("Invalid sandbox configuration");
+            let ext = BlockBuilder::<$runtime>::new_ext_with_config(config);
+            let mut sandbox = Self { ext, forks: Default::default() };
+            // Keep the storage of the default parameters untouched.
+            if sandbox.parameters() != parameters {
+                sandbox.set_parameters(parameters);
+            }
+            $crate::Sandbox::on_block_initialized(&mut sandbox);
+            sandbox
+        }
+
+        /// Creates a new sandbox with the given runtime parameters.
+        pub fn with_parameters(parameters: $crate::SandboxParameters) -> Self {
+            let mut sandbox = Self::default();
+            sandbox.set_parameters(parameters);
+            sandbox
+        }
+
+        /// Overrides the runtime parameters of the sandbox.
+        pub fn set_parameters(&mut self, parameters: $crate::SandboxParameters) {
+            self.ext.execute_with(|| {
+                ContractsDepositPerByte::set(&parameters.deposit_per_byte);
+                ContractsDepositPerItem::set(&parameters.deposit_per_item);
+                DefaultDepositLimit::set(&parameters.default_deposit_limit);
+                CodeHashLockupDepositPercent::set(&parameters.code_hash_lockup_deposit_percent);
+                WeightToFee::set(&parameters.weight_to_fee);
+                TransactionByteFee::set(&parameters.transaction_byte_fee);
+                RandomnessSeed::set(&parameters.seed);
+                ContractsMaxCodeLen::set(&parameters.max_code_len);
+                MaxDelegateDependencies::set(&parameters.max_delegate_dependencies);
+                AssetsDeposit::set(&parameters.asset_deposit);
+                AssetsAccountDeposit::set(&parameters.asset_account_deposit);
+                AssetsApprovalDeposit::set(&parameters.asset_approval_deposit);
+                ContractsSchedule::set(&parameters.schedule);
+                DefaultGasLimit::set(&parameters.default_gas_limit);
+            })
+        }
+
+        /// Returns the current runtime parameters of the sandbox.
+        pub fn parameters(&mut self) -> $crate::SandboxParameters {
+            self.ext.execute_with(|| $crate::SandboxParameters {
+                deposit_per_byte: ContractsDepositPerByte::get(),
+                deposit_per_item: ContractsDepositPerItem::get(),
+                default_deposit_limit: DefaultDepositLimit::get(),
+                code_hash_lockup_deposit_percent: CodeHashLockupDepositPercent::get(),
+                weight_to_fee: WeightToFee::get(),
+                transaction_byte_fee: TransactionByteFee::get(),
+                seed: RandomnessSeed::get(),
+                max_code_len: ContractsMaxCodeLen::get(),
+                max_delegate_dependencies: MaxDelegateDependencies::get(),
+                asset_deposit: AssetsDeposit::get(),
+                asset_account_deposit: AssetsAccountDeposit::get(),
+                asset_approval_deposit: AssetsApprovalDeposit::get(),
+                schedule: ContractsSchedule::get(),
+                default_gas_limit: DefaultGasLimit::get(),
+            })
+        }
+
+        /// Updates some of the runtime parameters of the sandbox, taking effect from the next
+        /// call or transaction.
+        ///
+        /// ```rust, ignore
+        /// sandbox.update_parameters(|parameters| parameters.deposit_per_byte = 10);
+        /// ```
+        pub fn update_parameters(&mut self, update: impl FnOnce(&mut $crate::SandboxParameters)) {
+            let mut parameters = self.parameters();
+            update(&mut parameters);
+            self.set_parameters(parameters);
+        }
+
+        /// Returns the fee of an extrinsic with the given `weight` and encoded `length`, according
+        /// to the fee model of the sandbox.
+        pub fn fee(&mut self, weight: Weight, length: u32) -> BalanceOf {
+            self.ext.execute_with(|| {
+                $crate::macros::weight_to_fee(&WeightToFee::get(), weight)
+                    .saturating_add(TransactionByteFee::get().saturating_mul(length.into()))
+            })
+        }
+
+        /// Creates a new branch of the chain, forked off the block `height` of the current
+        /// branch, and returns its identifier. Only the retained blocks (the last
+        /// [`MAX_REORG_DEPTH`]($crate::forks::MAX_REORG_DEPTH) by default, see
+        /// [`Self::set_state_retention`]) can be forked off.
+        ///
+        /// The current branch stays the current one until [`Self::switch_fork`] is called.
+        pub fn fork_at(
+            &mut self,
+            height: $crate::frame_system::pallet_prelude::BlockNumberFor<$runtime>,
+        ) -> Result<$crate::forks::ForkId, $crate::forks::ForkError> {
+            // Keep the changes made to the current block so far, in case it is the fork point.
+            $crate::Sandbox::on_block_initialized(self);
+            self.forks.fork_at(height.into())
+        }
+
+        /// Makes `fork` the current branch of the chain, e.g. to emulate a reorg. The state of the
+        /// branch left is kept, so it can be switched back to later.
+        pub fn switch_fork(
+            &mut self,
+            fork: $crate::forks::ForkId,
+        ) -> Result<(), $crate::forks::ForkError> {
+            // Ensure, that there are no pending changes that would affect the new backend.
+            self.ext.commit_all().expect("Failed to commit changes");
+            let state = self.ext.as_backend();
+            self.ext.backend = self.forks.switch(fork, state)?;
+            Ok(())
+        }
+
+        /// Returns the identifier of the current branch of the chain.
+        pub fn current_fork(&self) -> $crate::forks::ForkId {
+            self.forks.current()
+        }
+
+        /// Returns a read-only view of the state at the end of the block `height` of the current
+        /// branch. See the `history` module.
+        pub fn at_block(
+            &mut self,
+            height: $crate::frame_system::pallet_prelude::BlockNumberFor<$runtime>,
+        ) -> Result<$crate::history::BlockState<Self>, $crate::forks::ForkError> {
+            // Include the pending changes in the state of the latest block.
+            self.ext.commit_all().expect("Failed to commit changes");
+            let latest = self.ext.execute_with($crate::frame_system::Pallet::<$runtime>::block_number);
+            let state = match self.forks.state_after(height.into(), latest.into())? {
+                Some(state) => state.clone(),
+                None => self.ext.as_backend(),
+            };
+            Ok($crate::history::BlockState::new(height.into(), state))
+        }
+
+        /// Keeps the state of the last `blocks` blocks of every branch (at least one), which can
+        /// be forked off and queried. The default is
+        /// [`MAX_REORG_DEPTH`]($crate::forks::MAX_REORG_DEPTH), lower it to save memory in long
+        /// tests or raise it to query older blocks.
+        pub fn set_state_retention(&mut self, blocks: usize) {
+            self.forks.set_retention(blocks)
+        }
+
+        /// Returns a snapshot of the current state of the sandbox, which can be restored with
+        /// [`Self::restore_snapshot`]. See the `snapshot` module.
+        pub fn take_snapshot(&mut self) -> $crate::snapshot::Snapshot {
+            // Include the pending changes in the snapshot.
+            self.ext.commit_all().expect("Failed to commit changes");
+            $crate::snapshot::Snapshot::new(self.ext.as_backend())
+        }
+
+        /// Restores the state of the sandbox to `snapshot`, e.g. to roll back the changes made
+        /// since it was taken.
+        pub fn restore_snapshot(&mut self, snapshot: &$crate::snapshot::Snapshot) {
+            // Ensure, that there are no pending changes that would affect the restored backend.
+            self.ext.commit_all().expect("Failed to commit changes");
+            self.ext.backend = snapshot.state().clone();
         }
     }
 
     // Implement `Sandbox` trait.
-    $crate::impl_sandbox!($sandbox, $runtime, DEFAULT_ACCOUNT);
+    $crate::impl_sandbox!($sandbox, $runtime, DEFAULT_ACCOUNT, forks, DefaultGasLimit);
 
 }
 
 // Export runtime type itself, pallets and useful types from the auxiliary module
 pub use construct_runtime::{
-    $sandbox, $runtime, Assets, Balances, Nfts, Contracts, PalletInfo, RuntimeCall, RuntimeEvent, RuntimeHoldReason,
+    $sandbox, $runtime, Assets, Balances, CALL_STACK_DEPTH, Nfts, Contracts, PalletInfo, RuntimeCall, RuntimeEvent, RuntimeHoldReason,
     RuntimeOrigin, System, Timestamp,
 };
+$crate::sandbox_pallets!(@exports $kind);
+    };
+}
+
+/// Macro creating a runtime like [`crate::create_sandbox`], with the pallets of a parachain like
+/// Pop on top: collective (as `Council`), motion, treasury, identity, foreign assets, tx pause,
+/// authorship, collator selection, session, preimage, scheduler, referenda, parachain system,
+/// parachain info and xcm. Calls are filtered by the tx pause pallet, and the code is set through
+/// the parachain system pallet.
+///
+/// These pallets are opt-in: the runtime of [`crate::create_sandbox`] is kept minimal. The
+/// arguments are the same as the ones of [`crate::create_sandbox`]:
+///
+/// ```rust, ignore
+/// create_parachain_sandbox!(GovernanceSandbox);
+/// ```
+#[macro_export]
+macro_rules! create_parachain_sandbox {
+    ($name:ident) => {
+        $crate::paste::paste! {
+            $crate::create_parachain_sandbox!($name, [<$name Runtime>], (), (), {});
+        }
+    };
+    ($name:ident, $chain_extension: ty, $debug: ty) => {
+        $crate::paste::paste! {
+            $crate::create_parachain_sandbox!($name, [<$name Runtime>], $chain_extension, $debug, {});
+        }
+    };
+    ($name:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? } $(, call_stack_depth = $depth:expr)?) => {
+        $crate::paste::paste! {
+            $crate::create_parachain_sandbox!($name, [<$name Runtime>], $chain_extension, $debug, {
+                $(
+                    $pallet_name : $pallet,
+                )*
+            } $(, call_stack_depth = $depth)?);
+        }
+    };
+    ($sandbox:ident, $runtime:ident, $chain_extension: ty, $debug: ty, { $( $pallet_name:tt : $pallet:ident ),* $(,)? } $(, call_stack_depth = $depth:expr)?) => {
+        $crate::create_sandbox!(@parachain $sandbox, $runtime, $chain_extension, $debug, {
+            $(
+                $pallet_name : $pallet,
+            )*
+        } $(, call_stack_depth = $depth)?);
+    };
+}
+
+// Macro declaring the pallets of a sandbox: the minimal ones of `create_sandbox!`, and the
+// parachain ones of `create_parachain_sandbox!` on top.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! sandbox_pallets {
+    (@runtime minimal, $runtime:ident, { $( $pallet_name:tt : $pallet:ident ),* $(,)? }) => {
+        construct_runtime!(
+            pub enum $runtime {
+                System: $crate::frame_system,
+                Assets: $crate::pallet_assets::<Instance1>,
+                Balances: $crate::pallet_balances,
+                Timestamp: $crate::pallet_timestamp,
+                Contracts: $crate::pallet_contracts,
+                Nfts: $crate::pallet_nfts::<Instance1>,
+                $(
+                    $pallet_name: $pallet,
+                )*
+            }
+        );
+    };
+    (@runtime parachain, $runtime:ident, { $( $pallet_name:tt : $pallet:ident ),* $(,)? }) => {
+        construct_runtime!(
+            pub enum $runtime {
+                System: $crate::frame_system,
+                Assets: $crate::pallet_assets::<Instance1>,
+                Balances: $crate::pallet_balances,
+                Timestamp: $crate::pallet_timestamp,
+                Contracts: $crate::pallet_contracts,
+                Nfts: $crate::pallet_nfts::<Instance1>,
+                Council: $crate::pallet_collective::<Instance1>,
+                Motion: $crate::pallet_motion,
+                Identity: $crate::pallet_identity,
+                ForeignAssets: $crate::pallet_assets::<Instance2>,
+                TxPause: $crate::pallet_tx_pause,
+                Authorship: $crate::pallet_authorship,
+                Treasury: $crate::pallet_treasury,
+                CollatorSelection: $crate::pallet_collator_selection,
+                Session: $crate::pallet_session,
+                Preimage: $crate::pallet_preimage,
+                Scheduler: $crate::pallet_scheduler,
+                Referenda: $crate::pallet_referenda,
+                ParachainSystem: $crate::cumulus_pallet_parachain_system,
+                ParachainInfo: $crate::staging_parachain_info,
+                PolkadotXcm: $crate::pallet_xcm,
+                $(
+                    $pallet_name: $pallet,
+                )*
+            }
+        );
+    };
+    (@config minimal, $runtime:ident) => {
+        type SandboxCallFilter = $crate::frame_support::traits::Everything;
+        type SandboxOnSetCode = ();
+    };
+    (@config parachain, $runtime:ident) => {
+        use $crate::frame_system::EnsureRoot;
+
+        type SandboxCallFilter = TxPause;
+        type SandboxOnSetCode = $crate::cumulus_pallet_parachain_system::ParachainSetCode<$runtime>;
+
+        // Configure pallet authorship
+        impl $crate::pallet_authorship::Config for $runtime {
+            type FindAuthor = ();
+            type EventHandler = CollatorSelection;
+        }
+
+        parameter_types! {
+            pub const PotId: $crate::frame_support::PalletId =
+                $crate::frame_support::PalletId(*b"PotStake");
+        }
+
+        // Configure pallet collator selection
+        impl $crate::pallet_collator_selection::Config for $runtime {
+            type Currency = Balances;
+            type KickThreshold = ConstU32<10>;
+            type MaxCandidates = ConstU32<100>;
+            type MaxInvulnerables = ConstU32<20>;
+            type MinEligibleCollators = ConstU32<1>;
+            type PotId = PotId;
+            type RuntimeEvent = RuntimeEvent;
+            type UpdateOrigin = EnsureRoot<Self::AccountId>;
+            type ValidatorId = AccountId32;
+            type ValidatorIdOf = $crate::pallet_collator_selection::IdentityCollator;
+            type ValidatorRegistration = Session;
+            type WeightInfo = ();
+        }
+
+        // Session handler accepting the test keys of the sandbox, without any consensus behind them.
+        pub enum SandboxSessionHandler {}
+        impl $crate::pallet_session::SessionHandler<AccountId32> for SandboxSessionHandler {
+            const KEY_TYPE_IDS: &'static [$crate::frame_support::sp_runtime::KeyTypeId] =
+                &[$crate::frame_support::sp_runtime::key_types::DUMMY];
+
+            fn on_genesis_session<Ks: $crate::frame_support::sp_runtime::traits::OpaqueKeys>(_validators: &[(AccountId32, Ks)]) {}
+
+            fn on_new_session<Ks: $crate::frame_support::sp_runtime::traits::OpaqueKeys>(
+                _changed: bool,
+                _validators: &[(AccountId32, Ks)],
+                _queued_validators: &[(AccountId32, Ks)],
+            ) {}
+
+            fn on_disabled(_validator_index: u32) {}
+        }
+
+        // Configure pallet session, with sessions of 10 blocks
+        type SessionPeriod = $crate::pallet_session::PeriodicSessions<ConstU32<10>, ConstU32<0>>;
+        impl $crate::pallet_session::Config for $runtime {
+            type DisablingStrategy = ();
+            type Keys = $crate::frame_support::sp_runtime::testing::UintAuthorityId;
+            type NextSessionRotation = SessionPeriod;
+            type RuntimeEvent = RuntimeEvent;
+            type SessionHandler = SandboxSessionHandler;
+            type SessionManager = CollatorSelection;
+            type ShouldEndSession = SessionPeriod;
+            type ValidatorId = AccountId32;
+            type ValidatorIdOf = $crate::pallet_collator_selection::IdentityCollator;
+            type WeightInfo = ();
+        }
+
+        // Configure pallet tx pause
+        impl $crate::pallet_tx_pause::Config for $runtime {
+            type RuntimeEvent = RuntimeEvent;
+            type RuntimeCall = RuntimeCall;
+            type PauseOrigin = EnsureRoot<Self::AccountId>;
+            type UnpauseOrigin = EnsureRoot<Self::AccountId>;
+            type WhitelistedCalls = $crate::frame_support::traits::Nothing;
+            type MaxNameLen = ConstU32<256>;
+            type WeightInfo = ();
+        }
+
+        // Configure pallet assets for foreign assets
+        type ForeignAssetsInstance = $crate::pallet_assets::Instance2;
+        impl $crate::pallet_assets::Config<ForeignAssetsInstance> for $runtime {
+            type ApprovalDeposit = AssetsApprovalDeposit;
+            type AssetAccountDeposit = AssetsAccountDeposit;
+            type AssetDeposit = AssetsDeposit;
+            type AssetId = u32;
+            type AssetIdParameter = u32;
+            type Balance = u128;
+            type CallbackHandle = ();
+            type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<Self::AccountId>>;
+            type Currency = Balances;
+            type Extra = ();
+            type ForceOrigin = EnsureSigned<Self::AccountId>;
+            type Freezer = ();
+            type Holder = ();
+            type MetadataDepositBase = ConstU128<1>;
+            type MetadataDepositPerByte = ConstU128<1>;
+            type RemoveItemsLimit = ConstU32<5>;
+            type RuntimeEvent = RuntimeEvent;
+            type StringLimit = ConstU32<50>;
+            type WeightInfo = ();
+        }
+
+        parameter_types! {
+            pub CouncilMaxProposalWeight: Weight = Weight::from_parts(u64::MAX, u64::MAX);
+        }
+
+        // Configure pallet collective (council)
+        type CouncilInstance = $crate::pallet_collective::Instance1;
+        impl $crate::pallet_collective::Config<CouncilInstance> for $runtime {
+            type Consideration = ();
+            type DefaultVote = $crate::pallet_collective::PrimeDefaultVote;
+            type DisapproveOrigin = EnsureRoot<Self::AccountId>;
+            type KillOrigin = EnsureRoot<Self::AccountId>;
+            type MaxMembers = ConstU32<100>;
+            type MaxProposalWeight = CouncilMaxProposalWeight;
+            type MaxProposals = ConstU32<100>;
+            type MotionDuration = ConstU32<10>;
+            type Proposal = RuntimeCall;
+            type RuntimeEvent = RuntimeEvent;
+            type RuntimeOrigin = RuntimeOrigin;
+            type SetMembersOrigin = EnsureRoot<Self::AccountId>;
+            type WeightInfo = ();
+        }
+
+        // Configure pallet motion, dispatching calls as root with a proportion of the council
+        impl $crate::pallet_motion::Config for $runtime {
+            type RuntimeCall = RuntimeCall;
+            type RuntimeEvent = RuntimeEvent;
+            type SimpleMajorityOrigin =
+                $crate::pallet_collective::EnsureProportionMoreThan<AccountId32, CouncilInstance, 1, 2>;
+            type SuperMajorityOrigin =
+                $crate::pallet_collective::EnsureProportionAtLeast<AccountId32, CouncilInstance, 2, 3>;
+            type UnanimousOrigin =
+                $crate::pallet_collective::EnsureProportionAtLeast<AccountId32, CouncilInstance, 1, 1>;
+            type WeightInfo = ();
+        }
+
+        parameter_types! {
+            pub const TreasuryPalletId: $crate::frame_support::PalletId =
+                $crate::frame_support::PalletId(*b"py/trsry");
+            pub TreasuryAccount: AccountId32 = Treasury::account_id();
+            pub const TreasuryMaxSpend: u128 = u128::MAX;
+        }
+
+        // Configure pallet treasury, spending with the root origin or a majority of the council
+        type TreasurySpendOrigin = $crate::frame_support::traits::EitherOf<
+            $crate::frame_system::EnsureRootWithSuccess<AccountId32, TreasuryMaxSpend>,
+            $crate::frame_system::EnsureWithSuccess<
+                $crate::pallet_collective::EnsureProportionAtLeast<AccountId32, CouncilInstance, 1, 2>,
+                AccountId32,
+                TreasuryMaxSpend,
+            >,
+        >;
+        impl $crate::pallet_treasury::Config for $runtime {
+            type AssetKind = ();
+            type BalanceConverter = $crate::frame_support::traits::tokens::UnityAssetBalanceConversion;
+            type Beneficiary = AccountId32;
+            type BeneficiaryLookup = $crate::frame_support::sp_runtime::traits::IdentityLookup<AccountId32>;
+            type BlockNumberProvider = System;
+            type Burn = ();
+            type BurnDestination = ();
+            type Currency = Balances;
+            type MaxApprovals = ConstU32<100>;
+            type PalletId = TreasuryPalletId;
+            type Paymaster = $crate::frame_support::traits::tokens::pay::PayFromAccount<Balances, TreasuryAccount>;
+            type PayoutPeriod = ConstU32<100>;
+            type RejectOrigin = EnsureRoot<Self::AccountId>;
+            type RuntimeEvent = RuntimeEvent;
+            type SpendFunds = ();
+            type SpendOrigin = TreasurySpendOrigin;
+            type SpendPeriod = ConstU32<10>;
+            type WeightInfo = ();
+        }
+
+        // Configure pallet preimage
+        impl $crate::pallet_preimage::Config for $runtime {
+            type Consideration = ();
+            type Currency = Balances;
+            type ManagerOrigin = EnsureRoot<Self::AccountId>;
+            type RuntimeEvent = RuntimeEvent;
+            type WeightInfo = ();
+        }
+
+        parameter_types! {
+            pub SchedulerMaxWeight: Weight = Weight::from_parts(u64::MAX, u64::MAX);
+        }
+
+        // Configure pallet scheduler
+        impl $crate::pallet_scheduler::Config for $runtime {
+            type MaxScheduledPerBlock = ConstU32<50>;
+            type MaximumWeight = SchedulerMaxWeight;
+            type OriginPrivilegeCmp = $crate::frame_support::traits::EqualPrivilegeOnly;
+            type PalletsOrigin = OriginCaller;
+            type Preimages = Preimage;
+            type RuntimeCall = RuntimeCall;
+            type RuntimeEvent = RuntimeEvent;
+            type RuntimeOrigin = RuntimeOrigin;
+            type ScheduleOrigin = EnsureRoot<Self::AccountId>;
+            type WeightInfo = ();
+        }
+
+        // Tracks of the referenda: a single, short track for the proposals dispatched with the root
+        // origin, so referenda can be fast-forwarded to enactment in a few blocks.
+        pub enum SandboxTracks {}
+        impl $crate::pallet_referenda::TracksInfo<u128, u32> for SandboxTracks {
+            type Id = u16;
+            type RuntimeOrigin = OriginCaller;
+
+            fn tracks() -> &'static [(Self::Id, $crate::pallet_referenda::TrackInfo<u128, u32>)] {
+                static TRACKS: [(u16, $crate::pallet_referenda::TrackInfo<u128, u32>); 1] = [(
+                    0,
+                    $crate::pallet_referenda::TrackInfo {
+                        name: "root",
+                        max_deciding: 1,
+                        decision_deposit: 10,
+                        prepare_period: 1,
+                        decision_period: 10,
+                        confirm_period: 1,
+                        min_enactment_period: 1,
+                        min_approval: $crate::pallet_referenda::Curve::LinearDecreasing {
+                            length: Perbill::from_percent(100),
+                            floor: Perbill::from_percent(50),
+                            ceil: Perbill::from_percent(100),
+                        },
+                        min_support: $crate::pallet_referenda::Curve::LinearDecreasing {
+                            length: Perbill::from_percent(100),
+                            floor: Perbill::from_percent(0),
+                            ceil: Perbill::from_percent(50),
+                        },
+                    },
+                )];
+                &TRACKS
+            }
+
+            fn track_for(origin: &Self::RuntimeOrigin) -> Result<Self::Id, ()> {
+                match origin {
+                    OriginCaller::system($crate::frame_system::RawOrigin::Root) => Ok(0),
+                    _ => Err(()),
+                }
+            }
+        }
+
+        // Configure pallet referenda
+        impl $crate::pallet_referenda::Config for $runtime {
+            type AlarmInterval = ConstU32<1>;
+            type CancelOrigin = EnsureRoot<Self::AccountId>;
+            type Currency = Balances;
+            type KillOrigin = EnsureRoot<Self::AccountId>;
+            type MaxQueued = ConstU32<100>;
+            type Preimages = Preimage;
+            type RuntimeCall = RuntimeCall;
+            type RuntimeEvent = RuntimeEvent;
+            type Scheduler = Scheduler;
+            type Slash = ();
+            type SubmissionDeposit = ConstU128<1>;
+            type SubmitOrigin = AsEnsureOriginWithArg<EnsureSigned<Self::AccountId>>;
+            type Tally = $crate::api::referenda_api::Tally;
+            type Tracks = SandboxTracks;
+            type UndecidingTimeout = ConstU32<100>;
+            type Votes = u32;
+            type WeightInfo = ();
+        }
+
+        // Configure the parachain system pallet, whose relay chain state is mocked by the sandbox
+        // (see `ParachainAPI`) instead of being set by the validation data inherent
+        impl $crate::cumulus_pallet_parachain_system::Config for $runtime {
+            type CheckAssociatedRelayNumber =
+                $crate::cumulus_pallet_parachain_system::RelayNumberMonotonicallyIncreases;
+            type ConsensusHook = $crate::cumulus_pallet_parachain_system::consensus_hook::ExpectParentIncluded;
+            type DmpQueue = $crate::frame_support::traits::EnqueueWithOrigin<(), $crate::frame_support::traits::ConstU8<0>>;
+            type OnSystemEvent = ();
+            type OutboundXcmpMessageSource = ();
+            type ReservedDmpWeight = ();
+            type ReservedXcmpWeight = ();
+            type RuntimeEvent = RuntimeEvent;
+            type SelectCore = $crate::cumulus_pallet_parachain_system::DefaultCoreSelector<$runtime>;
+            type SelfParaId = ParachainInfo;
+            type WeightInfo = ();
+            type XcmpMessageHandler = ();
+        }
+
+        // Configure the parachain info pallet
+        impl $crate::staging_parachain_info::Config for $runtime {}
+
+        parameter_types! {
+            pub const RelayNetwork: Option<$crate::xcm::latest::NetworkId> = None;
+            pub UniversalLocation: $crate::xcm::latest::InteriorLocation =
+                $crate::xcm::latest::Junction::Parachain(ParachainInfo::parachain_id().into()).into();
+            pub const XcmUnitWeightCost: Weight = Weight::from_parts(1_000, 0);
+            pub const XcmMaxInstructions: u32 = 100;
+        }
+
+        type LocationToAccountId = $crate::xcm_builder::AccountId32Aliases<RelayNetwork, AccountId32>;
+        type LocalOriginToLocation = $crate::xcm_builder::SignedToAccountId32<RuntimeOrigin, AccountId32, RelayNetwork>;
+        type XcmWeigher = $crate::xcm_builder::FixedWeightBounds<XcmUnitWeightCost, RuntimeCall, XcmMaxInstructions>;
+
+        // Configure the XCM executor, which doesn't route messages to other chains: their responses to
+        // queries are injected instead (see `XcmAPI`)
+        pub struct XcmConfig;
+        impl $crate::xcm_executor::Config for XcmConfig {
+            type Aliasers = $crate::frame_support::traits::Nothing;
+            type AssetClaims = PolkadotXcm;
+            type AssetExchanger = ();
+            type AssetLocker = ();
+            type AssetTransactor = ();
+            type AssetTrap = PolkadotXcm;
+            type Barrier = $crate::xcm_builder::AllowUnpaidExecutionFrom<$crate::frame_support::traits::Everything>;
+            type CallDispatcher = RuntimeCall;
+            type FeeManager = ();
+            type HrmpChannelAcceptedHandler = ();
+            type HrmpChannelClosingHandler = ();
+            type HrmpNewChannelOpenRequestHandler = ();
+            type IsReserve = ();
+            type IsTeleporter = ();
+            type MaxAssetsIntoHolding = ConstU32<64>;
+            type MessageExporter = ();
+            type OriginConverter = ();
+            type PalletInstancesInfo = ();
+            type ResponseHandler = PolkadotXcm;
+            type RuntimeCall = RuntimeCall;
+            type SafeCallFilter = $crate::frame_support::traits::Everything;
+            type SubscriptionService = PolkadotXcm;
+            type Trader = ();
+            type TransactionalProcessor = $crate::xcm_builder::FrameTransactionalProcessor;
+            type UniversalAliases = $crate::frame_support::traits::Nothing;
+            type UniversalLocation = UniversalLocation;
+            type Weigher = XcmWeigher;
+            type XcmRecorder = ();
+            type XcmSender = ();
+        }
+
+        // Configure pallet xcm
+        impl $crate::pallet_xcm::Config for $runtime {
+            const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+            type AdminOrigin = EnsureRoot<Self::AccountId>;
+            type AdvertisedXcmVersion = $crate::pallet_xcm::CurrentXcmVersion;
+            type AuthorizedAliasConsideration = ();
+            type Currency = Balances;
+            type CurrencyMatcher = ();
+            type ExecuteXcmOrigin = $crate::xcm_builder::EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
+            type MaxLockers = ConstU32<8>;
+            type MaxRemoteLockConsumers = ConstU32<0>;
+            type RemoteLockConsumerIdentifier = ();
+            type RuntimeCall = RuntimeCall;
+            type RuntimeEvent = RuntimeEvent;
+            type RuntimeOrigin = RuntimeOrigin;
+            type SendXcmOrigin = $crate::xcm_builder::EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
+            type SovereignAccountOf = LocationToAccountId;
+            type TrustedLockers = ();
+            type UniversalLocation = UniversalLocation;
+            type Weigher = XcmWeigher;
+            type WeightInfo = $crate::pallet_xcm::TestWeightInfo;
+            type XcmExecuteFilter = $crate::frame_support::traits::Nothing;
+            type XcmExecutor = $crate::xcm_executor::XcmExecutor<XcmConfig>;
+            type XcmReserveTransferFilter = $crate::frame_support::traits::Everything;
+            type XcmRouter = ();
+            type XcmTeleportFilter = $crate::frame_support::traits::Everything;
+        }
+
+        // Configure pallet identity
+        impl $crate::pallet_identity::Config for $runtime {
+            type BasicDeposit = ConstU128<1>;
+            type ByteDeposit = ConstU128<1>;
+            type Currency = Balances;
+            type ForceOrigin = EnsureRoot<Self::AccountId>;
+            type IdentityInformation = $crate::pallet_identity::legacy::IdentityInfo<ConstU32<100>>;
+            type MaxRegistrars = ConstU32<20>;
+            type MaxSubAccounts = ConstU32<100>;
+            type MaxSuffixLength = ConstU32<7>;
+            type MaxUsernameLength = ConstU32<32>;
+            type OffchainSignature = Noop;
+            type PendingUsernameExpiration = ConstU32<100>;
+            type RegistrarOrigin = EnsureRoot<Self::AccountId>;
+            type RuntimeEvent = RuntimeEvent;
+            type SigningPublicKey = Noop;
+            type Slashed = ();
+            type SubAccountDeposit = ConstU128<1>;
+            type UsernameAuthorityOrigin = EnsureRoot<Self::AccountId>;
+            type UsernameDeposit = ConstU128<1>;
+            type UsernameGracePeriod = ConstU32<100>;
+            type WeightInfo = ();
+        }
+    };
+    (@exports minimal) => {};
+    (@exports parachain) => {
+        pub use construct_runtime::{
+            Council, Motion, ForeignAssets, Identity, TxPause, Authorship, Treasury,
+            CollatorSelection, Session, Preimage, Scheduler, Referenda, ParachainSystem,
+            ParachainInfo, PolkadotXcm,
+        };
     };
 }
 
 create_sandbox!(DefaultSandbox);
+
+/// The sandbox with the parachain pallets, see [`crate::create_parachain_sandbox`].
+pub mod parachain {
+	crate::create_parachain_sandbox!(ParachainSandbox);
+}
+
+pub use parachain::ParachainSandbox;
+
+#[cfg(test)]
+mod tests {
+	use frame_support::{traits::Get, weights::WeightToFee};
+
+	use super::*;
+	use crate::{AccountId32, RuntimeOf, Sandbox};
+
+	type Runtime = RuntimeOf<DefaultSandbox>;
+
+	#[test]
+	fn parameters_can_be_overridden() {
+		let parameters = SandboxParameters {
+			deposit_per_byte: 5,
+			deposit_per_item: 50,
+			default_deposit_limit: 1_000,
+			code_hash_lockup_deposit_percent: Perbill::from_percent(10),
+			..Default::default()
+		};
+		let mut sandbox = DefaultSandbox::with_parameters(parameters);
+
+		sandbox.execute_with(|| {
+			assert_eq!(<Runtime as pallet_contracts::Config>::DepositPerByte::get(), 5);
+			assert_eq!(<Runtime as pallet_contracts::Config>::DepositPerItem::get(), 50);
+			assert_eq!(<Runtime as pallet_contracts::Config>::DefaultDepositLimit::get(), 1_000);
+			assert_eq!(
+				<Runtime as pallet_contracts::Config>::CodeHashLockupDepositPercent::get(),
+				Perbill::from_percent(10)
+			);
+		});
+
+		// Other instances are not affected.
+		DefaultSandbox::default().execute_with(|| {
+			assert_eq!(<Runtime as pallet_contracts::Config>::DepositPerByte::get(), 1);
+		});
+	}
+
+	#[test]
+	fn parameters_can_be_updated_within_a_test() {
+		let mut sandbox = DefaultSandbox::default();
+		let parameters = sandbox.parameters();
+		assert_eq!(parameters, SandboxParameters { seed: parameters.seed, ..Default::default() });
+
+		sandbox.update_parameters(|parameters| {
+			parameters.deposit_per_byte = 10;
+			parameters.asset_deposit = 100;
+		});
+
+		assert_eq!(sandbox.parameters().deposit_per_byte, 10);
+		assert_eq!(sandbox.parameters().deposit_per_item, parameters.deposit_per_item);
+		sandbox.execute_with(|| {
+			assert_eq!(<Runtime as pallet_contracts::Config>::DepositPerByte::get(), 10);
+			assert_eq!(
+				<Runtime as pallet_assets::Config<pallet_assets::Instance1>>::AssetDeposit::get(),
+				100
+			);
+		});
+	}
+
+	#[test]
+	fn parachain_sandbox_shares_the_parameters() {
+		type ParachainRuntime = RuntimeOf<ParachainSandbox>;
+
+		let mut sandbox = ParachainSandbox::default();
+		assert_eq!(sandbox.parameters(), DefaultSandbox::default().parameters());
+
+		sandbox.update_parameters(|parameters| parameters.asset_deposit = 100);
+		let deposit = sandbox.execute_with(
+			<ParachainRuntime as pallet_assets::Config<pallet_assets::Instance2>>::AssetDeposit::get,
+		);
+		assert_eq!(deposit, 100);
+	}
+
+	#[test]
+	fn default_gas_limit_can_be_configured() {
+		let mut sandbox = DefaultSandbox::default();
+		assert_eq!(sandbox.configured_gas_limit(), DefaultSandbox::default_gas_limit());
+
+		let gas_limit = Weight::from_parts(1_000_000, 1024);
+		let mut sandbox = DefaultSandbox::with_parameters(
+			SandboxParameters::default().default_gas_limit(gas_limit),
+		);
+		assert_eq!(sandbox.configured_gas_limit(), gas_limit);
+		assert_eq!(sandbox.parameters().default_gas_limit, gas_limit);
+	}
+
+	// A sandbox whose runtime is declared outside of `create_sandbox!`, as done for the runtimes
+	// of production chains.
+	mod external {
+		use crate::{config::DEFAULT_ACCOUNT, macros::DefaultSandboxRuntime, TestExternalities};
+
+		pub struct ExternalSandbox {
+			pub ext: TestExternalities,
+		}
+
+		crate::impl_sandbox!(ExternalSandbox, DefaultSandboxRuntime, DEFAULT_ACCOUNT);
+	}
+
+	#[test]
+	fn impl_sandbox_uses_the_default_gas_limit() {
+		let mut sandbox =
+			external::ExternalSandbox { ext: BlockBuilder::<Runtime>::new_ext(Vec::new()) };
+		assert_eq!(sandbox.configured_gas_limit(), external::ExternalSandbox::default_gas_limit());
+	}
+
+	#[test]
+	fn schedule_presets_can_be_used() {
+		let mut sandbox = DefaultSandbox::default();
+		sandbox.execute_with(|| {
+			assert_eq!(
+				<Runtime as pallet_contracts::Config>::Schedule::get(),
+				pallet_contracts::Schedule::<Runtime>::default()
+			);
+		});
+
+		let lenient = ScheduleParameters::preset("development-lenient").unwrap();
+		sandbox.update_parameters(|parameters| parameters.schedule = lenient);
+		sandbox.execute_with(|| {
+			let schedule = <Runtime as pallet_contracts::Config>::Schedule::get();
+			assert_eq!(schedule.instruction_weights.base, 0);
+			assert_eq!(schedule.limits.event_topics, 16);
+			assert_eq!(schedule.limits.memory_pages, 32);
+		});
+
+		assert_eq!(ScheduleParameters::preset("default"), Some(ScheduleParameters::default()));
+		assert_eq!(ScheduleParameters::preset("unknown"), None);
+	}
+
+	struct QuadraticFee;
+	impl WeightToFeePolynomial for QuadraticFee {
+		type Balance = u128;
+
+		fn polynomial() -> frame_support::weights::WeightToFeeCoefficients<u128> {
+			[
+				WeightToFeeCoefficient {
+					coeff_integer: 2,
+					coeff_frac: Perbill::zero(),
+					negative: false,
+					degree: 2,
+				},
+				WeightToFeeCoefficient {
+					coeff_integer: 0,
+					coeff_frac: Perbill::from_percent(50),
+					negative: false,
+					degree: 1,
+				},
+			]
+			.into_iter()
+			.collect()
+		}
+	}
+
+	#[test]
+	fn genesis_storage_is_cached() {
+		let balances =
+			vec![(AccountId32::new([1u8; 32]), 1_000), (AccountId32::new([2u8; 32]), 2_000)];
+		let genesis = BlockBuilder::<Runtime>::genesis_storage(balances.clone(), &[], |_| {});
+		assert_eq!(
+			BlockBuilder::<Runtime>::genesis_storage(balances.clone(), &[], |_| {}),
+			genesis
+		);
+		assert_ne!(
+			BlockBuilder::<Runtime>::genesis_storage(balances[..1].to_vec(), &[], |_| {}),
+			genesis
+		);
+
+		// A different extension (e.g. the assets of a `SandboxConfig`) gets its own entry, built
+		// with its own `extend`.
+		let extended =
+			BlockBuilder::<Runtime>::genesis_storage(balances.clone(), &[1], |storage| {
+				storage.top.insert(b"extension".to_vec(), vec![1]);
+			});
+		assert_ne!(extended, genesis);
+		assert_eq!(
+			BlockBuilder::<Runtime>::genesis_storage(balances.clone(), &[1], |_| {}),
+			extended
+		);
+		assert_eq!(BlockBuilder::<Runtime>::genesis_storage(balances, &[], |_| {}), genesis);
+	}
+
+	#[test]
+	fn randomness_is_derived_from_the_seed() {
+		use frame_support::traits::Randomness;
+
+		let random = |seed: u64| {
+			DefaultSandbox::with_parameters(SandboxParameters::default().seed(seed)).execute_with(
+				|| <Runtime as pallet_contracts::Config>::Randomness::random(b"subject"),
+			)
+		};
+		assert_eq!(random(7), random(7));
+		assert_ne!(random(7), random(8));
+	}
+
+	#[test]
+	fn default_fee_equals_ref_time() {
+		let mut sandbox = DefaultSandbox::default();
+		assert_eq!(sandbox.fee(Weight::from_parts(1_000, 7), 100), 1_000);
+	}
+
+	#[test]
+	fn fee_model_can_be_plugged_in() {
+		let mut sandbox = DefaultSandbox::with_parameters(
+			SandboxParameters::default().with_fees::<QuadraticFee>(3),
+		);
+		let weight = Weight::from_parts(10, 0);
+
+		assert_eq!(sandbox.fee(weight, 100), 2 * 10 * 10 + 5 + 3 * 100);
+		sandbox.execute_with(|| {
+			assert_eq!(
+				QuadraticFee::weight_to_fee(&weight),
+				weight_to_fee(&QuadraticFee::polynomial(), weight)
+			);
+		});
+	}
+
+	#[test]
+	fn forks_share_history_and_can_be_switched() {
+		use crate::{
+			api::prelude::{BalanceAPI, SystemAPI},
+			forks::ForkError,
+		};
+
+		let mut sandbox = DefaultSandbox::default();
+		let account = AccountId32::new([2u8; 32]);
+		let fork_point = sandbox.build_block();
+		sandbox.mint_into(&account, 100).unwrap();
+		sandbox.build_blocks(2);
+
+		let fork = sandbox.fork_at(fork_point).unwrap();
+		assert_eq!(sandbox.current_fork(), 0);
+		sandbox.switch_fork(fork).unwrap();
+		assert_eq!(sandbox.current_fork(), fork);
+		assert_eq!(sandbox.block_number(), fork_point);
+		assert_eq!(sandbox.free_balance(&account), 0);
+
+		// Switching back to the original branch restores its state.
+		sandbox.build_block();
+		sandbox.switch_fork(0).unwrap();
+		assert_eq!(sandbox.block_number(), fork_point + 2);
+		assert_eq!(sandbox.free_balance(&account), 100);
+
+		assert_eq!(sandbox.switch_fork(7), Err(ForkError::UnknownFork(7)));
+		let unknown = fork_point + 3;
+		assert_eq!(sandbox.fork_at(unknown), Err(ForkError::UnknownBlock(unknown.into())));
+	}
+
+	#[test]
+	fn past_blocks_can_be_queried() {
+		use crate::{
+			api::prelude::{BalanceAPI, SystemAPI},
+			forks::ForkError,
+		};
+
+		let mut sandbox = DefaultSandbox::default();
+		let account = AccountId32::new([2u8; 32]);
+		let first = sandbox.build_block();
+		sandbox.mint_into(&account, 100).unwrap();
+		let second = sandbox.build_block();
+		sandbox.mint_into(&account, 50).unwrap();
+		let latest = sandbox.skip_blocks(3);
+		sandbox.mint_into(&account, 25).unwrap();
+
+		assert_eq!(sandbox.at_block(first).unwrap().free_balance(&account), 100);
+		assert_eq!(sandbox.at_block(second).unwrap().free_balance(&account), 150);
+		// Skipped blocks share the state at the end of the skip.
+		assert_eq!(sandbox.at_block(second + 1).unwrap().free_balance(&account), 150);
+		assert_eq!(sandbox.at_block(latest).unwrap().free_balance(&account), 175);
+		assert!(matches!(sandbox.at_block(latest + 1), Err(ForkError::UnknownBlock(_))));
+
+		sandbox.set_state_retention(2);
+		assert!(matches!(sandbox.at_block(first), Err(ForkError::UnknownBlock(_))));
+		assert_eq!(sandbox.at_block(second).unwrap().free_balance(&account), 150);
+	}
+
+	#[test]
+	fn snapshots_can_be_restored() {
+		use crate::api::prelude::{BalanceAPI, SystemAPI};
+
+		let mut sandbox = DefaultSandbox::default();
+		let account = AccountId32::new([2u8; 32]);
+		sandbox.build_block();
+		let snapshot = sandbox.take_snapshot();
+		let height = sandbox.block_number();
+
+		for _ in 0..2 {
+			sandbox.mint_into(&account, 100).unwrap();
+			sandbox.build_blocks(2);
+			assert_eq!(sandbox.free_balance(&account), 100);
+
+			sandbox.restore_snapshot(&snapshot);
+			assert_eq!(sandbox.block_number(), height);
+			assert_eq!(sandbox.free_balance(&account), 0);
+		}
+	}
+
+	mod deep {
+		crate::create_sandbox!(DeepSandbox, (), (), {}, call_stack_depth = 8);
+	}
+
+	#[test]
+	fn call_stack_depth_can_be_configured() {
+		assert_eq!(CALL_STACK_DEPTH, DEFAULT_CALL_STACK_DEPTH);
+		assert_eq!(deep::CALL_STACK_DEPTH, 8);
+	}
+}
@@ -0,0 +1,63 @@
+//! Recording of the runtime calls dispatched by contracts via `call_runtime`.
+//!
+//! The sandbox runtime uses [`RecordRuntimeCalls`] as the call filter of the contracts pallet,
+//! which sees every runtime call a contract dispatches. The calls are kept in an extension of the
+//! externalities until taken with [`crate::api::prelude::ContractAPI::take_runtime_calls`].
+
+use frame_support::traits::Contains;
+use scale::Encode;
+use sp_externalities::{decl_extension, ExternalitiesExt};
+
+decl_extension! {
+	/// The (SCALE-encoded) runtime calls dispatched by contracts, not taken yet.
+	pub struct RecordedRuntimeCalls(Vec<Vec<u8>>);
+}
+
+/// Call filter recording the runtime calls dispatched by contracts. Doesn't filter any call.
+pub struct RecordRuntimeCalls;
+
+impl<Call: Encode> Contains<Call> for RecordRuntimeCalls {
+	fn contains(call: &Call) -> bool {
+		sp_externalities::with_externalities(|ext| {
+			if let Some(recorded) = ext.extension::<RecordedRuntimeCalls>() {
+				recorded.0.push(call.encode());
+			}
+		});
+		true
+	}
+}
+
+/// Takes the runtime calls recorded so far.
+///
+/// Must be called within the externalities.
+pub(crate) fn take() -> Vec<Vec<u8>> {
+	sp_externalities::with_externalities(|ext| {
+		ext.extension::<RecordedRuntimeCalls>()
+			.map(|recorded| std::mem::take(&mut recorded.0))
+			.unwrap_or_default()
+	})
+	.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use frame_support::sp_runtime::AccountId32;
+
+	use super::*;
+	use crate::{api::prelude::*, DefaultSandbox, RuntimeCall, RuntimeOf, Sandbox};
+
+	#[test]
+	fn dispatched_calls_are_recorded() {
+		let mut sandbox = DefaultSandbox::default();
+		let call = RuntimeCall::<RuntimeOf<DefaultSandbox>>::Balances(
+			pallet_balances::Call::transfer_allow_death {
+				dest: AccountId32::new([2u8; 32]).into(),
+				value: 100,
+			},
+		);
+
+		assert!(sandbox.execute_with(|| RecordRuntimeCalls::contains(&call)));
+		assert_eq!(sandbox.take_runtime_calls(), vec![call]);
+		assert!(sandbox.take_runtime_calls().is_empty());
+	}
+}
@@ -0,0 +1,64 @@
+//! Deterministic seed shared by the whole test suite.
+//!
+//! Every source of randomness of the sandboxes (e.g. the randomness exposed to contracts or the
+//! generated salts) is derived from a single seed. The seed is read from the `DRINK_SEED`
+//! environment variable, or picked at random and printed once per process otherwise, so a flaky
+//! failure can be reproduced exactly by re-running the suite with the printed seed.
+
+use std::{
+	collections::hash_map::RandomState,
+	hash::{BuildHasher, Hasher},
+	sync::OnceLock,
+};
+
+use scale::Encode;
+use sp_io::hashing::blake2_256;
+
+/// Environment variable fixing the seed of the test suite.
+pub const SEED_ENV_VAR: &str = "DRINK_SEED";
+
+static SUITE_SEED: OnceLock<u64> = OnceLock::new();
+
+/// Returns the seed of the test suite.
+///
+/// Panics if `DRINK_SEED` is set to something else than a `u64`.
+pub fn suite_seed() -> u64 {
+	*SUITE_SEED.get_or_init(|| {
+		let seed = match std::env::var(SEED_ENV_VAR) {
+			Ok(seed) => seed
+				.trim()
+				.parse()
+				.unwrap_or_else(|_| panic!("`{SEED_ENV_VAR}` must be a `u64`, got `{seed}`")),
+			Err(_) => RandomState::new().build_hasher().finish(),
+		};
+		eprintln!("drink: using seed {seed} (re-run with `{SEED_ENV_VAR}={seed}` to reproduce)");
+		seed
+	})
+}
+
+/// Derives 32 pseudo-random bytes for `subject` from `seed`.
+///
+/// # Arguments
+///
+/// * `seed` - The seed, e.g. [`suite_seed`].
+/// * `subject` - What the bytes are used for, making them independent of other subjects.
+pub fn derive(seed: u64, subject: impl Encode) -> [u8; 32] {
+	blake2_256(&(seed, subject).encode())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn derivation_is_deterministic() {
+		assert_eq!(derive(42, b"salt"), derive(42, b"salt"));
+		assert_ne!(derive(42, b"salt"), derive(43, b"salt"));
+		assert_ne!(derive(42, b"salt"), derive(42, b"random"));
+	}
+
+	#[test]
+	fn suite_seed_is_stable() {
+		assert_eq!(suite_seed(), suite_seed());
+	}
+}
@@ -0,0 +1,123 @@
+//! Thread-safe handle to a sandbox.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use frame_support::sp_runtime::{traits::Dispatchable, DispatchResultWithInfo};
+
+use crate::{api::system_api::SystemAPI, RuntimeCall, Sandbox};
+
+/// Alias for the post-dispatch info of a runtime call.
+type PostInfoOf<S> = <RuntimeCall<<S as Sandbox>::Runtime> as Dispatchable>::PostInfo;
+
+/// A cloneable handle to a sandbox, which can be sent to and shared between threads.
+///
+/// Every command submitted through the handle is executed with exclusive access to the sandbox,
+/// so commands of multiple threads are serialized, each of them running to completion before the
+/// next one starts.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let sandbox = SharedSandbox::new(DefaultSandbox::default());
+/// let handles = (0..4).map(|_| {
+///     let sandbox = sandbox.clone();
+///     std::thread::spawn(move || sandbox.execute(|sandbox| sandbox.call_contract(..)))
+/// });
+/// ```
+pub struct SharedSandbox<S>(Arc<Mutex<S>>);
+
+impl<S> Clone for SharedSandbox<S> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<S: Sandbox + Default> Default for SharedSandbox<S> {
+	fn default() -> Self {
+		Self::new(S::default())
+	}
+}
+
+impl<S: Sandbox> SharedSandbox<S> {
+	/// Wraps the sandbox into a shared handle.
+	pub fn new(sandbox: S) -> Self {
+		Self(Arc::new(Mutex::new(sandbox)))
+	}
+
+	/// Executes the command with exclusive access to the sandbox and returns its result.
+	///
+	/// A command that panicked doesn't make the sandbox unusable for the other handles.
+	///
+	/// # Arguments
+	///
+	/// * `command` - The command to execute.
+	pub fn execute<R>(&self, command: impl FnOnce(&mut S) -> R) -> R {
+		let mut sandbox = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+		command(&mut sandbox)
+	}
+
+	/// Dispatches a runtime call.
+	///
+	/// # Arguments
+	///
+	/// * `call` - The runtime call to dispatch.
+	/// * `origin` - The origin of the call.
+	pub fn runtime_call<Origin>(
+		&self,
+		call: RuntimeCall<S::Runtime>,
+		origin: Origin,
+	) -> DispatchResultWithInfo<PostInfoOf<S>>
+	where
+		Origin: Into<<RuntimeCall<S::Runtime> as Dispatchable>::RuntimeOrigin>,
+	{
+		self.execute(|sandbox| sandbox.runtime_call(call, origin))
+	}
+
+	/// Returns the sandbox if this is its only handle, or the handle otherwise.
+	pub fn into_inner(self) -> Result<S, Self> {
+		Arc::try_unwrap(self.0)
+			.map(|sandbox| sandbox.into_inner().unwrap_or_else(PoisonError::into_inner))
+			.map_err(Self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+
+	use frame_support::sp_runtime::AccountId32;
+
+	use super::*;
+	use crate::{api::prelude::*, DefaultSandbox, RuntimeOf};
+
+	const RECIPIENT: AccountId32 = AccountId32::new([2u8; 32]);
+
+	#[test]
+	fn commands_from_multiple_threads_are_serialized() {
+		let sandbox = SharedSandbox::<DefaultSandbox>::default();
+
+		let handles: Vec<_> = (0..4)
+			.map(|_| {
+				let sandbox = sandbox.clone();
+				thread::spawn(move || {
+					for _ in 0..10 {
+						let call = RuntimeCall::<RuntimeOf<DefaultSandbox>>::Balances(
+							pallet_balances::Call::transfer_allow_death {
+								dest: RECIPIENT.into(),
+								value: 1_000,
+							},
+						);
+						sandbox
+							.runtime_call(call, Some(DefaultSandbox::default_actor()))
+							.expect("Transfer should succeed");
+					}
+				})
+			})
+			.collect();
+		handles.into_iter().for_each(|handle| handle.join().unwrap());
+
+		assert_eq!(sandbox.execute(|sandbox| sandbox.free_balance(&RECIPIENT)), 40_000);
+		let mut sandbox = sandbox.into_inner().ok().expect("Only one handle should be left");
+		assert_eq!(sandbox.free_balance(&RECIPIENT), 40_000);
+	}
+}
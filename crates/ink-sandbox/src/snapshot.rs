@@ -0,0 +1,38 @@
+//! Snapshots of the state of a sandbox, to checkpoint it before risky operations and roll back
+//! instantly:
+//!
+//! ```rust, ignore
+//! let snapshot = sandbox.take_snapshot();
+//! sandbox.build_blocks(3);
+//! sandbox.restore_snapshot(&snapshot);
+//! // The state (and the block number) are the ones of the snapshot again.
+//! ```
+//!
+//! A snapshot is the whole storage of the sandbox, so it can be restored any number of times.
+//! Unlike the branches of [`crate::forks`], snapshots are not tied to blocks.
+
+use sp_core::Blake2Hasher;
+use sp_state_machine::InMemoryBackend;
+
+type Backend = InMemoryBackend<Blake2Hasher>;
+
+/// The state of a sandbox at some point. See [`crate::snapshot`].
+#[derive(Clone)]
+pub struct Snapshot {
+	state: Backend,
+}
+
+impl Snapshot {
+	/// Creates a snapshot of `state`. Used by the sandboxes created with
+	/// [`crate::create_sandbox`].
+	#[doc(hidden)]
+	pub fn new(state: Backend) -> Self {
+		Self { state }
+	}
+
+	/// Returns the state of the snapshot.
+	#[doc(hidden)]
+	pub fn state(&self) -> &Backend {
+		&self.state
+	}
+}
@@ -0,0 +1,22 @@
+;; Dummy contract writing a single storage entry on deployment.
+(module
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; The key ([1, 2, 3, 4]) followed by the value (42u32).
+	(data (i32.const 0) "\01\02\03\04\2a\00\00\00")
+
+	(func (export "deploy")
+		;; store the value under the key
+		(drop
+			(call $seal_set_storage
+				(i32.const 0) ;; The key buffer
+				(i32.const 4) ;; The key buffer's length
+				(i32.const 4) ;; The value buffer
+				(i32.const 4) ;; The value buffer's length
+			)
+		)
+	)
+
+	(func (export "call"))
+)
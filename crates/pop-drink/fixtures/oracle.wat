@@ -0,0 +1,156 @@
+;; Oracle mock, storing the price of trading pairs (e.g. "DOT/USD") with the ABI of ink! (see
+;; `oracle.json` for its metadata). Anyone can set a price, which emits
+;; `PriceUpdated { pair: String, price: u128 }` with the pair as topic.
+;;
+;; Storage:
+;; pair (SCALE-encoded): the price ++ the number of the block it was set in
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "seal_block_number" (func $seal_block_number (param i32 i32)))
+	(import "seal1" "get_storage" (func $seal_get_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_deposit_event" (func $seal_deposit_event (param i32 i32 i32 i32)))
+	(import "seal0" "seal_hash_blake2_256" (func $seal_hash_blake2_256 (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4): the length of a host function output
+	;; [64, 68): the selector of the input
+	;; [68, 1088): the arguments of the input, starting with the pair
+	;; [1200, 1216): the price
+	;; [1216, 1224): the block number
+	;; [1300, 1322): the output
+	;; [1400, 1465): the topics of an event
+	;; [1500, 1644): the data of an event
+	;; [2000, 2032): the signature topic of `PriceUpdated`
+
+	;; `blake2_256("PriceUpdated(String,u128)")`
+	(data (i32.const 2000)
+		"\d4\58\3a\1a\50\8d\83\23\4f\24\5b\d8\9b\4b\d0\00\49\ea\1e\53\3e\98\0f\f5\9f\79\f1\29\d1\5d\a8\84"
+	)
+
+	(func $copy (param $dst i32) (param $src i32) (param $len i32)
+		(block $done
+			(loop $next
+				(br_if $done (i32.eqz (local.get $len)))
+				(i32.store8 (local.get $dst) (i32.load8_u (local.get $src)))
+				(local.set $dst (i32.add (local.get $dst) (i32.const 1)))
+				(local.set $src (i32.add (local.get $src) (i32.const 1)))
+				(local.set $len (i32.sub (local.get $len) (i32.const 1)))
+				(br $next)
+			)
+		)
+	)
+
+	;; Returns the length of the encoded pair (the storage key), at most 128 bytes.
+	(func $pair_len (result i32)
+		(local $len i32)
+		(local.set $len
+			(if (result i32)
+				(i32.eqz (i32.and (i32.load8_u (i32.const 68)) (i32.const 3)))
+				(then
+					(i32.add (i32.const 1) (i32.shr_u (i32.load8_u (i32.const 68)) (i32.const 2)))
+				)
+				(else
+					(if (i32.ne (i32.and (i32.load8_u (i32.const 68)) (i32.const 3)) (i32.const 1))
+						(then unreachable)
+					)
+					(i32.add (i32.const 2) (i32.shr_u (i32.load16_u (i32.const 68)) (i32.const 2)))
+				)
+			)
+		)
+		(if (i32.gt_u (local.get $len) (i32.const 128))
+			(then unreachable)
+		)
+		(local.get $len)
+	)
+
+	;; Emits `PriceUpdated { pair, price }`, with the pair of `$pair_len` bytes in the input and the
+	;; price at 1200. The topic of the pair is its encoding padded with zeros if it fits, its hash
+	;; otherwise.
+	(func $emit_price_updated (param $pair_len i32)
+		(i32.store8 (i32.const 1400) (i32.const 8))
+		(call $copy (i32.const 1401) (i32.const 2000) (i32.const 32))
+		(if (i32.gt_u (local.get $pair_len) (i32.const 32))
+			(then
+				(call $seal_hash_blake2_256 (i32.const 68) (local.get $pair_len) (i32.const 1433))
+			)
+			(else
+				(i64.store (i32.const 1433) (i64.const 0))
+				(i64.store (i32.const 1441) (i64.const 0))
+				(i64.store (i32.const 1449) (i64.const 0))
+				(i64.store (i32.const 1457) (i64.const 0))
+				(call $copy (i32.const 1433) (i32.const 68) (local.get $pair_len))
+			)
+		)
+
+		(call $copy (i32.const 1500) (i32.const 68) (local.get $pair_len))
+		(call $copy (i32.add (i32.const 1500) (local.get $pair_len)) (i32.const 1200) (i32.const 16))
+		(call $seal_deposit_event
+			(i32.const 1400) (i32.const 65)
+			(i32.const 1500) (i32.add (local.get $pair_len) (i32.const 16))
+		)
+	)
+
+	;; new()
+	(func (export "deploy")
+		(i32.store8 (i32.const 1300) (i32.const 0))
+		(call $seal_return (i32.const 0) (i32.const 1300) (i32.const 1))
+	)
+
+	(func (export "call")
+		(local $selector i32)
+		(local $pair_len i32)
+		(i32.store (i32.const 0) (i32.const 1024))
+		(call $seal_input (i32.const 64) (i32.const 0))
+		(local.set $selector (i32.load (i32.const 64)))
+
+		;; set_price(pair: String, price: u128)
+		(if (i32.eq (local.get $selector) (i32.const 0xaf58f93d))
+			(then
+				(local.set $pair_len (call $pair_len))
+				(i64.store (i32.const 1200)
+					(i64.load (i32.add (i32.const 68) (local.get $pair_len)))
+				)
+				(i64.store (i32.const 1208)
+					(i64.load (i32.add (i32.const 76) (local.get $pair_len)))
+				)
+				(i64.store (i32.const 1216) (i64.const 0))
+				(i32.store (i32.const 0) (i32.const 8))
+				(call $seal_block_number (i32.const 1216) (i32.const 0))
+				(drop
+					(call $seal_set_storage
+						(i32.const 68) (local.get $pair_len) (i32.const 1200) (i32.const 20)
+					)
+				)
+				(call $emit_price_updated (local.get $pair_len))
+				(i32.store8 (i32.const 1300) (i32.const 0))
+				(call $seal_return (i32.const 0) (i32.const 1300) (i32.const 1))
+			)
+		)
+
+		;; get_price(pair: String) -> Option<(u128, u32)>
+		(if (i32.eq (local.get $selector) (i32.const 0x6c4b3df2))
+			(then
+				(i32.store16 (i32.const 1300) (i32.const 0x0100))
+				(i32.store (i32.const 0) (i32.const 20))
+				(if
+					(call $seal_get_storage
+						(i32.const 68) (call $pair_len) (i32.const 1302) (i32.const 0)
+					)
+					;; Not found: `Ok(None)`.
+					(then
+						(i32.store8 (i32.const 1301) (i32.const 0))
+						(call $seal_return (i32.const 0) (i32.const 1300) (i32.const 2))
+					)
+				)
+				(call $seal_return (i32.const 0) (i32.const 1300) (i32.const 22))
+			)
+		)
+
+		;; Unknown selector: revert with `Err(LangError::CouldNotReadInput)`.
+		(i32.store16 (i32.const 1300) (i32.const 0x0101))
+		(call $seal_return (i32.const 1) (i32.const 1300) (i32.const 2))
+	)
+)
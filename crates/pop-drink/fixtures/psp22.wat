@@ -0,0 +1,410 @@
+;; Reference PSP22 token, implementing the `PSP22` trait with the ABI of ink! (see `psp22.json` for
+;; its metadata). The `Transfer` and `Approval` events are emitted like by the `PSP22` crate
+;; (`Transfer { from: Option<AccountId>, to: Option<AccountId>, value: u128 }` and
+;; `Approval { owner: AccountId, spender: AccountId, amount: u128 }`, all accounts being topics).
+;;
+;; Storage:
+;; [0]: the total supply
+;; [1] ++ owner: the balance of `owner`
+;; [2] ++ owner ++ spender: the allowance of `spender` over the tokens of `owner`
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "seal1" "get_storage" (func $seal_get_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal2" "set_storage" (func $seal_set_storage (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_deposit_event" (func $seal_deposit_event (param i32 i32 i32 i32)))
+	(import "seal0" "seal_hash_blake2_256" (func $seal_hash_blake2_256 (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; Memory layout:
+	;; [0, 4): the length of a host function output
+	;; [16, 48): the caller
+	;; [64, 68): the selector of the input
+	;; [68, 1088): the arguments of the input
+	;; [1200, 1265): a storage key
+	;; [1300, 1316), [1320, 1336): u128 values
+	;; [1400, 1500): the output
+	;; [1500, 1597): the topics of an event
+	;; [1600, 1682): the data of an event
+	;; [1700, 1733): an encoded `Option<AccountId>` to hash
+	;; [2000, 2064): the signature topics of the events
+
+	;; `blake2_256("Transfer(Option<AccountId>,Option<AccountId>,u128)")`
+	(data (i32.const 2000)
+		"\99\0d\f0\76\cb\1e\95\27\aa\10\2c\d1\00\c1\48\1e\fe\39\3e\ea\bb\58\25\f9\af\1f\5e\58\22\18\64\de"
+	)
+	;; `blake2_256("Approval(AccountId,AccountId,u128)")`
+	(data (i32.const 2032)
+		"\25\cd\b6\c9\38\82\e9\25\ab\bf\c9\a8\b7\c8\58\84\b7\3c\03\8c\03\a2\49\2f\23\8a\5e\5b\a3\fb\ff\8c"
+	)
+
+	(func $copy32 (param $dst i32) (param $src i32)
+		(i64.store (local.get $dst) (i64.load (local.get $src)))
+		(i64.store offset=8 (local.get $dst) (i64.load offset=8 (local.get $src)))
+		(i64.store offset=16 (local.get $dst) (i64.load offset=16 (local.get $src)))
+		(i64.store offset=24 (local.get $dst) (i64.load offset=24 (local.get $src)))
+	)
+
+	(func $eq32 (param $a i32) (param $b i32) (result i32)
+		(i32.and
+			(i32.and
+				(i64.eq (i64.load (local.get $a)) (i64.load (local.get $b)))
+				(i64.eq (i64.load offset=8 (local.get $a)) (i64.load offset=8 (local.get $b)))
+			)
+			(i32.and
+				(i64.eq (i64.load offset=16 (local.get $a)) (i64.load offset=16 (local.get $b)))
+				(i64.eq (i64.load offset=24 (local.get $a)) (i64.load offset=24 (local.get $b)))
+			)
+		)
+	)
+
+	;; u128 arithmetic, on little-endian values in memory.
+
+	(func $is_zero (param $a i32) (result i32)
+		(i64.eqz (i64.or (i64.load (local.get $a)) (i64.load offset=8 (local.get $a))))
+	)
+
+	;; a < b
+	(func $lt (param $a i32) (param $b i32) (result i32)
+		(if (result i32)
+			(i64.eq (i64.load offset=8 (local.get $a)) (i64.load offset=8 (local.get $b)))
+			(then (i64.lt_u (i64.load (local.get $a)) (i64.load (local.get $b))))
+			(else (i64.lt_u (i64.load offset=8 (local.get $a)) (i64.load offset=8 (local.get $b))))
+		)
+	)
+
+	;; a -= b, with a >= b
+	(func $sub (param $a i32) (param $b i32)
+		(local $lo i64)
+		(local.set $lo (i64.sub (i64.load (local.get $a)) (i64.load (local.get $b))))
+		(i64.store offset=8 (local.get $a)
+			(i64.sub
+				(i64.sub (i64.load offset=8 (local.get $a)) (i64.load offset=8 (local.get $b)))
+				(i64.extend_i32_u (i64.lt_u (i64.load (local.get $a)) (i64.load (local.get $b))))
+			)
+		)
+		(i64.store (local.get $a) (local.get $lo))
+	)
+
+	;; a += b, saturating at the maximum value
+	(func $add (param $a i32) (param $b i32)
+		(local $lo i64) (local $hi i64) (local $carry i64)
+		(local.set $lo (i64.add (i64.load (local.get $a)) (i64.load (local.get $b))))
+		(local.set $carry (i64.extend_i32_u (i64.lt_u (local.get $lo) (i64.load (local.get $a)))))
+		(local.set $hi (i64.add (i64.load offset=8 (local.get $a)) (i64.load offset=8 (local.get $b))))
+		(if
+			(i32.or
+				(i64.lt_u (local.get $hi) (i64.load offset=8 (local.get $a)))
+				(i64.lt_u (i64.add (local.get $hi) (local.get $carry)) (local.get $hi))
+			)
+			(then
+				(local.set $lo (i64.const -1))
+				(local.set $hi (i64.const -1))
+				(local.set $carry (i64.const 0))
+			)
+		)
+		(i64.store (local.get $a) (local.get $lo))
+		(i64.store offset=8 (local.get $a) (i64.add (local.get $hi) (local.get $carry)))
+	)
+
+	;; Storage.
+
+	(func $balance_key (param $owner i32)
+		(i32.store8 (i32.const 1200) (i32.const 1))
+		(call $copy32 (i32.const 1201) (local.get $owner))
+	)
+
+	(func $allowance_key (param $owner i32) (param $spender i32)
+		(i32.store8 (i32.const 1200) (i32.const 2))
+		(call $copy32 (i32.const 1201) (local.get $owner))
+		(call $copy32 (i32.const 1233) (local.get $spender))
+	)
+
+	;; Loads the u128 value under the storage key (0 if there is none).
+	(func $load (param $key_len i32) (param $dst i32)
+		(i64.store (local.get $dst) (i64.const 0))
+		(i64.store offset=8 (local.get $dst) (i64.const 0))
+		(i32.store (i32.const 0) (i32.const 16))
+		(drop
+			(call $seal_get_storage
+				(i32.const 1200) (local.get $key_len) (local.get $dst) (i32.const 0)
+			)
+		)
+	)
+
+	(func $store (param $key_len i32) (param $src i32)
+		(drop
+			(call $seal_set_storage
+				(i32.const 1200) (local.get $key_len) (local.get $src) (i32.const 16)
+			)
+		)
+	)
+
+	;; Events.
+
+	;; Encodes the `Option<AccountId>` of `$account` (0 for `None`) to `$dst`. Returns the end of
+	;; the encoding.
+	(func $encode_option (param $dst i32) (param $account i32) (result i32)
+		(if (result i32) (i32.eqz (local.get $account))
+			(then
+				(i32.store8 (local.get $dst) (i32.const 0))
+				(i32.add (local.get $dst) (i32.const 1))
+			)
+			(else
+				(i32.store8 (local.get $dst) (i32.const 1))
+				(call $copy32 (i32.add (local.get $dst) (i32.const 1)) (local.get $account))
+				(i32.add (local.get $dst) (i32.const 33))
+			)
+		)
+	)
+
+	;; Writes the topic of the `Option<AccountId>` of `$account` (0 for `None`) to `$dst`: its
+	;; encoding padded with zeros if it fits, its hash otherwise.
+	(func $option_topic (param $dst i32) (param $account i32)
+		(if (i32.eqz (local.get $account))
+			(then
+				(i64.store (local.get $dst) (i64.const 0))
+				(i64.store offset=8 (local.get $dst) (i64.const 0))
+				(i64.store offset=16 (local.get $dst) (i64.const 0))
+				(i64.store offset=24 (local.get $dst) (i64.const 0))
+			)
+			(else
+				(drop (call $encode_option (i32.const 1700) (local.get $account)))
+				(call $seal_hash_blake2_256 (i32.const 1700) (i32.const 33) (local.get $dst))
+			)
+		)
+	)
+
+	;; Emits `Transfer { from, to, value }`, `$from` or `$to` being 0 for `None`.
+	(func $emit_transfer (param $from i32) (param $to i32) (param $value i32)
+		(local $end i32)
+		(i32.store8 (i32.const 1500) (i32.const 12))
+		(call $copy32 (i32.const 1501) (i32.const 2000))
+		(call $option_topic (i32.const 1533) (local.get $from))
+		(call $option_topic (i32.const 1565) (local.get $to))
+
+		(local.set $end
+			(call $encode_option
+				(call $encode_option (i32.const 1600) (local.get $from))
+				(local.get $to)
+			)
+		)
+		(i64.store (local.get $end) (i64.load (local.get $value)))
+		(i64.store offset=8 (local.get $end) (i64.load offset=8 (local.get $value)))
+		(call $seal_deposit_event
+			(i32.const 1500) (i32.const 97)
+			(i32.const 1600) (i32.sub (i32.add (local.get $end) (i32.const 16)) (i32.const 1600))
+		)
+	)
+
+	;; Emits `Approval { owner, spender, amount }`.
+	(func $emit_approval (param $owner i32) (param $spender i32) (param $amount i32)
+		(i32.store8 (i32.const 1500) (i32.const 12))
+		(call $copy32 (i32.const 1501) (i32.const 2032))
+		(call $copy32 (i32.const 1533) (local.get $owner))
+		(call $copy32 (i32.const 1565) (local.get $spender))
+
+		(call $copy32 (i32.const 1600) (local.get $owner))
+		(call $copy32 (i32.const 1632) (local.get $spender))
+		(i64.store (i32.const 1664) (i64.load (local.get $amount)))
+		(i64.store (i32.const 1672) (i64.load offset=8 (local.get $amount)))
+		(call $seal_deposit_event (i32.const 1500) (i32.const 97) (i32.const 1600) (i32.const 80))
+	)
+
+	;; Output.
+
+	;; Returns `Ok(value)`.
+	(func $return_value (param $src i32)
+		(i32.store8 (i32.const 1400) (i32.const 0))
+		(i64.store (i32.const 1401) (i64.load (local.get $src)))
+		(i64.store (i32.const 1409) (i64.load offset=8 (local.get $src)))
+		(call $seal_return (i32.const 0) (i32.const 1400) (i32.const 17))
+	)
+
+	;; Returns `Ok(Ok(()))` if `error` is 0, and reverts with `Ok(Err(error))` otherwise, `error`
+	;; being the index of the variant of `PSP22Error`.
+	(func $return_result (param $error i32)
+		(i32.store16 (i32.const 1400) (i32.const 0))
+		(if (i32.eqz (local.get $error))
+			(then (call $seal_return (i32.const 0) (i32.const 1400) (i32.const 2)))
+		)
+		(i32.store8 (i32.const 1401) (i32.const 1))
+		(i32.store8 (i32.const 1402) (local.get $error))
+		(call $seal_return (i32.const 1) (i32.const 1400) (i32.const 3))
+	)
+
+	;; Moves `value` from `from` to `to`. Returns 0, or 1 (`InsufficientBalance`).
+	(func $transfer (param $from i32) (param $to i32) (param $value i32) (result i32)
+		(if
+			(i32.or (call $eq32 (local.get $from) (local.get $to)) (call $is_zero (local.get $value)))
+			(then (return (i32.const 0)))
+		)
+		(call $balance_key (local.get $from))
+		(call $load (i32.const 33) (i32.const 1300))
+		(if (call $lt (i32.const 1300) (local.get $value))
+			(then (return (i32.const 1)))
+		)
+		(call $sub (i32.const 1300) (local.get $value))
+		(call $store (i32.const 33) (i32.const 1300))
+
+		(call $balance_key (local.get $to))
+		(call $load (i32.const 33) (i32.const 1300))
+		(call $add (i32.const 1300) (local.get $value))
+		(call $store (i32.const 33) (i32.const 1300))
+		(call $emit_transfer (local.get $from) (local.get $to) (local.get $value))
+		(i32.const 0)
+	)
+
+	;; new(supply: u128)
+	(func (export "deploy")
+		(i32.store (i32.const 0) (i32.const 1024))
+		(call $seal_input (i32.const 64) (i32.const 0))
+		(i32.store (i32.const 0) (i32.const 32))
+		(call $seal_caller (i32.const 16) (i32.const 0))
+
+		(i32.store8 (i32.const 1200) (i32.const 0))
+		(call $store (i32.const 1) (i32.const 68))
+		(call $balance_key (i32.const 16))
+		(call $store (i32.const 33) (i32.const 68))
+		(if (i32.eqz (call $is_zero (i32.const 68)))
+			(then (call $emit_transfer (i32.const 0) (i32.const 16) (i32.const 68)))
+		)
+
+		(i32.store8 (i32.const 1400) (i32.const 0))
+		(call $seal_return (i32.const 0) (i32.const 1400) (i32.const 1))
+	)
+
+	(func (export "call")
+		(local $selector i32)
+		(i32.store (i32.const 0) (i32.const 1024))
+		(call $seal_input (i32.const 64) (i32.const 0))
+		(i32.store (i32.const 0) (i32.const 32))
+		(call $seal_caller (i32.const 16) (i32.const 0))
+		(local.set $selector (i32.load (i32.const 64)))
+
+		;; PSP22::total_supply()
+		(if (i32.eq (local.get $selector) (i32.const 0xc2f82d16))
+			(then
+				(i32.store8 (i32.const 1200) (i32.const 0))
+				(call $load (i32.const 1) (i32.const 1300))
+				(call $return_value (i32.const 1300))
+			)
+		)
+
+		;; PSP22::balance_of(owner: AccountId)
+		(if (i32.eq (local.get $selector) (i32.const 0x2f386865))
+			(then
+				(call $balance_key (i32.const 68))
+				(call $load (i32.const 33) (i32.const 1300))
+				(call $return_value (i32.const 1300))
+			)
+		)
+
+		;; PSP22::allowance(owner: AccountId, spender: AccountId)
+		(if (i32.eq (local.get $selector) (i32.const 0x21d9474d))
+			(then
+				(call $allowance_key (i32.const 68) (i32.const 100))
+				(call $load (i32.const 65) (i32.const 1300))
+				(call $return_value (i32.const 1300))
+			)
+		)
+
+		;; PSP22::transfer(to: AccountId, value: u128, data: Vec<u8>)
+		(if (i32.eq (local.get $selector) (i32.const 0xf5f920db))
+			(then
+				(call $return_result
+					(call $transfer (i32.const 16) (i32.const 68) (i32.const 100))
+				)
+			)
+		)
+
+		;; PSP22::transfer_from(from: AccountId, to: AccountId, value: u128, data: Vec<u8>)
+		(if (i32.eq (local.get $selector) (i32.const 0x6ec7b354))
+			(then
+				(if
+					(i32.or
+						(call $eq32 (i32.const 68) (i32.const 100))
+						(call $is_zero (i32.const 132))
+					)
+					(then (call $return_result (i32.const 0)))
+				)
+				(call $allowance_key (i32.const 68) (i32.const 16))
+				(call $load (i32.const 65) (i32.const 1320))
+				(if (call $lt (i32.const 1320) (i32.const 132))
+					(then (call $return_result (i32.const 2)))
+				)
+				(call $balance_key (i32.const 68))
+				(call $load (i32.const 33) (i32.const 1300))
+				(if (call $lt (i32.const 1300) (i32.const 132))
+					(then (call $return_result (i32.const 1)))
+				)
+				(call $allowance_key (i32.const 68) (i32.const 16))
+				(call $sub (i32.const 1320) (i32.const 132))
+				(call $store (i32.const 65) (i32.const 1320))
+				(call $emit_approval (i32.const 68) (i32.const 16) (i32.const 1320))
+				(call $return_result
+					(call $transfer (i32.const 68) (i32.const 100) (i32.const 132))
+				)
+			)
+		)
+
+		;; PSP22::approve(spender: AccountId, value: u128)
+		(if (i32.eq (local.get $selector) (i32.const 0xbd1b0fb2))
+			(then
+				(if (call $eq32 (i32.const 16) (i32.const 68))
+					(then (call $return_result (i32.const 0)))
+				)
+				(call $allowance_key (i32.const 16) (i32.const 68))
+				(call $store (i32.const 65) (i32.const 100))
+				(call $emit_approval (i32.const 16) (i32.const 68) (i32.const 100))
+				(call $return_result (i32.const 0))
+			)
+		)
+
+		;; PSP22::increase_allowance(spender: AccountId, delta_value: u128)
+		(if (i32.eq (local.get $selector) (i32.const 0x7ab5d696))
+			(then
+				(if
+					(i32.or
+						(call $eq32 (i32.const 16) (i32.const 68))
+						(call $is_zero (i32.const 100))
+					)
+					(then (call $return_result (i32.const 0)))
+				)
+				(call $allowance_key (i32.const 16) (i32.const 68))
+				(call $load (i32.const 65) (i32.const 1320))
+				(call $add (i32.const 1320) (i32.const 100))
+				(call $store (i32.const 65) (i32.const 1320))
+				(call $emit_approval (i32.const 16) (i32.const 68) (i32.const 1320))
+				(call $return_result (i32.const 0))
+			)
+		)
+
+		;; PSP22::decrease_allowance(spender: AccountId, delta_value: u128)
+		(if (i32.eq (local.get $selector) (i32.const 0xd557cbfe))
+			(then
+				(if
+					(i32.or
+						(call $eq32 (i32.const 16) (i32.const 68))
+						(call $is_zero (i32.const 100))
+					)
+					(then (call $return_result (i32.const 0)))
+				)
+				(call $allowance_key (i32.const 16) (i32.const 68))
+				(call $load (i32.const 65) (i32.const 1320))
+				(if (call $lt (i32.const 1320) (i32.const 100))
+					(then (call $return_result (i32.const 2)))
+				)
+				(call $sub (i32.const 1320) (i32.const 100))
+				(call $store (i32.const 65) (i32.const 1320))
+				(call $emit_approval (i32.const 16) (i32.const 68) (i32.const 1320))
+				(call $return_result (i32.const 0))
+			)
+		)
+
+		;; Unknown selector: revert with `Err(LangError::CouldNotReadInput)`.
+		(i32.store16 (i32.const 1400) (i32.const 0x0101))
+		(call $seal_return (i32.const 1) (i32.const 1400) (i32.const 2))
+	)
+)
@@ -0,0 +1,174 @@
+//! Coverage of the Pop API by a test suite: the functions of the API are compared with the
+//! chain-extension calls of the contracts under test (see `ink_sandbox::chain_extension`), so
+//! teams targeting Pop can see the untested API surface before deploying.
+//!
+//! The calls are traced for the whole test binary, so the report is printed at the end of a test,
+//! e.g. of the last test of a suite run with `--test-threads=1`:
+//!
+//! ```rs
+//! call::<Pop, (), Psp22Error>(&mut session, "PSP22::transfer", [arg_account(&BOB), "10", "[]"], None)?;
+//! print_api_coverage(FUNGIBLES_V0);
+//! ```
+//!
+//! Only sandboxes whose runtime uses `TracingChainExtension` as chain extension (e.g. created with
+//! `create_sandbox!`) trace the calls.
+
+use std::{collections::BTreeSet, fmt};
+
+use ink_sandbox::chain_extension::{traced_calls, ChainExtensionCall};
+
+/// The function of the Pop chain extension called by an API function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiFunctionKind {
+	/// Dispatches a call of the runtime.
+	Dispatch = 0,
+	/// Reads the state of the runtime.
+	ReadState = 1,
+}
+
+/// A function of the Pop API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ApiFunction {
+	/// The name of the function, e.g. `fungibles::transfer`.
+	pub name: &'static str,
+	/// The function of the chain extension it calls.
+	pub kind: ApiFunctionKind,
+	/// The version of the function.
+	pub version: u8,
+	/// The index of the API module (the index of its pallet in the runtime).
+	pub module: u8,
+	/// The index of the function in its module, i.e. of the dispatchable or of the state read.
+	pub index: u8,
+}
+
+impl ApiFunction {
+	const fn new(
+		name: &'static str,
+		kind: ApiFunctionKind,
+		version: u8,
+		module: u8,
+		index: u8,
+	) -> Self {
+		Self { name, kind, version, module, index }
+	}
+
+	/// Returns the identifier of the chain-extension method of the function.
+	pub fn id(&self) -> u32 {
+		u32::from_le_bytes([self.kind as u8, self.version, self.module, self.index])
+	}
+}
+
+/// The index of the fungibles module of the Pop API.
+const FUNGIBLES: u8 = 150;
+
+/// The functions of version 0 of the fungibles module of the Pop API.
+pub const FUNGIBLES_V0: &[ApiFunction] = {
+	use ApiFunctionKind::{Dispatch, ReadState};
+	&[
+		ApiFunction::new("fungibles::total_supply", ReadState, 0, FUNGIBLES, 0),
+		ApiFunction::new("fungibles::balance_of", ReadState, 0, FUNGIBLES, 1),
+		ApiFunction::new("fungibles::allowance", ReadState, 0, FUNGIBLES, 2),
+		ApiFunction::new("fungibles::transfer", Dispatch, 0, FUNGIBLES, 3),
+		ApiFunction::new("fungibles::transfer_from", Dispatch, 0, FUNGIBLES, 4),
+		ApiFunction::new("fungibles::approve", Dispatch, 0, FUNGIBLES, 5),
+		ApiFunction::new("fungibles::increase_allowance", Dispatch, 0, FUNGIBLES, 6),
+		ApiFunction::new("fungibles::decrease_allowance", Dispatch, 0, FUNGIBLES, 7),
+		ApiFunction::new("fungibles::token_name", ReadState, 0, FUNGIBLES, 8),
+		ApiFunction::new("fungibles::token_symbol", ReadState, 0, FUNGIBLES, 9),
+		ApiFunction::new("fungibles::token_decimals", ReadState, 0, FUNGIBLES, 10),
+		ApiFunction::new("fungibles::create", Dispatch, 0, FUNGIBLES, 11),
+		ApiFunction::new("fungibles::start_destroy", Dispatch, 0, FUNGIBLES, 12),
+		ApiFunction::new("fungibles::set_metadata", Dispatch, 0, FUNGIBLES, 16),
+		ApiFunction::new("fungibles::clear_metadata", Dispatch, 0, FUNGIBLES, 17),
+		ApiFunction::new("fungibles::token_exists", ReadState, 0, FUNGIBLES, 18),
+		ApiFunction::new("fungibles::mint", Dispatch, 0, FUNGIBLES, 19),
+		ApiFunction::new("fungibles::burn", Dispatch, 0, FUNGIBLES, 20),
+	]
+};
+
+/// The functions of the Pop API exercised and not exercised by the test suite so far.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApiCoverage {
+	/// The functions called at least once.
+	pub exercised: Vec<ApiFunction>,
+	/// The functions never called.
+	pub untested: Vec<ApiFunction>,
+	/// The identifiers of the chain-extension calls matching none of the functions, e.g. of
+	/// functions of other versions.
+	pub unknown: Vec<u32>,
+}
+
+impl ApiCoverage {
+	/// Returns the coverage of `functions` by the chain-extension calls traced so far.
+	pub fn of(functions: &[ApiFunction]) -> Self {
+		Self::from_calls(functions, &traced_calls())
+	}
+
+	/// Returns the coverage of `functions` by `calls`.
+	pub fn from_calls(functions: &[ApiFunction], calls: &BTreeSet<ChainExtensionCall>) -> Self {
+		let ids = calls.iter().map(ChainExtensionCall::id).collect::<BTreeSet<_>>();
+		let (exercised, untested) =
+			functions.iter().partition(|function| ids.contains(&function.id()));
+		let unknown = ids
+			.into_iter()
+			.filter(|id| functions.iter().all(|function| function.id() != *id))
+			.collect();
+		Self { exercised, untested, unknown }
+	}
+
+	/// Returns whether all the functions were exercised at least once.
+	pub fn is_complete(&self) -> bool {
+		self.untested.is_empty()
+	}
+}
+
+impl fmt::Display for ApiCoverage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let total = self.exercised.len() + self.untested.len();
+		writeln!(f, "Pop API coverage: {}/{total}", self.exercised.len())?;
+		for function in &self.exercised {
+			writeln!(f, "  [x] {} (v{})", function.name, function.version)?;
+		}
+		for function in &self.untested {
+			writeln!(f, "  [ ] {} (v{})", function.name, function.version)?;
+		}
+		for id in &self.unknown {
+			writeln!(f, "  [?] unknown function {id:#010x}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Prints the coverage of `functions` by the chain-extension calls of the test suite so far, and
+/// returns it.
+pub fn print_api_coverage(functions: &[ApiFunction]) -> ApiCoverage {
+	let coverage = ApiCoverage::of(functions);
+	println!("{coverage}");
+	coverage
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use ink_sandbox::chain_extension::ChainExtensionCall;
+
+	use super::{ApiCoverage, FUNGIBLES_V0};
+
+	#[test]
+	fn coverage_is_computed_from_calls() {
+		let transfer = FUNGIBLES_V0.iter().find(|f| f.name == "fungibles::transfer").unwrap();
+		let unknown = u32::from_le_bytes([0, 1, 150, 3]);
+		let calls = BTreeSet::from([
+			ChainExtensionCall::from_id(transfer.id()),
+			ChainExtensionCall::from_id(unknown),
+		]);
+
+		let coverage = ApiCoverage::from_calls(FUNGIBLES_V0, &calls);
+		assert_eq!(coverage.exercised, vec![*transfer]);
+		assert_eq!(coverage.untested.len(), FUNGIBLES_V0.len() - 1);
+		assert_eq!(coverage.unknown, vec![unknown]);
+		assert!(!coverage.is_complete());
+		assert!(coverage.to_string().contains("[x] fungibles::transfer (v0)"));
+	}
+}
@@ -0,0 +1,162 @@
+//! Helpers for passing arguments to contract calls.
+//!
+//! Arguments are passed as text, parsed by the transcoder of the contract according to the types
+//! declared in its metadata. Values that are awkward to write as text, like byte arrays and
+//! accounts, can be rendered with [`arg_bytes`] and [`arg_account`], or passed SCALE-encoded with
+//! [`Arg::encoded`]:
+//!
+//! ```rs
+//! call::<Pop, (), ContractError>(
+//!     &mut session,
+//!     "transfer",
+//!     [arg_account(&BOB).into(), Arg::encoded(&100u128), arg_bytes(&data).into()],
+//!     None,
+//! );
+//! ```
+
+use drink::{
+	session::{contract_transcode::ContractMessageTranscoder, error::SessionError},
+	AccountId32,
+};
+use scale::Encode;
+
+/// An argument of a contract call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Arg {
+	/// A value written as text, e.g. `"42"`, `"Some(1)"` or `"0x0102"`.
+	Text(String),
+	/// A SCALE-encoded value.
+	Encoded(Vec<u8>),
+}
+
+impl Arg {
+	/// Creates an argument from the SCALE encoding of `value`.
+	pub fn encoded(value: &impl Encode) -> Self {
+		Arg::Encoded(value.encode())
+	}
+}
+
+impl From<String> for Arg {
+	fn from(text: String) -> Self {
+		Arg::Text(text)
+	}
+}
+
+impl From<&String> for Arg {
+	fn from(text: &String) -> Self {
+		Arg::Text(text.clone())
+	}
+}
+
+impl From<&str> for Arg {
+	fn from(text: &str) -> Self {
+		Arg::Text(text.to_string())
+	}
+}
+
+/// Renders `bytes` as an argument of type `Vec<u8>` or `[u8; N]`.
+pub fn arg_bytes(bytes: &[u8]) -> String {
+	bytes.iter().fold("0x".to_string(), |hex, byte| hex + &format!("{byte:02x}"))
+}
+
+/// Renders `account` as an argument of type `AccountId`, e.g. a runtime account or an account of
+/// the contract environment.
+pub fn arg_account(account: &impl AsRef<[u8; 32]>) -> String {
+	AccountId32::new(*account.as_ref()).to_string()
+}
+
+/// Renders the arguments of the constructor or message `name` as text, decoding the encoded ones
+/// with the types declared in the metadata of `transcoder`.
+///
+/// The transcoder is only required if there are encoded arguments.
+pub(crate) fn render(
+	transcoder: Option<&ContractMessageTranscoder>,
+	name: &str,
+	args: impl IntoIterator<Item = impl Into<Arg>>,
+) -> Result<Vec<String>, SessionError> {
+	let args = args.into_iter().map(Into::into).collect::<Vec<_>>();
+	let texts = args.iter().map(|arg| match arg {
+		Arg::Text(text) => Some(text.clone()),
+		Arg::Encoded(_) => None,
+	});
+	if let Some(texts) = texts.collect::<Option<Vec<_>>>() {
+		return Ok(texts);
+	}
+
+	let transcoder = transcoder.ok_or(SessionError::NoTranscoder)?;
+	let spec = transcoder.metadata().spec();
+	let types = spec
+		.constructors()
+		.iter()
+		.find(|constructor| constructor.label() == name)
+		.map(|constructor| constructor.args())
+		.or_else(|| {
+			spec.messages()
+				.iter()
+				.find(|message| message.label() == name)
+				.map(|message| message.args())
+		})
+		.ok_or_else(|| SessionError::Encoding(format!("No constructor or message `{name}`")))?
+		.iter()
+		.map(|arg| arg.ty().ty().id)
+		.collect::<Vec<_>>();
+	if types.len() != args.len() {
+		return Err(SessionError::Encoding(format!(
+			"`{name}` takes {} argument(s), but {} were given",
+			types.len(),
+			args.len()
+		)));
+	}
+
+	args.into_iter()
+		.zip(types)
+		.map(|(arg, type_id)| match arg {
+			Arg::Text(text) => Ok(text),
+			Arg::Encoded(bytes) => transcoder
+				.decode(type_id, &mut &bytes[..])
+				.map(|value| value.to_string())
+				.map_err(|err| SessionError::Encoding(err.to_string())),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bytes_are_rendered_as_hex() {
+		assert_eq!(arg_bytes(&[]), "0x");
+		assert_eq!(arg_bytes(&[0, 1, 0xab, 0xff]), "0x0001abff");
+		assert_eq!(arg_bytes(&[7u8; 32]), format!("0x{}", "07".repeat(32)));
+	}
+
+	#[test]
+	fn accounts_are_rendered_as_ss58() {
+		let account = AccountId32::new([1u8; 32]);
+		assert_eq!(arg_account(&account), account.to_string());
+		assert_eq!(
+			arg_account(&pop_api::primitives::AccountId::from([1u8; 32])),
+			account.to_string()
+		);
+	}
+
+	#[test]
+	fn text_arguments_do_not_need_a_transcoder() {
+		let owned = "2".to_string();
+		assert_eq!(
+			render(None, "new", [Arg::from("1"), Arg::from(&owned), arg_bytes(&[3]).into()])
+				.unwrap(),
+			vec!["1".to_string(), "2".to_string(), "0x03".to_string()]
+		);
+		assert!(render(None, "new", drink::session::NO_ARGS).unwrap().is_empty());
+	}
+
+	#[test]
+	fn encoded_arguments_need_a_transcoder() {
+		assert!(matches!(
+			render(None, "new", [Arg::encoded(&42u32)]),
+			Err(SessionError::NoTranscoder)
+		));
+	}
+}
@@ -0,0 +1,115 @@
+//! Handles of the contracts deployed with [`crate::deploy`], carrying the address of a contract,
+//! its metadata and the error type of its messages, so they aren't repeated at every call site:
+//!
+//! ```rs
+//! let token = deploy::<Pop, Psp22Error>(&mut session, bundle, "new", ["1000"], NO_SALT, None)?;
+//! token.call::<()>(&mut session, "PSP22::transfer", [arg_account(&BOB), "10", "[]"], None)?;
+//! let balance = token.call::<Balance>(&mut session, "PSP22::balance_of", [arg_account(&BOB)], None)?;
+//! ```
+//!
+//! Unlike [`crate::call`], which calls the contract deployed last, a handle always calls its own
+//! contract, so tests deploying several contracts can interleave their calls.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use drink::session::contract_transcode::ContractMessageTranscoder;
+use ink_sandbox::AccountIdFor;
+use scale::Decode;
+
+use crate::{pallet_contracts, Arg, CallError, Endowment, Sandbox, Session};
+
+/// A contract deployed in a session, whose messages return `Err(E)` on failure.
+pub struct Contract<S, E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	address: AccountIdFor<S::Runtime>,
+	name: String,
+	transcoder: Arc<ContractMessageTranscoder>,
+	_error: PhantomData<fn() -> E>,
+}
+
+impl<S, E> Contract<S, E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	/// Creates the handle of the contract at `address`, described by `transcoder`.
+	pub fn new(
+		address: AccountIdFor<S::Runtime>,
+		name: impl Into<String>,
+		transcoder: Arc<ContractMessageTranscoder>,
+	) -> Self {
+		Self { address, name: name.into(), transcoder, _error: PhantomData }
+	}
+
+	/// Returns the address of the contract.
+	pub fn address(&self) -> &AccountIdFor<S::Runtime> {
+		&self.address
+	}
+
+	/// Returns the name of the contract, as in its bundle.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns the transcoder of the contract, derived from its metadata.
+	pub fn transcoder(&self) -> &Arc<ContractMessageTranscoder> {
+		&self.transcoder
+	}
+
+	/// Calls `message` of the contract and decodes the returned data, like [`crate::call`].
+	///
+	/// # Generic Parameters:
+	/// - `O` - `Ok()` type returned by the message.
+	///
+	/// # Parameters:
+	/// - `session` - The session the contract was deployed in.
+	/// - `message` - The name of the contract method.
+	/// - `input` - The input arguments, as text or encoded (see [`Arg`]).
+	/// - `endowment` - Balance to transfer during the call (see [`Endowment`]). Requires the
+	///   contract method to be `payable`. Panics if the caller can't transfer it.
+	pub fn call<O: Decode>(
+		&self,
+		session: &mut Session<S>,
+		message: &str,
+		input: impl IntoIterator<Item = impl Into<Arg>>,
+		endowment: Option<Endowment>,
+	) -> Result<O, CallError<E>>
+	where
+		E: Decode,
+	{
+		crate::call_contract(
+			session,
+			Some(self.address.clone()),
+			Some(&*self.transcoder),
+			message,
+			input,
+			endowment,
+		)
+	}
+}
+
+impl<S, E> Clone for Contract<S, E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	fn clone(&self) -> Self {
+		Self::new(self.address.clone(), self.name.clone(), self.transcoder.clone())
+	}
+}
+
+impl<S, E> std::fmt::Debug for Contract<S, E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Contract")
+			.field("address", &self.address)
+			.field("name", &self.name)
+			.finish_non_exhaustive()
+	}
+}
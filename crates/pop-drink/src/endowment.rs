@@ -0,0 +1,152 @@
+//! Values transferred with deployments and calls, expressed in units of the native token.
+//!
+//! ```rs
+//! let value = Some(Endowment::units(5));
+//! deploy::<Pop, ContractError>(&mut session, bundle, "new", NO_ARGS, NO_SALT, value);
+//! call::<Pop, (), ContractError>(&mut session, "fund", NO_ARGS, Some(Endowment::millis(250)));
+//! ```
+//!
+//! Before dispatching, the value is converted into the balance type of the runtime and checked
+//! against the balance the caller can transfer, so a wrong magnitude fails the test with a clear
+//! message instead of an opaque runtime error.
+
+use std::{fmt, ops::Add};
+
+use drink::{
+	session::Session,
+	units::{dot_style_format, MICRO_UNIT, MILLI_UNIT, UNIT},
+	Sandbox,
+};
+use frame_support::traits::{
+	fungible::Inspect,
+	tokens::{Fortitude, Preservation},
+};
+use ink_sandbox::BalanceFor;
+
+/// A value transferred with a deployment or a call, in planck (the smallest fraction of the native
+/// token).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Endowment(u128);
+
+impl Endowment {
+	/// An endowment of `amount` planck.
+	pub const fn planck(amount: u128) -> Self {
+		Self(amount)
+	}
+
+	/// An endowment of `amount` units of the native token. Panics on overflow.
+	pub const fn units(amount: u128) -> Self {
+		Self::scaled(amount, UNIT)
+	}
+
+	/// An endowment of `amount` thousandths of a unit of the native token. Panics on overflow.
+	pub const fn millis(amount: u128) -> Self {
+		Self::scaled(amount, MILLI_UNIT)
+	}
+
+	/// An endowment of `amount` millionths of a unit of the native token. Panics on overflow.
+	pub const fn micros(amount: u128) -> Self {
+		Self::scaled(amount, MICRO_UNIT)
+	}
+
+	/// Returns the endowment in planck.
+	pub const fn as_planck(&self) -> u128 {
+		self.0
+	}
+
+	/// Adds two endowments, returning `None` on overflow.
+	pub const fn checked_add(self, other: Self) -> Option<Self> {
+		match self.0.checked_add(other.0) {
+			Some(amount) => Some(Self(amount)),
+			None => None,
+		}
+	}
+
+	const fn scaled(amount: u128, scale: u128) -> Self {
+		match amount.checked_mul(scale) {
+			Some(amount) => Self(amount),
+			None => panic!("The endowment overflows the `u128` planck amount"),
+		}
+	}
+}
+
+impl Add for Endowment {
+	type Output = Self;
+
+	/// Adds two endowments. Panics on overflow.
+	fn add(self, other: Self) -> Self {
+		self.checked_add(other)
+			.expect("The endowment overflows the `u128` planck amount")
+	}
+}
+
+impl fmt::Display for Endowment {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&dot_style_format(self.0))
+	}
+}
+
+/// Converts `endowment` into the balance type of the runtime and checks that the actor of the
+/// session can transfer it (keeping its account alive), panicking with a clear message otherwise.
+#[track_caller]
+pub(crate) fn checked_balance<S>(
+	session: &mut Session<S>,
+	endowment: Option<Endowment>,
+) -> Option<BalanceFor<S::Runtime>>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	let endowment = endowment?;
+	let value = BalanceFor::<S::Runtime>::try_from(endowment.as_planck()).unwrap_or_else(|_| {
+		panic!("The endowment of {endowment} doesn't fit the balance type of the runtime")
+	});
+
+	let actor = session.get_actor();
+	let available = session.sandbox().execute_with(|| {
+		<<S::Runtime as pallet_contracts::Config>::Currency as Inspect<_>>::reducible_balance(
+			&actor,
+			Preservation::Preserve,
+			Fortitude::Polite,
+		)
+	});
+	if value > available {
+		let available = available.try_into().map(dot_style_format).unwrap_or_default();
+		panic!(
+			"The endowment of {endowment} exceeds the transferable balance of {actor:?} \
+			 ({available})"
+		);
+	}
+	Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn endowments_are_expressed_in_units() {
+		assert_eq!(Endowment::units(5).as_planck(), 5 * UNIT);
+		assert_eq!(Endowment::millis(250).as_planck(), UNIT / 4);
+		assert_eq!(Endowment::micros(1), Endowment::planck(MICRO_UNIT));
+		assert_eq!(Endowment::units(1) + Endowment::millis(500), Endowment::millis(1_500));
+		assert_eq!(
+			Endowment::units(1) + Endowment::millis(5),
+			Endowment::planck(UNIT + MILLI_UNIT * 5)
+		);
+		assert_eq!(Endowment::millis(1_500).to_string(), "1.5 UNIT");
+	}
+
+	#[test]
+	#[should_panic(expected = "The endowment overflows")]
+	fn units_overflow_is_detected() {
+		Endowment::units(u128::MAX / UNIT + 1);
+	}
+
+	#[test]
+	fn addition_overflow_is_detected() {
+		assert_eq!(Endowment::planck(u128::MAX).checked_add(Endowment::planck(1)), None);
+		assert!(std::panic::catch_unwind(|| Endowment::planck(u128::MAX) + Endowment::planck(1))
+			.is_err());
+	}
+}
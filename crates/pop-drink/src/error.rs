@@ -1,12 +1,17 @@
 //! The error type and utilities for testing smart contracts using the Pop API.
 
-use std::fmt::Debug;
+use std::{
+	fmt::{self, Debug},
+	marker::PhantomData,
+};
 
 pub use drink::{
 	pallet_assets::Error as AssetsError, pallet_balances::Error as BalancesError,
 	pallet_contracts::Error as ContractsError, pallet_nfts::Error as NftsError,
 };
-use scale::{Decode, Encode};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use scale::{Decode, Encode, Input, Output};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef};
 
 /// A simplified error type representing errors from the runtime and its modules.
 ///
@@ -72,6 +77,176 @@ where
 	}
 }
 
+/// Provides the metadata of a runtime, used to resolve the names of its module errors.
+///
+/// Implemented for the Pop runtimes. Any runtime built with `construct_runtime!` can implement it
+/// by returning `Runtime::metadata()`.
+pub trait RuntimeMetadataProvider {
+	/// Returns the metadata of the runtime.
+	fn runtime_metadata() -> RuntimeMetadataPrefixed;
+}
+
+/// A module error of the runtime `R`, whose pallet and variant names are resolved from the
+/// runtime metadata instead of a hand-maintained `RuntimeError` enum.
+///
+/// It can be used as the `ModuleError` of [`Error`], so `assert_err!` keeps working when pallets
+/// are added, removed or reordered by a runtime upgrade.
+///
+/// # Example
+///
+/// ```rs
+/// type Error = pop_drink::error::Error<ApiError, DynamicModuleError<Runtime>, 3>;
+///
+/// assert_err!(result, Error::Module(DynamicModuleError::named("Assets", "BalanceLow")));
+/// ```
+pub struct DynamicModuleError<R> {
+	/// Index of the pallet in the runtime.
+	index: u8,
+	/// The encoded error of the pallet.
+	error: [u8; 2],
+	_runtime: PhantomData<R>,
+}
+
+impl<R> DynamicModuleError<R> {
+	/// Creates the error from the pallet index and the encoded pallet error.
+	pub fn new(index: u8, error: [u8; 2]) -> Self {
+		Self { index, error, _runtime: PhantomData }
+	}
+
+	/// Returns the index of the pallet in the runtime.
+	pub fn index(&self) -> u8 {
+		self.index
+	}
+
+	/// Returns the encoded pallet error.
+	pub fn error(&self) -> [u8; 2] {
+		self.error
+	}
+}
+
+impl<R: RuntimeMetadataProvider> DynamicModuleError<R> {
+	/// Creates the error `error` of the pallet `pallet`, looked up in the runtime metadata.
+	///
+	/// Panics if the runtime has no such pallet or error.
+	#[track_caller]
+	pub fn named(pallet: &str, error: &str) -> Self {
+		let (index, variant) = module_error_index(&R::runtime_metadata(), pallet, error)
+			.unwrap_or_else(|| panic!("Error `{pallet}::{error}` not found in the runtime"));
+		Self::new(index, [variant, 0])
+	}
+
+	/// Returns the pallet and error names, if they can be found in the runtime metadata.
+	pub fn name(&self) -> Option<(String, String)> {
+		module_error_name(&R::runtime_metadata(), self.index, self.error[0])
+	}
+}
+
+impl<R: RuntimeMetadataProvider> Debug for DynamicModuleError<R> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.name() {
+			Some((pallet, error)) => write!(f, "{pallet}({error})"),
+			None => f
+				.debug_struct("Module")
+				.field("index", &self.index)
+				.field("error", &self.error)
+				.finish(),
+		}
+	}
+}
+
+impl<R> Clone for DynamicModuleError<R> {
+	fn clone(&self) -> Self {
+		Self::new(self.index, self.error)
+	}
+}
+
+impl<R> PartialEq for DynamicModuleError<R> {
+	fn eq(&self, other: &Self) -> bool {
+		(self.index, self.error) == (other.index, other.error)
+	}
+}
+
+impl<R> Eq for DynamicModuleError<R> {}
+
+impl<R> Encode for DynamicModuleError<R> {
+	fn size_hint(&self) -> usize {
+		3
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		dest.push_byte(self.index);
+		dest.write(&self.error);
+	}
+}
+
+impl<R> Decode for DynamicModuleError<R> {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, scale::Error> {
+		Ok(Self::new(u8::decode(input)?, <[u8; 2]>::decode(input)?))
+	}
+}
+
+/// Returns the types and the `(name, index, error type)` of every pallet with errors.
+fn pallet_errors(
+	metadata: &RuntimeMetadataPrefixed,
+) -> Option<(&PortableRegistry, Vec<(&str, u8, u32)>)> {
+	match &metadata.1 {
+		RuntimeMetadata::V14(metadata) => Some((
+			&metadata.types,
+			metadata
+				.pallets
+				.iter()
+				.filter_map(|pallet| {
+					Some((pallet.name.as_str(), pallet.index, pallet.error.as_ref()?.ty.id))
+				})
+				.collect(),
+		)),
+		RuntimeMetadata::V15(metadata) => Some((
+			&metadata.types,
+			metadata
+				.pallets
+				.iter()
+				.filter_map(|pallet| {
+					Some((pallet.name.as_str(), pallet.index, pallet.error.as_ref()?.ty.id))
+				})
+				.collect(),
+		)),
+		_ => None,
+	}
+}
+
+fn error_variants(types: &PortableRegistry, ty: u32) -> Vec<(&str, u8)> {
+	match types.resolve(ty).map(|ty| &ty.type_def) {
+		Some(TypeDef::<PortableForm>::Variant(errors)) => errors
+			.variants
+			.iter()
+			.map(|variant| (variant.name.as_str(), variant.index))
+			.collect(),
+		_ => vec![],
+	}
+}
+
+fn module_error_name(
+	metadata: &RuntimeMetadataPrefixed,
+	index: u8,
+	error: u8,
+) -> Option<(String, String)> {
+	let (types, pallets) = pallet_errors(metadata)?;
+	let (pallet, _, ty) = pallets.into_iter().find(|(_, i, _)| *i == index)?;
+	let (error, _) = error_variants(types, ty).into_iter().find(|(_, i)| *i == error)?;
+	Some((pallet.to_string(), error.to_string()))
+}
+
+fn module_error_index(
+	metadata: &RuntimeMetadataPrefixed,
+	pallet: &str,
+	error: &str,
+) -> Option<(u8, u8)> {
+	let (types, pallets) = pallet_errors(metadata)?;
+	let (_, index, ty) = pallets.into_iter().find(|(name, ..)| *name == pallet)?;
+	let (_, variant) = error_variants(types, ty).into_iter().find(|(name, _)| *name == error)?;
+	Some((index, variant))
+}
+
 fn decode<T: Decode>(data: &[u8]) -> T {
 	T::decode(&mut &data[..]).expect("Decoding failed")
 }
@@ -119,6 +294,69 @@ mod test {
 		});
 	}
 
+	impl RuntimeMetadataProvider for crate::mock::Test {
+		fn runtime_metadata() -> RuntimeMetadataPrefixed {
+			crate::mock::Test::metadata()
+		}
+	}
+
+	#[test]
+	fn dynamic_module_errors_are_resolved_from_metadata() {
+		use frame_support::traits::PalletInfoAccess;
+
+		let error = DynamicModuleError::<crate::mock::Test>::named("Assets", "NoAccount");
+		assert_eq!(error.encode(), vec![crate::mock::Assets::index() as u8, 1, 0]);
+		assert_eq!(format!("{error:?}"), "Assets(NoAccount)");
+
+		let unknown = DynamicModuleError::<crate::mock::Test>::new(u8::MAX, [0, 0]);
+		assert_eq!(unknown.name(), None);
+		assert_eq!(format!("{unknown:?}"), "Module { index: 255, error: [0, 0] }");
+	}
+
+	#[test]
+	fn assert_err_works_with_dynamic_module_errors() {
+		use frame_support::traits::PalletInfoAccess;
+
+		type DynamicError = Error<ApiError, DynamicModuleError<crate::mock::Test>, 3>;
+		let api_error =
+			ApiError::Module { index: crate::mock::Balances::index() as u8, error: [1, 0] };
+		crate::assert_err!(
+			Result::<(), ApiError>::Err(api_error),
+			DynamicError::Module(DynamicModuleError::named("Balances", "LiquidityRestrictions")),
+		);
+	}
+
+	#[test]
+	fn assert_dispatch_err_works() {
+		use frame_support::sp_runtime::DispatchError;
+
+		let error: DispatchError =
+			AssetsError::<crate::mock::Test, pallet_assets::Instance1>::NoAccount.into();
+		crate::assert_dispatch_err!(
+			Result::<(), _>::Err(error),
+			Assets(NoAccount),
+			crate::mock::Test
+		);
+		crate::assert_dispatch_err!(
+			Result::<(), _>::Err(DispatchError::BadOrigin),
+			DispatchError::BadOrigin
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "right: Assets(BalanceLow)")]
+	fn assert_dispatch_err_reports_error_names() {
+		use frame_support::sp_runtime::DispatchError;
+
+		let error: DispatchError =
+			AssetsError::<crate::mock::Test, pallet_assets::Instance1>::NoAccount.into();
+		crate::assert_dispatch_err!(
+			Result::<(), _>::Err(error),
+			Assets(BalanceLow),
+			crate::mock::Test
+		);
+	}
+
 	#[test]
 	fn assert_err_works() {
 		test_cases().into_iter().for_each(|t| {
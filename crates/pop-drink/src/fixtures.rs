@@ -0,0 +1,231 @@
+//! Precompiled contracts, to be deployed as the counterparts of the contracts under test.
+//!
+//! ```rs
+//! let token = deploy_reference_psp22(&mut session, 1_000_000)?;
+//! // Deploy a DEX trading `token`.
+//! deploy::<Pop, DexError>(&mut session, dex, "new", [arg_account(&token)], NO_SALT, None)?;
+//! ```
+
+use std::sync::Arc;
+
+use drink::session::{contract_transcode::ContractMessageTranscoder, error::SessionError};
+use ink_sandbox::AccountIdFor;
+
+use crate::{pallet_contracts, ContractBundle, Sandbox, Session};
+
+/// The code of the reference PSP22 token.
+const PSP22_WAT: &str = include_str!("../fixtures/psp22.wat");
+/// The metadata of the reference PSP22 token.
+const PSP22_METADATA: &str = include_str!("../fixtures/psp22.json");
+/// The code of the oracle mock.
+const ORACLE_WAT: &str = include_str!("../fixtures/oracle.wat");
+/// The metadata of the oracle mock.
+const ORACLE_METADATA: &str = include_str!("../fixtures/oracle.json");
+
+/// Returns the bundle of the reference PSP22 token.
+///
+/// The token implements the messages of the `PSP22` trait (`PSP22::transfer`,
+/// `PSP22::balance_of`, ...) with the ABI of ink!, and is created with `new(supply: u128)`,
+/// minting the supply to the caller. It emits the `Transfer` and `Approval` events of the `PSP22`
+/// crate, so they can be decoded with the transcoder of the bundle.
+pub fn reference_psp22() -> ContractBundle {
+	bundle("reference_psp22", PSP22_WAT, PSP22_METADATA)
+}
+
+/// Deploys a reference PSP22 token (see [`reference_psp22`]) with a total supply of `supply`,
+/// owned by the actor of the session, and returns its address.
+///
+/// The transcoder of the token is registered, so it can be called with `call_with_address` like
+/// any contract deployed from a bundle.
+pub fn deploy_reference_psp22<S>(
+	session: &mut Session<S>,
+	supply: u128,
+) -> Result<AccountIdFor<S::Runtime>, SessionError>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	let salt = session.next_salt();
+	session.deploy_bundle(reference_psp22(), "new", &[supply.to_string()], salt, None)
+}
+
+/// Returns the bundle of the oracle mock, see [`crate::price_feed::PriceFeed`] to control it.
+///
+/// The oracle is created with `new()`, and stores the price of trading pairs (e.g. `"DOT/USD"`):
+/// `set_price(pair: String, price: u128)` sets the price of a pair, and
+/// `get_price(pair: String) -> Option<(u128, u32)>` returns it along with the number of the block
+/// it was set in. Anyone can set a price, which emits `PriceUpdated { pair: String, price: u128 }`
+/// with the pair as topic.
+pub fn oracle_mock() -> ContractBundle {
+	bundle("oracle_mock", ORACLE_WAT, ORACLE_METADATA)
+}
+
+fn bundle(name: &str, wat: &str, metadata: &str) -> ContractBundle {
+	let wasm = wat::parse_str(wat).expect("The fixture is valid WAT");
+	let metadata = serde_json::from_str(metadata).expect("The metadata of the fixture is valid");
+	ContractBundle {
+		name: name.to_string(),
+		wasm,
+		transcoder: Arc::new(ContractMessageTranscoder::new(metadata)),
+		ink_version: None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use drink::{
+		accounts::{fund_accounts, BOB},
+		minimal::MinimalSandbox,
+		units::units,
+		AccountId32,
+	};
+	use scale::Decode;
+
+	use super::*;
+
+	#[test]
+	fn reference_psp22_works() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let token = deploy_reference_psp22(&mut session, 1_000).unwrap();
+
+		let balance_of = |session: &mut Session<MinimalSandbox>, account: &AccountId32| {
+			session
+				.call_with_address::<_, u128>(
+					token.clone(),
+					"PSP22::balance_of",
+					&[account.to_string()],
+					None,
+				)
+				.unwrap()
+				.unwrap()
+		};
+		assert_eq!(balance_of(&mut session, &owner), 1_000);
+
+		session
+			.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::transfer",
+				&[BOB.to_string(), "400".to_string(), "[]".to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+		assert_eq!(balance_of(&mut session, &owner), 600);
+		assert_eq!(balance_of(&mut session, &BOB), 400);
+
+		// `InsufficientBalance`.
+		assert!(matches!(
+			session.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::transfer",
+				&[BOB.to_string(), "601".to_string(), "[]".to_string()],
+				None,
+			),
+			Err(SessionError::CallReverted(error)) if error == [0, 1, 1]
+		));
+	}
+
+	#[test]
+	fn reference_psp22_spends_allowances() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let token = deploy_reference_psp22(&mut session, 1_000).unwrap();
+		session
+			.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::approve",
+				&[BOB.to_string(), "100".to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+
+		// Pays the storage deposits of its balance.
+		fund_accounts(&mut session, &[BOB], units(100)).unwrap();
+		session.set_actor(BOB);
+		let transfer_from = |session: &mut Session<MinimalSandbox>, value: &str| {
+			session.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::transfer_from",
+				&[owner.to_string(), BOB.to_string(), value.to_string(), "[]".to_string()],
+				None,
+			)
+		};
+		transfer_from(&mut session, "60").unwrap().unwrap();
+		// `InsufficientAllowance`.
+		assert!(matches!(
+			transfer_from(&mut session, "41"),
+			Err(SessionError::CallReverted(error)) if error == [0, 1, 2]
+		));
+
+		let allowance: u128 = session
+			.call_with_address(
+				token.clone(),
+				"PSP22::allowance",
+				&[owner.to_string(), BOB.to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+		assert_eq!(allowance, 40);
+	}
+
+	#[derive(Debug, PartialEq, Decode)]
+	struct Transfer {
+		from: Option<AccountId32>,
+		to: Option<AccountId32>,
+		value: u128,
+	}
+
+	#[derive(Debug, PartialEq, Decode)]
+	struct Approval {
+		owner: AccountId32,
+		spender: AccountId32,
+		amount: u128,
+	}
+
+	#[test]
+	fn reference_psp22_emits_events() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let token = deploy_reference_psp22(&mut session, 1_000).unwrap();
+		let mint = Transfer { from: None, to: Some(owner.clone()), value: 1_000 };
+		session.record().last_event_batch().assert_last_contract_event(mint);
+
+		session
+			.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::approve",
+				&[BOB.to_string(), "100".to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+		let approval = Approval { owner: owner.clone(), spender: BOB, amount: 100 };
+		let batch = session.record().last_event_batch();
+		batch.assert_last_contract_event_topic(BOB);
+		batch.assert_last_contract_event(approval);
+
+		fund_accounts(&mut session, &[BOB], units(100)).unwrap();
+		session.set_actor(BOB);
+		session
+			.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::transfer_from",
+				&[owner.to_string(), BOB.to_string(), "60".to_string(), "[]".to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+		// The allowance is spent, then the tokens are transferred.
+		let batch = session.record().last_event_batch();
+		let events = batch.contract_events();
+		let approval = Approval { owner: owner.clone(), spender: BOB, amount: 40 };
+		assert_eq!(Approval::decode(&mut &events[0][..]).unwrap(), approval);
+		batch.assert_last_contract_event_topic(Some(BOB));
+		batch.assert_last_contract_event(Transfer { from: Some(owner), to: Some(BOB), value: 60 });
+		// Both events are described by the metadata of the token.
+		assert_eq!(batch.contract_events_decoded(&reference_psp22().transcoder).len(), 2);
+	}
+}
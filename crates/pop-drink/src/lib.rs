@@ -1,27 +1,103 @@
 //! A library for testing smart contracts on Pop Network.
 
 pub use drink::*;
+use drink::{errors::LangError, session::contract_transcode::ContractMessageTranscoder};
 pub use frame_support::{self, assert_ok};
-pub use ink_sandbox::api::assets_api::AssetsAPI;
-use ink_sandbox::{AccountIdFor, BalanceFor};
-use scale::Decode;
-pub use session::{error::SessionError, ContractBundle, Session, NO_SALT};
+use frame_support::{sp_runtime::traits::Zero, traits::Get};
+pub use ink_sandbox::api::{
+	assets_api::AssetsAPI, authorship_api::AuthorshipAPI, foreign_assets_api::ForeignAssetsAPI,
+	governance_api::GovernanceAPI, parachain_api::ParachainAPI, referenda_api::ReferendaAPI,
+	staking_api::StakingAPI, tx_pause_api::TxPauseAPI,
+};
+use ink_sandbox::{api::prelude::*, AccountIdFor, BalanceFor};
+use scale::{Decode, DecodeAll, Encode};
+pub use session::{error::SessionError, ContractBundle, Session, NO_ARGS, NO_SALT};
 pub use sp_io::TestExternalities;
 
+pub use crate::{
+	api_coverage::{print_api_coverage, ApiCoverage},
+	args::{arg_account, arg_bytes, Arg},
+	contract::Contract,
+	endowment::Endowment,
+	fixtures::deploy_reference_psp22,
+	price_feed::PriceFeed,
+	scenario::Scenario,
+	state::{export_contract_state, import_contract_state},
+};
+
+/// Coverage of the Pop API by a test suite.
+pub mod api_coverage;
+/// Helpers for passing arguments to contract calls.
+pub mod args;
+/// Handles of the deployed contracts.
+pub mod contract;
+/// Values transferred with deployments and calls.
+pub mod endowment;
 /// Error type and utilities for testing contracts using the Pop API.
 pub mod error;
+/// Precompiled contracts, e.g. a reference PSP22 token.
+pub mod fixtures;
 /// Collection of macros for testing contracts using the Pop API.
 pub mod macros;
 #[cfg(test)]
 mod mock;
+/// Price feeds controlled by tests.
+pub mod price_feed;
+/// Error types of the Pop API, parameterized by the runtime and the version of the API.
+pub mod runtime_error;
+/// Call scenarios loaded from JSON or CSV files.
+pub mod scenario;
+/// Contract state fixtures, exported to and imported from files.
+pub mod state;
+
+/// The Pop runtime resolving the module errors of `assert_dispatch_err!` by default.
+#[cfg(feature = "testnet")]
+#[doc(hidden)]
+pub type PopRuntime = pop_runtime_testnet::Runtime;
+/// The Pop runtime resolving the module errors of `assert_dispatch_err!` by default.
+#[cfg(all(feature = "devnet", not(feature = "testnet")))]
+#[doc(hidden)]
+pub type PopRuntime = pop_runtime_devnet::Runtime;
 
 #[cfg(any(feature = "devnet", feature = "testnet"))]
 macro_rules! define_runtime_utilities {
-	($runtime_type:ident) => {
+	($variant:ident, $runtime_crate:ident) => {
+		pub use $runtime_crate::Runtime;
+
+		use super::*;
+		pub use crate::error::*;
+
+		/// Error related utilities for smart contracts using Pop API.
+		pub mod error {
+			pub use $runtime_crate::RuntimeError::*;
+
+			pub use crate::error::*;
+
+			/// Error types for smart contracts using Pop API V0.
+			pub mod v0 {
+				pub use pop_api::primitives::v0::{self, Error as ApiError, *};
+
+				use crate::runtime_error::{$variant, V0};
+
+				/// Error type for writing tests (see `error` module).
+				pub type Error = crate::runtime_error::Error<$variant, V0>;
+
+				/// Error type for writing tests, resolving the module errors from the runtime
+				/// metadata (see `DynamicModuleError`).
+				pub type DynamicError = crate::runtime_error::DynamicError<$variant, V0>;
+			}
+		}
+
 		/// Alias for the balance type.
-		pub type Balance = BalanceFor<$runtime_type>;
+		pub type Balance = BalanceFor<Runtime>;
 		/// Alias for the account ID type.
-		pub type AccountId = AccountIdFor<$runtime_type>;
+		pub type AccountId = AccountIdFor<Runtime>;
+
+		impl crate::error::RuntimeMetadataProvider for Runtime {
+			fn runtime_metadata() -> ink_sandbox::RuntimeMetadataPrefixed {
+				Runtime::metadata()
+			}
+		}
 
 		/// Converts an AccountId from Pop's runtime to the account ID used in the contract
 		/// environment.
@@ -36,58 +112,22 @@ macro_rules! define_runtime_utilities {
 /// API.
 #[cfg(feature = "devnet")]
 pub mod devnet {
-	pub use pop_runtime_devnet::Runtime;
-
-	use super::*;
-	pub use crate::error::*;
-
-	/// Error related utilities for smart contracts using Pop API.
-	pub mod error {
-		pub use pop_runtime_devnet::RuntimeError::*;
-
-		pub use crate::error::*;
-
-		/// Error types for smart contracts using Pop API V0.
-		pub mod v0 {
-			pub use pop_api::primitives::v0::{self, Error as ApiError, *};
-
-			/// Error type for writing tests (see `error` module).
-			pub type Error = crate::error::Error<v0::Error, pop_runtime_devnet::RuntimeError, 3>;
-		}
-	}
-
-	define_runtime_utilities!(Runtime);
+	define_runtime_utilities!(Devnet, pop_runtime_devnet);
 }
 
 /// Types and utilities for testing smart contracts interacting with Pop Network Testnet via the Pop
 /// API.
 #[cfg(feature = "testnet")]
 pub mod testnet {
-	pub use pop_runtime_testnet::Runtime;
-
-	use super::*;
-	pub use crate::error::*;
-
-	/// Error related utilities for smart contracts using Pop API.
-	pub mod error {
-		pub use pop_runtime_testnet::RuntimeError::*;
-
-		pub use crate::error::*;
-
-		/// Error types for smart contracts using Pop API V0.
-		pub mod v0 {
-			pub use pop_api::primitives::v0::{self, Error as ApiError, *};
-
-			/// Error type for writing tests (see `error` module).
-			pub type Error = crate::error::Error<v0::Error, pop_runtime_testnet::RuntimeError, 3>;
-		}
-	}
-
-	define_runtime_utilities!(Runtime);
+	define_runtime_utilities!(Testnet, pop_runtime_testnet);
 }
 
 /// Deploy a contract with a given constructor, arguments, salt and an initial value. In
-/// case of success, returns the address of the deployed contract.
+/// case of success, returns the [`Contract`] handle of the deployed contract, carrying its address,
+/// its metadata and its error type `E`.
+///
+/// Panics if the contract was built with a release of ink! requiring host functions the sandbox
+/// doesn't provide (see `Session::verify_api_version`).
 ///
 /// # Generic Parameters:
 /// - `S` - Sandbox environment.
@@ -97,10 +137,10 @@ pub mod testnet {
 /// - `session` - The session for interacting with contracts.
 /// - `bundle` - The contract bundle.
 /// - `method` - The name of the constructor method.
-/// - `input` - The input arguments.
+/// - `input` - The input arguments, as text or encoded (see [`Arg`]).
 /// - `salt` - Optional deployment salt.
-/// - `init_value` - Initial balance to transfer during the contract creation. Requires the contract
-///   method to be `payable`.
+/// - `init_value` - Initial balance to transfer during the contract creation (see [`Endowment`]).
+///   Requires the contract method to be `payable`. Panics if the caller can't transfer it.
 ///
 /// # Example:
 /// ```rs
@@ -111,32 +151,185 @@ pub mod testnet {
 ///    // Deploy contract.
 ///    //
 ///    // `ContractError` is the error type used by the contract.
-///    assert_ok!(deploy<Pop, ContractError>(&mut session, bundle, "new", input, salt, init_value));
+///    let contract =
+///        deploy::<Pop, ContractError>(&mut session, bundle, "new", input, salt, init_value)?;
+///
+///    // Call the deployed contract.
+///    let value = contract.call::<u32>(&mut session, "get", NO_ARGS, None)?;
 /// }
 /// ```
 pub fn deploy<S, E>(
 	session: &mut Session<S>,
 	bundle: ContractBundle,
 	method: &str,
-	input: Vec<String>,
+	input: impl IntoIterator<Item = impl Into<Arg>>,
 	salt: Vec<u8>,
-	init_value: Option<BalanceFor<S::Runtime>>,
-) -> Result<AccountIdFor<S::Runtime>, E>
+	init_value: Option<Endowment>,
+) -> Result<Contract<S, E>, E>
 where
 	S: Sandbox,
 	S::Runtime: pallet_contracts::Config,
 	E: Decode,
 {
-	let result = session.deploy_bundle(bundle, method, &input, salt, init_value);
-	if result.is_err() {
-		let deployment_result = session.record().last_deploy_result().result.clone();
-		let error = deployment_result.unwrap().result.data;
-		return Err(E::decode(&mut &error[2..]).unwrap());
+	let input = args::render(Some(&bundle.transcoder), method, input)
+		.expect("Failed to encode the arguments");
+	let init_value = crate::endowment::checked_balance(session, init_value);
+	let (name, transcoder) = (bundle.name.clone(), bundle.transcoder.clone());
+	let address = match session.deploy_bundle(bundle, method, &input, salt, init_value) {
+		Ok(address) => address,
+		// The contract can't run in the sandbox, e.g. it targets a newer API version.
+		Err(error @ SessionError::IncompatibleApiVersion { .. }) => panic!("{error}"),
+		Err(_) => {
+			let deployment_result = session.record().last_deploy_result().result.clone();
+			return Err(constructor_error(&deployment_result.unwrap().result.data));
+		},
+	};
+	Ok(Contract::new(address, name, transcoder))
+}
+
+/// Deploy `count` instances of a contract with a given constructor and arguments, the salt of
+/// every instance being returned by `salter` for its index. In case of success, returns the
+/// addresses of the instances, in order.
+///
+/// Meant for scalability tests (e.g. a factory with thousands of children): the code is uploaded
+/// once, the arguments are encoded once, and the instances are instantiated from the code hash. A
+/// new block is built whenever the next instantiation could exceed the maximum weight of the
+/// current block.
+///
+/// The transcoder of the bundle is registered for every instance, so they can be called with
+/// `Session::call_with_address`. The instantiations aren't part of the record of the session, so
+/// [`call`] keeps calling the contract deployed last with [`deploy`].
+///
+/// # Generic Parameters:
+/// - `S` - Sandbox environment.
+/// - `E` - `Err()` type returned by the contract.
+///
+/// # Parameters:
+/// - `session` - The session for interacting with contracts.
+/// - `bundle` - The contract bundle.
+/// - `method` - The name of the constructor method.
+/// - `input` - The input arguments, as text or encoded (see [`Arg`]).
+/// - `count` - The number of instances.
+/// - `salter` - Returns the salt of the instance of the given index. Salts must be unique.
+///
+/// # Example:
+/// ```rs
+/// let children = deploy_many::<Pop, ContractError>(
+///     &mut session,
+///     bundle,
+///     "new",
+///     NO_ARGS,
+///     1_000,
+///     |index| index.to_le_bytes().to_vec(),
+/// )?;
+/// ```
+pub fn deploy_many<S, E>(
+	session: &mut Session<S>,
+	bundle: ContractBundle,
+	method: &str,
+	input: impl IntoIterator<Item = impl Into<Arg>>,
+	count: usize,
+	mut salter: impl FnMut(usize) -> Vec<u8>,
+) -> Result<Vec<AccountIdFor<S::Runtime>>, E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+	E: Decode,
+{
+	let input = args::render(Some(&bundle.transcoder), method, input)
+		.expect("Failed to encode the arguments");
+	let transcoder = bundle.transcoder.clone();
+	let data = transcoder.encode(method, &input).expect("Failed to encode the arguments");
+	let code_hash = session.upload_bundle(bundle).expect("Failed to upload the code");
+
+	let max_block = <S::Runtime as frame_system::Config>::BlockWeights::get().max_block;
+	let actor = session.get_actor();
+	let gas_limit = session.get_gas_limit();
+	let mut block_weight = Weight::zero();
+	let mut instance_weight = Weight::zero();
+	let mut addresses = Vec::with_capacity(count);
+	for index in 0..count {
+		if block_weight.saturating_add(instance_weight).any_gt(max_block) {
+			session.sandbox().build_block();
+			block_weight = Weight::zero();
+		}
+		let result = session.sandbox().instantiate_contract(
+			code_hash.encode(),
+			Zero::zero(),
+			data.clone(),
+			salter(index),
+			actor.clone(),
+			gas_limit,
+			None,
+		);
+		let instance = result
+			.result
+			.unwrap_or_else(|error| panic!("Failed to deploy instance #{index}: {error:?}"));
+		if instance.result.did_revert() {
+			return Err(constructor_error(&instance.result.data));
+		}
+		instance_weight = result.gas_consumed;
+		block_weight = block_weight.saturating_add(instance_weight);
+		session.set_transcoder(instance.account_id.clone(), &transcoder);
+		addresses.push(instance.account_id);
+	}
+	Ok(addresses)
+}
+
+/// The error of a contract call made with [`call`] or [`Contract::call`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum CallError<E> {
+	/// The contract reverted with the error `E`.
+	Reverted(E),
+	/// The contract trapped, e.g. because it panicked or an assertion failed. Contains the panic
+	/// message if the contract wrote it to the debug buffer (i.e. if it was built with the
+	/// `ink-debug` feature of `ink`).
+	Trapped(Option<String>),
+	/// ink! couldn't dispatch the call to a message of the contract, e.g. because its input
+	/// doesn't decode into the arguments of the message.
+	Lang(LangError),
+}
+
+impl<E> CallError<E> {
+	/// Returns whether the contract trapped.
+	pub fn is_trap(&self) -> bool {
+		matches!(self, CallError::Trapped(_))
+	}
+
+	/// Returns the error the contract reverted with, panicking if it trapped or ink! couldn't
+	/// dispatch the call instead.
+	///
+	/// Used to assert that a call must revert, never trap.
+	#[track_caller]
+	pub fn expect_revert(self) -> E {
+		match self {
+			CallError::Reverted(error) => error,
+			CallError::Trapped(Some(message)) =>
+				panic!("Expected the call to revert, but the contract panicked: {message}"),
+			CallError::Trapped(None) =>
+				panic!("Expected the call to revert, but the contract trapped"),
+			CallError::Lang(error) =>
+				panic!("Expected the call to revert, but it wasn't dispatched: {error}"),
+		}
 	}
-	Ok(result.unwrap())
 }
 
-/// Call a method and decode the returned data.
+/// Converts the error of a call into the status code of the error the contract reverted with, so
+/// the result of [`call`] can be used with `assert_err!`. Panics if the contract trapped or the
+/// call wasn't dispatched.
+impl<E: Into<u32>> From<CallError<E>> for u32 {
+	#[track_caller]
+	fn from(error: CallError<E>) -> Self {
+		error.expect_revert().into()
+	}
+}
+
+/// Call a method of the contract deployed last and decode the returned data. See
+/// [`Contract::call`] to call a given contract.
+///
+/// Returns [`CallError::Reverted`] if the contract reverted, [`CallError::Trapped`] if it trapped
+/// (e.g. panicked) and [`CallError::Lang`] if ink! couldn't dispatch the call, so tests can tell
+/// them apart. Panics on any other failure.
 ///
 /// # Generic Parameters:
 /// - `S` - Sandbox environment.
@@ -146,9 +339,9 @@ where
 /// # Parameters:
 /// - `session` - The session for interacting with contracts.
 /// - `func_name` - The name of the contract method.
-/// - `input` - The input arguments.
-/// - `init_value` - Balance to transfer during the call. Requires the contract method to be
-///   `payable`.
+/// - `input` - The input arguments, as text or encoded (see [`Arg`]).
+/// - `endowment` - Balance to transfer during the call (see [`Endowment`]). Requires the contract
+///   method to be `payable`. Panics if the caller can't transfer it.
 ///
 /// # Example:
 /// ```rs
@@ -161,30 +354,73 @@ where
 ///    //
 ///    // `()` is the successful result type used by the contract.
 ///    // `ContractError` is the error type used by the contract.
-///    call::<Pop, (), ContractError>(
+///    let result = call::<Pop, (), ContractError>(
 ///     session,
 ///     "transfer",
 ///     input,
-///     init_value,
-///    )
+///     Some(Endowment::units(5)),
+///    );
+///
+///    // The contract must revert, not panic.
+///    assert_eq!(result.unwrap_err().expect_revert(), ContractError::InsufficientBalance);
 /// }
 /// ```
 pub fn call<S, O, E>(
 	session: &mut Session<S>,
 	func_name: &str,
-	input: Vec<String>,
-	endowment: Option<BalanceFor<S::Runtime>>,
-) -> Result<O, E>
+	input: impl IntoIterator<Item = impl Into<Arg>>,
+	endowment: Option<Endowment>,
+) -> Result<O, CallError<E>>
 where
 	S: Sandbox,
 	S::Runtime: pallet_contracts::Config,
 	O: Decode,
 	E: Decode,
 {
-	match session.call::<String, ()>(func_name, &input, endowment) {
-		// If the call is reverted, decode the error into the specified error type.
-		Err(SessionError::CallReverted(error)) =>
-			Err(E::decode(&mut &error[2..]).expect("Decoding failed")),
+	let transcoder = session
+		.record()
+		.deploy_returns()
+		.last()
+		.and_then(|address| session.get_transcoder(address));
+	call_contract(session, None, transcoder.as_deref(), func_name, input, endowment)
+}
+
+/// Calls a method of the contract at `address` (the contract deployed last if `None`), rendering
+/// the arguments with `transcoder`, and decodes the returned data.
+fn call_contract<S, O, E>(
+	session: &mut Session<S>,
+	address: Option<AccountIdFor<S::Runtime>>,
+	transcoder: Option<&ContractMessageTranscoder>,
+	func_name: &str,
+	input: impl IntoIterator<Item = impl Into<Arg>>,
+	endowment: Option<Endowment>,
+) -> Result<O, CallError<E>>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+	O: Decode,
+	E: Decode,
+{
+	let input = args::render(transcoder, func_name, input).expect("Failed to encode the arguments");
+	let endowment = crate::endowment::checked_balance(session, endowment);
+	let result = match address {
+		Some(address) =>
+			session.call_with_address::<String, ()>(address, func_name, &input, endowment),
+		None => session.call::<String, ()>(func_name, &input, endowment),
+	};
+	match result {
+		// If the call is reverted, decode the error into the specified error type, unless ink!
+		// couldn't dispatch the call.
+		Err(SessionError::CallReverted(data)) => Err(match decode_revert(&data) {
+			Ok(error) => CallError::Reverted(error),
+			Err(error) => CallError::Lang(error),
+		}),
+		// If the contract trapped, extract the panic message from the debug buffer.
+		Err(SessionError::CallFailed(error))
+			if error == pallet_contracts::Error::<S::Runtime>::ContractTrapped.into() =>
+			Err(CallError::Trapped(panic_message(
+				&session.record().last_call_result().debug_message,
+			))),
 		// If the call is successful, decode the last returned value.
 		Ok(_) => Ok(session
 			.record()
@@ -199,6 +435,38 @@ where
 	}
 }
 
+/// Decodes the data a contract reverted with.
+///
+/// ink! returns `Result<Result<T, E>, LangError>` from messages and constructors: the contract
+/// reverts with `Ok(Err(E))` if it returned an error, and with `Err(LangError)` if ink! couldn't
+/// dispatch the input (e.g. `LangError::CouldNotReadInput`).
+fn decode_revert<E: Decode>(data: &[u8]) -> Result<E, LangError> {
+	match data {
+		[0, 1, error @ ..] => Ok(E::decode(&mut &error[..]).expect("Decoding failed")),
+		[1, error @ ..] => Err(LangError::decode(&mut &error[..]).expect("Decoding failed")),
+		_ => panic!("Expected the contract to revert with an error, got {data:?}"),
+	}
+}
+
+/// Decodes the data a constructor reverted with, panicking if ink! couldn't dispatch the input.
+fn constructor_error<E: Decode>(data: &[u8]) -> E {
+	decode_revert(data).unwrap_or_else(|error| panic!("The constructor wasn't dispatched: {error}"))
+}
+
+/// Extracts the panic message of a contract from its debug buffer, if the contract panicked.
+///
+/// Supports both the current (`panicked at src/lib.rs:1:1:\nmessage`) and the former
+/// (`panicked at 'message', src/lib.rs:1:1`) format of panic messages.
+fn panic_message(debug_buffer: &[u8]) -> Option<String> {
+	let buffer = String::from_utf8_lossy(debug_buffer);
+	let panic = &buffer[buffer.rfind("panicked at ")? + "panicked at ".len()..];
+	let message = match panic.split_once('\n') {
+		Some((_location, message)) => message,
+		None => panic.strip_prefix('\'')?.rsplit_once("', ")?.0,
+	};
+	Some(message.trim_end().to_string())
+}
+
 /// Get the last contract event.
 ///
 /// # Generic Parameters:
@@ -230,7 +498,230 @@ where
 	session.record().last_event_batch().contract_events().last().cloned()
 }
 
+/// Get the last `n` contract events of the last contract call, in the order they were emitted.
+///
+/// Returns fewer events if the call emitted less than `n` events.
+///
+/// # Generic Parameters:
+/// - `S` - Sandbox environment.
+///
+/// # Parameters:
+/// - `session` - The session for interacting with contracts.
+/// - `n` - The number of events to return.
+///
+/// # Example:
+/// ```rs
+/// use drink::last_contract_events;
+///
+/// assert_eq!(
+///    last_contract_events::<Pop>(&session, 2),
+///    vec![
+///     Approval { owner, spender, value: 0 }.encode(),
+///     Transfer { from: Some(owner), to: Some(to), value }.encode(),
+///    ]
+/// );
+/// ```
+pub fn last_contract_events<S>(session: &Session<S>, n: usize) -> Vec<Vec<u8>>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+	<S::Runtime as frame_system::Config>::RuntimeEvent:
+		TryInto<pallet_contracts::Event<S::Runtime>>,
+{
+	let mut events = session.record().last_event_batch().contract_events();
+	events.drain(..events.len().saturating_sub(n));
+	events
+}
+
+/// Get all the contract events of the last contract call which decode into `E`, in the order they
+/// were emitted.
+///
+/// Events that can't be decoded into `E` (e.g. because they were emitted by another contract) are
+/// skipped, so `E` is typically the enum of all the events of the contract.
+///
+/// # Generic Parameters:
+/// - `S` - Sandbox environment.
+/// - `E` - The type to decode the events into.
+///
+/// # Parameters:
+/// - `session` - The session for interacting with contracts.
+///
+/// # Example:
+/// ```rs
+/// use drink::contract_events_of;
+///
+/// assert_eq!(
+///    contract_events_of::<Pop, Event>(&session),
+///    vec![
+///     Event::Approval(Approval { owner, spender, value: 0 }),
+///     Event::Transfer(Transfer { from: Some(owner), to: Some(to), value }),
+///    ]
+/// );
+/// ```
+pub fn contract_events_of<S, E>(session: &Session<S>) -> Vec<E>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+	<S::Runtime as frame_system::Config>::RuntimeEvent:
+		TryInto<pallet_contracts::Event<S::Runtime>>,
+	E: Decode,
+{
+	session
+		.record()
+		.last_event_batch()
+		.contract_events()
+		.into_iter()
+		.filter_map(|event| E::decode_all(&mut &event[..]).ok())
+		.collect()
+}
+
 #[cfg(any(feature = "devnet", feature = "testnet"))]
 fn account_id_from_slice(s: &[u8; 32]) -> pop_api::primitives::AccountId {
 	pop_api::primitives::AccountId::decode(&mut &s[..]).expect("Should be decoded to AccountId")
 }
+
+/// Converts an account into the account ID used in the contract environment.
+///
+/// Implemented for the runtime account IDs, including the addresses of the contracts deployed with
+/// [`deploy`], so they can be compared with the accounts of contract events.
+///
+/// # Example:
+/// ```rs
+/// assert_last_contract_event!(
+///     &session,
+///     Transfer { from: Some(contract.into_pop_account()), to: Some(BOB.into_pop_account()), value }
+/// );
+/// ```
+pub trait IntoPopAccount {
+	/// Converts `self` into the contract environment's account ID.
+	fn into_pop_account(self) -> pop_api::primitives::AccountId;
+}
+
+impl<A: AsRef<[u8; 32]>> IntoPopAccount for A {
+	fn into_pop_account(self) -> pop_api::primitives::AccountId {
+		(*self.as_ref()).into()
+	}
+}
+
+/// Converts an account into the account ID used by the runtime.
+///
+/// Implemented for the account IDs of the contract environment, e.g. to look up the balance of an
+/// account emitted in a contract event.
+pub trait IntoRuntimeAccount {
+	/// Converts `self` into the runtime's account ID.
+	fn into_runtime_account(self) -> AccountId32;
+}
+
+impl<A: AsRef<[u8; 32]>> IntoRuntimeAccount for A {
+	fn into_runtime_account(self) -> AccountId32 {
+		AccountId32::new(*self.as_ref())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use drink::minimal::MinimalSandbox;
+
+	use super::*;
+
+	#[test]
+	fn account_conversions_roundtrip() {
+		let account = AccountId32::new([7u8; 32]);
+		let pop_account = account.clone().into_pop_account();
+		assert_eq!(pop_account, pop_api::primitives::AccountId::from([7u8; 32]));
+		assert_eq!((&pop_account).into_runtime_account(), account);
+		assert_eq!(pop_account.into_runtime_account().into_pop_account(), pop_account);
+	}
+
+	#[test]
+	fn panic_messages_are_extracted_from_the_debug_buffer() {
+		assert_eq!(
+			panic_message(b"panicked at src/lib.rs:42:13:\nattempt to add with overflow\n"),
+			Some("attempt to add with overflow".to_string())
+		);
+		assert_eq!(
+			panic_message(b"panicked at 'not enough funds', src/lib.rs:7:9"),
+			Some("not enough funds".to_string())
+		);
+		assert_eq!(panic_message(b"some debug output\n"), None);
+		assert_eq!(panic_message(b""), None);
+	}
+
+	#[test]
+	fn many_instances_share_their_code() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let tokens = deploy_many::<_, ()>(
+			&mut session,
+			fixtures::reference_psp22(),
+			"new",
+			["1000"],
+			3,
+			|index| index.encode(),
+		)
+		.unwrap();
+
+		assert_eq!(tokens.len(), 3);
+		assert!(tokens.iter().enumerate().all(|(i, token)| !tokens[..i].contains(token)));
+		for token in &tokens {
+			let balance: u128 = session
+				.call_with_address(token.clone(), "PSP22::balance_of", &[owner.to_string()], None)
+				.unwrap()
+				.unwrap();
+			assert_eq!(balance, 1_000);
+		}
+		let code_hash = session.upload(fixtures::reference_psp22().wasm).unwrap();
+		assert_eq!(session.sandbox().code_refcount(&code_hash), Some(3));
+	}
+
+	#[test]
+	fn contract_handles_call_their_own_contract() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let first = deploy::<_, ()>(
+			&mut session,
+			fixtures::reference_psp22(),
+			"new",
+			["1000"],
+			vec![1],
+			None,
+		)
+		.unwrap();
+		let second =
+			deploy::<_, ()>(&mut session, fixtures::reference_psp22(), "new", ["5"], vec![2], None)
+				.unwrap();
+
+		assert_ne!(first.address(), second.address());
+		let balance: u128 = first
+			.call(&mut session, "PSP22::balance_of", [owner.to_string()], None)
+			.unwrap();
+		assert_eq!(balance, 1_000);
+		let balance: u128 = second
+			.call(&mut session, "PSP22::balance_of", [owner.to_string()], None)
+			.unwrap();
+		assert_eq!(balance, 5);
+	}
+
+	#[test]
+	fn lang_errors_cannot_be_mistaken_for_contract_errors() {
+		// `Ok(Err(3u32))`
+		assert_eq!(decode_revert::<u32>(&[0, 1, 3, 0, 0, 0]), Ok(3));
+		// `Err(LangError::CouldNotReadInput)`
+		assert_eq!(decode_revert::<u32>(&[1, 1]), Err(LangError::CouldNotReadInput));
+
+		let error = CallError::<u32>::Lang(LangError::CouldNotReadInput);
+		assert!(!error.is_trap());
+		assert!(std::panic::catch_unwind(|| error.expect_revert()).is_err());
+	}
+
+	#[test]
+	fn traps_cannot_be_mistaken_for_reverts() {
+		let revert = CallError::<u32>::Reverted(3);
+		assert!(!revert.is_trap());
+		assert_eq!(u32::from(revert), 3);
+
+		let trap = CallError::<u32>::Trapped(Some("attempt to add with overflow".to_string()));
+		assert!(trap.is_trap());
+		assert!(std::panic::catch_unwind(|| trap.expect_revert()).is_err());
+	}
+}
@@ -1,9 +1,12 @@
 use std::fmt::Debug;
 
-use drink::{session::Session, Sandbox};
+use drink::{frame_support::sp_runtime::ModuleError, session::Session, DispatchError, Sandbox};
 use scale::{Decode, Encode};
 
-use crate::last_contract_event;
+use crate::{
+	error::{DynamicModuleError, RuntimeMetadataProvider},
+	last_contract_event,
+};
 
 /// Asserts that a result matches an expected `Error`.
 ///
@@ -85,7 +88,7 @@ use crate::last_contract_event;
 ///     ...
 ///
 ///     // Call a contract method that returns a `Result<(), CustomError>`.
-///     let result = call::<Pop, (), CustomError>(session, "hello_world", vec![], None);
+///     let result = call::<Pop, (), CustomError>(session, "hello_world", NO_ARGS, None);
 ///
 ///     // Assert the result to the expected error.
 ///     assert_err!(result, Error::Raw(BadOrigin)));
@@ -126,6 +129,80 @@ where
 	}
 }
 
+/// Asserts that a result of a runtime-level call (e.g. of the sandbox API) matches an expected
+/// `DispatchError`.
+///
+/// Module errors are written as `Pallet(Error)` and resolved by name in the runtime metadata, so a
+/// failing assertion reports the names of the actual and expected errors instead of their indices.
+/// Only the top-level variant of the pallet error is compared.
+///
+/// # Example
+///
+/// ```rs
+/// // Resolved in the metadata of the Pop runtime (testnet, or devnet if it is the only enabled
+/// // feature).
+/// assert_dispatch_err!(session.sandbox().transfer(TOKEN, &ALICE, &BOB, 100), Assets(BalanceLow));
+///
+/// // Resolved in the metadata of the given runtime.
+/// assert_dispatch_err!(result, Assets(BalanceLow), Runtime);
+///
+/// // Any other `DispatchError`.
+/// assert_dispatch_err!(result, DispatchError::BadOrigin);
+/// ```
+///
+/// # Parameters:
+/// - `result` - The result which contains the `DispatchError`.
+/// - `error` - The expected error.
+/// - `runtime` - The runtime resolving the module error (optional, must implement
+///   `RuntimeMetadataProvider`).
+#[macro_export]
+macro_rules! assert_dispatch_err {
+	($result:expr, $pallet:ident($error:ident), $runtime:ty $(,)?) => {
+		$crate::macros::assert_dispatch_err_inner::<$runtime, _>(
+			$result,
+			stringify!($pallet),
+			stringify!($error),
+		);
+	};
+	($result:expr, $pallet:ident($error:ident) $(,)?) => {
+		$crate::assert_dispatch_err!($result, $pallet($error), $crate::PopRuntime);
+	};
+	($result:expr, $error:expr $(,)?) => {
+		$crate::macros::assert_dispatch_err_eq_inner::<_>($result, $error);
+	};
+}
+
+#[track_caller]
+pub fn assert_dispatch_err_inner<R, T>(result: Result<T, DispatchError>, pallet: &str, error: &str)
+where
+	R: RuntimeMetadataProvider,
+	T: Debug,
+{
+	let expected = DynamicModuleError::<R>::named(pallet, error);
+	match result {
+		Err(DispatchError::Module(ModuleError { index, error, .. }))
+			if index == expected.index() && error[0] == expected.error()[0] => {},
+		Err(DispatchError::Module(ModuleError { index, error, .. })) => panic!(
+			"{}",
+			assert_message(&DynamicModuleError::<R>::new(index, [error[0], error[1]]), &expected)
+		),
+		Err(error) => panic!("{}", assert_message(&error, &expected)),
+		Ok(value) => panic!("{}", assert_message(&Ok::<_, ()>(value), &expected)),
+	}
+}
+
+#[track_caller]
+pub fn assert_dispatch_err_eq_inner<T: Debug>(
+	result: Result<T, DispatchError>,
+	expected_error: DispatchError,
+) {
+	match result {
+		Err(error) if error == expected_error => {},
+		Err(error) => panic!("{}", assert_message(&error, &expected_error)),
+		Ok(value) => panic!("{}", assert_message(&Ok::<_, ()>(value), &expected_error)),
+	}
+}
+
 /// Asserts that the latest event matches an expected `event`.
 ///
 /// This can be used to assert that an event emitted from the latest contract execution resulted in
@@ -137,8 +214,8 @@ where
 /// assert_last_contract_event!(
 ///     &session,
 ///     Transfer {
-///         from: Some(account_id_from_slice(&contract)),
-///         to: Some(account_id_from_slice(&BOB)),
+///         from: Some(contract.into_pop_account()),
+///         to: Some(BOB.into_pop_account()),
 ///         value,
 ///     }
 /// );
@@ -171,6 +248,7 @@ where
 			},
 		None => panic!("{}", assert_message(&"None", &event)),
 	}
+	session.record().last_event_batch().note_last_contract_event_asserted();
 }
 
 fn assert_message<L: Debug, R: Debug>(left: &L, right: &R) -> String {
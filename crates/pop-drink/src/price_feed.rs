@@ -0,0 +1,162 @@
+//! Price feeds controlled by tests, for simulating price movements in contracts consuming oracles.
+//!
+//! A [`PriceFeed`] deploys (or attaches to) the oracle mock (see [`crate::fixtures::oracle_mock`])
+//! and updates its prices, typically between blocks:
+//!
+//! ```rs
+//! let oracle = PriceFeed::deploy(&mut session)?.oracle();
+//! deploy::<Pop, LendingError>(&mut session, lending, "new", [arg_account(&oracle)], NO_SALT, None)?;
+//!
+//! // The price of DOT drops from 7.45 to 5.20 USD over three blocks.
+//! PriceFeed::attach(&mut session, oracle).simulate("DOT/USD", [7_45, 6_30, 5_20], |session, _| {
+//!     call::<Pop, (), LendingError>(session, "liquidate", [arg_account(&BOB)], None)
+//!         .map(|_| ())
+//!         .unwrap_or_else(|error| assert!(!error.is_trap()));
+//! })?;
+//! ```
+//!
+//! Prices are plain integers, their precision (e.g. cents) is agreed between the test and the
+//! contract under test.
+
+use ink_sandbox::{api::prelude::*, AccountIdFor};
+
+use crate::{fixtures::oracle_mock, pallet_contracts, Sandbox, Session, SessionError, NO_ARGS};
+
+/// Controller of an oracle mock, setting the prices of trading pairs.
+pub struct PriceFeed<'a, S: Sandbox> {
+	session: &'a mut Session<S>,
+	oracle: AccountIdFor<S::Runtime>,
+}
+
+impl<'a, S> PriceFeed<'a, S>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	/// Deploys an oracle mock and returns its controller.
+	pub fn deploy(session: &'a mut Session<S>) -> Result<Self, SessionError> {
+		let salt = session.next_salt();
+		let oracle = session.deploy_bundle(oracle_mock(), "new", NO_ARGS, salt, None)?;
+		Ok(Self { session, oracle })
+	}
+
+	/// Returns the controller of the oracle mock deployed under `oracle`.
+	pub fn attach(session: &'a mut Session<S>, oracle: AccountIdFor<S::Runtime>) -> Self {
+		session.set_transcoder(oracle.clone(), &oracle_mock().transcoder);
+		Self { session, oracle }
+	}
+
+	/// Returns the address of the oracle mock.
+	pub fn oracle(&self) -> AccountIdFor<S::Runtime> {
+		self.oracle.clone()
+	}
+
+	/// Returns the session of the oracle mock.
+	pub fn session(&mut self) -> &mut Session<S> {
+		self.session
+	}
+
+	/// Sets the price of `pair` (e.g. `"DOT/USD"`), as of the current block.
+	pub fn set_price(&mut self, pair: &str, price: u128) -> Result<&mut Self, SessionError> {
+		self.session.call_with_address::<_, ()>(
+			self.oracle.clone(),
+			"set_price",
+			&[format!("{pair:?}"), price.to_string()],
+			None,
+		)?;
+		Ok(self)
+	}
+
+	/// Returns the price of `pair` and the number of the block it was set in, if any.
+	pub fn price(&mut self, pair: &str) -> Result<Option<(u128, u32)>, SessionError> {
+		let price = self.session.call_with_address::<_, Option<(u128, u32)>>(
+			self.oracle.clone(),
+			"get_price",
+			&[format!("{pair:?}")],
+			None,
+		)?;
+		Ok(price.ok().flatten())
+	}
+
+	/// Builds a block, so the next prices are set in a later block.
+	pub fn next_block(&mut self) -> &mut Self {
+		self.session.sandbox().build_block();
+		self
+	}
+
+	/// Moves the price of `pair` along `prices`, one price per block: sets every price in a new
+	/// block and then calls `step` with the session and the price, e.g. to let the contract under
+	/// test react to it.
+	pub fn simulate(
+		&mut self,
+		pair: &str,
+		prices: impl IntoIterator<Item = u128>,
+		mut step: impl FnMut(&mut Session<S>, u128),
+	) -> Result<&mut Self, SessionError> {
+		for price in prices {
+			self.next_block().set_price(pair, price)?;
+			step(self.session, price);
+		}
+		Ok(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use drink::minimal::MinimalSandbox;
+
+	use super::*;
+
+	#[test]
+	fn prices_can_be_set_between_blocks() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let mut feed = PriceFeed::deploy(&mut session).unwrap();
+		assert_eq!(feed.price("DOT/USD").unwrap(), None);
+
+		let block = feed.session().sandbox().block_number();
+		feed.set_price("DOT/USD", 7_45).unwrap();
+		assert_eq!(feed.price("DOT/USD").unwrap(), Some((7_45, block)));
+		assert_eq!(feed.price("KSM/USD").unwrap(), None);
+
+		let mut observed = Vec::new();
+		feed.simulate("DOT/USD", [6_30, 5_20], |session, price| {
+			observed.push((session.sandbox().block_number(), price))
+		})
+		.unwrap();
+		assert_eq!(observed, [(block + 1, 6_30), (block + 2, 5_20)]);
+		assert_eq!(feed.price("DOT/USD").unwrap(), Some((5_20, block + 2)));
+	}
+
+	#[test]
+	fn feeds_can_be_attached_to_deployed_oracles() {
+		let mut session = Session::<MinimalSandbox>::default();
+		let oracle = PriceFeed::deploy(&mut session).unwrap().oracle();
+		PriceFeed::attach(&mut session, oracle.clone())
+			.set_price("DOT/USD", 7_45)
+			.unwrap();
+
+		let price: Option<(u128, u32)> = session
+			.call_with_address(oracle, "get_price", &["\"DOT/USD\""], None)
+			.unwrap()
+			.unwrap();
+		assert_eq!(price.map(|(price, _)| price), Some(7_45));
+	}
+
+	#[test]
+	fn price_updates_emit_events() {
+		#[derive(Debug, PartialEq, scale::Decode)]
+		struct PriceUpdated {
+			pair: String,
+			price: u128,
+		}
+
+		let mut session = Session::<MinimalSandbox>::default();
+		let mut feed = PriceFeed::deploy(&mut session).unwrap();
+		feed.set_price("DOT/USD", 7_45).unwrap();
+
+		let batch = feed.session().record().last_event_batch();
+		batch.assert_last_contract_event_topic("DOT/USD");
+		batch.assert_last_contract_event(PriceUpdated { pair: "DOT/USD".into(), price: 7_45 });
+		assert_eq!(batch.contract_events_decoded(&oracle_mock().transcoder).len(), 1);
+	}
+}
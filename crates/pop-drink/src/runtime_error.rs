@@ -0,0 +1,107 @@
+//! The error types of the Pop API, parameterized by the runtime (see [`RuntimeVariant`]) and the
+//! version of the API (see [`ApiVersion`]):
+//!
+//! ```rs
+//! use pop_drink::runtime_error::{Error, Testnet, V0};
+//!
+//! assert_err!(result, Error::<Testnet, V0>::Module(Assets(BalanceLow)));
+//! ```
+//!
+//! Supporting another runtime or version of the API only takes a type implementing the trait. The
+//! error modules of the runtimes (e.g. `pop_drink::testnet::error::v0`) alias these types.
+
+use std::fmt::Debug;
+
+use scale::{Decode, Encode};
+
+use crate::error::DynamicModuleError;
+
+/// Index of the `Module` variant in the errors of the Pop API.
+pub const MODULE_INDEX: u8 = 3;
+
+/// A Pop runtime.
+pub trait RuntimeVariant {
+	/// The runtime.
+	type Runtime;
+	/// The module errors of the runtime, one variant per pallet.
+	type RuntimeError: Decode + Encode + Debug;
+}
+
+/// A version of the Pop API.
+pub trait ApiVersion {
+	/// The error returned by the API.
+	type Error: Decode + Encode + Debug + From<u32> + Into<u32>;
+}
+
+/// Version 0 of the Pop API.
+pub struct V0;
+
+impl ApiVersion for V0 {
+	type Error = pop_api::primitives::v0::Error;
+}
+
+/// The Pop Devnet runtime.
+#[cfg(feature = "devnet")]
+pub struct Devnet;
+
+#[cfg(feature = "devnet")]
+impl RuntimeVariant for Devnet {
+	type Runtime = pop_runtime_devnet::Runtime;
+	type RuntimeError = pop_runtime_devnet::RuntimeError;
+}
+
+/// The Pop Testnet runtime.
+#[cfg(feature = "testnet")]
+pub struct Testnet;
+
+#[cfg(feature = "testnet")]
+impl RuntimeVariant for Testnet {
+	type Runtime = pop_runtime_testnet::Runtime;
+	type RuntimeError = pop_runtime_testnet::RuntimeError;
+}
+
+/// Error of version `V` of the Pop API on the runtime `R`, its module errors being the
+/// `RuntimeError` of the runtime (see [`crate::error::Error`]).
+pub type Error<R, V> = crate::error::Error<
+	<V as ApiVersion>::Error,
+	<R as RuntimeVariant>::RuntimeError,
+	MODULE_INDEX,
+>;
+
+/// Error of version `V` of the Pop API on the runtime `R`, its module errors being resolved from
+/// the runtime metadata (see [`DynamicModuleError`]).
+pub type DynamicError<R, V> = crate::error::Error<
+	<V as ApiVersion>::Error,
+	DynamicModuleError<<R as RuntimeVariant>::Runtime>,
+	MODULE_INDEX,
+>;
+
+#[cfg(test)]
+mod tests {
+	use frame_support::traits::PalletInfoAccess;
+	use pop_api::primitives::v0::Error as ApiError;
+
+	use super::{Error, RuntimeVariant, V0};
+	use crate::{
+		error::AssetsError::BalanceLow,
+		mock::{Assets, RuntimeError, Test},
+	};
+
+	struct Mock;
+
+	impl RuntimeVariant for Mock {
+		type Runtime = Test;
+		type RuntimeError = RuntimeError;
+	}
+
+	#[test]
+	fn errors_are_parameterized_by_runtime_and_version() {
+		let error = ApiError::Module { index: Assets::index() as u8, error: [0, 0] };
+		let status: u32 = Error::<Mock, V0>::Module(RuntimeError::Assets(BalanceLow)).into();
+		assert_eq!(status, u32::from(error));
+		assert!(matches!(
+			Error::<Mock, V0>::from(status),
+			Error::<Mock, V0>::Module(RuntimeError::Assets(BalanceLow))
+		));
+	}
+}
@@ -0,0 +1,446 @@
+//! Call scenarios loaded from JSON or CSV files, so regression scenarios can be authored without
+//! writing Rust.
+//!
+//! A scenario is a sequence of calls to a contract, each made by an actor with arguments and a
+//! value, and checked against an expected outcome. In JSON, a scenario is an array of steps:
+//!
+//! ```json
+//! [
+//!   { "actor": "//Alice", "message": "PSP22::transfer", "args": ["//Bob", "400", "[]"] },
+//!   { "message": "PSP22::balance_of", "args": ["//Bob"], "expect": "ok: Ok(400)" },
+//!   { "message": "PSP22::transfer", "args": ["//Bob", "601", "[]"], "expect": "revert" }
+//! ]
+//! ```
+//!
+//! In CSV, a scenario has a header and a step per line, the arguments being separated by `;`:
+//!
+//! ```csv
+//! actor,message,args,value,expect
+//! //Alice,PSP22::transfer,//Bob;400;[],,ok
+//! //Bob,PSP22::balance_of,//Bob,,ok: Ok(400)
+//! ```
+//!
+//! The fields of a step are:
+//! - `actor` - The caller: a dev account (`//Alice`), an SS58 address or a hex account. Defaults to
+//!   the actor of the session.
+//! - `message` - The name of the message.
+//! - `args` - The arguments, as text. Dev accounts (`//Bob`) are replaced by their SS58 address.
+//! - `value` - The value transferred with the call, in planck. Defaults to none.
+//! - `expect` - The expected outcome: `ok`, `revert` or `trap`. The value returned by a successful
+//!   or reverted call can be checked too, e.g. `ok: Ok(400)` (whitespace is ignored). Defaults to
+//!   `ok`.
+//!
+//! The scenario is then run as a test, against a deployed contract:
+//!
+//! ```rs
+//! let token = deploy_reference_psp22(&mut session, 1_000)?;
+//! Scenario::load("scenarios/transfers.csv")?.run(&mut session, &token);
+//! ```
+
+use std::{fmt, path::Path};
+
+use drink::{
+	accounts::{dev_account, parse_account},
+	Error,
+};
+use serde_json::Value;
+
+use crate::{
+	args::arg_bytes, endowment::checked_balance, pallet_contracts, AccountId32, Endowment, Sandbox,
+	Session, SessionError,
+};
+
+/// The outcome of a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+	/// The call succeeded, returning the given value.
+	Success(String),
+	/// The contract reverted, with the given value.
+	Revert(String),
+	/// The call failed, e.g. because the contract trapped.
+	Trap(String),
+}
+
+impl fmt::Display for Outcome {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Outcome::Success(value) => write!(f, "ok: {value}"),
+			Outcome::Revert(value) => write!(f, "revert: {value}"),
+			Outcome::Trap(error) => write!(f, "trap: {error}"),
+		}
+	}
+}
+
+/// The expected outcome of a step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expectation {
+	/// The call succeeds, returning the given value if any.
+	Success(Option<String>),
+	/// The contract reverts, with the given value if any.
+	Revert(Option<String>),
+	/// The call fails.
+	Trap,
+}
+
+impl Expectation {
+	/// Returns whether `outcome` meets the expectation.
+	pub fn is_met_by(&self, outcome: &Outcome) -> bool {
+		let matches = |expected: &Option<String>, value: &str| {
+			expected
+				.as_ref()
+				.map_or(true, |expected| normalize(expected) == normalize(value))
+		};
+		match (self, outcome) {
+			(Expectation::Success(expected), Outcome::Success(value)) |
+			(Expectation::Revert(expected), Outcome::Revert(value)) => matches(expected, value),
+			(Expectation::Trap, Outcome::Trap(_)) => true,
+			_ => false,
+		}
+	}
+
+	fn parse(text: &str) -> Result<Self, String> {
+		let (kind, value) = match text.split_once(':') {
+			Some((kind, value)) => (kind.trim(), Some(value.trim().to_string())),
+			None => (text.trim(), None),
+		};
+		match (kind, value) {
+			("" | "ok", value) => Ok(Expectation::Success(value)),
+			("revert", value) => Ok(Expectation::Revert(value)),
+			("trap", None) => Ok(Expectation::Trap),
+			_ => Err(format!("Invalid expectation `{text}`")),
+		}
+	}
+}
+
+impl fmt::Display for Expectation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Expectation::Success(None) => write!(f, "ok"),
+			Expectation::Success(Some(value)) => write!(f, "ok: {value}"),
+			Expectation::Revert(None) => write!(f, "revert"),
+			Expectation::Revert(Some(value)) => write!(f, "revert: {value}"),
+			Expectation::Trap => write!(f, "trap"),
+		}
+	}
+}
+
+/// A call of a scenario.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+	/// The caller, or `None` for the actor of the session.
+	pub actor: Option<AccountId32>,
+	/// The name of the message.
+	pub message: String,
+	/// The arguments, as text.
+	pub args: Vec<String>,
+	/// The value transferred with the call.
+	pub value: Option<Endowment>,
+	/// The expected outcome.
+	pub expect: Expectation,
+}
+
+/// A sequence of calls checked against their expected outcomes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scenario {
+	steps: Vec<Step>,
+}
+
+impl Scenario {
+	/// Loads a scenario from a JSON (`.json`) or CSV (`.csv`) file.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let path = path.as_ref();
+		let content = std::fs::read_to_string(path)
+			.map_err(|e| Error::ScenarioLoadFailed(format!("Failed to read the file:\n{e:?}")))?;
+		match path.extension().and_then(|extension| extension.to_str()) {
+			Some("json") => Self::from_json(&content),
+			Some("csv") => Self::from_csv(&content),
+			_ => Err(Error::ScenarioLoadFailed(format!(
+				"Unsupported file `{}`, expected a `.json` or `.csv` file",
+				path.display()
+			))),
+		}
+	}
+
+	/// Parses a scenario from JSON: an array of steps, with the fields described in the
+	/// [module documentation](self).
+	pub fn from_json(json: &str) -> Result<Self, Error> {
+		let steps: Value = serde_json::from_str(json)
+			.map_err(|e| Error::ScenarioLoadFailed(format!("Failed to parse the file:\n{e:?}")))?;
+		let steps = steps
+			.as_array()
+			.ok_or_else(|| Error::ScenarioLoadFailed("Expected an array of steps".to_string()))?
+			.iter()
+			.enumerate()
+			.map(|(index, step)| {
+				let field = |name: &str| match &step[name] {
+					Value::Null => None,
+					Value::String(text) => Some(text.clone()),
+					value => Some(value.to_string()),
+				};
+				let args = match &step["args"] {
+					Value::Null => Vec::new(),
+					Value::Array(args) => args
+						.iter()
+						.map(|arg| match arg {
+							Value::String(text) => text.clone(),
+							value => value.to_string(),
+						})
+						.collect(),
+					_ => return Err("`args` must be an array".to_string()),
+				};
+				Step::parse(field("actor"), field("message"), args, field("value"), field("expect"))
+					.map_err(|error| format!("step #{index}: {error}"))
+			})
+			.collect::<Result<_, _>>()
+			.map_err(Error::ScenarioLoadFailed)?;
+		Ok(Self { steps })
+	}
+
+	/// Parses a scenario from CSV: a header naming the columns (`actor`, `message`, `args`,
+	/// `value` and `expect`, in any order), and a step per line. The arguments are separated by
+	/// `;`. Fields can be quoted, e.g. to contain commas.
+	pub fn from_csv(csv: &str) -> Result<Self, Error> {
+		let mut lines = csv.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+		let (_, header) = lines
+			.next()
+			.ok_or_else(|| Error::ScenarioLoadFailed("Missing header".to_string()))?;
+		let header = csv_record(header).map_err(Error::ScenarioLoadFailed)?;
+		let column = |name: &str| header.iter().position(|column| column.trim() == name);
+
+		let steps = lines
+			.map(|(index, line)| {
+				let record = csv_record(line)?;
+				let field = |name: &str| {
+					column(name)
+						.and_then(|column| record.get(column))
+						.map(|field| field.trim().to_string())
+						.filter(|field| !field.is_empty())
+				};
+				let args = field("args")
+					.map(|args| args.split(';').map(|arg| arg.trim().to_string()).collect())
+					.unwrap_or_default();
+				Step::parse(field("actor"), field("message"), args, field("value"), field("expect"))
+					.map_err(|error| format!("line {}: {error}", index + 1))
+			})
+			.collect::<Result<_, _>>()
+			.map_err(Error::ScenarioLoadFailed)?;
+		Ok(Self { steps })
+	}
+
+	/// Returns the steps of the scenario.
+	pub fn steps(&self) -> &[Step] {
+		&self.steps
+	}
+
+	/// Runs the scenario against the contract at `contract`, and panics listing the steps whose
+	/// outcome doesn't meet the expectation. The actor of the session is restored afterwards.
+	///
+	/// The transcoder of the contract must be registered in the session (e.g. if it was deployed
+	/// from a bundle).
+	#[track_caller]
+	pub fn run<S>(&self, session: &mut Session<S>, contract: &AccountId32)
+	where
+		S: Sandbox,
+		S::Runtime: pallet_contracts::Config + frame_system::Config<AccountId = AccountId32>,
+	{
+		let transcoder = session
+			.get_transcoder(contract)
+			.unwrap_or_else(|| panic!("No transcoder is registered for {contract}"));
+		let original_actor = session.get_actor();
+
+		let mut failures = Vec::new();
+		for (index, step) in self.steps.iter().enumerate() {
+			session.set_actor(step.actor.clone().unwrap_or_else(|| original_actor.clone()));
+			let value = checked_balance(session, step.value);
+			let render = |data: &[u8]| {
+				transcoder
+					.decode_message_return(&step.message, &mut &data[..])
+					.map(|value| value.to_string())
+					.unwrap_or_else(|_| arg_bytes(data))
+			};
+			let outcome = match session.call_with_address::<_, ()>(
+				contract.clone(),
+				&step.message,
+				&step.args,
+				value,
+			) {
+				Ok(_) => Outcome::Success(render(session.record().last_call_return())),
+				Err(SessionError::CallReverted(data)) => Outcome::Revert(render(&data)),
+				Err(error) => Outcome::Trap(error.to_string()),
+			};
+			if !step.expect.is_met_by(&outcome) {
+				failures.push(format!(
+					"step #{index} (`{}`): expected `{}`, got `{outcome}`",
+					step.message, step.expect
+				));
+			}
+		}
+		session.set_actor(original_actor);
+
+		if !failures.is_empty() {
+			panic!(
+				"{} of {} step(s) of the scenario failed:\n{}",
+				failures.len(),
+				self.steps.len(),
+				failures.join("\n")
+			);
+		}
+	}
+}
+
+impl Step {
+	fn parse(
+		actor: Option<String>,
+		message: Option<String>,
+		args: Vec<String>,
+		value: Option<String>,
+		expect: Option<String>,
+	) -> Result<Self, String> {
+		Ok(Step {
+			actor: actor.as_deref().map(account).transpose()?,
+			message: message.ok_or("Missing `message`")?,
+			args: args
+				.into_iter()
+				.map(|arg| {
+					if arg.starts_with("//") {
+						account(&arg).map(|account| account.to_string())
+					} else {
+						Ok(arg)
+					}
+				})
+				.collect::<Result<_, _>>()?,
+			value: value
+				.map(|value| {
+					value.parse().map(Endowment::planck).map_err(|_| {
+						format!("Invalid value `{value}`, expected an amount of planck")
+					})
+				})
+				.transpose()?,
+			expect: Expectation::parse(expect.as_deref().unwrap_or("ok"))?,
+		})
+	}
+}
+
+/// Parses a dev account (`//Alice`), an SS58 address or a hex account.
+fn account(text: &str) -> Result<AccountId32, String> {
+	if text.starts_with("//") {
+		std::panic::catch_unwind(|| dev_account(text))
+			.map_err(|_| format!("Invalid dev account `{text}`"))
+	} else {
+		parse_account(text).map_err(|error| error.to_string())
+	}
+}
+
+/// Removes the whitespace of `text`, so renderings of values can be compared loosely.
+fn normalize(text: &str) -> String {
+	text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Splits a CSV line into its fields, unquoting the quoted ones (with `""` as an escaped quote).
+fn csv_record(line: &str) -> Result<Vec<String>, String> {
+	let mut fields = vec![String::new()];
+	let mut quoted = false;
+	let mut chars = line.chars().peekable();
+	while let Some(c) = chars.next() {
+		let field = fields.last_mut().expect("There is at least one field");
+		match (c, quoted) {
+			('"', true) if chars.peek() == Some(&'"') => {
+				chars.next();
+				field.push('"');
+			},
+			('"', true) => quoted = false,
+			('"', false) if field.trim().is_empty() => {
+				field.clear();
+				quoted = true;
+			},
+			(',', false) => fields.push(String::new()),
+			(c, _) => field.push(c),
+		}
+	}
+	if quoted {
+		return Err(format!("Unterminated quote in `{line}`"));
+	}
+	Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+	use drink::{
+		accounts::{fund_accounts, ALICE, BOB},
+		minimal::MinimalSandbox,
+		units::units,
+	};
+
+	use super::*;
+	use crate::fixtures::deploy_reference_psp22;
+
+	const JSON: &str = r#"[
+		{ "actor": "//Alice", "message": "PSP22::transfer", "args": ["//Bob", 400, "[]"] },
+		{ "actor": "//Bob", "message": "PSP22::balance_of", "args": ["//Bob"], "expect": "ok: Ok(400)" },
+		{
+			"actor": "//Bob", "message": "PSP22::transfer", "args": ["//Alice", "401", "[]"],
+			"expect": "revert"
+		},
+		{ "message": "PSP22::total_supply", "expect": "ok: Ok(1000)" }
+	]"#;
+
+	const CSV: &str = "actor,message,args,value,expect
+//Alice,PSP22::transfer,//Bob;400;[],,ok
+//Bob,PSP22::balance_of,//Bob,,\"ok: Ok(400)\"
+//Bob,PSP22::transfer,//Alice;401;[],,revert
+,PSP22::total_supply,,,ok: Ok(1000)
+";
+
+	fn session_with_token() -> (Session<MinimalSandbox>, AccountId32) {
+		let mut session = Session::<MinimalSandbox>::default().with_actor(ALICE);
+		fund_accounts(&mut session, &[ALICE, BOB], units(100)).unwrap();
+		let token = deploy_reference_psp22(&mut session, 1_000).unwrap();
+		(session, token)
+	}
+
+	#[test]
+	fn json_and_csv_scenarios_are_equivalent() {
+		let json = Scenario::from_json(JSON).unwrap();
+		assert_eq!(json, Scenario::from_csv(CSV).unwrap());
+		assert_eq!(json.steps().len(), 4);
+		assert_eq!(json.steps()[0].actor, Some(ALICE));
+		assert_eq!(
+			json.steps()[0].args,
+			vec![BOB.to_string(), "400".to_string(), "[]".to_string()]
+		);
+		assert_eq!(json.steps()[2].expect, Expectation::Revert(None));
+		assert_eq!(json.steps()[3].actor, None);
+	}
+
+	#[test]
+	fn scenarios_run_as_tests() {
+		let (mut session, token) = session_with_token();
+		Scenario::from_json(JSON).unwrap().run(&mut session, &token);
+		assert_eq!(session.get_actor(), ALICE);
+	}
+
+	#[test]
+	#[should_panic(expected = "1 of 1 step(s) of the scenario failed")]
+	fn unmet_expectations_fail_the_test() {
+		let (mut session, token) = session_with_token();
+		Scenario::from_csv("message,expect\nPSP22::total_supply,ok: Ok(999)")
+			.unwrap()
+			.run(&mut session, &token);
+	}
+
+	#[test]
+	fn invalid_steps_are_rejected() {
+		assert!(matches!(
+			Scenario::from_csv("actor,expect\n//Alice,ok"),
+			Err(Error::ScenarioLoadFailed(error)) if error.contains("Missing `message`")
+		));
+		assert!(matches!(
+			Scenario::from_json(r#"[{ "message": "get", "expect": "maybe" }]"#),
+			Err(Error::ScenarioLoadFailed(error)) if error.contains("Invalid expectation")
+		));
+		assert!(matches!(
+			Scenario::from_csv("message,value\nget,lots"),
+			Err(Error::ScenarioLoadFailed(error)) if error.contains("Invalid value")
+		));
+	}
+}
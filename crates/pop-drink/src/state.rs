@@ -0,0 +1,187 @@
+//! Contract state fixtures, for sharing complex pre-populated states (a DEX with liquidity, a
+//! registry with entries, ...) between tests.
+//!
+//! The storage of a contract is exported to a JSON file once, and imported into a freshly deployed
+//! instance of the same contract by the tests using it:
+//!
+//! ```rs
+//! // Populate the DEX once, then export its state.
+//! export_contract_state(&mut session, dex.address(), "fixtures/dex_with_liquidity.json")?;
+//!
+//! // In the tests, restore it instead of populating the DEX again.
+//! let dex = deploy::<Pop, DexError>(&mut session, bundle, "new", NO_ARGS, NO_SALT, None)?;
+//! import_contract_state(&mut session, dex.address(), "fixtures/dex_with_liquidity.json")?;
+//! ```
+//!
+//! Only the storage of the contract is exported: its balance, its storage deposit and the state
+//! of other contracts and pallets are not.
+
+use std::path::Path;
+
+use drink::Error;
+use ink_sandbox::{api::prelude::*, AccountIdFor};
+use serde_json::{json, Value};
+
+use crate::{arg_bytes, pallet_contracts, Sandbox, Session};
+
+/// The version of the format of exported states.
+const FORMAT_VERSION: u64 = 1;
+
+/// Exports the storage of the contract at `address` to the JSON file at `path`, with the keys and
+/// values of the storage entries hex-encoded.
+pub fn export_contract_state<S>(
+	session: &mut Session<S>,
+	address: &AccountIdFor<S::Runtime>,
+	path: impl AsRef<Path>,
+) -> Result<(), Error>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	ensure_contract(session, address)?;
+	let storage = session
+		.sandbox()
+		.contract_storage_iter(address)
+		.into_iter()
+		.map(|(key, value)| json!({ "key": arg_bytes(&key), "value": arg_bytes(&value) }))
+		.collect::<Vec<_>>();
+	let state = json!({ "version": FORMAT_VERSION, "storage": storage });
+
+	let json = serde_json::to_string_pretty(&state)
+		.map_err(|e| Error::ContractStateFailed(format!("Failed to serialize:\n{e:?}")))?;
+	std::fs::write(path, json)
+		.map_err(|e| Error::ContractStateFailed(format!("Failed to write the file:\n{e:?}")))
+}
+
+/// Replaces the storage of the contract at `address` with the state exported to the JSON file at
+/// `path` (see [`export_contract_state`]).
+///
+/// The storage deposit of the contract isn't adjusted to the imported state.
+pub fn import_contract_state<S>(
+	session: &mut Session<S>,
+	address: &AccountIdFor<S::Runtime>,
+	path: impl AsRef<Path>,
+) -> Result<(), Error>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	let json = std::fs::read_to_string(path)
+		.map_err(|e| Error::ContractStateFailed(format!("Failed to read the file:\n{e:?}")))?;
+	let state: Value = serde_json::from_str(&json)
+		.map_err(|e| Error::ContractStateFailed(format!("Failed to parse the file:\n{e:?}")))?;
+	if state["version"] != FORMAT_VERSION {
+		return Err(Error::ContractStateFailed(format!(
+			"Unsupported state version {} (expected {FORMAT_VERSION})",
+			state["version"]
+		)));
+	}
+	let storage = state["storage"]
+		.as_array()
+		.ok_or_else(|| Error::ContractStateFailed("Missing storage entries".to_string()))?
+		.iter()
+		.map(|entry| Ok((bytes(&entry["key"])?, bytes(&entry["value"])?)))
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	ensure_contract(session, address)?;
+	session.sandbox().replace_contract_storage(address, storage);
+	Ok(())
+}
+
+fn ensure_contract<S>(
+	session: &mut Session<S>,
+	address: &AccountIdFor<S::Runtime>,
+) -> Result<(), Error>
+where
+	S: Sandbox,
+	S::Runtime: pallet_contracts::Config,
+{
+	let code_hash = session
+		.sandbox()
+		.execute_with(|| pallet_contracts::Pallet::<S::Runtime>::code_hash(address));
+	match code_hash {
+		Some(_) => Ok(()),
+		None => Err(Error::ContractStateFailed(format!("No contract at {address:?}"))),
+	}
+}
+
+/// Parses a `0x`-prefixed hex string, as rendered by [`arg_bytes`].
+fn bytes(hex: &Value) -> Result<Vec<u8>, Error> {
+	let invalid = || Error::ContractStateFailed(format!("Invalid hex string: {hex}"));
+	let hex = hex.as_str().and_then(|hex| hex.strip_prefix("0x")).ok_or_else(invalid)?;
+	if !hex.is_ascii() || hex.len() % 2 != 0 {
+		return Err(invalid());
+	}
+	(0..hex.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid()))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use drink::{
+		accounts::BOB,
+		minimal::MinimalSandbox,
+		session::{NO_ARGS, NO_SALT},
+		AccountId32,
+	};
+
+	use super::*;
+	use crate::fixtures::{deploy_reference_psp22, reference_psp22};
+
+	fn balance_of(
+		session: &mut Session<MinimalSandbox>,
+		token: &AccountId32,
+		account: &AccountId32,
+	) -> u128 {
+		session
+			.call_with_address(token.clone(), "PSP22::balance_of", &[account.to_string()], None)
+			.unwrap()
+			.unwrap()
+	}
+
+	#[test]
+	fn contract_state_roundtrips() {
+		let path = std::env::temp_dir()
+			.join(format!("pop-drink-contract-state-{}.json", std::process::id()));
+		let mut session = Session::<MinimalSandbox>::default();
+		let owner = session.get_actor();
+		let token = deploy_reference_psp22(&mut session, 1_000).unwrap();
+		session
+			.call_with_address::<_, ()>(
+				token.clone(),
+				"PSP22::transfer",
+				&[BOB.to_string(), "400".to_string(), "[]".to_string()],
+				None,
+			)
+			.unwrap()
+			.unwrap();
+		export_contract_state(&mut session, &token, &path).unwrap();
+
+		let mut session = Session::<MinimalSandbox>::default();
+		let token = session.deploy_bundle(reference_psp22(), "new", &["0"], NO_SALT, None).unwrap();
+		import_contract_state(&mut session, &token, &path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+
+		assert_eq!(balance_of(&mut session, &token, &owner), 600);
+		assert_eq!(balance_of(&mut session, &token, &BOB), 400);
+		let supply: u128 = session
+			.call_with_address(token.clone(), "PSP22::total_supply", NO_ARGS, None)
+			.unwrap()
+			.unwrap();
+		assert_eq!(supply, 1_000);
+	}
+
+	#[test]
+	fn only_contracts_have_a_state() {
+		let path = std::env::temp_dir()
+			.join(format!("pop-drink-no-contract-state-{}.json", std::process::id()));
+		let mut session = Session::<MinimalSandbox>::default();
+		assert!(matches!(
+			export_contract_state(&mut session, &BOB, &path),
+			Err(Error::ContractStateFailed(_))
+		));
+		assert!(!path.exists());
+	}
+}